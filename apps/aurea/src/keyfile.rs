@@ -0,0 +1,567 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use clap::Args;
+use ed25519_dalek::SigningKey;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+pub const PASSPHRASE_ENV: &str = "AUREA_KEY_PASSPHRASE";
+pub const NEW_PASSPHRASE_ENV: &str = "AUREA_NEW_KEY_PASSPHRASE";
+const CIPHER: &str = "xchacha20poly1305";
+const KEY_FILE_CONTEXT: &str = "aurea 2026 signing key file wrapping key";
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct UnlockArgs {
+    #[arg(long, conflicts_with = "key_file")]
+    pub passphrase_stdin: bool,
+    #[arg(long, value_name = "PATH")]
+    pub key_file: Option<PathBuf>,
+    /// Read and write signing keys unencrypted.
+    #[arg(long, conflicts_with_all = ["passphrase_stdin", "key_file"])]
+    pub insecure_plaintext_keys: bool,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct NewUnlockArgs {
+    #[arg(long, conflicts_with = "new_key_file")]
+    pub new_passphrase_stdin: bool,
+    #[arg(long, value_name = "PATH")]
+    pub new_key_file: Option<PathBuf>,
+}
+
+impl UnlockArgs {
+    /// `--key-file`, then `--passphrase-stdin`, then `AUREA_KEY_PASSPHRASE`;
+    /// plaintext keys only with `--insecure-plaintext-keys`.
+    pub fn resolve(&self) -> Result<Unlock> {
+        let unlock = Unlock::resolve(
+            self.passphrase_stdin,
+            self.key_file.as_deref(),
+            PASSPHRASE_ENV,
+        )?;
+        self.choose(unlock)
+    }
+
+    /// Settles the unlock source found by `resolve` against
+    /// `--insecure-plaintext-keys`.
+    fn choose(&self, unlock: Option<Unlock>) -> Result<Unlock> {
+        match (unlock, self.insecure_plaintext_keys) {
+            (Some(_), true) => {
+                let source = if self.key_file.is_some() {
+                    "--key-file".to_string()
+                } else if self.passphrase_stdin {
+                    "--passphrase-stdin".to_string()
+                } else {
+                    format!("{PASSPHRASE_ENV} in the environment")
+                };
+                Err(anyhow!(
+                    "--insecure-plaintext-keys would leave keys unencrypted but {source} supplies a passphrase; drop one of them"
+                ))
+            }
+            (Some(unlock), false) => Ok(unlock),
+            (None, true) => Ok(Unlock::None),
+            (None, false) => Err(anyhow!(
+                "no key passphrase or key file; set {PASSPHRASE_ENV}, pass --passphrase-stdin or --key-file, or keep keys unencrypted with --insecure-plaintext-keys"
+            )),
+        }
+    }
+}
+
+impl NewUnlockArgs {
+    pub fn resolve(&self) -> Result<Unlock> {
+        Ok(Unlock::resolve(
+            self.new_passphrase_stdin,
+            self.new_key_file.as_deref(),
+            NEW_PASSPHRASE_ENV,
+        )?
+        .unwrap_or(Unlock::None))
+    }
+}
+
+/// What opens the secret half of a signing key file.
+pub enum Unlock {
+    /// Key files are read and written in plaintext.
+    None,
+    Passphrase {
+        passphrase: Zeroizing<String>,
+        kdf: KdfParams,
+    },
+    /// A wrapping key derived from the contents of an external file.
+    KeyFile(Zeroizing<[u8; 32]>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost_kib: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kdf", rename_all = "snake_case")]
+pub enum Kdf {
+    Argon2id {
+        salt: String,
+        #[serde(flatten)]
+        params: KdfParams,
+    },
+    KeyFile,
+}
+
+/// `secret_key` encrypted under a wrapping key; the kid and public key are
+/// bound in as associated data, so a sealed secret cannot be moved to
+/// another key record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedSecret {
+    #[serde(flatten)]
+    pub kdf: Kdf,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// `keys/<kid>.json` and its copy `keys/current.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredKey {
+    pub kid: String,
+    /// Plaintext base64 secret, only in files written without an unlock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed_secret: Option<SealedSecret>,
+    pub public_key: String,
+    pub created_at: String,
+}
+
+impl Unlock {
+    /// `None` when no source was given.
+    fn resolve(stdin: bool, key_file: Option<&Path>, env: &str) -> Result<Option<Self>> {
+        if let Some(path) = key_file {
+            return Self::from_key_file(path).map(Some);
+        }
+        let passphrase = if stdin {
+            let mut line = Zeroizing::new(String::new());
+            std::io::stdin()
+                .read_line(&mut line)
+                .context("read passphrase from stdin failed")?;
+            Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string())
+        } else {
+            match std::env::var(env) {
+                Ok(value) => Zeroizing::new(value),
+                Err(_) => return Ok(None),
+            }
+        };
+        if passphrase.is_empty() {
+            return Err(anyhow!("key passphrase is empty"));
+        }
+        Ok(Some(Self::Passphrase {
+            passphrase,
+            kdf: KdfParams::default(),
+        }))
+    }
+
+    fn from_key_file(path: &Path) -> Result<Self> {
+        check_private(path)?;
+        let contents = Zeroizing::new(
+            fs::read(path).with_context(|| format!("failed to read key file {path:?}"))?,
+        );
+        if contents.len() < 32 {
+            return Err(anyhow!("key file {path:?} must hold at least 32 bytes"));
+        }
+        Ok(Self::KeyFile(Zeroizing::new(blake3::derive_key(
+            KEY_FILE_CONTEXT,
+            &contents,
+        ))))
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    fn wrapping_key(&self, kdf: &Kdf) -> Result<Zeroizing<[u8; 32]>> {
+        match (self, kdf) {
+            (Self::Passphrase { passphrase, .. }, Kdf::Argon2id { salt, params }) => {
+                let salt = B64.decode(salt).context("invalid base64 salt")?;
+                let params = Params::new(params.m_cost_kib, params.t_cost, params.p_cost, Some(32))
+                    .map_err(|err| anyhow!("invalid argon2 parameters: {err}"))?;
+                let mut key = Zeroizing::new([0u8; 32]);
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+                    .map_err(|err| anyhow!("derive wrapping key failed: {err}"))?;
+                Ok(key)
+            }
+            (Self::KeyFile(key), Kdf::KeyFile) => Ok(key.clone()),
+            (Self::None, _) => Err(anyhow!(
+                "signing key is encrypted; set {PASSPHRASE_ENV}, pass --passphrase-stdin or --key-file"
+            )),
+            (Self::Passphrase { .. }, Kdf::KeyFile) => Err(anyhow!(
+                "signing key is sealed with a key file; pass --key-file"
+            )),
+            (Self::KeyFile(_), Kdf::Argon2id { .. }) => Err(anyhow!(
+                "signing key is sealed with a passphrase; set {PASSPHRASE_ENV} or pass --passphrase-stdin"
+            )),
+        }
+    }
+
+    fn new_kdf(&self) -> Option<Kdf> {
+        match self {
+            Self::None => None,
+            Self::Passphrase { kdf, .. } => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                Some(Kdf::Argon2id {
+                    salt: B64.encode(salt),
+                    params: *kdf,
+                })
+            }
+            Self::KeyFile(_) => Some(Kdf::KeyFile),
+        }
+    }
+}
+
+impl StoredKey {
+    pub fn new(
+        kid: String,
+        signer: &SigningKey,
+        created_at: String,
+        unlock: &Unlock,
+    ) -> Result<Self> {
+        let mut record = Self {
+            kid,
+            secret_key: None,
+            sealed_secret: None,
+            public_key: B64.encode(signer.verifying_key().to_bytes()),
+            created_at,
+        };
+        record.seal(signer, unlock)?;
+        Ok(record)
+    }
+
+    fn associated_data(&self) -> Vec<u8> {
+        format!("aurea-key:{}:{}", self.kid, self.public_key).into_bytes()
+    }
+
+    fn seal(&mut self, signer: &SigningKey, unlock: &Unlock) -> Result<()> {
+        let Some(kdf) = unlock.new_kdf() else {
+            warn!(kid = %self.kid, "writing signing key unencrypted (--insecure-plaintext-keys)");
+            self.secret_key = Some(B64.encode(signer.to_bytes()));
+            self.sealed_secret = None;
+            return Ok(());
+        };
+        let key = unlock.wrapping_key(&kdf)?;
+        let mut nonce = [0u8; 24];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: signer.as_bytes(),
+                    aad: &self.associated_data(),
+                },
+            )
+            .map_err(|_| anyhow!("encrypt signing key failed"))?;
+        self.secret_key = None;
+        self.sealed_secret = Some(SealedSecret {
+            kdf,
+            cipher: CIPHER.to_string(),
+            nonce: B64.encode(nonce),
+            ciphertext: B64.encode(ciphertext),
+        });
+        Ok(())
+    }
+
+    pub fn signing_key(&self, unlock: &Unlock) -> Result<SigningKey> {
+        let secret = match (&self.sealed_secret, &self.secret_key) {
+            (Some(sealed), _) => {
+                if sealed.cipher != CIPHER {
+                    return Err(anyhow!("unsupported key cipher {}", sealed.cipher));
+                }
+                let key = unlock.wrapping_key(&sealed.kdf)?;
+                let nonce = B64.decode(&sealed.nonce).context("invalid base64 nonce")?;
+                if nonce.len() != 24 {
+                    return Err(anyhow!("invalid nonce length"));
+                }
+                let ciphertext = B64
+                    .decode(&sealed.ciphertext)
+                    .context("invalid base64 ciphertext")?;
+                Zeroizing::new(
+                    XChaCha20Poly1305::new(key.as_ref().into())
+                        .decrypt(
+                            XNonce::from_slice(&nonce),
+                            Payload {
+                                msg: &ciphertext,
+                                aad: &self.associated_data(),
+                            },
+                        )
+                        .map_err(|_| {
+                            anyhow!(
+                                "cannot decrypt key {}: wrong passphrase or key file",
+                                self.kid
+                            )
+                        })?,
+                )
+            }
+            (None, Some(plain)) => {
+                if !unlock.is_none() {
+                    warn!(kid = %self.kid, "signing key is stored unencrypted; run `aurea keys rekey`");
+                }
+                Zeroizing::new(B64.decode(plain).context("invalid base64 secret key")?)
+            }
+            (None, None) => return Err(anyhow!("key {} has no secret", self.kid)),
+        };
+        let bytes: &[u8; 32] = secret
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("invalid secret key length"))?;
+        let signer = SigningKey::from_bytes(bytes);
+        if B64.encode(signer.verifying_key().to_bytes()) != self.public_key {
+            return Err(anyhow!(
+                "secret of key {} does not match its public key",
+                self.kid
+            ));
+        }
+        Ok(signer)
+    }
+
+    /// The same key sealed under `new`.
+    pub fn rekeyed(&self, old: &Unlock, new: &Unlock) -> Result<Self> {
+        let signer = self.signing_key(old)?;
+        let mut record = self.clone();
+        record.seal(&signer, new)?;
+        Ok(record)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        check_private(path)?;
+        let raw = fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+        serde_json::from_str(&raw).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(self).context("failed to serialize key record")?;
+        write_private(path, &bytes)
+    }
+
+    /// Writes the record next to `path` without replacing it; returns the
+    /// staged file for [`commit_staged`].
+    pub fn stage(&self, path: &Path) -> Result<PathBuf> {
+        let bytes = serde_json::to_vec_pretty(self).context("failed to serialize key record")?;
+        stage_private(path, &bytes)
+    }
+}
+
+/// Refuses files that the group or other users can read or write.
+#[cfg(unix)]
+pub fn check_private(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .with_context(|| format!("failed to stat {path:?}"))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(anyhow!(
+            "{path:?} is accessible by other users (mode {:o}); run `chmod 600` on it",
+            mode & 0o777
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn check_private(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Writes owner-only, through a temporary file renamed into place.
+pub fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = stage_private(path, bytes)?;
+    commit_staged(&tmp, path)
+}
+
+/// Moves a file written by [`stage_private`] over `path`.
+pub fn commit_staged(staged: &Path, path: &Path) -> Result<()> {
+    fs::rename(staged, path).with_context(|| format!("failed to write {path:?}"))
+}
+
+/// Writes `bytes` owner-only and synced to `<path>.tmp`, leaving `path`
+/// untouched.
+pub fn stage_private(path: &Path, bytes: &[u8]) -> Result<PathBuf> {
+    let tmp = path.with_extension("json.tmp");
+    // A leftover tmp keeps whatever mode it had; start from a fresh file.
+    match fs::remove_file(&tmp) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            return Err(err).with_context(|| format!("failed to remove stale {tmp:?}"));
+        }
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .with_context(|| format!("failed to create {tmp:?}"))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("failed to write {tmp:?}"))?;
+    Ok(tmp)
+}
+
+/// Creates the keys directory readable by its owner only.
+pub fn create_keys_dir(keys_dir: &Path) -> Result<()> {
+    if keys_dir.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(keys_dir).context("failed to create keys directory")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(keys_dir, fs::Permissions::from_mode(0o700))
+            .context("failed to restrict keys directory")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn weak_passphrase(passphrase: &str) -> Unlock {
+        Unlock::Passphrase {
+            passphrase: Zeroizing::new(passphrase.to_string()),
+            kdf: KdfParams {
+                m_cost_kib: 64,
+                t_cost: 1,
+                p_cost: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn sealed_keys_open_only_with_their_passphrase() {
+        let signer = SigningKey::generate(&mut OsRng);
+        let unlock = weak_passphrase("correct horse");
+        let record =
+            StoredKey::new("k1".to_string(), &signer, "now".to_string(), &unlock).expect("seal");
+        assert!(record.secret_key.is_none());
+        let json = serde_json::to_string(&record).expect("json");
+        assert!(!json.contains(&B64.encode(signer.to_bytes())));
+
+        let opened = record.signing_key(&unlock).expect("open");
+        assert_eq!(opened.to_bytes(), signer.to_bytes());
+        assert!(record.signing_key(&weak_passphrase("wrong")).is_err());
+        assert!(record.signing_key(&Unlock::None).is_err());
+
+        let mut moved = record.clone();
+        moved.kid = "k2".to_string();
+        assert!(moved.signing_key(&unlock).is_err());
+
+        let rekeyed = record
+            .rekeyed(&unlock, &weak_passphrase("battery staple"))
+            .expect("rekey");
+        assert!(rekeyed.signing_key(&unlock).is_err());
+        assert_eq!(
+            rekeyed
+                .signing_key(&weak_passphrase("battery staple"))
+                .expect("open")
+                .to_bytes(),
+            signer.to_bytes()
+        );
+    }
+
+    #[test]
+    fn plaintext_keys_need_the_explicit_flag() {
+        let Err(err) = UnlockArgs::default().choose(None) else {
+            panic!("resolved without an unlock source");
+        };
+        assert!(
+            err.to_string().contains("--insecure-plaintext-keys"),
+            "{err:#}"
+        );
+        let args = UnlockArgs {
+            insecure_plaintext_keys: true,
+            ..UnlockArgs::default()
+        };
+        assert!(matches!(args.choose(None), Ok(Unlock::None)));
+
+        let Err(err) = args.choose(Some(weak_passphrase("hunter2"))) else {
+            panic!("plaintext flag accepted alongside a passphrase");
+        };
+        assert!(err.to_string().contains(PASSPHRASE_ENV), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_tmp_files_do_not_leak_their_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("aurea-keyfile-{}", Uuid::new_v4()));
+        create_keys_dir(&dir).expect("keys dir");
+        let path = dir.join("current.json");
+        let stale = path.with_extension("json.tmp");
+        fs::write(&stale, b"stale").expect("stale tmp");
+        fs::set_permissions(&stale, fs::Permissions::from_mode(0o644)).expect("chmod");
+
+        let tmp = stage_private(&path, b"{}").expect("stage");
+        let mode = fs::metadata(&tmp).expect("metadata").permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read(&tmp).expect("read"), b"{}");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn world_readable_key_files_are_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("aurea-keyfile-{}", Uuid::new_v4()));
+        create_keys_dir(&dir).expect("keys dir");
+        let path = dir.join("current.json");
+        let signer = SigningKey::generate(&mut OsRng);
+        let record = StoredKey::new("k1".to_string(), &signer, "now".to_string(), &Unlock::None)
+            .expect("plaintext");
+        record.save(&path).expect("save");
+        assert!(StoredKey::load(&path).is_ok());
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).expect("chmod");
+        let err = StoredKey::load(&path).expect_err("world-readable key file");
+        assert!(err.to_string().contains("chmod 600"), "{err:#}");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).expect("chmod");
+        assert!(StoredKey::load(&path).is_err(), "group-readable key file");
+
+        let key_file = dir.join("wrap.key");
+        write_private(&key_file, &[7u8; 32]).expect("key file");
+        let unlock = Unlock::from_key_file(&key_file).expect("key file unlock");
+        let sealed = record.rekeyed(&Unlock::None, &unlock).expect("seal");
+        assert!(sealed.signing_key(&weak_passphrase("x")).is_err());
+        assert!(sealed.signing_key(&unlock).is_ok());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
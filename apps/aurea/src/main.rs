@@ -1,50 +1,78 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::Infallible;
 use std::fs;
 use std::fs::File;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
 use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
 use arrow_ipc::writer::FileWriter as ArrowFileWriter;
 use arrow_schema::{DataType, Field, Schema};
 use async_stream::stream;
+use aurea_artifacts_store::{ArtifactStore, GcReport};
 use aurea_artifacts_vcx_pack::verify as verify_pack_file;
-use aurea_core::{Receipt, WorkStatus, WorkUnit, cid_of, to_nrf_bytes};
+use aurea_core::{
+    Priority, Receipt, ReceiptSignature, SignatureThreshold, TraceParent, WorkStatus, WorkUnit,
+    cid_of, to_nrf_bytes,
+};
 use aurea_plugins::{EchoPlugin, PluginRegistry, VcxWorkerPlugin};
 use aurea_policy::{DefaultPolicy, Policy, PolicyEntry as PolicyTraceEntry, Route};
-use aurea_receipts::{anchor_day, save_anchor};
-use aurea_runtime::{AcceptDisposition, ReceiptVerification, Runtime, RuntimeMetrics};
-use aurea_storage::RedbStore;
+use aurea_receipts::{
+    KeyJudgement, KeyMetadata, KeyRing, KeyStatus, KeyVerdict, LocalSigner, ReceiptSigner,
+    RemoteSigner, SignedKeySet, SignerAddress, anchor_day, load_anchor, save_anchor,
+    serve_signer_connection, verify_receipt_with_anchor,
+};
+use aurea_runtime::{
+    AcceptDisposition, CancelDisposition, CosignOutcome, ReceiptVerification, ReplayOutcome,
+    Runtime, RuntimeConfig, RuntimeMetrics, SignedReplayReport, StreamEvent,
+};
+use aurea_storage::{
+    BackupArchive, EventRetention, Histogram, MemoryStore, MetricsConfig, QueuePartition,
+    ReceiptCursor, ReceiptQuery, RedbStore, RetryConfig, RetryOn, SectionDigest, Store,
+    StoreConfig,
+};
 use aurea_ui_web::{
     Lang, PlanCardData, ReceiptArtifactView, ReceiptData, render_plan_card_html,
     render_receipt_html,
 };
 use axum::extract::{Path as AxumPath, Query, Request, State};
-use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::middleware::{self, Next};
 use axum::response::sse::{Event, KeepAlive, Sse};
-use axum::response::{Html, Response};
-use axum::routing::{get, post};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, post};
 use axum::{Json, Router};
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as B64;
-use chrono::{NaiveDate, Utc};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
 use ed25519_dalek::SigningKey;
 use parquet::arrow::ArrowWriter;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tokio::sync::RwLock;
-use tracing::info;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+mod keyfile;
+mod telemetry;
+
+use keyfile::{NewUnlockArgs, StoredKey, Unlock, UnlockArgs, create_keys_dir, write_private};
+use telemetry::TraceExport;
+
 const MAX_INTENT_PAYLOAD_BYTES: usize = 256 * 1024;
 const DUAL_CONTROL_PHRASE: &str = "Conferi e confirmo o plano.";
 const TENANT_RATE_LIMIT_PER_MINUTE: u32 = 120;
+const MEMORY_DB: &str = "memory:";
+const STREAM_REPLAY_PAGE: usize = 500;
+const RECEIPTS_PAGE_DEFAULT: usize = 100;
+const RECEIPTS_PAGE_MAX: usize = 1000;
 const UX_EVENTS: [&str; 6] = [
     "open_plan_card",
     "edit_slot",
@@ -61,6 +89,24 @@ struct Cli {
     command: Command,
 }
 
+#[derive(Args, Debug)]
+struct TraceArgs {
+    #[arg(long, value_name = "URL", conflicts_with = "trace_file")]
+    otlp_endpoint: Option<String>,
+    #[arg(long, value_name = "PATH")]
+    trace_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct SignerArgs {
+    #[command(flatten)]
+    unlock: UnlockArgs,
+    #[arg(long, value_name = "URL")]
+    remote_signer: Option<String>,
+    #[arg(long, default_value_t = 5000)]
+    remote_signer_timeout_ms: u64,
+}
+
 #[derive(Subcommand, Debug)]
 enum Command {
     Serve {
@@ -70,6 +116,50 @@ enum Command {
         db: String,
         #[arg(long, default_value = "./keys")]
         keys_dir: String,
+        #[arg(long = "topic-weight", value_name = "TOPIC=WEIGHT")]
+        topic_weights: Vec<String>,
+        #[arg(long, default_value_t = false)]
+        fair_by_tenant: bool,
+        #[arg(long, default_value_t = 3)]
+        max_attempts: u32,
+        #[arg(long = "topic-max-attempts", value_name = "TOPIC=ATTEMPTS")]
+        topic_max_attempts: Vec<String>,
+        #[arg(long, default_value_t = 500)]
+        retry_backoff_ms: u64,
+        #[arg(long, default_value_t = 60_000)]
+        retry_backoff_max_ms: u64,
+        #[arg(long = "retry-on", value_name = "PATTERN")]
+        retry_on: Vec<String>,
+        #[arg(long, default_value_t = 4)]
+        workers: usize,
+        #[arg(long = "topic-concurrency", value_name = "TOPIC=LIMIT")]
+        topic_concurrency: Vec<String>,
+        #[arg(long, default_value_t = 30_000)]
+        drain_timeout_ms: u64,
+        #[arg(long, default_value_t = 100_000)]
+        event_log_max: u64,
+        #[arg(long, default_value_t = 168)]
+        event_log_max_age_hours: i64,
+        #[arg(long, default_value_t = 100)]
+        metrics_max_tenants: usize,
+        #[arg(long, default_value_t = 50)]
+        metrics_max_topic_families: usize,
+        #[arg(long, default_value_t = 50)]
+        metrics_max_stages: usize,
+        #[arg(long, default_value = "./replays")]
+        replay_dir: String,
+        #[arg(long, default_value = "./artifacts")]
+        artifacts_dir: String,
+        #[arg(long = "cosign", value_name = "TOPIC=KID[,KID...]")]
+        cosign: Vec<String>,
+        #[arg(long, default_value = "./backups")]
+        backup_dir: String,
+        #[arg(long, value_name = "PATH")]
+        admin_token_file: Option<PathBuf>,
+        #[command(flatten)]
+        signer: Box<SignerArgs>,
+        #[command(flatten)]
+        traces: Box<TraceArgs>,
     },
     Keys {
         #[command(subcommand)]
@@ -87,6 +177,39 @@ enum Command {
         #[command(subcommand)]
         command: RetentionCommand,
     },
+    Artifacts {
+        #[command(subcommand)]
+        command: ArtifactsCommand,
+    },
+    DeadLetters {
+        #[command(subcommand)]
+        command: DeadLettersCommand,
+    },
+    Work {
+        #[command(subcommand)]
+        command: WorkCommand,
+    },
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    Replay {
+        receipt_cid: String,
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[arg(long)]
+        payload: Option<String>,
+        #[arg(long, default_value = "./replays")]
+        out_dir: String,
+        #[arg(long, default_value_t = false)]
+        keep_output: bool,
+        #[arg(long, default_value = "./artifacts")]
+        artifacts_dir: String,
+        #[command(flatten)]
+        signer: SignerArgs,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -94,17 +217,77 @@ enum KeysCommand {
     Rotate {
         #[arg(long, default_value = "./keys")]
         keys_dir: String,
+        #[command(flatten)]
+        unlock: UnlockArgs,
+    },
+    Rekey {
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[command(flatten)]
+        unlock: UnlockArgs,
+        #[command(flatten)]
+        new_unlock: NewUnlockArgs,
     },
     Revoke {
         #[arg(long, default_value = "./keys")]
         keys_dir: String,
         #[arg(long)]
         kid: String,
+        #[arg(long)]
+        compromised_at: Option<DateTime<Utc>>,
+    },
+    Window {
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[arg(long)]
+        kid: String,
+        #[arg(long)]
+        valid_from: Option<DateTime<Utc>>,
+        #[arg(long)]
+        valid_until: Option<DateTime<Utc>>,
+    },
+    Verify {
+        receipt: PathBuf,
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        /// Also checks the receipt against `<dir>/<day>.json`, the anchor of
+        /// the day it claims, when that file exists.
+        #[arg(long)]
+        anchors_dir: Option<String>,
     },
     List {
         #[arg(long, default_value = "./keys")]
         keys_dir: String,
     },
+    AddCosigner {
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[arg(long)]
+        kid: String,
+        #[arg(long)]
+        public_key: String,
+    },
+    Cosign {
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[arg(long)]
+        cid: String,
+        #[command(flatten)]
+        signer: SignerArgs,
+    },
+    ServeSigner {
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        /// `unix:/PATH`; the socket is created with mode 600.
+        #[arg(long, default_value = "unix:./keys/signer.sock")]
+        listen: String,
+        #[arg(long, default_value_t = 5000)]
+        io_timeout_ms: u64,
+        #[arg(long, default_value_t = 16)]
+        max_connections: usize,
+        #[command(flatten)]
+        unlock: UnlockArgs,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -136,20 +319,122 @@ enum RetentionCommand {
         db: String,
         #[arg(long, default_value_t = 30)]
         older_than_days: i64,
+        #[arg(long, default_value = "./artifacts")]
+        artifacts_dir: String,
+        #[arg(long, default_value_t = 60)]
+        artifact_grace_minutes: i64,
+        #[arg(long, default_value_t = false)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ArtifactsCommand {
+    Gc {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value = "./artifacts")]
+        artifacts_dir: String,
+        #[arg(long, default_value_t = 60)]
+        grace_minutes: i64,
         #[arg(long, default_value_t = false)]
         apply: bool,
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum DeadLettersCommand {
+    List {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+    },
+    Show {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long)]
+        seq: u64,
+    },
+    Requeue {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long)]
+        seq: u64,
+    },
+    Purge {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long)]
+        seq: Option<u64>,
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum WorkCommand {
+    Cancel {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value = "./keys")]
+        keys_dir: String,
+        #[arg(long)]
+        id: Uuid,
+        #[command(flatten)]
+        signer: SignerArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DbCommand {
+    Migrate {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    Backup {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value = "./backups")]
+        out_dir: String,
+    },
+    Restore {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long)]
+        archive: String,
+    },
+    Fsck {
+        #[arg(long, default_value = "./aurea.redb")]
+        db: String,
+        #[arg(long, default_value_t = false)]
+        repair: bool,
+    },
+}
+
 #[derive(Clone)]
 struct AppState {
     runtime: Runtime,
-    keyring: KeyRing,
+    keys: Arc<PublishedKeys>,
     policy: DefaultPolicy,
     previews: Arc<RwLock<HashMap<String, StoredPreview>>>,
     schemas: Arc<HashMap<String, SchemaSpec>>,
     tenant_rate: Arc<RwLock<HashMap<String, TenantRateWindow>>>,
     ux_events: Arc<RwLock<HashMap<String, u64>>>,
+    admin: Arc<AdminConfig>,
+}
+
+/// Settings for the `/v1/admin/*` routes. Without a token they are disabled.
+#[derive(Debug, Clone)]
+struct AdminConfig {
+    token: Option<blake3::Hash>,
+    backup_dir: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+struct ServerConfig {
+    drain_timeout: Duration,
+    admin: AdminConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -181,6 +466,9 @@ struct SubmitWorkRequest {
     payload: Value,
     idem_key: Option<String>,
     plan_hash: Option<String>,
+    #[serde(default)]
+    priority: Priority,
+    not_before: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -188,6 +476,8 @@ struct SubmitWorkResponse {
     status: String,
     work_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    not_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     receipt_cid: Option<String>,
     duplicate: bool,
     in_flight: bool,
@@ -201,6 +491,15 @@ struct VerifyReceiptRequest {
     receipt: Option<Receipt>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ReplayRequest {
+    receipt_cid: String,
+    #[serde(default)]
+    payload: Option<Value>,
+    #[serde(default)]
+    keep_output: bool,
+}
+
 #[derive(Debug, Serialize)]
 struct VerifyReceiptResponse {
     ok: bool,
@@ -209,8 +508,14 @@ struct VerifyReceiptResponse {
     key_known: bool,
     key_match: bool,
     key_revoked: bool,
+    threshold_met: bool,
+    signed_by: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    missing_kids: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     key_id: Option<String>,
+    /// The keyring's verdict on each signature at the receipt's `created_at`.
+    keys: Vec<KeyVerdict>,
     #[serde(skip_serializing_if = "Option::is_none")]
     reason: Option<String>,
 }
@@ -239,6 +544,17 @@ struct StreamQuery {
     id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ReceiptsQuery {
+    tenant: Option<String>,
+    topic: Option<String>,
+    status: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
 #[derive(Debug, Deserialize)]
 struct UiQuery {
     lang: Option<String>,
@@ -305,6 +621,9 @@ struct OcCommitRequest {
     idem_key: Option<String>,
     confirm_phrase: Option<String>,
     tenant: Option<String>,
+    #[serde(default)]
+    priority: Priority,
+    not_before: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -312,6 +631,8 @@ struct OcCommitResponse {
     status: String,
     work_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    not_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     receipt_cid: Option<String>,
     duplicate: bool,
     in_flight: bool,
@@ -331,6 +652,14 @@ struct ExportResponse {
     path: String,
 }
 
+#[derive(Debug, Serialize)]
+struct BackupResponse {
+    status: String,
+    cid: String,
+    path: String,
+    sections: Vec<SectionDigest>,
+}
+
 #[derive(Debug, Clone, Copy)]
 enum ExportFormat {
     Parquet,
@@ -376,100 +705,248 @@ struct ApiErrorPayload {
     message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct StoredKey {
-    kid: String,
-    secret_key: String,
-    public_key: String,
-    created_at: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "snake_case")]
-enum KeyStatus {
-    Active,
-    Retired,
-    Revoked,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct KeyMetadata {
-    kid: String,
-    public_key: String,
-    created_at: String,
-    status: KeyStatus,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    revoked_at: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct KeyRing {
-    active_kid: Option<String>,
-    keys: Vec<KeyMetadata>,
-}
-
-#[derive(Debug, Clone)]
-struct KeyPolicy {
-    known: bool,
-    key_match: bool,
-    revoked: bool,
-}
-
-impl KeyPolicy {
-    fn ok(&self) -> bool {
-        self.known && self.key_match && !self.revoked
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
-
     let cli = Cli::parse();
+    let export = match &cli.command {
+        Command::Serve { traces, .. } => TraceExport::from_args(
+            traces.otlp_endpoint.as_deref(),
+            traces.trace_file.as_deref(),
+        ),
+        _ => None,
+    };
+    let _telemetry = telemetry::init(export)?;
 
     match cli.command {
         Command::Serve {
             listen,
             db,
             keys_dir,
-        } => run_server(listen, db, keys_dir).await,
+            topic_weights,
+            fair_by_tenant,
+            max_attempts,
+            topic_max_attempts,
+            retry_backoff_ms,
+            retry_backoff_max_ms,
+            retry_on,
+            workers,
+            topic_concurrency,
+            drain_timeout_ms,
+            event_log_max,
+            event_log_max_age_hours,
+            metrics_max_tenants,
+            metrics_max_topic_families,
+            metrics_max_stages,
+            replay_dir,
+            artifacts_dir,
+            cosign,
+            backup_dir,
+            admin_token_file,
+            signer,
+            traces: _,
+        } => {
+            let mut store_config = store_config_from_args(&topic_weights, fair_by_tenant)?;
+            store_config.events =
+                event_retention_from_args(event_log_max, event_log_max_age_hours)?;
+            store_config.metrics = metrics_config_from_args(
+                metrics_max_tenants,
+                metrics_max_topic_families,
+                metrics_max_stages,
+            );
+            store_config.retry = retry_config_from_args(
+                max_attempts,
+                &topic_max_attempts,
+                retry_backoff_ms,
+                retry_backoff_max_ms,
+                &retry_on,
+            )?;
+            let mut runtime_config = runtime_config_from_args(workers, &topic_concurrency)?;
+            runtime_config.replay_dir = PathBuf::from(replay_dir);
+            runtime_config.artifacts = Some(ArtifactStore::open(artifacts_dir)?);
+            runtime_config.cosign = cosign_rules_from_args(&cosign)?;
+            run_server(
+                listen,
+                db,
+                keys_dir,
+                *signer,
+                store_config,
+                runtime_config,
+                ServerConfig {
+                    drain_timeout: Duration::from_millis(drain_timeout_ms),
+                    admin: admin_config_from_args(backup_dir, admin_token_file.as_deref())?,
+                },
+            )
+            .await
+        }
         Command::Keys { command } => run_keys_command(command),
         Command::Runtime { command } => run_runtime_command(command),
         Command::Anchors { command } => run_anchors_command(command),
         Command::Retention { command } => run_retention_command(command),
+        Command::Artifacts { command } => run_artifacts_command(command),
+        Command::DeadLetters { command } => run_dead_letters_command(command),
+        Command::Work { command } => run_work_command(command),
+        Command::Db { command } => run_db_command(command),
+        Command::Replay {
+            receipt_cid,
+            db,
+            keys_dir,
+            payload,
+            out_dir,
+            keep_output,
+            artifacts_dir,
+            signer,
+        } => {
+            let config = RuntimeConfig {
+                replay_dir: PathBuf::from(out_dir),
+                artifacts: Some(ArtifactStore::open(artifacts_dir)?),
+                ..RuntimeConfig::default()
+            };
+            run_replay_command(
+                &receipt_cid,
+                &db,
+                &keys_dir,
+                &signer,
+                payload.as_deref(),
+                config,
+                keep_output,
+            )
+            .await
+        }
     }
 }
 
 fn run_keys_command(command: KeysCommand) -> Result<()> {
     match command {
-        KeysCommand::Rotate { keys_dir } => {
-            let (record, _) = rotate_and_activate_key(Path::new(&keys_dir))?;
+        KeysCommand::Rotate { keys_dir, unlock } => {
+            let (record, _) = rotate_and_activate_key(Path::new(&keys_dir), &unlock.resolve()?)?;
             println!(
                 "rotated signing key: kid={} public_key={}",
                 record.kid, record.public_key
             );
         }
-        KeysCommand::Revoke { keys_dir, kid } => {
-            revoke_key(Path::new(&keys_dir), &kid)?;
-            println!("revoked signing key: kid={}", kid);
+        KeysCommand::Rekey {
+            keys_dir,
+            unlock,
+            new_unlock,
+        } => {
+            let old = unlock.resolve()?;
+            let new = new_unlock.resolve()?;
+            if new.is_none() {
+                return Err(anyhow!(
+                    "rekey needs a new passphrase or key file; set {}, pass --new-passphrase-stdin or --new-key-file",
+                    keyfile::NEW_PASSPHRASE_ENV
+                ));
+            }
+            let rekeyed = rekey_key_files(Path::new(&keys_dir), &old, &new)?;
+            println!("rekeyed {rekeyed} key files");
+        }
+        KeysCommand::Revoke {
+            keys_dir,
+            kid,
+            compromised_at,
+        } => {
+            let compromised_at = revoke_key(Path::new(&keys_dir), &kid, compromised_at)?;
+            println!(
+                "revoked signing key: kid={} compromised_at={}",
+                kid,
+                compromised_at.to_rfc3339()
+            );
+        }
+        KeysCommand::Window {
+            keys_dir,
+            kid,
+            valid_from,
+            valid_until,
+        } => {
+            set_key_window(Path::new(&keys_dir), &kid, valid_from, valid_until)?;
+            println!("updated validity window: kid={kid}");
+        }
+        KeysCommand::Verify {
+            receipt,
+            keys_dir,
+            anchors_dir,
+        } => {
+            let raw = fs::read_to_string(&receipt)
+                .with_context(|| format!("failed to read {receipt:?}"))?;
+            let receipt: Receipt = serde_json::from_str(&raw)
+                .with_context(|| format!("failed to parse {receipt:?}"))?;
+            let keyring = load_keyring(Path::new(&keys_dir))?.unwrap_or_default();
+            let anchor = anchors_dir
+                .map(|dir| {
+                    Path::new(&dir).join(format!("{}.json", receipt.created_at.date_naive()))
+                })
+                .filter(|path| path.exists())
+                .map(|path| load_anchor(&path))
+                .transpose()?;
+            let verdict = verify_receipt_with_anchor(&receipt, &keyring, anchor.as_ref());
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&verdict).context("serialize verdict failed")?
+            );
+            if !verdict.ok {
+                return Err(anyhow!(
+                    "receipt {} failed: {}",
+                    verdict.cid,
+                    verdict.reason
+                ));
+            }
         }
         KeysCommand::List { keys_dir } => {
             let ring = load_keyring(Path::new(&keys_dir))?.unwrap_or_default();
             println!(
-                "active_kid={}",
-                ring.active_kid.as_deref().unwrap_or("<none>")
+                "active_kid={} version={}",
+                ring.active_kid.as_deref().unwrap_or("<none>"),
+                ring.version
             );
+            let show = |at: Option<DateTime<Utc>>| at.map_or("-".to_string(), |at| at.to_rfc3339());
             for key in ring.keys {
                 println!(
-                    "kid={} status={:?} revoked_at={}",
+                    "kid={} status={:?} revoked_at={} valid_from={} valid_until={} compromised_at={}",
                     key.kid,
                     key.status,
-                    key.revoked_at.as_deref().unwrap_or("-")
+                    key.revoked_at.as_deref().unwrap_or("-"),
+                    show(key.valid_from),
+                    show(key.valid_until),
+                    show(key.compromised_at)
                 );
             }
         }
+        KeysCommand::AddCosigner {
+            keys_dir,
+            kid,
+            public_key,
+        } => {
+            add_cosigner_key(Path::new(&keys_dir), &kid, &public_key)?;
+            println!("added cosigning key: kid={kid}");
+        }
+        KeysCommand::Cosign {
+            keys_dir,
+            cid,
+            signer,
+        } => {
+            let (signer, _) = open_signer(Path::new(&keys_dir), &signer)?;
+            let signature = aurea_receipts::cosign_receipt(&cid, signer.as_ref())?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&signature).context("serialize signature failed")?
+            );
+        }
+        KeysCommand::ServeSigner {
+            keys_dir,
+            listen,
+            io_timeout_ms,
+            max_connections,
+            unlock,
+        } => {
+            let (kid, signing_key, _) =
+                load_or_create_key_material(Path::new(&keys_dir), &unlock.resolve()?)?;
+            serve_signer(
+                &listen,
+                LocalSigner::new(kid, signing_key),
+                Duration::from_millis(io_timeout_ms),
+                max_connections,
+            )?;
+        }
     }
     Ok(())
 }
@@ -481,8 +958,12 @@ fn run_runtime_command(command: RuntimeCommand) -> Result<()> {
                 return Err(anyhow!("MVP only supports `aurea runtime sweep --expired`"));
             }
             let store = RedbStore::open(&db)?;
-            let moved = store.reassign_expired_leases()?;
-            println!("sweep complete: reassigned_expired={moved}");
+            let report = store.reassign_expired_leases()?;
+            println!(
+                "sweep complete: reassigned_expired={} exhausted={}",
+                report.reassigned,
+                report.exhausted.len()
+            );
         }
     }
     Ok(())
@@ -491,14 +972,19 @@ fn run_runtime_command(command: RuntimeCommand) -> Result<()> {
 fn run_anchors_command(command: AnchorsCommand) -> Result<()> {
     match command {
         AnchorsCommand::Rebuild { date, db, out_dir } => {
-            parse_day(&date)?;
+            let (from, to) = day_range(&date)?;
             let store = RedbStore::open(&db)?;
-            let receipts = store.list_receipts()?;
-            let cids: Vec<String> = receipts_for_day(&receipts, &date)
+            let cids: Vec<String> = store
+                .query_receipts(&ReceiptQuery {
+                    from: Some(from),
+                    to: Some(to),
+                    ..ReceiptQuery::default()
+                })?
+                .receipts
                 .into_iter()
-                .map(|r| r.cid.clone())
+                .map(|r| r.cid)
                 .collect();
-            let anchor = anchor_day(&date, &cids);
+            let anchor = anchor_day(&date, &cids, store.clock().as_ref());
 
             let out_dir = Path::new(&out_dir);
             fs::create_dir_all(out_dir)
@@ -518,11 +1004,124 @@ fn run_anchors_command(command: AnchorsCommand) -> Result<()> {
     Ok(())
 }
 
+fn run_db_command(command: DbCommand) -> Result<()> {
+    match command {
+        DbCommand::Migrate { db, dry_run } => {
+            let plan = RedbStore::migration_plan(&db)?;
+            println!(
+                "schema migration: from=v{} to=v{} pending={} mode={}",
+                plan.from,
+                plan.to,
+                plan.steps.len(),
+                if dry_run { "dry-run" } else { "apply" }
+            );
+            for step in &plan.steps {
+                println!("step v{}: {}", step.version, step.description);
+            }
+            if !dry_run && !plan.is_empty() {
+                let store = RedbStore::open(&db)?;
+                println!("schema migrated: version=v{}", store.schema_version()?);
+            }
+        }
+        DbCommand::Backup { db, out_dir } => {
+            let store = RedbStore::open(&db)?;
+            let archive = BackupArchive::new(store.snapshot()?, store.clock().now())?;
+            let (cid, path) = write_backup(&archive, Path::new(&out_dir))?;
+            println!("backup: cid={cid} path={}", path.display());
+            for section in &archive.manifest.sections {
+                println!(
+                    "section {}: count={} cid={}",
+                    section.name, section.count, section.cid
+                );
+            }
+        }
+        DbCommand::Restore { db, archive } => {
+            let archive = BackupArchive::read_from(&archive)?;
+            verify_backup(&archive)?;
+            let store = RedbStore::open(&db)?;
+            store.restore(&archive.snapshot)?;
+            let snapshot = &archive.snapshot;
+            println!(
+                "restore: cid={} ready={} scheduled={} leased={} dead_letters={} receipts={} idem_keys={}",
+                archive.cid()?,
+                snapshot.ready_jobs.len(),
+                snapshot.scheduled_jobs.len(),
+                snapshot.leased_jobs.len(),
+                snapshot.dead_letters.len(),
+                snapshot.receipts.len(),
+                snapshot.idem_keys.len()
+            );
+        }
+        DbCommand::Fsck { db, repair } => {
+            let store = RedbStore::open(&db)?;
+            let report = store.fsck(repair)?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).context("serialize fsck report failed")?
+            );
+            let open = report.problems.iter().filter(|p| !p.repaired).count();
+            if open > 0 {
+                return Err(anyhow!("fsck found {open} unrepaired problems"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The admin token is read from a file so it stays out of `ps` and shell
+/// history; only its hash is kept.
+fn admin_config_from_args(backup_dir: String, token_file: Option<&Path>) -> Result<AdminConfig> {
+    let token = match token_file {
+        Some(path) => {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("read {} failed", path.display()))?;
+            let token = raw.trim();
+            if token.is_empty() {
+                return Err(anyhow!("admin token file {} is empty", path.display()));
+            }
+            Some(blake3::hash(token.as_bytes()))
+        }
+        None => None,
+    };
+    Ok(AdminConfig {
+        token,
+        backup_dir: PathBuf::from(backup_dir),
+    })
+}
+
+fn write_backup(archive: &BackupArchive, dir: &Path) -> Result<(String, PathBuf)> {
+    fs::create_dir_all(dir).with_context(|| format!("create {} failed", dir.display()))?;
+    let cid = archive.cid()?;
+    let path = dir.join(format!("aurea-backup-{cid}.json"));
+    archive.write_to(&path)?;
+    Ok((cid, path))
+}
+
+/// Rejects an archive whose manifest does not match its sections, or that
+/// holds any receipt with a wrong CID or signature. Nothing is imported
+/// unless every receipt checks out.
+fn verify_backup(archive: &BackupArchive) -> Result<()> {
+    archive.verify()?;
+    for receipt in &archive.snapshot.receipts {
+        let result = aurea_receipts::verify_receipt(receipt);
+        if !result.ok {
+            return Err(anyhow!(
+                "backup receipt {} failed verification: {}",
+                receipt.cid,
+                result.reason.unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn run_retention_command(command: RetentionCommand) -> Result<()> {
     match command {
         RetentionCommand::Receipts {
             db,
             older_than_days,
+            artifacts_dir,
+            artifact_grace_minutes,
             apply,
         } => {
             if older_than_days < 1 {
@@ -530,13 +1129,18 @@ fn run_retention_command(command: RetentionCommand) -> Result<()> {
             }
 
             let store = RedbStore::open(&db)?;
-            let receipts = store.list_receipts()?;
+            let total = store.count_receipts()?;
             let cutoff = Utc::now() - chrono::Duration::days(older_than_days);
-            let candidates = receipts_older_than(&receipts, cutoff);
+            let candidates = store
+                .query_receipts(&ReceiptQuery {
+                    to: Some(cutoff),
+                    ..ReceiptQuery::default()
+                })?
+                .receipts;
 
             println!(
                 "retention receipts: total={} cutoff={} candidates={} mode={}",
-                receipts.len(),
+                total,
                 cutoff.to_rfc3339(),
                 candidates.len(),
                 if apply { "apply" } else { "dry-run" }
@@ -555,28 +1159,375 @@ fn run_retention_command(command: RetentionCommand) -> Result<()> {
             if apply {
                 let report = store.purge_receipts(&candidates)?;
                 println!(
-                    "retention applied: deleted_receipts={} deleted_idem_keys={}",
-                    report.deleted_receipts, report.deleted_idem_keys
+                    "retention applied: deleted_receipts={} deleted_idem_keys={} deleted_blobs={} deleted_work_events={}",
+                    report.deleted_receipts,
+                    report.deleted_idem_keys,
+                    report.deleted_blobs,
+                    report.deleted_work_events
                 );
+                if Path::new(&artifacts_dir).is_dir() {
+                    let gc =
+                        collect_artifacts(&store, &artifacts_dir, artifact_grace_minutes, true)?;
+                    print_artifact_gc(&gc);
+                }
             }
         }
     }
     Ok(())
 }
 
-async fn run_server(listen: String, db: String, keys_dir: String) -> Result<()> {
-    let store = RedbStore::open(&db)?;
-    let mut plugins = PluginRegistry::new();
-    plugins.register(EchoPlugin);
-    plugins.register(VcxWorkerPlugin);
+fn run_artifacts_command(command: ArtifactsCommand) -> Result<()> {
+    match command {
+        ArtifactsCommand::Gc {
+            db,
+            artifacts_dir,
+            grace_minutes,
+            apply,
+        } => {
+            let store = RedbStore::open(&db)?;
+            let gc = collect_artifacts(&store, &artifacts_dir, grace_minutes, apply)?;
+            for cid in gc.unreferenced.iter().take(20) {
+                println!("candidate cid={cid}");
+            }
+            if gc.unreferenced.len() > 20 {
+                println!("... {} more candidates", gc.unreferenced.len() - 20);
+            }
+            print_artifact_gc(&gc);
+        }
+    }
+    Ok(())
+}
 
-    let (kid, signing_key, keyring) = load_or_create_key_material(Path::new(&keys_dir))?;
-    let runtime = Runtime::new_with_signer(store, plugins, signing_key, kid);
-    let _worker = runtime.start_background_worker();
+/// Artifacts no stored receipt lists, and not touched for `grace_minutes`,
+/// are garbage. The grace period covers jobs whose receipt is not written yet.
+fn collect_artifacts(
+    store: &dyn Store,
+    artifacts_dir: &str,
+    grace_minutes: i64,
+    apply: bool,
+) -> Result<GcReport> {
+    if grace_minutes < 0 {
+        return Err(anyhow!("--grace-minutes must be >= 0"));
+    }
+    let artifacts = ArtifactStore::open(artifacts_dir)?;
+    let live = store.live_artifacts()?;
+    let cutoff = Utc::now() - chrono::Duration::minutes(grace_minutes);
+    artifacts.collect_garbage(&live, cutoff, apply)
+}
+
+fn print_artifact_gc(gc: &GcReport) {
+    println!(
+        "artifact gc: scanned={} unreferenced={} bytes={} stale_staging={} mode={}",
+        gc.scanned,
+        gc.unreferenced.len(),
+        gc.unreferenced_bytes,
+        gc.stale_staging,
+        if gc.applied { "apply" } else { "dry-run" }
+    );
+}
+
+fn run_dead_letters_command(command: DeadLettersCommand) -> Result<()> {
+    match command {
+        DeadLettersCommand::List { db } => {
+            let store = RedbStore::open(&db)?;
+            let letters = store.list_dead_letters()?;
+            println!("dead letters: total={}", letters.len());
+            for letter in letters {
+                println!(
+                    "seq={} work_id={} tenant={} topic={} attempts={} dead_at={} reason={}",
+                    letter.job.seq,
+                    letter.job.work.id,
+                    letter.job.work.tenant,
+                    letter.job.work.topic,
+                    letter.job.attempt,
+                    letter.dead_at.to_rfc3339(),
+                    letter.reason
+                );
+            }
+        }
+        DeadLettersCommand::Show { db, seq } => {
+            let store = RedbStore::open(&db)?;
+            let letter = store
+                .get_dead_letter(seq)?
+                .ok_or_else(|| anyhow!("dead letter not found: seq={seq}"))?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&letter).context("serialize dead letter failed")?
+            );
+        }
+        DeadLettersCommand::Requeue { db, seq } => {
+            let store = RedbStore::open(&db)?;
+            let job = store
+                .requeue_dead_letter(seq)?
+                .ok_or_else(|| anyhow!("dead letter not found: seq={seq}"))?;
+            println!(
+                "requeued dead letter: seq={} work_id={}",
+                job.seq, job.work.id
+            );
+        }
+        DeadLettersCommand::Purge { db, seq, all } => {
+            if seq.is_none() && !all {
+                return Err(anyhow!("provide --seq <SEQ> or --all"));
+            }
+            let store = RedbStore::open(&db)?;
+            let purged = store.purge_dead_letters(seq)?;
+            println!("dead letters purged: {purged}");
+        }
+    }
+    Ok(())
+}
+
+fn run_work_command(command: WorkCommand) -> Result<()> {
+    match command {
+        WorkCommand::Cancel {
+            db,
+            keys_dir,
+            id,
+            signer,
+        } => {
+            let store = RedbStore::open(&db)?;
+            let (signer, _) = open_signer(Path::new(&keys_dir), &signer)?;
+            let runtime = Runtime::new_with_backend(
+                store,
+                PluginRegistry::new(),
+                signer,
+                RuntimeConfig::default(),
+            );
+            match runtime.cancel_work(id)? {
+                CancelDisposition::Cancelled { receipt_cid } => {
+                    println!("work cancelled: work_id={id} receipt_cid={receipt_cid}");
+                }
+                CancelDisposition::Cancelling => {
+                    println!("work cancelling: work_id={id}");
+                }
+                CancelDisposition::LeasedElsewhere { lease_expires_at } => {
+                    return Err(anyhow!(
+                        "work {id} is running under a lease held by the server until {}; cancel it with DELETE /v1/work/{id}",
+                        lease_expires_at.map_or("-".to_string(), |at| at.to_rfc3339())
+                    ));
+                }
+                CancelDisposition::NotFound => {
+                    return Err(anyhow!("no queued or running work with id {id}"));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints the signed report and fails unless the replay reproduced the
+/// receipt exactly.
+async fn run_replay_command(
+    receipt_cid: &str,
+    db: &str,
+    keys_dir: &str,
+    signer: &SignerArgs,
+    payload: Option<&str>,
+    config: RuntimeConfig,
+    keep_output: bool,
+) -> Result<()> {
+    let payload = match payload {
+        Some(path) => {
+            let raw = fs::read(path).with_context(|| format!("read payload {path} failed"))?;
+            Some(serde_json::from_slice::<Value>(&raw).context("parse payload failed")?)
+        }
+        None => None,
+    };
+    let store = RedbStore::open(db)?;
+    let (signer, _) = open_signer(Path::new(keys_dir), signer)?;
+    let runtime = Runtime::new_with_backend(store, default_plugins(), signer, config);
+    let report = match runtime.replay(receipt_cid, payload, keep_output).await? {
+        ReplayOutcome::Replayed(report) => report,
+        ReplayOutcome::NotFound => return Err(anyhow!("no receipt with cid {receipt_cid}")),
+        ReplayOutcome::MissingPayload => {
+            return Err(anyhow!(
+                "receipt {receipt_cid} has no stored payload; pass one with --payload"
+            ));
+        }
+        ReplayOutcome::PlanMismatch { expected, actual } => {
+            return Err(anyhow!(
+                "payload plan_hash {actual} does not match the receipt's {expected}"
+            ));
+        }
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).context("serialize replay report failed")?
+    );
+    if !report.report.identical {
+        return Err(anyhow!(
+            "replay differs from receipt {receipt_cid} in {} fields",
+            report.report.differences.len()
+        ));
+    }
+    Ok(())
+}
+
+fn default_plugins() -> PluginRegistry {
+    let mut plugins = PluginRegistry::new();
+    plugins.register(EchoPlugin);
+    plugins.register(VcxWorkerPlugin);
+    plugins
+}
+
+fn retry_config_from_args(
+    max_attempts: u32,
+    topic_max_attempts: &[String],
+    backoff_ms: u64,
+    backoff_max_ms: u64,
+    retry_on: &[String],
+) -> Result<RetryConfig> {
+    if max_attempts == 0 {
+        return Err(anyhow!("--max-attempts must be >= 1"));
+    }
+    let mut config = RetryConfig::default();
+    config.default.max_attempts = max_attempts;
+    config.default.backoff.initial_ms = backoff_ms;
+    config.default.backoff.max_ms = backoff_max_ms.max(backoff_ms);
+    if !retry_on.is_empty() {
+        config.default.retry_on = RetryOn::Matching(retry_on.to_vec());
+    }
+    for raw in topic_max_attempts {
+        let (topic, attempts) = raw.split_once('=').ok_or_else(|| {
+            anyhow!("invalid --topic-max-attempts `{raw}`; expected TOPIC=ATTEMPTS")
+        })?;
+        let attempts: u32 = attempts
+            .parse()
+            .with_context(|| format!("invalid attempts in --topic-max-attempts `{raw}`"))?;
+        if attempts == 0 {
+            return Err(anyhow!("--topic-max-attempts `{raw}` must be >= 1"));
+        }
+        let mut policy = config.default.clone();
+        policy.max_attempts = attempts;
+        config.topics.insert(topic.to_string(), policy);
+    }
+    Ok(config)
+}
+
+fn runtime_config_from_args(workers: usize, topic_concurrency: &[String]) -> Result<RuntimeConfig> {
+    if workers == 0 {
+        return Err(anyhow!("--workers must be >= 1"));
+    }
+    let mut config = RuntimeConfig {
+        workers,
+        ..RuntimeConfig::default()
+    };
+    for raw in topic_concurrency {
+        let (topic, limit) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid --topic-concurrency `{raw}`; expected TOPIC=LIMIT"))?;
+        let limit: usize = limit
+            .parse()
+            .with_context(|| format!("invalid limit in --topic-concurrency `{raw}`"))?;
+        if limit == 0 {
+            return Err(anyhow!("--topic-concurrency `{raw}` must be >= 1"));
+        }
+        config.topic_concurrency.insert(topic.to_string(), limit);
+    }
+    Ok(config)
+}
+
+/// `TOPIC=KID,KID` makes receipts of `TOPIC` wait for a signature from each
+/// listed key on top of the issuer's.
+fn cosign_rules_from_args(raw_rules: &[String]) -> Result<BTreeMap<String, SignatureThreshold>> {
+    let mut rules = BTreeMap::new();
+    for raw in raw_rules {
+        let (topic, kids) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid --cosign `{raw}`; expected TOPIC=KID[,KID...]"))?;
+        let required_kids = kids
+            .split(',')
+            .map(str::trim)
+            .filter(|kid| !kid.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if topic.is_empty() || required_kids.is_empty() {
+            return Err(anyhow!(
+                "--cosign `{raw}` needs a topic and at least one kid"
+            ));
+        }
+        rules.insert(
+            topic.to_string(),
+            SignatureThreshold {
+                min_signatures: required_kids.len() + 1,
+                required_kids,
+            },
+        );
+    }
+    Ok(rules)
+}
+
+/// Zero disables the respective limit.
+fn event_retention_from_args(max_events: u64, max_age_hours: i64) -> Result<EventRetention> {
+    if max_age_hours < 0 {
+        return Err(anyhow!("--event-log-max-age-hours must be >= 0"));
+    }
+    Ok(EventRetention {
+        max_events: (max_events > 0).then_some(max_events),
+        max_age: (max_age_hours > 0).then(|| chrono::Duration::hours(max_age_hours)),
+    })
+}
+
+/// Zero lifts the respective label cap.
+fn metrics_config_from_args(
+    max_tenants: usize,
+    max_topic_families: usize,
+    max_stages: usize,
+) -> MetricsConfig {
+    MetricsConfig {
+        max_tenants: (max_tenants > 0).then_some(max_tenants),
+        max_topic_families: (max_topic_families > 0).then_some(max_topic_families),
+        max_stages: (max_stages > 0).then_some(max_stages),
+    }
+}
 
+fn store_config_from_args(topic_weights: &[String], fair_by_tenant: bool) -> Result<StoreConfig> {
+    let mut config = StoreConfig::default();
+    if fair_by_tenant {
+        config.scheduler.partition = QueuePartition::TenantTopic;
+    }
+    for raw in topic_weights {
+        let (topic, weight) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid --topic-weight `{raw}`; expected TOPIC=WEIGHT"))?;
+        let weight: u32 = weight
+            .parse()
+            .with_context(|| format!("invalid weight in --topic-weight `{raw}`"))?;
+        if weight == 0 {
+            return Err(anyhow!("--topic-weight `{raw}` must be >= 1"));
+        }
+        config.scheduler.weights.insert(topic.to_string(), weight);
+    }
+    Ok(config)
+}
+
+async fn run_server(
+    listen: String,
+    db: String,
+    keys_dir: String,
+    signer: SignerArgs,
+    store_config: StoreConfig,
+    runtime_config: RuntimeConfig,
+    server: ServerConfig,
+) -> Result<()> {
+    let plugins = default_plugins();
+    let (signer, keyring, unlock) = open_signer_with_unlock(Path::new(&keys_dir), &signer)?;
+    let endorsers = retired_signers(Path::new(&keys_dir), &keyring, unlock.as_ref());
+    let keys = PublishedKeys::new(PathBuf::from(&keys_dir), signer.clone(), endorsers, keyring)?;
+    let runtime = if db == MEMORY_DB {
+        warn!("using the in-memory store; nothing survives a restart");
+        let store = MemoryStore::with_config(store_config);
+        Runtime::new_with_backend(store, plugins, signer, runtime_config)
+    } else {
+        let store = RedbStore::open_with_config(&db, store_config)?;
+        Runtime::new_with_backend(store, plugins, signer, runtime_config)
+    };
+    let worker = runtime.start_background_worker();
+
+    let shutdown_runtime = runtime.clone();
     let state = AppState {
         runtime,
-        keyring,
+        keys: Arc::new(keys),
         policy: DefaultPolicy,
         previews: Arc::new(RwLock::new(HashMap::new())),
         schemas: Arc::new(default_schemas()),
@@ -587,14 +1538,32 @@ async fn run_server(listen: String, db: String, keys_dir: String) -> Result<()>
                 .map(|name| ((*name).to_string(), 0u64))
                 .collect(),
         )),
+        admin: Arc::new(server.admin),
     };
+    let drain_timeout = server.drain_timeout;
+
+    // Destructive routes; reads of the same paths stay on the open router.
+    let admin = Router::new()
+        .route("/v1/admin/backup", post(admin_backup))
+        .route("/v1/dead_letters", delete(purge_dead_letters))
+        .route("/v1/dead_letters/{seq}", delete(purge_dead_letter))
+        .route("/v1/dead_letters/{seq}/requeue", post(requeue_dead_letter))
+        .layer(middleware::from_fn_with_state(state.clone(), require_admin));
 
     let app = Router::new()
         .route("/healthz", get(healthz))
         .route("/v1/work", post(submit_work))
+        .route("/v1/work/{id}", get(get_work).delete(cancel_work))
+        .route("/v1/work/{id}/events", get(work_events))
         .route("/v1/stream", get(stream_events))
+        .route("/v1/keys", get(get_keys))
+        .route("/v1/receipts", get(list_receipts))
         .route("/v1/receipts/{cid}", get(get_receipt))
+        .route("/v1/receipts/{cid}/cosign", post(cosign_receipt))
+        .route("/v1/blobs/{cid}", get(get_blob))
+        .route("/v1/artifacts/{cid}", get(get_artifact))
         .route("/v1/verify/receipt", post(verify_receipt))
+        .route("/v1/replay", post(replay_receipt))
         .route("/v1/verify/pack", post(verify_pack))
         .route("/v1/anchors/{day}", get(anchor_for_day))
         .route("/v1/ui/plan_card/{plan_hash}", get(ui_plan_card))
@@ -602,11 +1571,14 @@ async fn run_server(listen: String, db: String, keys_dir: String) -> Result<()>
         .route("/v1/ux/event", post(ux_event))
         .route("/v1/metrics", get(metrics))
         .route("/v1/export", post(export_data))
+        .route("/v1/dead_letters", get(list_dead_letters))
+        .route("/v1/dead_letters/{seq}", get(get_dead_letter))
         .route("/v1/capabilities", get(capabilities))
         .route("/v1/schema/{schema_id}/{v}", get(get_schema))
         .route("/v1/oc/parse_intent", post(parse_intent))
         .route("/v1/oc/plan_preview", post(plan_preview))
         .route("/v1/oc/commit", post(oc_commit))
+        .merge(admin)
         .layer(middleware::from_fn(with_standard_headers))
         .with_state(state);
 
@@ -617,18 +1589,60 @@ async fn run_server(listen: String, db: String, keys_dir: String) -> Result<()>
 
     info!(%addr, db, keys_dir, "aurea server listening");
     axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!("shutdown requested; draining in-flight jobs");
+            shutdown_runtime.shutdown();
+        })
         .await
         .context("axum server failed")?;
 
+    if tokio::time::timeout(drain_timeout, worker).await.is_err() {
+        warn!(
+            drain_timeout_ms = drain_timeout.as_millis() as u64,
+            "drain timed out; unfinished leases will be reassigned after expiry"
+        );
+    }
+
     Ok(())
 }
 
-async fn with_standard_headers(req: Request, next: Next) -> Response {
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!(error = %err, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                warn!(error = %err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+async fn with_standard_headers(mut req: Request, next: Next) -> Response {
     let request_id = req
         .headers()
         .get("x-request-id")
         .cloned()
         .unwrap_or_else(|| HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap());
+    req.headers_mut().insert("x-request-id", request_id.clone());
 
     let mut response = next.run(req).await;
     response
@@ -638,12 +1652,59 @@ async fn with_standard_headers(req: Request, next: Next) -> Response {
     response
 }
 
+/// Admits a request only with `Authorization: Bearer <token>` matching the
+/// configured admin token.
+async fn require_admin(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let Some(expected) = state.admin.token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            api_error(
+                "ADMIN_DISABLED",
+                "admin routes need --admin-token-file",
+                None,
+            ),
+        ));
+    };
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // blake3::Hash compares in constant time.
+    if presented.is_none_or(|token| blake3::hash(token.as_bytes()) != expected) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            api_error("UNAUTHORIZED", "missing or invalid admin token", None),
+        ));
+    }
+    Ok(next.run(req).await)
+}
+
+/// Carries a valid W3C `traceparent` and the request id into the work unit;
+/// a malformed `traceparent` is dropped and the runtime starts a new trace.
+fn attach_request_context(work: &mut WorkUnit, headers: &HeaderMap) {
+    work.traceparent = headers
+        .get("traceparent")
+        .and_then(|value| value.to_str().ok())
+        .and_then(TraceParent::parse)
+        .map(|parent| parent.to_string());
+    work.request_id = headers
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+}
+
 async fn healthz() -> Json<Value> {
     Json(json!({"ok": true}))
 }
 
 async fn submit_work(
     State(state): State<AppState>,
+    request_headers: HeaderMap,
     Json(req): Json<SubmitWorkRequest>,
 ) -> Result<(HeaderMap, Json<SubmitWorkResponse>), (StatusCode, HeaderMap, Json<Value>)> {
     if let Some(retry_after) = check_tenant_rate_limit(&state, &req.tenant).await {
@@ -666,6 +1727,10 @@ async fn submit_work(
     }
 
     let mut work = WorkUnit::new(req.tenant, req.topic, req.idem_key, req.payload);
+    work.priority = req.priority;
+    work.not_before = req.not_before;
+    attach_request_context(&mut work, &request_headers);
+    let scheduled_for = scheduled_for(&work, state.runtime.clock().now());
     let plan_hash = req
         .plan_hash
         .unwrap_or(work.plan_hash().map_err(internal_error_h)?);
@@ -688,8 +1753,9 @@ async fn submit_work(
         AcceptDisposition::Enqueued => {
             headers.insert("x-idempotent-replay", HeaderValue::from_static("false"));
             SubmitWorkResponse {
-                status: "accepted".to_string(),
+                status: accepted_status(&scheduled_for),
                 work_id: accepted.work_id.to_string(),
+                not_before: scheduled_for,
                 receipt_cid: None,
                 duplicate: false,
                 in_flight: false,
@@ -706,6 +1772,7 @@ async fn submit_work(
             SubmitWorkResponse {
                 status: "duplicate".to_string(),
                 work_id: accepted.work_id.to_string(),
+                not_before: None,
                 receipt_cid: Some(receipt_cid),
                 duplicate: true,
                 in_flight: false,
@@ -722,6 +1789,7 @@ async fn submit_work(
             SubmitWorkResponse {
                 status: "duplicate_in_flight".to_string(),
                 work_id: accepted.work_id.to_string(),
+                not_before: None,
                 receipt_cid: None,
                 duplicate: true,
                 in_flight: true,
@@ -763,44 +1831,288 @@ async fn check_tenant_rate_limit(state: &AppState, tenant: &str) -> Option<u32>
 
 async fn stream_events(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<StreamQuery>,
 ) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
     let mut rx = state.runtime.subscribe_events();
+    let runtime = state.runtime.clone();
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    // Subscribed first, so nothing falls between the replay and the live feed.
+    let history = match (last_event_id, query.id.as_deref().map(Uuid::parse_str)) {
+        (None, Some(Ok(work_id))) => runtime.work_events(work_id).unwrap_or_else(|err| {
+            warn!(%work_id, "failed to load work history: {err:#}");
+            Vec::new()
+        }),
+        _ => Vec::new(),
+    };
 
     let stream = stream! {
+        let mut last_seq = last_event_id.unwrap_or(0);
+        for event in history {
+            last_seq = last_seq.max(event.seq);
+            yield Ok::<Event, Infallible>(sse_event(&event));
+        }
+
+        let mut catch_up = last_event_id.is_some();
         loop {
-            let Ok(event) = rx.recv().await else {
-                break;
+            while catch_up {
+                let page = match runtime.events_after(last_seq, STREAM_REPLAY_PAGE) {
+                    Ok(page) => page,
+                    Err(err) => {
+                        warn!("failed to replay event log: {err:#}");
+                        break;
+                    }
+                };
+                if let Some(first) = page.first()
+                    && first.seq > last_seq + 1
+                {
+                    let gap = json!({"from": last_seq + 1, "to": first.seq - 1});
+                    yield Ok::<Event, Infallible>(Event::default().event("gap").data(gap.to_string()));
+                }
+                catch_up = page.len() == STREAM_REPLAY_PAGE;
+                for event in page {
+                    last_seq = event.seq;
+                    if stream_matches(&query, &event) {
+                        yield Ok::<Event, Infallible>(sse_event(&event));
+                    }
+                }
+            }
+
+            let received = tokio::select! {
+                received = rx.recv() => received,
+                _ = runtime.shutdown_requested() => break,
+            };
+            let event = match received {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "stream subscriber lagged; catching up from the event log");
+                    catch_up = true;
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
             };
 
-            if let Some(tenant) = &query.tenant
-                && &event.tenant != tenant
-            {
-                continue;
-            }
-            if let Some(topic) = &query.topic
-                && &event.topic != topic
-            {
-                continue;
+            // Unpersisted events carry seq 0 and are passed through as they are.
+            if event.seq != 0 {
+                if event.seq <= last_seq {
+                    continue;
+                }
+                last_seq = event.seq;
             }
-            if let Some(work_id) = &query.id
-                && event.work_id.to_string() != *work_id
-            {
-                continue;
+            if stream_matches(&query, &event) {
+                yield Ok::<Event, Infallible>(sse_event(&event));
             }
-
-            let sse_event = Event::default()
-                .event(status_name(event.status))
-                .json_data(&event)
-                .unwrap_or_else(|_| Event::default().event("error").data("serialization_error"));
-
-            yield Ok::<Event, Infallible>(sse_event);
         }
     };
 
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
+fn stream_matches(query: &StreamQuery, event: &StreamEvent) -> bool {
+    query
+        .tenant
+        .as_ref()
+        .is_none_or(|tenant| &event.tenant == tenant)
+        && query
+            .topic
+            .as_ref()
+            .is_none_or(|topic| &event.topic == topic)
+        && query
+            .id
+            .as_ref()
+            .is_none_or(|work_id| event.work_id.to_string() == *work_id)
+}
+
+fn sse_event(event: &StreamEvent) -> Event {
+    let sse = Event::default()
+        .event(status_name(event.status))
+        .json_data(event)
+        .unwrap_or_else(|_| Event::default().event("error").data("serialization_error"));
+    if event.seq == 0 {
+        sse
+    } else {
+        sse.id(event.seq.to_string())
+    }
+}
+
+async fn get_work(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let work_id = parse_work_id(&id)?;
+    match state.runtime.work_state(work_id).map_err(internal_error)? {
+        Some(work) => Ok(Json(serde_json::to_value(work).map_err(internal_error)?)),
+        None => Err(work_not_found(work_id)),
+    }
+}
+
+async fn work_events(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let work_id = parse_work_id(&id)?;
+    let events = state.runtime.work_events(work_id).map_err(internal_error)?;
+    if events.is_empty() {
+        return Err(work_not_found(work_id));
+    }
+    Ok(Json(json!({
+        "work_id": work_id,
+        "total": events.len(),
+        "events": events,
+    })))
+}
+
+async fn cancel_work(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let work_id = parse_work_id(&id)?;
+    // Cancelling queued work signs its receipt, possibly through a remote
+    // signer, so keep it off the async workers.
+    let runtime = state.runtime.clone();
+    let disposition = tokio::task::spawn_blocking(move || runtime.cancel_work(work_id))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?;
+    match disposition {
+        CancelDisposition::Cancelled { receipt_cid } => Ok((
+            StatusCode::OK,
+            Json(json!({
+                "status": "cancelled",
+                "work_id": work_id,
+                "receipt_cid": receipt_cid,
+            })),
+        )),
+        CancelDisposition::Cancelling => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({"status": "cancelling", "work_id": work_id})),
+        )),
+        CancelDisposition::LeasedElsewhere { lease_expires_at } => Err((
+            StatusCode::CONFLICT,
+            api_error(
+                "WORK_LEASED",
+                "work is leased by another process",
+                Some(json!({"work_id": work_id, "lease_expires_at": lease_expires_at})),
+            ),
+        )),
+        CancelDisposition::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            api_error(
+                "NOT_FOUND",
+                "no queued or running work with this id",
+                Some(json!({"work_id": work_id})),
+            ),
+        )),
+    }
+}
+
+fn parse_work_id(id: &str) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    Uuid::parse_str(id).map_err(|_| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            api_error(
+                "INVALID_WORK_ID",
+                "work id must be a UUID",
+                Some(json!({"id": id})),
+            ),
+        )
+    })
+}
+
+fn work_not_found(work_id: Uuid) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        api_error(
+            "NOT_FOUND",
+            "work not found",
+            Some(json!({"work_id": work_id})),
+        ),
+    )
+}
+
+async fn list_receipts(
+    State(state): State<AppState>,
+    Query(params): Query<ReceiptsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let invalid = |field: &str, value: &str| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            api_error(
+                "SCHEMA_INVALID",
+                &format!("invalid `{field}`"),
+                Some(json!({ field: value })),
+            ),
+        )
+    };
+    let status = params
+        .status
+        .as_deref()
+        .map(|raw| parse_status(raw).ok_or_else(|| invalid("status", raw)))
+        .transpose()?;
+    let from = params
+        .from
+        .as_deref()
+        .map(|raw| parse_rfc3339(raw).ok_or_else(|| invalid("from", raw)))
+        .transpose()?;
+    let to = params
+        .to
+        .as_deref()
+        .map(|raw| parse_rfc3339(raw).ok_or_else(|| invalid("to", raw)))
+        .transpose()?;
+    let after = params
+        .cursor
+        .as_deref()
+        .map(|raw| {
+            raw.parse::<ReceiptCursor>()
+                .map_err(|_| invalid("cursor", raw))
+        })
+        .transpose()?;
+    let limit = params
+        .limit
+        .unwrap_or(RECEIPTS_PAGE_DEFAULT)
+        .clamp(1, RECEIPTS_PAGE_MAX);
+
+    let page = state
+        .runtime
+        .query_receipts(&ReceiptQuery {
+            tenant: params.tenant,
+            topic: params.topic,
+            status,
+            work_id: None,
+            from,
+            to,
+            after,
+            limit: Some(limit),
+        })
+        .map_err(internal_error)?;
+    Ok(Json(json!({
+        "receipts": page.receipts,
+        "next_cursor": page.next.map(|cursor| cursor.to_string()),
+    })))
+}
+
+/// The keyring as a JWKS document, signed by the active key at startup.
+async fn get_keys(
+    State(state): State<AppState>,
+) -> Result<Json<SignedKeySet>, (StatusCode, Json<Value>)> {
+    let (_, key_set) = current_keys(&state).await?;
+    Ok(Json(key_set.as_ref().clone()))
+}
+
+async fn current_keys(
+    state: &AppState,
+) -> Result<(KeyRing, Arc<SignedKeySet>), (StatusCode, Json<Value>)> {
+    let keys = state.keys.clone();
+    tokio::task::spawn_blocking(move || keys.refresh())
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)
+}
+
 async fn get_receipt(
     State(state): State<AppState>,
     AxumPath(cid): AxumPath<String>,
@@ -814,6 +2126,144 @@ async fn get_receipt(
     }
 }
 
+/// Serves the stored bytes as-is so clients can hash them against the CID.
+async fn get_blob(
+    State(state): State<AppState>,
+    AxumPath(cid): AxumPath<String>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    match state.runtime.get_blob(&cid).map_err(internal_error)? {
+        Some(bytes) => Ok(([(header::CONTENT_TYPE, "application/json")], bytes).into_response()),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            api_error("NOT_FOUND", "blob not found", Some(json!({"cid": cid}))),
+        )),
+    }
+}
+
+async fn get_artifact(
+    State(state): State<AppState>,
+    AxumPath(cid): AxumPath<String>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    match state.runtime.get_artifact(&cid).map_err(internal_error)? {
+        Some(bytes) => {
+            Ok(([(header::CONTENT_TYPE, "application/octet-stream")], bytes).into_response())
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            api_error("NOT_FOUND", "artifact not found", Some(json!({"cid": cid}))),
+        )),
+    }
+}
+
+async fn list_dead_letters(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let letters = state.runtime.list_dead_letters().map_err(internal_error)?;
+    Ok(Json(json!({
+        "total": letters.len(),
+        "dead_letters": letters,
+    })))
+}
+
+async fn get_dead_letter(
+    State(state): State<AppState>,
+    AxumPath(seq): AxumPath<u64>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match state.runtime.get_dead_letter(seq).map_err(internal_error)? {
+        Some(letter) => Ok(Json(serde_json::to_value(letter).map_err(internal_error)?)),
+        None => Err(dead_letter_not_found(seq)),
+    }
+}
+
+async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    AxumPath(seq): AxumPath<u64>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let job = state
+        .runtime
+        .requeue_dead_letter(seq)
+        .map_err(internal_error)?
+        .ok_or_else(|| dead_letter_not_found(seq))?;
+    Ok(Json(json!({
+        "status": "requeued",
+        "seq": job.seq,
+        "work_id": job.work.id,
+    })))
+}
+
+async fn purge_dead_letter(
+    State(state): State<AppState>,
+    AxumPath(seq): AxumPath<u64>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let purged = state
+        .runtime
+        .purge_dead_letters(Some(seq))
+        .map_err(internal_error)?;
+    if purged == 0 {
+        return Err(dead_letter_not_found(seq));
+    }
+    Ok(Json(json!({"status": "purged", "purged": purged})))
+}
+
+async fn purge_dead_letters(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let purged = state
+        .runtime
+        .purge_dead_letters(None)
+        .map_err(internal_error)?;
+    Ok(Json(json!({"status": "purged", "purged": purged})))
+}
+
+fn dead_letter_not_found(seq: u64) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        api_error(
+            "NOT_FOUND",
+            "dead letter not found",
+            Some(json!({"seq": seq})),
+        ),
+    )
+}
+
+async fn replay_receipt(
+    State(state): State<AppState>,
+    Json(req): Json<ReplayRequest>,
+) -> Result<Json<SignedReplayReport>, (StatusCode, Json<Value>)> {
+    match state
+        .runtime
+        .replay(&req.receipt_cid, req.payload, req.keep_output)
+        .await
+        .map_err(internal_error)?
+    {
+        ReplayOutcome::Replayed(report) => Ok(Json(*report)),
+        ReplayOutcome::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            api_error(
+                "NOT_FOUND",
+                "receipt not found",
+                Some(json!({"cid": req.receipt_cid})),
+            ),
+        )),
+        ReplayOutcome::MissingPayload => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            api_error(
+                "PAYLOAD_REQUIRED",
+                "receipt has no stored payload; send one",
+                Some(json!({"cid": req.receipt_cid})),
+            ),
+        )),
+        ReplayOutcome::PlanMismatch { expected, actual } => Err((
+            StatusCode::CONFLICT,
+            api_error(
+                "PLAN_CONFLICT",
+                "payload does not match the receipt's plan_hash",
+                Some(json!({"expected": expected, "actual": actual})),
+            ),
+        )),
+    }
+}
+
 async fn verify_receipt(
     State(state): State<AppState>,
     Json(req): Json<VerifyReceiptRequest>,
@@ -846,20 +2296,83 @@ async fn verify_receipt(
         .runtime
         .verify_receipt(&receipt)
         .map_err(internal_error)?;
-    let key_policy = evaluate_key_policy(
-        &state.keyring,
-        &receipt.signature.kid,
-        &receipt.signature.public_key,
-    );
+    let (keyring, _) = current_keys(&state).await?;
+    let key_verdicts = keyring.judge_receipt(&receipt);
     bump_ux_event(&state, "verify_receipt").await;
 
     Ok(Json(map_verification(
         check,
-        key_policy,
-        &receipt.signature.kid,
+        key_verdicts,
+        receipt.issuer().map(|signature| signature.kid.clone()),
     )))
 }
 
+/// Adds an independent key's signature to a stored receipt. The key has to
+/// be in the keyring (`aurea keys add-cosigner`) and trusted now, when it
+/// signs, and the CID does not change.
+async fn cosign_receipt(
+    State(state): State<AppState>,
+    AxumPath(cid): AxumPath<String>,
+    Json(signature): Json<ReceiptSignature>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let Some(receipt) = state.runtime.get_receipt(&cid).map_err(internal_error)? else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            api_error("NOT_FOUND", "receipt not found", Some(json!({"cid": cid}))),
+        ));
+    };
+    // Same instant verification judges every signature at.
+    let (keyring, _) = current_keys(&state).await?;
+    let verdict = keyring.judge(&signature.kid, &signature.public_key, receipt.created_at);
+    if !verdict.ok() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            api_error(
+                "KEY_UNTRUSTED",
+                "cosigning key is not trusted",
+                Some(json!({"kid": signature.kid, "reason": verdict.reason})),
+            ),
+        ));
+    }
+    let kid = signature.kid.clone();
+    match state
+        .runtime
+        .cosign_receipt(&cid, signature)
+        .map_err(internal_error)?
+    {
+        CosignOutcome::Cosigned(receipt) => {
+            let check = state
+                .runtime
+                .verify_receipt(&receipt)
+                .map_err(internal_error)?;
+            Ok(Json(json!({
+                "receipt": receipt,
+                "verification": check,
+            })))
+        }
+        CosignOutcome::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            api_error("NOT_FOUND", "receipt not found", Some(json!({"cid": cid}))),
+        )),
+        CosignOutcome::AlreadySigned => Err((
+            StatusCode::CONFLICT,
+            api_error(
+                "ALREADY_SIGNED",
+                "key already signed this receipt",
+                Some(json!({"cid": cid, "kid": kid})),
+            ),
+        )),
+        CosignOutcome::InvalidSignature => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            api_error(
+                "SIGNATURE_INVALID",
+                "signature does not cover the receipt cid",
+                Some(json!({"cid": cid, "kid": kid})),
+            ),
+        )),
+    }
+}
+
 async fn verify_pack(
     Json(req): Json<VerifyPackRequest>,
 ) -> Result<Json<VerifyPackResponse>, (StatusCode, Json<Value>)> {
@@ -910,64 +2423,74 @@ async fn verify_pack(
 
 fn map_verification(
     check: ReceiptVerification,
-    key_policy: KeyPolicy,
-    kid: &str,
+    key_verdicts: Vec<KeyVerdict>,
+    issuer: Option<String>,
 ) -> VerifyReceiptResponse {
+    let has = |judgement| key_verdicts.iter().any(|v| v.judgement == judgement);
+    let untrusted = key_verdicts.iter().find(|verdict| !verdict.ok());
     let reason = if !check.cid_match {
         Some("receipt cid mismatch".to_string())
     } else if !check.signature_valid {
         Some("invalid receipt signature".to_string())
-    } else if !key_policy.known {
-        Some("key_id not found in keyring".to_string())
-    } else if !key_policy.key_match {
-        Some("public key mismatch for key_id".to_string())
-    } else if key_policy.revoked {
-        Some("key_id is revoked".to_string())
+    } else if let Some(verdict) = untrusted {
+        Some(verdict.reason.clone())
+    } else if !check.threshold_met {
+        Some(if check.missing_kids.is_empty() {
+            "not enough cosignatures".to_string()
+        } else {
+            format!(
+                "awaiting cosignatures from {}",
+                check.missing_kids.join(", ")
+            )
+        })
     } else {
         None
     };
 
     VerifyReceiptResponse {
-        ok: check.ok && key_policy.ok(),
+        ok: check.ok && untrusted.is_none(),
         cid_match: check.cid_match,
         signature_valid: check.signature_valid,
-        key_known: key_policy.known,
-        key_match: key_policy.key_match,
-        key_revoked: key_policy.revoked,
-        key_id: Some(kid.to_string()),
+        key_known: !has(KeyJudgement::Unknown),
+        key_match: !has(KeyJudgement::KeyMismatch),
+        key_revoked: has(KeyJudgement::Compromised),
+        threshold_met: check.threshold_met,
+        signed_by: check.signed_by,
+        missing_kids: check.missing_kids,
+        key_id: issuer,
+        keys: key_verdicts,
         reason,
     }
 }
 
-fn evaluate_key_policy(keyring: &KeyRing, kid: &str, public_key: &str) -> KeyPolicy {
-    let Some(meta) = keyring.keys.iter().find(|k| k.kid == kid) else {
-        return KeyPolicy {
-            known: false,
-            key_match: false,
-            revoked: false,
-        };
-    };
-
-    KeyPolicy {
-        known: true,
-        key_match: meta.public_key == public_key,
-        revoked: meta.status == KeyStatus::Revoked,
-    }
-}
-
 async fn anchor_for_day(
     State(state): State<AppState>,
     AxumPath(day): AxumPath<String>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let receipts = state.runtime.list_receipts().map_err(internal_error)?;
-    let mut cids = receipts
-        .iter()
-        .filter(|r| r.created_at.date_naive().to_string() == day)
-        .map(|r| r.cid.clone())
+    let (from, to) = day_range(&day).map_err(|_| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            api_error(
+                "SCHEMA_INVALID",
+                "day must be YYYY-MM-DD",
+                Some(json!({"day": day})),
+            ),
+        )
+    })?;
+    let cids = state
+        .runtime
+        .query_receipts(&ReceiptQuery {
+            from: Some(from),
+            to: Some(to),
+            ..ReceiptQuery::default()
+        })
+        .map_err(internal_error)?
+        .receipts
+        .into_iter()
+        .map(|r| r.cid)
         .collect::<Vec<_>>();
-    cids.sort();
 
-    let anchor = anchor_day(&day, &cids);
+    let anchor = anchor_day(&day, &cids, state.runtime.clock().as_ref());
     Ok(Json(json!({
         "date": anchor.date,
         "root": anchor.root,
@@ -1112,35 +2635,101 @@ async fn metrics(State(state): State<AppState>) -> Result<String, (StatusCode, J
 fn render_prometheus(metrics: &RuntimeMetrics, ux_events: &HashMap<String, u64>) -> String {
     let mut out = String::new();
 
-    out.push_str("# HELP queue_depth Current ready queue depth.\n");
+    let labelled = &metrics.labelled;
+
+    out.push_str(
+        "# HELP queue_depth Current ready queue depth, in total and by tenant and topic family.\n",
+    );
     out.push_str("# TYPE queue_depth gauge\n");
     out.push_str(&format!("queue_depth {}\n", metrics.queue_depth));
+    for ((tenant, family), depth) in &labelled.queue_depth {
+        out.push_str(&format!(
+            "queue_depth{{{}}} {depth}\n",
+            tenant_family_labels(tenant, family)
+        ));
+    }
+
+    out.push_str("# HELP scheduled_depth Jobs waiting for their not_before time.\n");
+    out.push_str("# TYPE scheduled_depth gauge\n");
+    out.push_str(&format!("scheduled_depth {}\n", metrics.scheduled_depth));
+
+    out.push_str("# HELP workers Configured runtime workers.\n");
+    out.push_str("# TYPE workers gauge\n");
+    out.push_str(&format!("workers {}\n", metrics.workers));
+
+    out.push_str("# HELP in_flight_jobs Jobs currently executing.\n");
+    out.push_str("# TYPE in_flight_jobs gauge\n");
+    out.push_str(&format!("in_flight_jobs {}\n", metrics.in_flight));
+
+    out.push_str("# HELP dead_letter_depth Jobs parked in the dead-letter table.\n");
+    out.push_str("# TYPE dead_letter_depth gauge\n");
+    out.push_str(&format!(
+        "dead_letter_depth {}\n",
+        metrics.dead_letter_depth
+    ));
 
     out.push_str("# HELP reassigns_total Total expired lease reassignments.\n");
     out.push_str("# TYPE reassigns_total counter\n");
     out.push_str(&format!("reassigns_total {}\n", metrics.reassigns_total));
 
-    out.push_str("# HELP jobs_total Total jobs by lifecycle status.\n");
+    out.push_str("# HELP jobs_total Total jobs by tenant, topic family and lifecycle status.\n");
     out.push_str("# TYPE jobs_total counter\n");
-    for (status, total) in &metrics.status_totals {
-        out.push_str(&format!("jobs_total{{status=\"{}\"}} {}\n", status, total));
+    for ((tenant, family, status), total) in &labelled.jobs_total {
+        out.push_str(&format!(
+            "jobs_total{{{},status=\"{}\"}} {total}\n",
+            tenant_family_labels(tenant, family),
+            escape_label(status)
+        ));
     }
 
+    // The unlabelled series is the all-tenant total; the labelled ones break
+    // it down, so aggregate with `{tenant=""}` or `{tenant!=""}`, not both.
+    let ttft = Histogram {
+        buckets: metrics.ttft_bucket_counts.clone(),
+        sum: metrics.ttft_sum_ms,
+        count: metrics.ttft_count,
+    };
     append_histogram(
         &mut out,
         "ttft_ms",
         "Time to first transition (accepted to assigned) in milliseconds.",
-        &metrics.ttft_bucket_counts,
-        metrics.ttft_sum_ms,
-        metrics.ttft_count,
+        std::iter::once((String::new(), &ttft)).chain(
+            labelled
+                .ttft
+                .iter()
+                .map(|((tenant, family), h)| (tenant_family_labels(tenant, family), h)),
+        ),
     );
+    let ttr = Histogram {
+        buckets: metrics.ttr_bucket_counts.clone(),
+        sum: metrics.ttr_sum_ms,
+        count: metrics.ttr_count,
+    };
     append_histogram(
         &mut out,
         "ttr_ms",
         "Time to result (accepted to done/fail) in milliseconds.",
-        &metrics.ttr_bucket_counts,
-        metrics.ttr_sum_ms,
-        metrics.ttr_count,
+        std::iter::once((String::new(), &ttr)).chain(
+            labelled
+                .ttr
+                .iter()
+                .map(|((tenant, family), h)| (tenant_family_labels(tenant, family), h)),
+        ),
+    );
+    append_histogram(
+        &mut out,
+        "stage_duration_ms",
+        "Plugin-reported stage durations in milliseconds.",
+        labelled.stages.iter().map(|((family, stage), h)| {
+            (
+                format!(
+                    "topic_family=\"{}\",stage=\"{}\"",
+                    escape_label(family),
+                    escape_label(stage)
+                ),
+                h,
+            )
+        }),
     );
 
     let done = metrics.status_totals.get("done").copied().unwrap_or(0);
@@ -1179,25 +2768,47 @@ fn render_prometheus(metrics: &RuntimeMetrics, ux_events: &HashMap<String, u64>)
     out
 }
 
-fn append_histogram(
+fn append_histogram<'a>(
     out: &mut String,
     name: &str,
     help: &str,
-    buckets: &[(u64, u64)],
-    sum: u64,
-    count: u64,
+    series: impl IntoIterator<Item = (String, &'a Histogram)>,
 ) {
     out.push_str(&format!("# HELP {name} {help}\n"));
     out.push_str(&format!("# TYPE {name} histogram\n"));
-    for (le, bucket_count) in buckets {
+    for (labels, histogram) in series {
+        let (bucket_prefix, braced) = if labels.is_empty() {
+            (String::new(), String::new())
+        } else {
+            (format!("{labels},"), format!("{{{labels}}}"))
+        };
+        for (le, bucket_count) in &histogram.buckets {
+            out.push_str(&format!(
+                "{name}_bucket{{{bucket_prefix}le=\"{le}\"}} {bucket_count}\n"
+            ));
+        }
         out.push_str(&format!(
-            "{name}_bucket{{le=\"{}\"}} {}\n",
-            le, bucket_count
+            "{name}_bucket{{{bucket_prefix}le=\"+Inf\"}} {}\n",
+            histogram.count
         ));
+        out.push_str(&format!("{name}_sum{braced} {}\n", histogram.sum));
+        out.push_str(&format!("{name}_count{braced} {}\n", histogram.count));
     }
-    out.push_str(&format!("{name}_bucket{{le=\"+Inf\"}} {}\n", count));
-    out.push_str(&format!("{name}_sum {}\n", sum));
-    out.push_str(&format!("{name}_count {}\n", count));
+}
+
+fn tenant_family_labels(tenant: &str, family: &str) -> String {
+    format!(
+        "tenant=\"{}\",topic_family=\"{}\"",
+        escape_label(tenant),
+        escape_label(family)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 async fn export_data(
@@ -1216,10 +2827,14 @@ async fn export_data(
         ));
     };
 
-    let mut receipts = state.runtime.list_receipts().map_err(internal_error)?;
-    if let Some(topic) = req.topic {
-        receipts.retain(|r| r.topic == topic);
-    }
+    let receipts = state
+        .runtime
+        .query_receipts(&ReceiptQuery {
+            topic: req.topic,
+            ..ReceiptQuery::default()
+        })
+        .map_err(internal_error)?
+        .receipts;
 
     let rows = receipts
         .iter()
@@ -1247,11 +2862,33 @@ async fn export_data(
         }
     };
 
-    Ok(Json(ExportResponse {
+    Ok(Json(ExportResponse {
+        status: "ok".to_string(),
+        format: format.as_str().to_string(),
+        records: rows.len(),
+        path: path.display().to_string(),
+    }))
+}
+
+async fn admin_backup(
+    State(state): State<AppState>,
+) -> Result<Json<BackupResponse>, (StatusCode, Json<Value>)> {
+    let runtime = state.runtime.clone();
+    let dir = state.admin.backup_dir.clone();
+    let (archive, cid, path) = tokio::task::spawn_blocking(move || {
+        let archive = runtime.backup()?;
+        let (cid, path) = write_backup(&archive, &dir)?;
+        anyhow::Ok((archive, cid, path))
+    })
+    .await
+    .map_err(internal_error)?
+    .map_err(internal_error)?;
+
+    Ok(Json(BackupResponse {
         status: "ok".to_string(),
-        format: format.as_str().to_string(),
-        records: rows.len(),
+        cid,
         path: path.display().to_string(),
+        sections: archive.manifest.sections,
     }))
 }
 
@@ -1592,6 +3229,7 @@ async fn plan_preview(
 
 async fn oc_commit(
     State(state): State<AppState>,
+    request_headers: HeaderMap,
     Json(req): Json<OcCommitRequest>,
 ) -> Result<Json<OcCommitResponse>, (StatusCode, Json<Value>)> {
     let preview = {
@@ -1644,7 +3282,11 @@ async fn oc_commit(
     );
 
     let idem_key = req.idem_key.or_else(|| Some(req.plan_hash.clone()));
-    let work = WorkUnit::new(tenant, preview.intent.topic, idem_key, payload);
+    let mut work = WorkUnit::new(tenant, preview.intent.topic, idem_key, payload);
+    work.priority = req.priority;
+    work.not_before = req.not_before;
+    attach_request_context(&mut work, &request_headers);
+    let scheduled_for = scheduled_for(&work, state.runtime.clock().now());
 
     let accepted = state
         .runtime
@@ -1654,8 +3296,9 @@ async fn oc_commit(
 
     let response = match accepted.disposition {
         AcceptDisposition::Enqueued => OcCommitResponse {
-            status: "accepted".to_string(),
+            status: accepted_status(&scheduled_for),
             work_id: accepted.work_id.to_string(),
+            not_before: scheduled_for,
             receipt_cid: None,
             duplicate: false,
             in_flight: false,
@@ -1663,6 +3306,7 @@ async fn oc_commit(
         AcceptDisposition::DuplicateReceipt { receipt_cid } => OcCommitResponse {
             status: "duplicate".to_string(),
             work_id: accepted.work_id.to_string(),
+            not_before: None,
             receipt_cid: Some(receipt_cid),
             duplicate: true,
             in_flight: false,
@@ -1670,6 +3314,7 @@ async fn oc_commit(
         AcceptDisposition::DuplicateInFlight => OcCommitResponse {
             status: "duplicate_in_flight".to_string(),
             work_id: accepted.work_id.to_string(),
+            not_before: None,
             receipt_cid: None,
             duplicate: true,
             in_flight: true,
@@ -1680,6 +3325,20 @@ async fn oc_commit(
     Ok(Json(response))
 }
 
+fn scheduled_for(work: &WorkUnit, now: DateTime<Utc>) -> Option<String> {
+    work.not_before
+        .filter(|_| !work.is_due(now))
+        .map(|at| at.to_rfc3339())
+}
+
+fn accepted_status(scheduled_for: &Option<String>) -> String {
+    if scheduled_for.is_some() {
+        "scheduled".to_string()
+    } else {
+        "accepted".to_string()
+    }
+}
+
 fn merge_payload_meta(payload: &Value, meta: Value) -> Value {
     if let Value::Object(existing) = payload {
         let mut cloned = existing.clone();
@@ -1849,29 +3508,32 @@ fn status_name(status: WorkStatus) -> &'static str {
         WorkStatus::Accepted => "accepted",
         WorkStatus::Assigned => "assigned",
         WorkStatus::Progress => "progress",
+        WorkStatus::Retrying => "retrying",
         WorkStatus::Done => "done",
         WorkStatus::Fail => "fail",
+        WorkStatus::Cancelled => "cancelled",
     }
 }
 
+fn parse_status(raw: &str) -> Option<WorkStatus> {
+    serde_json::from_value(Value::String(raw.to_string())).ok()
+}
+
+fn parse_rfc3339(raw: &str) -> Option<chrono::DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
 fn parse_day(day: &str) -> Result<NaiveDate> {
     NaiveDate::parse_from_str(day, "%Y-%m-%d")
         .with_context(|| format!("invalid --date value `{day}`; expected YYYY-MM-DD"))
 }
 
-fn receipts_for_day<'a>(receipts: &'a [Receipt], day: &str) -> Vec<&'a Receipt> {
-    receipts
-        .iter()
-        .filter(|receipt| receipt.created_at.date_naive().to_string() == day)
-        .collect()
-}
-
-fn receipts_older_than(receipts: &[Receipt], cutoff: chrono::DateTime<Utc>) -> Vec<Receipt> {
-    receipts
-        .iter()
-        .filter(|receipt| receipt.created_at < cutoff)
-        .cloned()
-        .collect()
+/// `[start, end)` of a UTC day.
+fn day_range(day: &str) -> Result<(chrono::DateTime<Utc>, chrono::DateTime<Utc>)> {
+    let start = parse_day(day)?.and_time(chrono::NaiveTime::MIN).and_utc();
+    Ok((start, start + chrono::Duration::days(1)))
 }
 
 fn api_error(code: &str, message: &str, details: Option<Value>) -> Json<Value> {
@@ -1884,75 +3546,104 @@ fn api_error(code: &str, message: &str, details: Option<Value>) -> Json<Value> {
     }))
 }
 
-fn load_or_create_key_material(keys_dir: &Path) -> Result<(String, SigningKey, KeyRing)> {
-    fs::create_dir_all(keys_dir).context("failed to create keys directory")?;
+fn load_or_create_key_material(
+    keys_dir: &Path,
+    unlock: &Unlock,
+) -> Result<(String, SigningKey, KeyRing)> {
+    create_keys_dir(keys_dir)?;
     let current_path = keys_dir.join("current.json");
 
     if current_path.exists() {
-        let raw = fs::read_to_string(&current_path).context("failed to read current key file")?;
-        let record: StoredKey =
-            serde_json::from_str(&raw).context("failed to parse current key file")?;
-        let signer = signing_key_from_record(&record)?;
-
-        let ring = load_keyring(keys_dir)?.unwrap_or_default();
-        let normalized = normalize_keyring_with_active(ring, &record);
-        persist_keyring(keys_dir, &normalized)?;
+        let record = StoredKey::load(&current_path).context("failed to load current key file")?;
+        let signer = record.signing_key(unlock)?;
+
+        let ring = load_published_keyring(keys_dir)?;
+        let mut normalized = normalize_keyring_with_active(
+            ring,
+            &record.kid,
+            &record.public_key,
+            &record.created_at,
+        )?;
+        persist_keyring(keys_dir, &mut normalized)?;
 
         return Ok((record.kid, signer, normalized));
     }
 
-    let (record, ring) = rotate_and_activate_key(keys_dir)?;
-    let signer = signing_key_from_record(&record)?;
+    if let Some(active) = load_published_keyring(keys_dir)?.active_kid {
+        return Err(anyhow!(
+            "{current_path:?} is missing but the keyring's active key is {active}; restore the key file or run `aurea keys rotate`"
+        ));
+    }
+    let (record, ring) = rotate_and_activate_key(keys_dir, unlock)?;
+    let signer = record.signing_key(unlock)?;
     Ok((record.kid, signer, ring))
 }
 
-fn rotate_and_activate_key(keys_dir: &Path) -> Result<(StoredKey, KeyRing)> {
-    fs::create_dir_all(keys_dir).context("failed to create keys directory")?;
+fn rotate_and_activate_key(keys_dir: &Path, unlock: &Unlock) -> Result<(StoredKey, KeyRing)> {
+    create_keys_dir(keys_dir)?;
 
     let signer = SigningKey::generate(&mut OsRng);
+    let now = Utc::now();
     let kid = format!(
         "{}-{:04x}",
-        Utc::now().format("%Y%m%d-%H%M%S"),
+        now.format("%Y%m%d-%H%M%S"),
         rand::random::<u16>()
     );
 
-    let record = StoredKey {
-        kid: kid.clone(),
-        secret_key: B64.encode(signer.to_bytes()),
-        public_key: B64.encode(signer.verifying_key().to_bytes()),
-        created_at: Utc::now().to_rfc3339(),
-    };
-
-    let mut ring = load_keyring(keys_dir)?.unwrap_or_default();
-    for key in &mut ring.keys {
-        if key.status == KeyStatus::Active {
-            key.status = KeyStatus::Retired;
-            key.revoked_at = None;
-        }
-    }
-
-    ring.keys.push(KeyMetadata {
-        kid: kid.clone(),
-        public_key: record.public_key.clone(),
-        created_at: record.created_at.clone(),
-        status: KeyStatus::Active,
-        revoked_at: None,
-    });
-    ring.active_kid = Some(kid.clone());
+    let record = StoredKey::new(kid.clone(), &signer, now.to_rfc3339(), unlock)?;
 
-    let kid_path = keys_dir.join(format!("{kid}.json"));
-    let current_path = keys_dir.join("current.json");
-    let bytes = serde_json::to_vec_pretty(&record).context("failed to serialize key record")?;
+    let mut ring = load_published_keyring(keys_dir)?;
+    activate_rotated_key(&mut ring, &kid, &record.public_key, &record.created_at, now);
 
-    fs::write(&kid_path, &bytes).with_context(|| format!("failed to write {kid_path:?}"))?;
-    fs::write(&current_path, &bytes)
-        .with_context(|| format!("failed to write {current_path:?}"))?;
-    persist_keyring(keys_dir, &ring)?;
+    record.save(&keys_dir.join(format!("{kid}.json")))?;
+    record.save(&keys_dir.join("current.json"))?;
+    persist_keyring(keys_dir, &mut ring)?;
 
     Ok((record, ring))
 }
 
-fn revoke_key(keys_dir: &Path, kid: &str) -> Result<()> {
+/// Re-seals every key file under `new`. All files are opened with `old`
+/// before any is rewritten, so a wrong passphrase leaves the directory as
+/// it was.
+fn rekey_key_files(keys_dir: &Path, old: &Unlock, new: &Unlock) -> Result<usize> {
+    let mut records = Vec::new();
+    for entry in fs::read_dir(keys_dir).with_context(|| format!("failed to read {keys_dir:?}"))? {
+        let path = entry
+            .with_context(|| format!("failed to read {keys_dir:?}"))?
+            .path();
+        if path.extension().is_none_or(|ext| ext != "json") || path == keyring_path(keys_dir) {
+            continue;
+        }
+        let record = StoredKey::load(&path)?;
+        records.push((path, record.rekeyed(old, new)?));
+    }
+    // Stage every file before replacing any, so a failure midway leaves the
+    // old keys untouched.
+    let mut staged = Vec::with_capacity(records.len());
+    for (path, record) in &records {
+        match record.stage(path) {
+            Ok(tmp) => staged.push((tmp, path)),
+            Err(err) => {
+                for (tmp, _) in &staged {
+                    let _ = fs::remove_file(tmp);
+                }
+                return Err(err);
+            }
+        }
+    }
+    for (tmp, path) in &staged {
+        keyfile::commit_staged(tmp, path)?;
+    }
+    Ok(records.len())
+}
+
+/// Marks `kid` revoked. Receipts it signed before `compromised_at` (default:
+/// now) keep verifying; later ones do not. Returns the compromise time.
+fn revoke_key(
+    keys_dir: &Path,
+    kid: &str,
+    compromised_at: Option<DateTime<Utc>>,
+) -> Result<DateTime<Utc>> {
     let mut ring =
         load_keyring(keys_dir)?.ok_or_else(|| anyhow!("keyring not found, nothing to revoke"))?;
 
@@ -1967,18 +3658,78 @@ fn revoke_key(keys_dir: &Path, kid: &str) -> Result<()> {
         return Err(anyhow!("kid {} not found in keyring", kid));
     };
 
+    let now = Utc::now();
+    let compromised_at = compromised_at.unwrap_or(now);
     meta.status = KeyStatus::Revoked;
-    meta.revoked_at = Some(Utc::now().to_rfc3339());
+    meta.revoked_at = Some(now.to_rfc3339());
+    meta.compromised_at = Some(compromised_at);
+
+    persist_keyring(keys_dir, &mut ring)?;
+    Ok(compromised_at)
+}
+
+fn set_key_window(
+    keys_dir: &Path,
+    kid: &str,
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let mut ring = load_keyring(keys_dir)?.ok_or_else(|| anyhow!("keyring not found"))?;
+    let Some(meta) = ring.keys.iter_mut().find(|k| k.kid == kid) else {
+        return Err(anyhow!("kid {} not found in keyring", kid));
+    };
+    let valid_from = valid_from.or(meta.valid_from);
+    let valid_until = valid_until.or(meta.valid_until);
+    if let (Some(from), Some(until)) = (valid_from, valid_until)
+        && from >= until
+    {
+        return Err(anyhow!("valid_from must be before valid_until"));
+    }
+    meta.valid_from = valid_from;
+    meta.valid_until = valid_until;
+    persist_keyring(keys_dir, &mut ring)
+}
+
+fn add_cosigner_key(keys_dir: &Path, kid: &str, public_key: &str) -> Result<()> {
+    let bytes = B64
+        .decode(public_key.as_bytes())
+        .context("invalid base64 public key")?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("invalid public key length"))?;
+    ed25519_dalek::VerifyingKey::from_bytes(&bytes).context("invalid public key")?;
 
-    persist_keyring(keys_dir, &ring)
+    let mut ring = load_published_keyring(keys_dir)?;
+    if ring.keys.iter().any(|k| k.kid == kid) {
+        return Err(anyhow!("kid {} is already in the keyring", kid));
+    }
+    ring.keys.push(KeyMetadata {
+        kid: kid.to_string(),
+        public_key: public_key.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        status: KeyStatus::Cosigner,
+        revoked_at: None,
+        valid_from: None,
+        valid_until: None,
+        compromised_at: None,
+    });
+    create_keys_dir(keys_dir)?;
+    persist_keyring(keys_dir, &mut ring)
 }
 
-fn normalize_keyring_with_active(mut ring: KeyRing, active: &StoredKey) -> KeyRing {
+fn normalize_keyring_with_active(
+    mut ring: KeyRing,
+    kid: &str,
+    public_key: &str,
+    created_at: &str,
+) -> Result<KeyRing> {
+    check_known_public_key(&ring, kid, public_key)?;
     for key in &mut ring.keys {
-        if key.kid == active.kid {
+        if key.kid == kid {
             key.status = KeyStatus::Active;
-            key.public_key = active.public_key.clone();
-            key.created_at = active.created_at.clone();
+            key.public_key = public_key.to_string();
+            key.created_at = created_at.to_string();
             key.revoked_at = None;
         } else if key.status == KeyStatus::Active {
             key.status = KeyStatus::Retired;
@@ -1986,24 +3737,344 @@ fn normalize_keyring_with_active(mut ring: KeyRing, active: &StoredKey) -> KeyRi
         }
     }
 
-    if !ring.keys.iter().any(|k| k.kid == active.kid) {
+    if !ring.keys.iter().any(|k| k.kid == kid) {
         ring.keys.push(KeyMetadata {
-            kid: active.kid.clone(),
-            public_key: active.public_key.clone(),
-            created_at: active.created_at.clone(),
+            kid: kid.to_string(),
+            public_key: public_key.to_string(),
+            created_at: created_at.to_string(),
             status: KeyStatus::Active,
             revoked_at: None,
+            valid_from: parse_rfc3339(created_at),
+            valid_until: None,
+            compromised_at: None,
         });
     }
 
-    ring.active_kid = Some(active.kid.clone());
-    ring
+    ring.active_kid = Some(kid.to_string());
+    Ok(ring)
+}
+
+/// Refuses a key that reuses a kid the keyring already holds with another
+/// public key.
+fn check_known_public_key(ring: &KeyRing, kid: &str, public_key: &str) -> Result<()> {
+    match ring.get(kid) {
+        Some(known) if known.public_key != public_key => Err(anyhow!(
+            "key {kid} does not match the public key the keyring holds for it"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Makes `kid` the active key from `now` on and closes the window of the key
+/// it replaces.
+fn activate_rotated_key(
+    ring: &mut KeyRing,
+    kid: &str,
+    public_key: &str,
+    created_at: &str,
+    now: DateTime<Utc>,
+) {
+    for key in &mut ring.keys {
+        if key.status == KeyStatus::Active {
+            key.status = KeyStatus::Retired;
+            key.revoked_at = None;
+            key.valid_until = Some(key.valid_until.map_or(now, |until| until.min(now)));
+        }
+    }
+    ring.keys.push(KeyMetadata {
+        kid: kid.to_string(),
+        public_key: public_key.to_string(),
+        created_at: created_at.to_string(),
+        status: KeyStatus::Active,
+        revoked_at: None,
+        valid_from: Some(now),
+        valid_until: None,
+        compromised_at: None,
+    });
+    ring.active_kid = Some(kid.to_string());
+}
+
+/// The signer receipts are issued with: `--remote-signer` when given, the key
+/// in `keys_dir/current.json` otherwise. A remote key the keyring has not seen
+/// is rotated in as the active key so that receipts it signs verify here; a
+/// known one must already be active.
+fn open_signer(keys_dir: &Path, args: &SignerArgs) -> Result<(Arc<dyn ReceiptSigner>, KeyRing)> {
+    let (signer, keyring, _) = open_signer_with_unlock(keys_dir, args)?;
+    Ok((signer, keyring))
+}
+
+/// [`open_signer`], plus what opens the local key files when there is one:
+/// always with a local key, and with a remote signer only if an unlock
+/// source was given.
+fn open_signer_with_unlock(
+    keys_dir: &Path,
+    args: &SignerArgs,
+) -> Result<(Arc<dyn ReceiptSigner>, KeyRing, Option<Unlock>)> {
+    let Some(remote) = &args.remote_signer else {
+        let unlock = args.unlock.resolve()?;
+        let (kid, signing_key, keyring) = load_or_create_key_material(keys_dir, &unlock)?;
+        return Ok((
+            Arc::new(LocalSigner::new(kid, signing_key)),
+            keyring,
+            Some(unlock),
+        ));
+    };
+    let signer = RemoteSigner::connect(
+        SignerAddress::parse(remote)?,
+        Duration::from_millis(args.remote_signer_timeout_ms),
+    )
+    .with_context(|| format!("connect to remote signer {remote} failed"))?;
+    create_keys_dir(keys_dir)?;
+    let mut ring = load_published_keyring(keys_dir)?;
+    let kid = signer.key_id().to_string();
+    let public_key = B64.encode(signer.verifying_key().to_bytes());
+    check_known_public_key(&ring, &kid, &public_key)
+        .with_context(|| format!("remote signer {remote} refused"))?;
+    match ring.get(&kid) {
+        None => {
+            let now = Utc::now();
+            activate_rotated_key(&mut ring, &kid, &public_key, &now.to_rfc3339(), now);
+        }
+        Some(_) if ring.active_kid.as_deref() == Some(kid.as_str()) => {}
+        Some(known) => {
+            return Err(anyhow!(
+                "remote signer key {kid} is {:?} in the keyring, not active",
+                known.status
+            ));
+        }
+    }
+    persist_keyring(keys_dir, &mut ring)?;
+    info!(%remote, kid, "signing receipts with a remote signer");
+    Ok((Arc::new(signer), ring, args.unlock.resolve().ok()))
+}
+
+/// The retired keys of `ring` whose files are still in `keys_dir`, to endorse
+/// the published key set. Compromised keys never endorse; files that do not
+/// open are skipped.
+fn retired_signers(keys_dir: &Path, ring: &KeyRing, unlock: Option<&Unlock>) -> Vec<LocalSigner> {
+    let Some(unlock) = unlock else {
+        return Vec::new();
+    };
+    ring.keys
+        .iter()
+        .filter(|key| key.status == KeyStatus::Retired && key.compromised_at.is_none())
+        .filter_map(|key| {
+            let path = keys_dir.join(format!("{}.json", key.kid));
+            if !path.exists() {
+                return None;
+            }
+            let opened = StoredKey::load(&path).and_then(|record| {
+                if record.kid != key.kid || record.public_key != key.public_key {
+                    return Err(anyhow!("{path:?} does not hold key {}", key.kid));
+                }
+                record.signing_key(unlock)
+            });
+            match opened {
+                Ok(signing_key) => Some(LocalSigner::new(key.kid.clone(), signing_key)),
+                Err(err) => {
+                    warn!(kid = %key.kid, error = %err, "retired key will not endorse the key set");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// What `GET /v1/keys` serves, kept in step with `keyring.json`: whenever its
+/// version moves the set is signed again, as long as the keyring's active key
+/// is still the one this process signs with.
+struct PublishedKeys {
+    keys_dir: PathBuf,
+    signer: Arc<dyn ReceiptSigner>,
+    endorsers: Vec<LocalSigner>,
+    current: std::sync::RwLock<(KeyRing, Arc<SignedKeySet>)>,
+}
+
+impl PublishedKeys {
+    fn new(
+        keys_dir: PathBuf,
+        signer: Arc<dyn ReceiptSigner>,
+        endorsers: Vec<LocalSigner>,
+        keyring: KeyRing,
+    ) -> Result<Self> {
+        let key_set = publish_key_set(&keyring, signer.as_ref(), &endorsers)?;
+        Ok(Self {
+            keys_dir,
+            signer,
+            endorsers,
+            current: std::sync::RwLock::new((keyring, Arc::new(key_set))),
+        })
+    }
+
+    fn refresh(&self) -> Result<(KeyRing, Arc<SignedKeySet>)> {
+        let current = self
+            .current
+            .read()
+            .map_err(|_| anyhow!("published keys lock poisoned"))?
+            .clone();
+        let Some(ring) = load_keyring(&self.keys_dir)? else {
+            return Ok(current);
+        };
+        if ring.version == current.0.version {
+            return Ok(current);
+        }
+        if ring.active_kid.as_deref() != Some(self.signer.key_id()) {
+            warn!(
+                active_kid = ?ring.active_kid,
+                kid = self.signer.key_id(),
+                "keyring has a new active key; restart to publish it"
+            );
+            return Ok(current);
+        }
+        let key_set = Arc::new(publish_key_set(
+            &ring,
+            self.signer.as_ref(),
+            &self.endorsers,
+        )?);
+        info!(
+            version = ring.version,
+            "keyring changed, key set signed again"
+        );
+        let mut guard = self
+            .current
+            .write()
+            .map_err(|_| anyhow!("published keys lock poisoned"))?;
+        if guard.0.version < ring.version {
+            *guard = (ring, key_set);
+        }
+        Ok(guard.clone())
+    }
+}
+
+/// Signs `ring` with the active key, endorsed by those of `endorsers` that
+/// are still retired and uncompromised in it.
+fn publish_key_set(
+    ring: &KeyRing,
+    signer: &dyn ReceiptSigner,
+    endorsers: &[LocalSigner],
+) -> Result<SignedKeySet> {
+    let endorsers = endorsers
+        .iter()
+        .filter(|endorser| {
+            ring.get(endorser.key_id())
+                .is_some_and(|key| key.status == KeyStatus::Retired && key.compromised_at.is_none())
+        })
+        .map(|endorser| endorser as &dyn ReceiptSigner)
+        .collect::<Vec<_>>();
+    aurea_receipts::sign_key_set(ring, signer, &endorsers, Utc::now())
+        .context("sign key set failed")
+}
+
+/// Answers the remote signer protocol on `listen` until the process is
+/// killed, one thread per connection and at most `max_connections` at once.
+/// The protocol has no authentication, so it is only served on a Unix socket
+/// that its owner alone may open.
+fn serve_signer(
+    listen: &str,
+    signer: LocalSigner,
+    io_timeout: Duration,
+    max_connections: usize,
+) -> Result<()> {
+    let pool = SignerPool {
+        signer: Arc::new(signer),
+        open: Arc::new(AtomicUsize::new(0)),
+        max_connections,
+    };
+    match SignerAddress::parse(listen)? {
+        SignerAddress::Tcp(addr) => {
+            return Err(anyhow!(
+                "signer listens only on a unix: socket, not {addr}; anyone who can reach a TCP port could have it sign"
+            ));
+        }
+        #[cfg(unix)]
+        SignerAddress::Unix(path) => {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("remove stale socket {} failed", path.display()))?;
+            }
+            // The socket is created owner-only, never briefly open to others.
+            // SAFETY: umask only swaps the process file mode mask.
+            let previous = unsafe { libc::umask(0o177) };
+            let bound = std::os::unix::net::UnixListener::bind(&path);
+            // SAFETY: as above, restoring the mask read before the bind.
+            unsafe { libc::umask(previous) };
+            let listener =
+                bound.with_context(|| format!("bind signer on {} failed", path.display()))?;
+            println!(
+                "signer listening: kid={} socket={}",
+                pool.signer.key_id(),
+                path.display()
+            );
+            for stream in listener.incoming() {
+                let stream = stream.context("accept signer connection failed")?;
+                stream.set_read_timeout(Some(io_timeout))?;
+                stream.set_write_timeout(Some(io_timeout))?;
+                pool.spawn(stream);
+            }
+        }
+    }
+    Ok(())
+}
+
+struct SignerPool {
+    signer: Arc<LocalSigner>,
+    open: Arc<AtomicUsize>,
+    max_connections: usize,
+}
+
+impl SignerPool {
+    /// Serves `stream` on its own thread, or closes it straight away when
+    /// `max_connections` are already being served.
+    fn spawn(&self, stream: impl std::io::Read + std::io::Write + Send + 'static) {
+        if self.open.fetch_add(1, Ordering::AcqRel) >= self.max_connections {
+            self.open.fetch_sub(1, Ordering::AcqRel);
+            warn!(
+                max_connections = self.max_connections,
+                "signer busy, dropping connection"
+            );
+            return;
+        }
+        let signer = self.signer.clone();
+        let open = self.open.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = serve_signer_connection(stream, signer.as_ref()) {
+                warn!(error = %err, "signer request failed");
+            }
+            open.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
 }
 
 fn keyring_path(keys_dir: &Path) -> PathBuf {
     keys_dir.join("keyring.json")
 }
 
+/// The keyring to build on when changing it. A missing `keyring.json` is only
+/// a fresh start when no key was ever written; otherwise starting over would
+/// publish version 1 again and clients that pinned a later one would see a
+/// rollback.
+fn load_published_keyring(keys_dir: &Path) -> Result<KeyRing> {
+    if let Some(ring) = load_keyring(keys_dir)? {
+        return Ok(ring);
+    }
+    if keys_dir.is_dir() {
+        for entry in
+            fs::read_dir(keys_dir).with_context(|| format!("failed to read {keys_dir:?}"))?
+        {
+            let path = entry
+                .with_context(|| format!("failed to read {keys_dir:?}"))?
+                .path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                return Err(anyhow!(
+                    "{:?} is missing but {path:?} is not; restore the keyring from a backup instead of starting a new one",
+                    keyring_path(keys_dir)
+                ));
+            }
+        }
+    }
+    Ok(KeyRing::default())
+}
+
 fn load_keyring(keys_dir: &Path) -> Result<Option<KeyRing>> {
     let path = keyring_path(keys_dir);
     if !path.exists() {
@@ -2015,22 +4086,24 @@ fn load_keyring(keys_dir: &Path) -> Result<Option<KeyRing>> {
     Ok(Some(ring))
 }
 
-fn persist_keyring(keys_dir: &Path, ring: &KeyRing) -> Result<()> {
-    let path = keyring_path(keys_dir);
+/// Writes the keyring, bumping its version whenever the keys or the active
+/// kid differ from what is on disk.
+fn persist_keyring(keys_dir: &Path, ring: &mut KeyRing) -> Result<()> {
+    let content = |ring: &KeyRing| serde_json::to_value((&ring.active_kid, &ring.keys));
+    let stored = load_keyring(keys_dir)?;
+    let version = stored
+        .as_ref()
+        .map_or(ring.version, |stored| stored.version.max(ring.version));
+    let changed = match &stored {
+        Some(stored) => {
+            content(stored).context("failed to serialize keyring")?
+                != content(ring).context("failed to serialize keyring")?
+        }
+        None => true,
+    };
+    ring.version = if changed { version + 1 } else { version };
     let bytes = serde_json::to_vec_pretty(ring).context("failed to serialize keyring")?;
-    fs::write(&path, &bytes).with_context(|| format!("failed to write {path:?}"))?;
-    Ok(())
-}
-
-fn signing_key_from_record(record: &StoredKey) -> Result<SigningKey> {
-    let secret = B64
-        .decode(record.secret_key.as_bytes())
-        .context("invalid base64 secret key")?;
-    let key: [u8; 32] = secret
-        .as_slice()
-        .try_into()
-        .map_err(|_| anyhow!("invalid secret key length"))?;
-    Ok(SigningKey::from_bytes(&key))
+    write_private(&keyring_path(keys_dir), &bytes)
 }
 
 fn internal_error(err: impl std::fmt::Display) -> (StatusCode, Json<Value>) {
@@ -2053,6 +4126,8 @@ mod tests {
     use super::*;
     use std::collections::BTreeMap;
 
+    use aurea_receipts::verify_receipt_with_keyring;
+    use aurea_storage::LabelledMetrics;
     use chrono::{Duration, TimeZone};
     use uuid::Uuid;
 
@@ -2068,16 +4143,46 @@ mod tests {
             policy_trace: vec![],
             stage_time_ms: BTreeMap::new(),
             artifacts: vec![],
+            attempts: vec![],
             created_at,
-            signature: aurea_core::ReceiptSignature {
+            signatures: vec![aurea_core::ReceiptSignature {
                 alg: "ed25519".to_string(),
                 kid: "kid-1".to_string(),
                 public_key: "pk".to_string(),
                 signature: "sig".to_string(),
-            },
+            }],
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
         }
     }
 
+    #[test]
+    fn request_context_drops_malformed_traceparent() {
+        let mut work = WorkUnit::new("acme".into(), "echo:test".into(), None, json!({}));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            HeaderValue::from_static("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01"),
+        );
+        headers.insert("x-request-id", HeaderValue::from_static("req-1"));
+        attach_request_context(&mut work, &headers);
+        assert_eq!(work.traceparent, None);
+        assert_eq!(work.request_id.as_deref(), Some("req-1"));
+
+        headers.insert(
+            "traceparent",
+            HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        );
+        attach_request_context(&mut work, &headers);
+        assert_eq!(
+            work.trace_id().as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+    }
+
     #[test]
     fn missing_paths_detects_absent_fields() {
         let payload = json!({"a": 1});
@@ -2127,6 +4232,138 @@ mod tests {
         assert!(ExportFormat::parse("csv").is_none());
     }
 
+    #[test]
+    fn store_config_parses_topic_weights() {
+        let config =
+            store_config_from_args(&["echo=4".to_string(), "vcx:commit=2".to_string()], true)
+                .expect("valid weights");
+        assert_eq!(config.scheduler.partition, QueuePartition::TenantTopic);
+        assert_eq!(config.scheduler.weight_for("echo:test"), 4);
+        assert_eq!(config.scheduler.weight_for("vcx:commit"), 2);
+        assert_eq!(config.scheduler.weight_for("science:run"), 1);
+        assert!(store_config_from_args(&["echo".to_string()], false).is_err());
+        assert!(store_config_from_args(&["echo=0".to_string()], false).is_err());
+    }
+
+    #[test]
+    fn retry_config_parses_topic_attempts() {
+        let config = retry_config_from_args(
+            5,
+            &["vcx=1".to_string()],
+            200,
+            1_000,
+            &["timeout".to_string()],
+        )
+        .expect("valid retry args");
+        assert_eq!(config.policy_for("echo:test").max_attempts, 5);
+        assert_eq!(config.policy_for("vcx:commit").max_attempts, 1);
+        assert_eq!(config.policy_for("echo:test").backoff.delay_ms(4), 1_000);
+        assert!(config.default.retry_on.allows("plugin timeout"));
+        assert!(!config.default.retry_on.allows("schema invalid"));
+        assert!(retry_config_from_args(0, &[], 200, 1_000, &[]).is_err());
+        assert!(retry_config_from_args(3, &["vcx=0".to_string()], 200, 1_000, &[]).is_err());
+    }
+
+    #[test]
+    fn runtime_config_parses_topic_concurrency() {
+        let config = runtime_config_from_args(8, &["vcx:*=2".to_string()]).expect("valid args");
+        assert_eq!(config.workers, 8);
+        assert_eq!(config.topic_concurrency.get("vcx:*"), Some(&2));
+        assert!(runtime_config_from_args(0, &[]).is_err());
+        assert!(runtime_config_from_args(4, &["vcx:*=0".to_string()]).is_err());
+        assert!(runtime_config_from_args(4, &["vcx".to_string()]).is_err());
+    }
+
+    #[test]
+    fn cosign_rules_count_the_issuer_towards_the_threshold() {
+        let rules =
+            cosign_rules_from_args(&["vcx:commit=auditor, legal".to_string()]).expect("valid args");
+        let rule = rules.get("vcx:commit").expect("rule");
+        assert_eq!(rule.required_kids, ["auditor", "legal"]);
+        assert_eq!(rule.min_signatures, 3);
+        assert!(cosign_rules_from_args(&["vcx:commit".to_string()]).is_err());
+        assert!(cosign_rules_from_args(&["vcx:commit=".to_string()]).is_err());
+        assert!(cosign_rules_from_args(&["=auditor".to_string()]).is_err());
+    }
+
+    #[test]
+    fn event_retention_treats_zero_as_unlimited() {
+        let retention = event_retention_from_args(0, 24).expect("valid args");
+        assert_eq!(retention.max_events, None);
+        assert_eq!(retention.max_age, Some(chrono::Duration::hours(24)));
+        let retention = event_retention_from_args(500, 0).expect("valid args");
+        assert_eq!(retention.max_events, Some(500));
+        assert_eq!(retention.max_age, None);
+        assert!(event_retention_from_args(10, -1).is_err());
+    }
+
+    #[test]
+    fn metrics_caps_treat_zero_as_unlimited() {
+        let config = metrics_config_from_args(0, 20, 5);
+        assert_eq!(config.max_tenants, None);
+        assert_eq!(config.max_topic_families, Some(20));
+        assert_eq!(config.max_stages, Some(5));
+    }
+
+    #[test]
+    fn prometheus_series_carry_tenant_and_topic_family() {
+        let mut labelled = LabelledMetrics::default();
+        labelled.jobs_total.insert(
+            ("acme".to_string(), "echo".to_string(), "done".to_string()),
+            3,
+        );
+        labelled
+            .queue_depth
+            .insert(("a\"b".to_string(), "vcx".to_string()), 2);
+        labelled.stages.insert(
+            ("echo".to_string(), "plan".to_string()),
+            Histogram {
+                buckets: vec![(10, 0), (50, 1)],
+                sum: 40,
+                count: 1,
+            },
+        );
+        let metrics = RuntimeMetrics {
+            queue_depth: 2,
+            scheduled_depth: 0,
+            leased_depth: 0,
+            in_flight: 0,
+            workers: 1,
+            dead_letter_depth: 0,
+            reassigns_total: 0,
+            receipts_total: 0,
+            plugins_total: 0,
+            status_totals: BTreeMap::new(),
+            ttft_sum_ms: 300,
+            ttft_count: 1,
+            ttr_sum_ms: 0,
+            ttr_count: 0,
+            ttft_bucket_counts: vec![(250, 0), (500, 1)],
+            ttr_bucket_counts: Vec::new(),
+            labelled,
+        };
+        let text = render_prometheus(&metrics, &HashMap::new());
+        assert!(
+            text.contains("jobs_total{tenant=\"acme\",topic_family=\"echo\",status=\"done\"} 3\n")
+        );
+        assert!(text.contains("queue_depth{tenant=\"a\\\"b\",topic_family=\"vcx\"} 2\n"));
+        assert!(text.contains(
+            "stage_duration_ms_bucket{topic_family=\"echo\",stage=\"plan\",le=\"50\"} 1\n"
+        ));
+        assert!(text.contains("stage_duration_ms_count{topic_family=\"echo\",stage=\"plan\"} 1\n"));
+        assert!(text.contains("\nqueue_depth 2\n"));
+        assert!(text.contains("\nttft_ms_bucket{le=\"500\"} 1\n"));
+        assert!(text.contains("\nttft_ms_sum 300\n"));
+        assert!(text.contains("\nttr_ms_count 0\n"));
+
+        let idle = RuntimeMetrics {
+            queue_depth: 0,
+            labelled: LabelledMetrics::default(),
+            ..metrics
+        };
+        assert!(render_prometheus(&idle, &HashMap::new()).contains("\nqueue_depth 0\n"));
+    }
+
     #[test]
     fn parse_day_requires_iso_date() {
         assert!(parse_day("2026-02-19").is_ok());
@@ -2134,22 +4371,43 @@ mod tests {
     }
 
     #[test]
-    fn receipts_helpers_filter_by_day_and_cutoff() {
+    fn receipt_queries_filter_by_day_and_cutoff() {
+        let path = std::env::temp_dir().join(format!("aurea-app-receipts-{}.redb", Uuid::new_v4()));
+        let store = RedbStore::open(&path).expect("open redb");
         let now = Utc
             .with_ymd_and_hms(2026, 2, 19, 12, 0, 0)
             .single()
             .expect("valid fixed datetime");
-        let old = make_receipt("old", now - Duration::days(40));
-        let fresh = make_receipt("fresh", now - Duration::days(2));
-        let receipts = vec![old.clone(), fresh.clone()];
-
-        let by_day = receipts_for_day(&receipts, "2026-02-17");
+        store
+            .put_receipt(&make_receipt("old", now - Duration::days(40)))
+            .expect("put old");
+        store
+            .put_receipt(&make_receipt("fresh", now - Duration::days(2)))
+            .expect("put fresh");
+
+        let (from, to) = day_range("2026-02-17").expect("valid day");
+        let by_day = store
+            .query_receipts(&ReceiptQuery {
+                from: Some(from),
+                to: Some(to),
+                ..ReceiptQuery::default()
+            })
+            .expect("query day")
+            .receipts;
         assert_eq!(by_day.len(), 1);
         assert_eq!(by_day[0].cid, "fresh");
 
-        let older = receipts_older_than(&receipts, now - Duration::days(30));
+        let older = store
+            .query_receipts(&ReceiptQuery {
+                to: Some(now - Duration::days(30)),
+                ..ReceiptQuery::default()
+            })
+            .expect("query cutoff")
+            .receipts;
         assert_eq!(older.len(), 1);
         assert_eq!(older[0].cid, "old");
+
+        let _ = std::fs::remove_file(&path);
     }
 
     #[test]
@@ -2212,13 +4470,19 @@ mod tests {
                 path: "./packs/sample.vcxpack".to_string(),
                 size_bytes: 123,
             }],
+            attempts: vec![],
             created_at: Utc::now(),
-            signature: aurea_core::ReceiptSignature {
+            signatures: vec![aurea_core::ReceiptSignature {
                 alg: "ed25519".to_string(),
                 kid: "k1".to_string(),
                 public_key: "pk".to_string(),
                 signature: "sig".to_string(),
-            },
+            }],
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
         };
 
         write_rocrate_export(&dir, &[receipt]).expect("write ro-crate");
@@ -2231,4 +4495,237 @@ mod tests {
         let _ = std::fs::remove_file(dir.join("receipts.json"));
         let _ = std::fs::remove_dir(dir);
     }
+
+    #[test]
+    fn backups_with_unverifiable_receipts_are_rejected() {
+        let snapshot = aurea_storage::Snapshot {
+            receipts: vec![make_receipt("cid-forged", Utc::now())],
+            ..Default::default()
+        };
+        let archive = BackupArchive::new(snapshot, Utc::now()).expect("archive");
+        archive.verify().expect("manifest matches");
+
+        let err = verify_backup(&archive).expect_err("forged receipt must be rejected");
+        assert!(err.to_string().contains("cid-forged"), "{err:#}");
+    }
+
+    #[test]
+    fn rotation_closes_the_outgoing_keys_validity_window() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        let (first, _) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        let (second, ring) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+
+        let outgoing = ring.get(&first.kid).expect("first");
+        let incoming = ring.get(&second.kid).expect("second");
+        assert_eq!(outgoing.status, KeyStatus::Retired);
+        assert_eq!(outgoing.valid_until, incoming.valid_from);
+        let handover = incoming.valid_from.expect("valid_from");
+        let judge = |at| ring.judge(&first.kid, &outgoing.public_key, at).ok();
+        assert!(judge(outgoing.valid_from.expect("valid_from")));
+        assert!(!judge(handover));
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    /// A remote signer stand-in answering `connections` requests.
+    fn remote_signer(kid: &str, connections: usize) -> SignerArgs {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let signer = LocalSigner::new(kid, SigningKey::generate(&mut OsRng));
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                serve_signer_connection(stream.expect("accept"), &signer).expect("serve");
+            }
+        });
+        SignerArgs {
+            unlock: UnlockArgs::default(),
+            remote_signer: Some(format!("http://{addr}")),
+            remote_signer_timeout_ms: 5000,
+        }
+    }
+
+    #[test]
+    fn remote_signer_keys_rotate_in_and_cannot_replace_a_known_kid() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        let (local, _) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+
+        let (_, ring) = open_signer(&keys_dir, &remote_signer("remote", 1)).expect("open");
+        let outgoing = ring.get(&local.kid).expect("local key");
+        let incoming = ring.get("remote").expect("remote key");
+        assert_eq!(ring.active_kid.as_deref(), Some("remote"));
+        assert_eq!(outgoing.status, KeyStatus::Retired);
+        assert!(incoming.valid_from.is_some());
+        assert_eq!(outgoing.valid_until, incoming.valid_from);
+
+        let err = open_signer(&keys_dir, &remote_signer("remote", 1))
+            .err()
+            .expect("same kid, another key");
+        assert!(format!("{err:#}").contains("public key"), "{err:#}");
+        let err = open_signer(&keys_dir, &remote_signer(&local.kid, 1))
+            .err()
+            .expect("retired kid");
+        assert!(format!("{err:#}").contains("public key"), "{err:#}");
+        assert_eq!(
+            load_keyring(&keys_dir)
+                .expect("load")
+                .expect("keyring")
+                .active_kid
+                .as_deref(),
+            Some("remote")
+        );
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    #[test]
+    fn signer_refuses_to_listen_on_tcp() {
+        for listen in ["http://0.0.0.0:0", "http://127.0.0.1:0"] {
+            let signer = LocalSigner::new("k", SigningKey::generate(&mut OsRng));
+            let err = serve_signer(listen, signer, std::time::Duration::from_secs(1), 1)
+                .expect_err("tcp address");
+            assert!(err.to_string().contains("unix:"), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn revoked_keys_still_vouch_for_receipts_signed_before_the_compromise() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        let (record, _) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        let signer = record.signing_key(&Unlock::None).expect("signer");
+        let issued = parse_rfc3339(&record.created_at).expect("created_at");
+        let sign_at = |created_at| {
+            let mut unsigned = make_receipt("unused", created_at).unsigned();
+            unsigned.idem_key = format!("idem-{created_at}");
+            aurea_receipts::sign_receipt(
+                &unsigned,
+                &LocalSigner::new(record.kid.clone(), signer.clone()),
+            )
+            .expect("sign")
+        };
+        let early = sign_at(issued + Duration::minutes(1));
+        let late = sign_at(issued + Duration::hours(1));
+
+        rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate again");
+        // Rotation closed the window just now; say the key served two hours.
+        set_key_window(
+            &keys_dir,
+            &record.kid,
+            None,
+            Some(issued + Duration::hours(2)),
+        )
+        .expect("widen window");
+        revoke_key(&keys_dir, &record.kid, Some(issued + Duration::minutes(30))).expect("revoke");
+        let keyring = load_keyring(&keys_dir).expect("load").expect("keyring");
+
+        let verdict = verify_receipt_with_keyring(&early, &keyring);
+        assert!(verdict.ok, "{}", verdict.reason);
+        let verdict = verify_receipt_with_keyring(&late, &keyring);
+        assert!(!verdict.ok);
+        assert_eq!(verdict.keys[0].judgement, KeyJudgement::Compromised);
+
+        assert!(
+            set_key_window(
+                &keys_dir,
+                &record.kid,
+                None,
+                Some(issued - Duration::hours(1))
+            )
+            .is_err()
+        );
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    #[test]
+    fn published_key_set_version_moves_only_when_the_keyring_changes() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        let (first, ring) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        assert_eq!(ring.version, 1);
+        let (_, _, ring) = load_or_create_key_material(&keys_dir, &Unlock::None).expect("load");
+        assert_eq!(ring.version, 1);
+
+        let (second, _) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        revoke_key(&keys_dir, &first.kid, None).expect("revoke");
+        let (kid, signing_key, ring) =
+            load_or_create_key_material(&keys_dir, &Unlock::None).expect("load");
+        assert_eq!(kid, second.kid);
+        assert_eq!(ring.version, 3);
+
+        let doc = publish_key_set(&ring, &LocalSigner::new(kid, signing_key), &[]).expect("sign");
+        let published = aurea_receipts::verify_key_set(&doc, &ring).expect("verify");
+        assert_eq!(
+            published.get(&first.kid).expect("first").status,
+            KeyStatus::Revoked
+        );
+        let newer = KeyRing {
+            version: 4,
+            ..ring.clone()
+        };
+        assert!(aurea_receipts::verify_key_set(&doc, &newer).is_err());
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    #[test]
+    fn published_keys_follow_the_keyring_and_chain_rotations() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        let (first, anchor) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        let (second, ring) = rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+        let signer = LocalSigner::new(
+            second.kid.clone(),
+            second.signing_key(&Unlock::None).expect("open"),
+        );
+        let endorsers = retired_signers(&keys_dir, &ring, Some(&Unlock::None));
+        assert_eq!(endorsers.len(), 1);
+        let keys = PublishedKeys::new(keys_dir.clone(), Arc::new(signer), endorsers, ring)
+            .expect("publish");
+
+        // A client that only ever saw the first key follows the rotation.
+        let (_, doc) = keys.refresh().expect("refresh");
+        let accepted = aurea_receipts::verify_key_set(&doc, &anchor).expect("chained");
+        assert_eq!(accepted.active_kid.as_deref(), Some(second.kid.as_str()));
+
+        revoke_key(&keys_dir, &first.kid, None).expect("revoke");
+        let (ring, doc) = keys.refresh().expect("refresh");
+        assert_eq!(doc.set.version, accepted.version + 1);
+        assert!(doc.endorsements.is_empty(), "revoked keys do not endorse");
+        let published = aurea_receipts::verify_key_set(&doc, &accepted).expect("verify");
+        assert_eq!(
+            published.get(&first.kid).expect("first").status,
+            KeyStatus::Revoked
+        );
+        assert_eq!(ring.version, doc.set.version);
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    #[test]
+    fn a_published_keyring_is_never_silently_started_over() {
+        let keys_dir = std::env::temp_dir().join(format!("aurea-keys-{}", Uuid::new_v4()));
+        rotate_and_activate_key(&keys_dir, &Unlock::None).expect("rotate");
+
+        std::fs::remove_file(keys_dir.join("current.json")).expect("remove current");
+        let err =
+            load_or_create_key_material(&keys_dir, &Unlock::None).expect_err("missing current key");
+        assert!(err.to_string().contains("keys rotate"), "{err:#}");
+
+        std::fs::remove_file(keyring_path(&keys_dir)).expect("remove keyring");
+        let err = rotate_and_activate_key(&keys_dir, &Unlock::None).expect_err("missing keyring");
+        assert!(err.to_string().contains("restore the keyring"), "{err:#}");
+        let _ = std::fs::remove_dir_all(keys_dir);
+    }
+
+    #[test]
+    fn admin_token_file_is_trimmed_and_must_not_be_empty() {
+        let path = std::env::temp_dir().join(format!("aurea-admin-{}", Uuid::new_v4()));
+        std::fs::write(&path, "s3cret\n").expect("write");
+        let config = admin_config_from_args("./b".to_string(), Some(&path)).expect("config");
+        assert_eq!(config.token, Some(blake3::hash(b"s3cret")));
+        assert_eq!(config.backup_dir, PathBuf::from("./b"));
+
+        std::fs::write(&path, "  \n").expect("write");
+        assert!(admin_config_from_args("./b".to_string(), Some(&path)).is_err());
+        assert!(
+            admin_config_from_args("./b".to_string(), None)
+                .expect("config")
+                .token
+                .is_none()
+        );
+        let _ = std::fs::remove_file(path);
+    }
 }
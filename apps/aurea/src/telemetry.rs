@@ -0,0 +1,147 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::{SpanId, TracerProvider as _};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanExporter};
+use serde_json::{Map, Value, json};
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Where finished spans go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceExport {
+    /// OTLP over HTTP/protobuf, e.g. `http://localhost:4318/v1/traces`.
+    Otlp(String),
+    /// One JSON object per span, appended to a local file.
+    File(PathBuf),
+}
+
+impl TraceExport {
+    pub fn from_args(otlp_endpoint: Option<&str>, trace_file: Option<&Path>) -> Option<Self> {
+        match (otlp_endpoint, trace_file) {
+            (Some(endpoint), _) => Some(Self::Otlp(endpoint.to_string())),
+            (None, Some(path)) => Some(Self::File(path.to_path_buf())),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Flushes and shuts the exporter down when dropped.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take()
+            && let Err(err) = provider.shutdown()
+        {
+            eprintln!("failed to flush trace exporter: {err}");
+        }
+    }
+}
+
+/// Installs the global subscriber: `RUST_LOG`-filtered logs, plus the
+/// runtime's spans when `export` is set.
+pub fn init(export: Option<TraceExport>) -> Result<Telemetry> {
+    let logs = tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env());
+    let Some(export) = export else {
+        tracing_subscriber::registry().with(logs).init();
+        return Ok(Telemetry { provider: None });
+    };
+
+    let builder = SdkTracerProvider::builder()
+        .with_resource(Resource::builder().with_service_name("aurea").build());
+    let provider = match export {
+        TraceExport::Otlp(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .build()
+                .context("build OTLP span exporter failed")?;
+            builder.with_batch_exporter(exporter).build()
+        }
+        TraceExport::File(path) => builder
+            .with_batch_exporter(FileExporter::create(&path)?)
+            .build(),
+    };
+    let spans = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer("aurea"))
+        .with_filter(
+            Targets::new()
+                .with_target("aurea", Level::INFO)
+                .with_target("aurea_runtime", Level::INFO),
+        );
+    tracing_subscriber::registry().with(logs).with(spans).init();
+    Ok(Telemetry {
+        provider: Some(provider),
+    })
+}
+
+#[derive(Debug)]
+struct FileExporter {
+    file: Mutex<File>,
+}
+
+impl FileExporter {
+    fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open trace file {} failed", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl SpanExporter for FileExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let mut out = Vec::new();
+        for span in &batch {
+            serde_json::to_writer(&mut out, &span_json(span))
+                .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+            out.push(b'\n');
+        }
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| OTelSdkError::InternalFailure("trace file lock poisoned".to_string()))?;
+        file.write_all(&out)
+            .and_then(|_| file.flush())
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))
+    }
+}
+
+fn span_json(span: &SpanData) -> Value {
+    let attributes = span
+        .attributes
+        .iter()
+        .map(|kv| (kv.key.to_string(), Value::String(kv.value.to_string())))
+        .collect::<Map<_, _>>();
+    let parent = (span.parent_span_id != SpanId::INVALID).then(|| span.parent_span_id.to_string());
+    json!({
+        "trace_id": span.span_context.trace_id().to_string(),
+        "span_id": span.span_context.span_id().to_string(),
+        "parent_span_id": parent,
+        "name": span.name,
+        "start_unix_nano": unix_nanos(span.start_time),
+        "end_unix_nano": unix_nanos(span.end_time),
+        "attributes": attributes,
+    })
+}
+
+fn unix_nanos(at: SystemTime) -> u128 {
+    at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos())
+}
@@ -0,0 +1,326 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, anyhow};
+use aurea_core::cid_of;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+const STAGING_DIR: &str = "tmp";
+
+/// Artifacts on disk, addressed by the CID of their bytes and laid out as
+/// `<root>/<cid[0..2]>/<cid[2..4]>/<cid>`. Files are written under
+/// `<root>/tmp` and renamed into place, so a reader never sees a partial
+/// artifact, and storing the same bytes twice keeps a single copy.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredArtifact {
+    pub cid: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub scanned: usize,
+    /// Artifacts no receipt references, old enough to collect.
+    pub unreferenced: Vec<String>,
+    pub unreferenced_bytes: u64,
+    /// Leftover staging files from writes that never finished.
+    pub stale_staging: usize,
+    pub applied: bool,
+}
+
+impl ArtifactStore {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(root.join(STAGING_DIR))
+            .with_context(|| format!("create artifact store {} failed", root.display()))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the artifact `cid` lives; `None` for strings that are not a CID.
+    pub fn path_for(&self, cid: &str) -> Option<PathBuf> {
+        let valid = cid.len() > 4
+            && cid
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit());
+        valid.then(|| self.root.join(&cid[..2]).join(&cid[2..4]).join(cid))
+    }
+
+    /// A fresh path under the staging directory for a plugin to build a file
+    /// in before handing it to [`ArtifactStore::import`].
+    pub fn staging_path(&self) -> PathBuf {
+        self.root.join(STAGING_DIR).join(Uuid::new_v4().to_string())
+    }
+
+    pub fn put(&self, bytes: &[u8]) -> Result<StoredArtifact> {
+        let staged = self.staging_path();
+        let mut file =
+            File::create(&staged).with_context(|| format!("create {} failed", staged.display()))?;
+        file.write_all(bytes)
+            .with_context(|| format!("write {} failed", staged.display()))?;
+        drop(file);
+        self.import(&staged)
+    }
+
+    /// Moves a finished file into the store under the CID of its contents.
+    /// The source is consumed either way; if the artifact is already stored
+    /// it is dropped and the stored copy's mtime is bumped, so garbage
+    /// collection treats it as freshly written until its receipt lands.
+    pub fn import(&self, source: &Path) -> Result<StoredArtifact> {
+        let bytes =
+            fs::read(source).with_context(|| format!("read {} failed", source.display()))?;
+        let artifact = StoredArtifact {
+            cid: cid_of(&bytes),
+            size_bytes: bytes.len() as u64,
+        };
+        let target = self
+            .path_for(&artifact.cid)
+            .ok_or_else(|| anyhow!("unexpected artifact cid {}", artifact.cid))?;
+        match File::open(&target).and_then(|file| file.set_modified(SystemTime::now())) {
+            Ok(()) => {
+                fs::remove_file(source)
+                    .with_context(|| format!("remove {} failed", source.display()))?;
+                return Ok(artifact);
+            }
+            // Not stored yet, or collected just now: store it below.
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("touch {} failed", target.display()));
+            }
+        }
+
+        let staged = if source.starts_with(self.root.join(STAGING_DIR)) {
+            source.to_path_buf()
+        } else {
+            let staged = self.staging_path();
+            fs::write(&staged, &bytes)
+                .with_context(|| format!("write {} failed", staged.display()))?;
+            fs::remove_file(source)
+                .with_context(|| format!("remove {} failed", source.display()))?;
+            staged
+        };
+        File::open(&staged)
+            .and_then(|file| file.sync_all())
+            .with_context(|| format!("sync {} failed", staged.display()))?;
+        if let Some(shard) = target.parent() {
+            fs::create_dir_all(shard)
+                .with_context(|| format!("create {} failed", shard.display()))?;
+        }
+        fs::rename(&staged, &target)
+            .with_context(|| format!("move artifact into {} failed", target.display()))?;
+        Ok(artifact)
+    }
+
+    pub fn contains(&self, cid: &str) -> bool {
+        self.path_for(cid).is_some_and(|path| path.is_file())
+    }
+
+    pub fn read(&self, cid: &str) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.path_for(cid).filter(|path| path.is_file()) else {
+            return Ok(None);
+        };
+        fs::read(&path)
+            .map(Some)
+            .with_context(|| format!("read {} failed", path.display()))
+    }
+
+    pub fn remove(&self, cid: &str) -> Result<bool> {
+        let Some(path) = self.path_for(cid).filter(|path| path.is_file()) else {
+            return Ok(false);
+        };
+        fs::remove_file(&path).with_context(|| format!("remove {} failed", path.display()))?;
+        Ok(true)
+    }
+
+    /// Every stored artifact CID.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut cids = Vec::new();
+        for shard in read_dir(&self.root)? {
+            if shard.file_name() == STAGING_DIR || !shard.path().is_dir() {
+                continue;
+            }
+            for sub in read_dir(&shard.path())? {
+                for entry in read_dir(&sub.path())? {
+                    cids.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+        cids.sort();
+        Ok(cids)
+    }
+
+    /// Finds artifacts outside `live` and staging files, both last written
+    /// before `cutoff`, and deletes them when `apply` is set. The cutoff keeps
+    /// artifacts of jobs that are still running, whose receipts are not
+    /// stored yet, out of reach.
+    pub fn collect_garbage(
+        &self,
+        live: &BTreeSet<String>,
+        cutoff: DateTime<Utc>,
+        apply: bool,
+    ) -> Result<GcReport> {
+        let mut report = GcReport {
+            applied: apply,
+            ..GcReport::default()
+        };
+        for cid in self.list()? {
+            report.scanned += 1;
+            let Some(path) = self.path_for(&cid) else {
+                continue;
+            };
+            if live.contains(&cid) || modified_at(&path)? > cutoff {
+                continue;
+            }
+            report.unreferenced_bytes += fs::metadata(&path)
+                .with_context(|| format!("stat {} failed", path.display()))?
+                .len();
+            if apply {
+                fs::remove_file(&path)
+                    .with_context(|| format!("remove {} failed", path.display()))?;
+            }
+            report.unreferenced.push(cid);
+        }
+        for entry in read_dir(&self.root.join(STAGING_DIR))? {
+            let path = entry.path();
+            if modified_at(&path)? > cutoff {
+                continue;
+            }
+            report.stale_staging += 1;
+            if apply {
+                fs::remove_file(&path)
+                    .with_context(|| format!("remove {} failed", path.display()))?;
+            }
+        }
+        Ok(report)
+    }
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<fs::DirEntry>> {
+    fs::read_dir(dir)
+        .with_context(|| format!("read {} failed", dir.display()))?
+        .map(|entry| entry.with_context(|| format!("read entry of {} failed", dir.display())))
+        .collect()
+}
+
+fn modified_at(path: &Path) -> Result<DateTime<Utc>> {
+    let modified = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .with_context(|| format!("stat {} failed", path.display()))?;
+    Ok(modified.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn temp_store() -> ArtifactStore {
+        ArtifactStore::open(
+            std::env::temp_dir().join(format!("aurea-artifacts-{}", Uuid::new_v4())),
+        )
+        .expect("open store")
+    }
+
+    #[test]
+    fn artifacts_are_sharded_by_cid_and_deduplicated() {
+        let store = temp_store();
+        let first = store.put(b"hello").expect("put");
+        assert_eq!(first.cid, cid_of(b"hello"));
+        assert_eq!(first.size_bytes, 5);
+        let path = store.path_for(&first.cid).expect("path");
+        assert_eq!(
+            path.strip_prefix(store.root()).expect("under root"),
+            Path::new(&first.cid[..2])
+                .join(&first.cid[2..4])
+                .join(&first.cid)
+        );
+
+        let source = store.root().join("hello.txt");
+        fs::write(&source, b"hello").expect("write source");
+        assert_eq!(store.import(&source).expect("import"), first);
+        assert!(!source.exists());
+        assert_eq!(
+            store.list().expect("list"),
+            std::slice::from_ref(&first.cid)
+        );
+        assert_eq!(
+            store.read(&first.cid).expect("read"),
+            Some(b"hello".to_vec())
+        );
+        assert!(store.path_for("../../etc/passwd").is_none());
+        assert!(store.read("../../etc/passwd").expect("read").is_none());
+
+        let _ = fs::remove_dir_all(store.root());
+    }
+
+    #[test]
+    fn garbage_collection_spares_live_and_recent_artifacts() {
+        let store = temp_store();
+        let live = store.put(b"live").expect("put");
+        let dead = store.put(b"dead").expect("put");
+        fs::write(store.staging_path(), b"partial").expect("stage");
+
+        let keep = BTreeSet::from([live.cid.clone()]);
+        let recent = store
+            .collect_garbage(&keep, Utc::now() - Duration::hours(1), true)
+            .expect("gc");
+        assert!(recent.unreferenced.is_empty());
+        assert_eq!(recent.stale_staging, 0);
+
+        let later = Utc::now() + Duration::seconds(1);
+        let dry_run = store.collect_garbage(&keep, later, false).expect("gc");
+        assert_eq!(dry_run.unreferenced, std::slice::from_ref(&dead.cid));
+        assert_eq!(dry_run.unreferenced_bytes, 4);
+        assert_eq!(dry_run.stale_staging, 1);
+        assert!(store.contains(&dead.cid));
+
+        store.collect_garbage(&keep, later, true).expect("gc");
+        assert!(!store.contains(&dead.cid));
+        assert!(store.contains(&live.cid));
+        assert_eq!(
+            read_dir(&store.root().join(STAGING_DIR)).expect("ls").len(),
+            0
+        );
+
+        let _ = fs::remove_dir_all(store.root());
+    }
+
+    #[test]
+    fn storing_an_existing_artifact_again_keeps_it_from_collection() {
+        let store = temp_store();
+        let stored = store.put(b"shared").expect("put");
+        let path = store.path_for(&stored.cid).expect("path");
+        let old = SystemTime::now() - std::time::Duration::from_secs(7200);
+        File::open(&path)
+            .and_then(|file| file.set_modified(old))
+            .expect("age artifact");
+
+        let cutoff = Utc::now() - Duration::hours(1);
+        let aged = store
+            .collect_garbage(&BTreeSet::new(), cutoff, false)
+            .expect("gc");
+        assert_eq!(aged.unreferenced, std::slice::from_ref(&stored.cid));
+
+        assert_eq!(store.put(b"shared").expect("put again"), stored);
+        let report = store
+            .collect_garbage(&BTreeSet::new(), cutoff, true)
+            .expect("gc");
+        assert!(report.unreferenced.is_empty());
+        assert!(store.contains(&stored.cid));
+
+        let _ = fs::remove_dir_all(store.root());
+    }
+}
@@ -2,7 +2,7 @@ use std::fs;
 use std::path::Path;
 
 use aurea_core::cid_of;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -87,7 +87,13 @@ pub enum PackError {
     InvalidFormat(String),
 }
 
-pub fn write_pack(path: &Path, inputs: &[PackInput]) -> Result<PackWriteResult, PackError> {
+/// Writes `inputs` as a pack stamped with `created_at`; the same inputs and
+/// time always give the same pack CID.
+pub fn write_pack(
+    path: &Path,
+    inputs: &[PackInput],
+    created_at: DateTime<Utc>,
+) -> Result<PackWriteResult, PackError> {
     let mut entries = Vec::with_capacity(inputs.len());
     let mut data = Vec::new();
     let mut offset = 0u64;
@@ -108,7 +114,7 @@ pub fn write_pack(path: &Path, inputs: &[PackInput]) -> Result<PackWriteResult,
 
     let manifest = PackManifest {
         version: "vcx-pack.v1".to_string(),
-        created_at: Utc::now().to_rfc3339(),
+        created_at: created_at.to_rfc3339(),
         entry_count: entries.len(),
         entries: entries.clone(),
     };
@@ -368,6 +374,7 @@ mod tests {
                     bytes: br#"{"x":1}"#.to_vec(),
                 },
             ],
+            Utc::now(),
         )
         .expect("write pack");
 
@@ -393,6 +400,7 @@ mod tests {
                 path: "a.txt".to_string(),
                 bytes: b"hello".to_vec(),
             }],
+            Utc::now(),
         )
         .expect("write pack");
 
@@ -417,4 +425,20 @@ mod tests {
         let _ = fs::remove_file(path);
         let _ = fs::remove_dir_all(dir);
     }
+
+    #[test]
+    fn same_inputs_and_time_give_the_same_cid() {
+        let dir = std::env::temp_dir().join(format!("vcx-pack-{}", uuid::Uuid::new_v4()));
+        let inputs = [PackInput {
+            path: "a.txt".to_string(),
+            bytes: b"hello".to_vec(),
+        }];
+        let at = Utc::now();
+
+        let first = write_pack(&dir.join("first.vcxpack"), &inputs, at).expect("write pack");
+        let second = write_pack(&dir.join("second.vcxpack"), &inputs, at).expect("write pack");
+        assert_eq!(first.pack_cid, second.pack_cid);
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+
+/// Source of timestamps for jobs, events and receipts.
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Folds in a timestamp issued elsewhere, by another node or before a
+    /// restart. Only clocks that promise monotonic readings need to act on it.
+    fn observe(&self, _at: DateTime<Utc>) {}
+}
+
+/// The operating system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Hybrid logical clock: follows its physical clock, but every reading is
+/// strictly later than the one before it, even when the wall clock steps
+/// back or two readings land on the same microsecond. Timestamps seen from
+/// other nodes, or persisted before a restart, are folded in with
+/// [`Clock::observe`].
+#[derive(Debug)]
+pub struct HybridClock {
+    physical: Arc<dyn Clock>,
+    last: Mutex<DateTime<Utc>>,
+}
+
+impl HybridClock {
+    /// Smallest step between two readings.
+    pub const TICK: Duration = Duration::microseconds(1);
+
+    pub fn new(physical: Arc<dyn Clock>) -> Self {
+        Self {
+            physical,
+            last: Mutex::new(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+
+    fn last(&self) -> MutexGuard<'_, DateTime<Utc>> {
+        self.last
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for HybridClock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl Clock for HybridClock {
+    fn now(&self) -> DateTime<Utc> {
+        let physical = self.physical.now().trunc_subsecs(6);
+        let mut last = self.last();
+        let next = physical.max(*last + Self::TICK);
+        *last = next;
+        next
+    }
+
+    /// Moves the clock past `remote` so readings taken after it sort after it.
+    fn observe(&self, remote: DateTime<Utc>) {
+        let mut last = self.last();
+        *last = (*last).max(remote.trunc_subsecs(6));
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a
+/// test can keep one and hand another to the store.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.lock() += by;
+    }
+
+    pub fn set(&self, at: DateTime<Utc>) {
+        *self.lock() = at;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DateTime<Utc>> {
+        self.now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
@@ -1,16 +1,20 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
+mod clock;
 pub mod nrf;
+mod trace;
 
+pub use clock::{Clock, HybridClock, MockClock, SystemClock};
 pub use nrf::canon::{
     CanonError, canonical_json_string, canonical_json_string_with_profile, to_nrf_bytes,
 };
 pub use nrf::hash::cid_of;
 pub use nrf::types::{CanonProfile, NumNorm};
+pub use trace::TraceParent;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -18,8 +22,32 @@ pub enum WorkStatus {
     Accepted,
     Assigned,
     Progress,
+    Retrying,
     Done,
     Fail,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl Priority {
+    /// Sort rank used by queue indexes: lower ranks are served first.
+    pub fn rank(self) -> u8 {
+        match self {
+            Self::Critical => 0,
+            Self::High => 1,
+            Self::Normal => 2,
+            Self::Low => 3,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,7 +58,18 @@ pub struct WorkUnit {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub idem_key: Option<String>,
     pub payload: serde_json::Value,
+    /// Stamped from the store's clock when the work is enqueued.
     pub submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+    /// W3C `traceparent` of the span that accepted the work.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
+    /// `x-request-id` of the submitting HTTP request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl WorkUnit {
@@ -46,14 +85,30 @@ impl WorkUnit {
             topic,
             idem_key,
             payload,
-            submitted_at: Utc::now(),
+            submitted_at: DateTime::<Utc>::UNIX_EPOCH,
+            priority: Priority::Normal,
+            not_before: None,
+            traceparent: None,
+            request_id: None,
         }
     }
 
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.not_before.is_none_or(|at| at <= now)
+    }
+
     pub fn plan_hash(&self) -> Result<String, CanonError> {
         cid_for(&self.payload)
     }
 
+    pub fn trace_parent(&self) -> Option<TraceParent> {
+        self.traceparent.as_deref().and_then(TraceParent::parse)
+    }
+
+    pub fn trace_id(&self) -> Option<String> {
+        self.trace_parent().map(|parent| parent.trace_id_hex())
+    }
+
     pub fn effective_idem_key(&self) -> Result<String, CanonError> {
         self.idem_key
             .clone()
@@ -62,6 +117,26 @@ impl WorkUnit {
     }
 }
 
+/// One lifecycle transition of a work unit, as streamed over SSE and kept in
+/// the per-work history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEvent {
+    /// Position in the event log; assigned when the event is persisted.
+    #[serde(default)]
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub tenant: String,
+    pub topic: String,
+    pub work_id: Uuid,
+    pub status: WorkStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_cid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyEntry {
     pub rule: String,
@@ -77,7 +152,17 @@ pub struct ArtifactRef {
     pub size_bytes: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One failed execution attempt, kept on the job and copied into its final receipt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttemptRecord {
+    pub attempt: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    pub failed_at: DateTime<Utc>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ReceiptSignature {
     pub alg: String,
     pub kid: String,
@@ -85,6 +170,43 @@ pub struct ReceiptSignature {
     pub signature: String,
 }
 
+/// Which keys have to sign a receipt before it verifies. It is part of the
+/// signed content, so cosignatures can be added later but the rule itself
+/// cannot be relaxed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignatureThreshold {
+    /// Kids that must all be among the signers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_kids: Vec<String>,
+    /// Distinct signers needed, counting the issuer.
+    pub min_signatures: usize,
+}
+
+impl SignatureThreshold {
+    /// The rule for receipts that carry none: any one valid signature.
+    pub fn single() -> Self {
+        Self {
+            required_kids: Vec::new(),
+            min_signatures: 1,
+        }
+    }
+
+    /// Required kids that are not among `signed_by`.
+    pub fn missing_kids<'a>(&'a self, signed_by: &[String]) -> Vec<&'a str> {
+        self.required_kids
+            .iter()
+            .filter(|kid| !signed_by.contains(kid))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether valid signatures by `signed_by` meet the rule.
+    pub fn is_met_by(&self, signed_by: &[String]) -> bool {
+        let distinct = signed_by.iter().collect::<BTreeSet<_>>().len();
+        self.missing_kids(signed_by).is_empty() && distinct >= self.min_signatures.max(1)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnsignedReceipt {
     pub work_id: Uuid,
@@ -93,10 +215,28 @@ pub struct UnsignedReceipt {
     pub status: WorkStatus,
     pub idem_key: String,
     pub plan_hash: String,
+    /// CID of the canonical work payload, kept as a blob by the store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cid: Option<String>,
     pub policy_trace: Vec<PolicyEntry>,
     pub stage_time_ms: BTreeMap<String, u64>,
     pub artifacts: Vec<ArtifactRef>,
+    /// CID of the plugin's canonical JSON result; only set on `done`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_cid: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<AttemptRecord>,
     pub created_at: DateTime<Utc>,
+    /// When the final attempt started. Plugins stamp their output with it
+    /// instead of the wall clock, so a replay reproduces the same hashes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Trace the work ran under; left out of older receipts' CIDs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// Cosigning rule for topics that need more than the issuer's signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<SignatureThreshold>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,11 +248,43 @@ pub struct Receipt {
     pub status: WorkStatus,
     pub idem_key: String,
     pub plan_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cid: Option<String>,
     pub policy_trace: Vec<PolicyEntry>,
     pub stage_time_ms: BTreeMap<String, u64>,
     pub artifacts: Vec<ArtifactRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_cid: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<AttemptRecord>,
     pub created_at: DateTime<Utc>,
-    pub signature: ReceiptSignature,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<SignatureThreshold>,
+    /// The issuer's signature first, then cosignatures in the order they
+    /// were added. None of them is covered by the CID.
+    #[serde(alias = "signature", deserialize_with = "one_or_many_signatures")]
+    pub signatures: Vec<ReceiptSignature>,
+}
+
+/// Receipts written before cosigning carry a single `signature` object.
+fn one_or_many_signatures<'de, D>(deserializer: D) -> Result<Vec<ReceiptSignature>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(ReceiptSignature),
+        Many(Vec<ReceiptSignature>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(signature) => vec![signature],
+        OneOrMany::Many(signatures) => signatures,
+    })
 }
 
 impl Receipt {
@@ -124,13 +296,30 @@ impl Receipt {
             status: self.status,
             idem_key: self.idem_key.clone(),
             plan_hash: self.plan_hash.clone(),
+            input_cid: self.input_cid.clone(),
             policy_trace: self.policy_trace.clone(),
             stage_time_ms: self.stage_time_ms.clone(),
             artifacts: self.artifacts.clone(),
+            result_cid: self.result_cid.clone(),
+            attempts: self.attempts.clone(),
             created_at: self.created_at,
+            started_at: self.started_at,
+            trace_id: self.trace_id.clone(),
+            threshold: self.threshold.clone(),
         }
     }
 
+    /// The signature of whoever issued the receipt.
+    pub fn issuer(&self) -> Option<&ReceiptSignature> {
+        self.signatures.first()
+    }
+
+    pub fn signature_threshold(&self) -> SignatureThreshold {
+        self.threshold
+            .clone()
+            .unwrap_or_else(SignatureThreshold::single)
+    }
+
     pub fn computed_cid(&self) -> Result<String, CanonError> {
         cid_for(&self.unsigned())
     }
@@ -150,7 +339,7 @@ pub fn cid_for<T: Serialize>(value: &T) -> Result<String, CanonError> {
 mod tests {
     use super::*;
     use serde::Deserialize;
-    use serde_json::Value;
+    use serde_json::{Value, json};
     use std::fs;
     use std::path::PathBuf;
 
@@ -236,9 +425,138 @@ mod tests {
             policy_trace: vec![],
             stage_time_ms: BTreeMap::new(),
             artifacts: vec![],
+            attempts: vec![],
             created_at: Utc::now(),
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
         };
         let cid = cid_for(&unsigned).unwrap();
         assert_eq!(cid.len(), 52);
     }
+
+    #[test]
+    fn empty_attempts_are_left_out_of_receipt_cid() {
+        let unsigned = UnsignedReceipt {
+            work_id: Uuid::nil(),
+            tenant: "t1".to_string(),
+            topic: "echo:test".to_string(),
+            status: WorkStatus::Fail,
+            idem_key: "ik".to_string(),
+            plan_hash: "ph".to_string(),
+            policy_trace: vec![],
+            stage_time_ms: BTreeMap::new(),
+            artifacts: vec![],
+            attempts: vec![],
+            created_at: Utc::now(),
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
+        };
+        let value = serde_json::to_value(&unsigned).unwrap();
+        assert!(value.get("attempts").is_none());
+        let parsed: UnsignedReceipt = serde_json::from_value(value).unwrap();
+        assert_eq!(cid_for(&parsed).unwrap(), cid_for(&unsigned).unwrap());
+    }
+
+    #[test]
+    fn single_signature_receipts_still_parse() {
+        let signature = json!({
+            "alg": "ed25519",
+            "kid": "issuer",
+            "public_key": "pk",
+            "signature": "sig"
+        });
+        let legacy = json!({
+            "cid": "c1",
+            "work_id": Uuid::nil(),
+            "tenant": "t1",
+            "topic": "echo:test",
+            "status": "done",
+            "idem_key": "ik",
+            "plan_hash": "ph",
+            "policy_trace": [],
+            "stage_time_ms": {},
+            "artifacts": [],
+            "created_at": "2026-03-01T12:00:00Z",
+            "signature": signature
+        });
+        let receipt: Receipt = serde_json::from_value(legacy).unwrap();
+        assert_eq!(receipt.signatures.len(), 1);
+        assert_eq!(receipt.issuer().map(|s| s.kid.as_str()), Some("issuer"));
+        assert_eq!(receipt.signature_threshold(), SignatureThreshold::single());
+
+        let reparsed: Receipt =
+            serde_json::from_value(serde_json::to_value(&receipt).unwrap()).unwrap();
+        assert_eq!(reparsed.signatures, receipt.signatures);
+    }
+
+    #[test]
+    fn threshold_needs_required_kids_and_enough_distinct_signers() {
+        let threshold = SignatureThreshold {
+            required_kids: vec!["auditor".to_string()],
+            min_signatures: 2,
+        };
+        let kids = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(!threshold.is_met_by(&kids(&["issuer"])));
+        assert_eq!(threshold.missing_kids(&kids(&["issuer"])), ["auditor"]);
+        assert!(!threshold.is_met_by(&kids(&["auditor", "auditor"])));
+        assert!(threshold.is_met_by(&kids(&["issuer", "auditor"])));
+        assert!(SignatureThreshold::single().is_met_by(&kids(&["issuer"])));
+        assert!(!SignatureThreshold::single().is_met_by(&[]));
+    }
+
+    #[test]
+    fn traceparent_round_trips_and_rejects_malformed_headers() {
+        let raw = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let parent = TraceParent::parse(raw).expect("valid traceparent");
+        assert_eq!(parent.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert!(parent.is_sampled());
+        assert_eq!(parent.to_string(), raw);
+
+        for bad in [
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        ] {
+            assert!(TraceParent::parse(bad).is_none(), "{bad}");
+        }
+        assert!(
+            TraceParent::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra")
+                .is_some()
+        );
+
+        let generated = TraceParent::generate();
+        assert_eq!(TraceParent::parse(&generated.to_string()), Some(generated));
+    }
+
+    #[test]
+    fn hybrid_clock_never_runs_backwards() {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let physical = MockClock::new(start);
+        let clock = HybridClock::new(std::sync::Arc::new(physical.clone()));
+
+        let first = clock.now();
+        assert_eq!(first, start);
+        let second = clock.now();
+        assert_eq!(second, start + HybridClock::TICK);
+
+        physical.advance(chrono::Duration::seconds(-5));
+        assert!(clock.now() > second);
+
+        clock.observe(start + chrono::Duration::seconds(10));
+        assert!(clock.now() > start + chrono::Duration::seconds(10));
+
+        physical.advance(chrono::Duration::minutes(1));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(55));
+    }
 }
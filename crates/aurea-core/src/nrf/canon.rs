@@ -36,7 +36,7 @@ fn canonicalize_value(value: &Value, profile: CanonProfile) -> Result<Value, Can
     match value {
         Value::Object(obj) => {
             let mut pairs: Vec<_> = obj.iter().collect();
-            pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+            pairs.sort_by_key(|(a, _)| *a);
 
             let mut out = Map::new();
             for (key, val) in pairs {
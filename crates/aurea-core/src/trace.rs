@@ -0,0 +1,101 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+/// A W3C `traceparent` header (version `00`): which trace a request belongs
+/// to and the span that made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: [u8; 16],
+    pub parent_id: [u8; 8],
+    pub flags: u8,
+}
+
+impl TraceParent {
+    pub const SAMPLED: u8 = 0x01;
+
+    /// A new sampled trace, for work submitted without a `traceparent`.
+    pub fn generate() -> Self {
+        let mut parent_id = [0u8; 8];
+        parent_id.copy_from_slice(&Uuid::new_v4().as_bytes()[..8]);
+        Self {
+            trace_id: Uuid::new_v4().into_bytes(),
+            parent_id,
+            flags: Self::SAMPLED,
+        }
+    }
+
+    /// Parses `00-<32 hex trace id>-<16 hex parent id>-<2 hex flags>`. Unknown
+    /// future versions are accepted as long as the first four fields parse;
+    /// all-zero ids are rejected.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        hex_byte(version)?;
+        let trace_id: [u8; 16] = hex_bytes(trace_id)?;
+        let parent_id: [u8; 8] = hex_bytes(parent_id)?;
+        if trace_id == [0; 16] || parent_id == [0; 8] {
+            return None;
+        }
+        Some(Self {
+            trace_id,
+            parent_id,
+            flags: hex_byte(flags)?,
+        })
+    }
+
+    pub fn trace_id_hex(&self) -> String {
+        to_hex(&self.trace_id)
+    }
+
+    pub fn parent_id_hex(&self) -> String {
+        to_hex(&self.parent_id)
+    }
+
+    pub fn is_sampled(&self) -> bool {
+        self.flags & Self::SAMPLED != 0
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id_hex(),
+            self.parent_id_hex(),
+            self.flags
+        )
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hex_byte(raw: &str) -> Option<u8> {
+    let [b] = hex_bytes::<1>(raw)?;
+    Some(b)
+}
+
+/// Lowercase hex only, as the spec requires.
+fn hex_bytes<const N: usize>(raw: &str) -> Option<[u8; N]> {
+    if raw.len() != N * 2
+        || !raw
+            .bytes()
+            .all(|c| c.is_ascii_digit() || (b'a'..=b'f').contains(&c))
+    {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, slot) in out.iter_mut().enumerate() {
+        *slot = u8::from_str_radix(&raw[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
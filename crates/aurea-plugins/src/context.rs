@@ -0,0 +1,151 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use aurea_artifacts_store::ArtifactStore;
+use chrono::{DateTime, Utc};
+use tokio_util::sync::CancellationToken;
+
+/// Runtime side of an [`ExecutionContext`]; implemented by whoever runs the job.
+pub trait ExecutionHooks: Send + Sync {
+    fn heartbeat(&self) -> Result<()>;
+    fn progress(&self, detail: &str);
+    fn stage(&self, name: &str, elapsed_ms: u64);
+}
+
+struct DetachedHooks;
+
+impl ExecutionHooks for DetachedHooks {
+    fn heartbeat(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn progress(&self, _detail: &str) {}
+
+    fn stage(&self, _name: &str, _elapsed_ms: u64) {}
+}
+
+/// Handed to [`crate::Plugin::execute`] so long-running plugins can keep their
+/// lease alive, report progress and time named stages.
+#[derive(Clone)]
+pub struct ExecutionContext {
+    attempt: u32,
+    hooks: Arc<dyn ExecutionHooks>,
+    cancel: CancellationToken,
+    started_at: DateTime<Utc>,
+    output_dir: Option<PathBuf>,
+    artifacts: Option<ArtifactStore>,
+}
+
+impl ExecutionContext {
+    /// `started_at` is when the attempt began by the runtime's clock.
+    pub fn new(attempt: u32, hooks: Arc<dyn ExecutionHooks>, started_at: DateTime<Utc>) -> Self {
+        Self {
+            attempt,
+            hooks,
+            cancel: CancellationToken::new(),
+            started_at,
+            output_dir: None,
+            artifacts: None,
+        }
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Confines every file the plugin writes to `dir`; used for replays.
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    /// Hands plugins a managed store to keep their artifacts in, instead of
+    /// paths of their own choosing.
+    pub fn with_artifact_store(mut self, store: ArtifactStore) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// A context that is not bound to a lease, for tests and one-off runs.
+    pub fn detached(started_at: DateTime<Utc>) -> Self {
+        Self::new(1, Arc::new(DetachedHooks), started_at)
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// When this attempt started. Plugins stamp their output with this
+    /// rather than the wall clock so that replays reproduce it byte for byte.
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn artifact_store(&self) -> Option<&ArtifactStore> {
+        self.artifacts.as_ref()
+    }
+
+    /// Where the plugin should actually write `path`. Unchanged outside a
+    /// replay; inside one, `path` is re-rooted under the output directory
+    /// and may not climb out of it. Plugins keep reporting `path` itself.
+    pub fn output_path(&self, path: &Path) -> PathBuf {
+        let Some(root) = &self.output_dir else {
+            return path.to_path_buf();
+        };
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .fold(root.clone(), |out, part| out.join(part))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Resolves once the job was cancelled. Plugins should stop and return an
+    /// error; the runtime then finishes the job with a `cancelled` receipt.
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await;
+    }
+
+    /// Extends the job lease. Fails when the lease was lost, in which case the
+    /// plugin should stop: the job has been handed to another worker.
+    pub fn heartbeat(&self) -> Result<()> {
+        self.hooks.heartbeat()
+    }
+
+    pub fn progress(&self, detail: impl AsRef<str>) {
+        self.hooks.progress(detail.as_ref());
+    }
+
+    pub fn record_stage(&self, name: &str, elapsed_ms: u64) {
+        self.hooks.stage(name, elapsed_ms);
+    }
+
+    pub fn stage(&self, name: &'static str) -> StageTimer<'_> {
+        StageTimer {
+            ctx: self,
+            name,
+            started: Instant::now(),
+        }
+    }
+}
+
+pub struct StageTimer<'a> {
+    ctx: &'a ExecutionContext,
+    name: &'static str,
+    started: Instant,
+}
+
+impl StageTimer<'_> {
+    pub fn finish(self) -> u64 {
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        self.ctx.record_stage(self.name, elapsed_ms);
+        elapsed_ms
+    }
+}
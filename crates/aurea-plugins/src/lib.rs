@@ -10,10 +10,14 @@ use base64::Engine;
 use base64::engine::general_purpose::STANDARD as B64;
 use serde_json::{Value, json};
 
+mod context;
+
+pub use context::{ExecutionContext, ExecutionHooks, StageTimer};
+
 #[async_trait]
 pub trait Plugin: Send + Sync {
     fn name(&self) -> &'static str;
-    async fn execute(&self, payload: Value) -> Result<Value>;
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> Result<Value>;
 }
 
 #[derive(Default, Clone)]
@@ -53,7 +57,7 @@ impl Plugin for EchoPlugin {
         "echo"
     }
 
-    async fn execute(&self, payload: Value) -> Result<Value> {
+    async fn execute(&self, _ctx: &ExecutionContext, payload: Value) -> Result<Value> {
         Ok(payload)
     }
 }
@@ -66,22 +70,41 @@ impl Plugin for VcxWorkerPlugin {
         "vcx"
     }
 
-    async fn execute(&self, payload: Value) -> Result<Value> {
+    /// With an artifact store in the context the pack is built in its
+    /// staging area and kept under the CID of its bytes, and `pack_dir` is
+    /// ignored; otherwise it is written to `pack_dir`.
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> Result<Value> {
         let inputs = extract_pack_inputs(&payload)?;
-        let pack_dir = payload
-            .get("pack_dir")
-            .and_then(Value::as_str)
-            .map(PathBuf::from)
-            .unwrap_or_else(|| PathBuf::from("./packs"));
-
-        std::fs::create_dir_all(&pack_dir)
-            .with_context(|| format!("create pack directory: {pack_dir:?}"))?;
-
         let payload_hash = cid_for(&payload).context("compute payload hash")?;
-        let pack_path = pack_dir.join(format!("{payload_hash}.vcxpack"));
+        let file_name = format!("{payload_hash}.vcxpack");
+        let (written_path, reported_path) = match ctx.artifact_store() {
+            Some(store) => (store.staging_path(), PathBuf::from(&file_name)),
+            None => {
+                let pack_dir = payload
+                    .get("pack_dir")
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from("./packs"));
+                let pack_path = pack_dir.join(&file_name);
+                (ctx.output_path(&pack_path), pack_path)
+            }
+        };
+        if let Some(dir) = written_path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("create pack directory: {dir:?}"))?;
+        }
 
-        let written = write_pack(&pack_path, &inputs).context("write vcx pack")?;
-        let verified = verify(&pack_path).context("verify vcx pack")?;
+        ctx.progress(format!("writing vcx pack ({} entries)", inputs.len()));
+        let stage = ctx.stage("pack_write_ms");
+        let written =
+            write_pack(&written_path, &inputs, ctx.started_at()).context("write vcx pack")?;
+        stage.finish();
+        ctx.heartbeat()?;
+
+        ctx.progress("verifying vcx pack");
+        let stage = ctx.stage("pack_verify_ms");
+        let verified = verify(&written_path).context("verify vcx pack")?;
+        stage.finish();
         if !verified.ok {
             return Err(anyhow!(
                 "vcx pack verification failed: {}",
@@ -91,17 +114,21 @@ impl Plugin for VcxWorkerPlugin {
             ));
         }
 
+        let artifact_cid = match ctx.artifact_store() {
+            Some(store) => json!(store.import(&written_path).context("store vcx pack")?.cid),
+            None => json!(verified.pack_cid),
+        };
         Ok(json!({
             "pack": {
                 "cid": written.pack_cid,
-                "path": pack_path.display().to_string(),
+                "path": reported_path.display().to_string(),
                 "entries": written.index.entries.len(),
                 "bytes_written": written.bytes_written,
             },
             "artifacts": [
                 {
-                    "cid": verified.pack_cid,
-                    "path": pack_path.display().to_string(),
+                    "cid": artifact_cid,
+                    "path": reported_path.display().to_string(),
                     "size_bytes": written.bytes_written,
                 }
             ]
@@ -157,6 +184,33 @@ fn extract_pack_inputs(payload: &Value) -> Result<Vec<PackInput>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        stages: Mutex<Vec<String>>,
+        progress: Mutex<Vec<String>>,
+    }
+
+    impl ExecutionHooks for RecordingHooks {
+        fn heartbeat(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn progress(&self, detail: &str) {
+            self.progress.lock().unwrap().push(detail.to_string());
+        }
+
+        fn stage(&self, name: &str, _elapsed_ms: u64) {
+            self.stages.lock().unwrap().push(name.to_string());
+        }
+    }
+
+    fn started_at() -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .to_utc()
+    }
 
     #[test]
     fn extract_pack_inputs_falls_back_to_payload_json() {
@@ -177,7 +231,10 @@ mod tests {
             ]
         });
 
-        let out = plugin.execute(payload).await.expect("plugin execute");
+        let out = plugin
+            .execute(&ExecutionContext::detached(started_at()), payload)
+            .await
+            .expect("plugin execute");
         let artifacts = out
             .get("artifacts")
             .and_then(Value::as_array)
@@ -186,4 +243,95 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(pack_dir);
     }
+
+    #[tokio::test]
+    async fn vcx_plugin_reports_stages_and_progress() {
+        let hooks = Arc::new(RecordingHooks::default());
+        let ctx = ExecutionContext::new(1, hooks.clone(), started_at());
+        let pack_dir =
+            std::env::temp_dir().join(format!("aurea-vcx-plugin-{}", uuid::Uuid::new_v4()));
+        let payload = json!({
+            "pack_dir": pack_dir.display().to_string(),
+            "items": [{"path":"a.txt","content":"hello"}]
+        });
+
+        VcxWorkerPlugin
+            .execute(&ctx, payload)
+            .await
+            .expect("plugin execute");
+
+        assert_eq!(
+            *hooks.stages.lock().unwrap(),
+            vec!["pack_write_ms".to_string(), "pack_verify_ms".to_string()]
+        );
+        assert_eq!(hooks.progress.lock().unwrap().len(), 2);
+
+        let _ = std::fs::remove_dir_all(pack_dir);
+    }
+
+    #[tokio::test]
+    async fn vcx_plugin_output_is_reproducible_in_a_sandbox() {
+        let pack_dir =
+            std::env::temp_dir().join(format!("aurea-vcx-plugin-{}", uuid::Uuid::new_v4()));
+        let sandbox =
+            std::env::temp_dir().join(format!("aurea-vcx-replay-{}", uuid::Uuid::new_v4()));
+        let payload = json!({
+            "pack_dir": pack_dir.display().to_string(),
+            "items": [{"path":"a.txt","content":"hello"}]
+        });
+
+        let original = VcxWorkerPlugin
+            .execute(&ExecutionContext::detached(started_at()), payload.clone())
+            .await
+            .expect("plugin execute");
+        let replayed = VcxWorkerPlugin
+            .execute(
+                &ExecutionContext::detached(started_at()).with_output_dir(&sandbox),
+                payload,
+            )
+            .await
+            .expect("plugin replay");
+
+        assert_eq!(original, replayed);
+        let written = std::fs::read_dir(&sandbox)
+            .expect("sandbox written")
+            .count();
+        assert_eq!(written, 1);
+
+        let _ = std::fs::remove_dir_all(pack_dir);
+        let _ = std::fs::remove_dir_all(sandbox);
+    }
+
+    #[tokio::test]
+    async fn vcx_plugin_keeps_packs_in_the_artifact_store() {
+        let root = std::env::temp_dir().join(format!("aurea-vcx-store-{}", uuid::Uuid::new_v4()));
+        let store = aurea_artifacts_store::ArtifactStore::open(&root).expect("open store");
+        let payload = json!({
+            "pack_dir": "/nonexistent/packs",
+            "items": [{"path":"a.txt","content":"hello"}]
+        });
+        let ctx = ExecutionContext::detached(started_at()).with_artifact_store(store.clone());
+
+        let first = VcxWorkerPlugin
+            .execute(&ctx, payload.clone())
+            .await
+            .expect("plugin execute");
+        let second = VcxWorkerPlugin
+            .execute(&ctx, payload.clone())
+            .await
+            .expect("plugin execute again");
+
+        assert_eq!(first, second);
+        let artifact = &first["artifacts"][0];
+        let cid = artifact["cid"].as_str().expect("artifact cid");
+        assert_eq!(
+            artifact["path"],
+            format!("{}.vcxpack", cid_for(&payload).expect("payload hash"))
+        );
+        let bytes = store.read(cid).expect("read").expect("stored pack");
+        assert_eq!(aurea_core::cid_of(&bytes), cid);
+        assert_eq!(store.list().expect("list"), [cid.to_string()]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
 }
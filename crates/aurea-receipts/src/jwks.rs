@@ -0,0 +1,467 @@
+use anyhow::{Context, Result, anyhow};
+use aurea_core::ReceiptSignature;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD as B64, URL_SAFE_NO_PAD as B64URL};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{KeyMetadata, KeyRing, KeyStatus, ReceiptSigner, check_signature, cosign_receipt};
+
+/// Prefixed to the set's CID before signing, so a key set signature can never
+/// pass for a receipt signature over the same bytes, or the other way round.
+const KEY_SET_CONTEXT: &str = "aurea-keyset-v1";
+
+/// One keyring entry as an RFC 8037 OKP key, plus the keyring's own fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    /// The raw public key, base64url without padding.
+    pub x: String,
+    pub kid: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+    pub status: KeyStatus,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compromised_at: Option<DateTime<Utc>>,
+}
+
+/// The signed part of the published key set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeySet {
+    /// Keyring version; it only ever grows, so a client that has seen
+    /// version N rejects anything older.
+    pub version: u64,
+    pub issued_at: DateTime<Utc>,
+    pub active_kid: Option<String>,
+    pub keys: Vec<Jwk>,
+}
+
+/// What `GET /v1/keys` serves: a JWKS document (`keys` at the top level)
+/// with the CID of its canonical form and the active key's signature over it.
+/// Retired keys endorse the set too, so that a client that trusts one of them
+/// can follow a rotation to the new active key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedKeySet {
+    #[serde(flatten)]
+    pub set: KeySet,
+    pub cid: String,
+    pub signature: ReceiptSignature,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endorsements: Vec<ReceiptSignature>,
+}
+
+impl Jwk {
+    pub fn from_metadata(meta: &KeyMetadata) -> Result<Self> {
+        let public_key = B64
+            .decode(meta.public_key.as_bytes())
+            .with_context(|| format!("decode public key of {} failed", meta.kid))?;
+        Ok(Self {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: B64URL.encode(public_key),
+            kid: meta.kid.clone(),
+            key_use: "sig".to_string(),
+            alg: "EdDSA".to_string(),
+            status: meta.status.clone(),
+            created_at: meta.created_at.clone(),
+            valid_from: meta.valid_from,
+            valid_until: meta.valid_until,
+            revoked_at: meta.revoked_at.clone(),
+            compromised_at: meta.compromised_at,
+        })
+    }
+
+    pub fn to_metadata(&self) -> Result<KeyMetadata> {
+        if self.kty != "OKP" || self.crv != "Ed25519" {
+            return Err(anyhow!(
+                "key {} is {}/{}, expected OKP/Ed25519",
+                self.kid,
+                self.kty,
+                self.crv
+            ));
+        }
+        let public_key = B64URL
+            .decode(self.x.as_bytes())
+            .with_context(|| format!("decode x of {} failed", self.kid))?;
+        Ok(KeyMetadata {
+            kid: self.kid.clone(),
+            public_key: B64.encode(public_key),
+            created_at: self.created_at.clone(),
+            status: self.status.clone(),
+            revoked_at: self.revoked_at.clone(),
+            valid_from: self.valid_from,
+            valid_until: self.valid_until,
+            compromised_at: self.compromised_at,
+        })
+    }
+}
+
+impl KeySet {
+    pub fn from_keyring(keyring: &KeyRing, issued_at: DateTime<Utc>) -> Result<Self> {
+        Ok(Self {
+            version: keyring.version,
+            issued_at,
+            active_kid: keyring.active_kid.clone(),
+            keys: keyring
+                .keys
+                .iter()
+                .map(Jwk::from_metadata)
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn cid(&self) -> Result<String> {
+        Ok(aurea_core::cid_for(self)?)
+    }
+
+    /// The CID of the keys alone, leaving out when the set was issued; two
+    /// sets of one version must agree on it.
+    fn content_cid(&self) -> Result<String> {
+        Self {
+            issued_at: DateTime::UNIX_EPOCH,
+            ..self.clone()
+        }
+        .cid()
+    }
+
+    pub fn to_keyring(&self) -> Result<KeyRing> {
+        Ok(KeyRing {
+            version: self.version,
+            active_kid: self.active_kid.clone(),
+            keys: self
+                .keys
+                .iter()
+                .map(Jwk::to_metadata)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Publishes `keyring`, signed by `signer`, which has to be its active key,
+/// and endorsed by each of `endorsers`, which have to be retired keys of it.
+pub fn sign_key_set(
+    keyring: &KeyRing,
+    signer: &dyn ReceiptSigner,
+    endorsers: &[&dyn ReceiptSigner],
+    issued_at: DateTime<Utc>,
+) -> Result<SignedKeySet> {
+    if keyring.active_kid.as_deref() != Some(signer.key_id()) {
+        return Err(anyhow!(
+            "key set must be signed by the active key, not {}",
+            signer.key_id()
+        ));
+    }
+    let set = KeySet::from_keyring(keyring, issued_at)?;
+    let cid = set.cid()?;
+    let signing_input = signing_input(&cid);
+    let signature = cosign_receipt(&signing_input, signer)?;
+    let endorsements = endorsers
+        .iter()
+        .map(|endorser| {
+            let endorsement = cosign_receipt(&signing_input, *endorser)?;
+            match keyring.get(&endorsement.kid) {
+                Some(key)
+                    if key.status == KeyStatus::Retired
+                        && key.public_key == endorsement.public_key =>
+                {
+                    Ok(endorsement)
+                }
+                _ => Err(anyhow!(
+                    "key set can only be endorsed by its retired keys, not {}",
+                    endorsement.kid
+                )),
+            }
+        })
+        .collect::<Result<_>>()?;
+    Ok(SignedKeySet {
+        set,
+        cid,
+        signature,
+        endorsements,
+    })
+}
+
+/// Checks a fetched key set against `anchor` and returns it as a keyring for
+/// `verify_receipt_with_keyring`.
+///
+/// `anchor` is what the caller already trusts: the last key set it accepted,
+/// or a keyring of pinned keys (version 0) on first contact. The set must be
+/// signed by its own active key, and that key must either be one the anchor
+/// trusts or have been endorsed by one, i.e. a rotation chained from a
+/// trusted key. Sets older than the anchor are refused as rollbacks, as are
+/// sets of the anchor's own version holding other keys, and sets that give a
+/// kid the anchor knows another public key.
+pub fn verify_key_set(doc: &SignedKeySet, anchor: &KeyRing) -> Result<KeyRing> {
+    if doc.set.version < anchor.version {
+        return Err(anyhow!(
+            "key set version {} is older than the pinned version {}",
+            doc.set.version,
+            anchor.version
+        ));
+    }
+    if doc.set.cid()? != doc.cid {
+        return Err(anyhow!("key set cid mismatch"));
+    }
+    if doc.set.version == anchor.version
+        && doc.set.content_cid()?
+            != KeySet::from_keyring(anchor, doc.set.issued_at)?.content_cid()?
+    {
+        return Err(anyhow!(
+            "key set version {} differs from the pinned set of the same version",
+            doc.set.version
+        ));
+    }
+    let keyring = doc.set.to_keyring()?;
+    let signer_kid = &doc.signature.kid;
+    if keyring.active_kid.as_ref() != Some(signer_kid) {
+        return Err(anyhow!(
+            "key set signed by {signer_kid}, not its active key"
+        ));
+    }
+    let active = keyring
+        .get(signer_kid)
+        .ok_or_else(|| anyhow!("active key {signer_kid} is not in the key set"))?;
+    if active.public_key != doc.signature.public_key {
+        return Err(anyhow!("public key mismatch for key_id {signer_kid}"));
+    }
+    let signing_input = signing_input(&doc.cid);
+    check_signature(&signing_input, &doc.signature)
+        .map_err(|reason| anyhow!("key set signature by {signer_kid}: {reason}"))?;
+    if let Some(key) = anchor.keys.iter().find(|key| {
+        keyring
+            .get(&key.kid)
+            .is_some_and(|published| published.public_key != key.public_key)
+    }) {
+        return Err(anyhow!(
+            "key set changes the public key of {}, which the anchor pins",
+            key.kid
+        ));
+    }
+
+    if !anchor_trusts(anchor, &doc.signature) {
+        let endorsed = doc.endorsements.iter().any(|endorsement| {
+            anchor_trusts(anchor, endorsement)
+                && keyring
+                    .get(&endorsement.kid)
+                    .is_some_and(|key| key.public_key == endorsement.public_key)
+                && check_signature(&signing_input, endorsement).is_ok()
+        });
+        if !endorsed {
+            return Err(anyhow!(
+                "key set signed by {signer_kid}, which the anchor does not trust and no key it trusts endorsed"
+            ));
+        }
+    }
+    Ok(keyring)
+}
+
+fn signing_input(cid: &str) -> String {
+    format!("{KEY_SET_CONTEXT}:{cid}")
+}
+
+/// Whether `anchor` holds the signing key as one of its own, uncompromised
+/// keys.
+fn anchor_trusts(anchor: &KeyRing, signature: &ReceiptSignature) -> bool {
+    anchor.get(&signature.kid).is_some_and(|key| {
+        matches!(key.status, KeyStatus::Active | KeyStatus::Retired)
+            && key.compromised_at.is_none()
+            && key.public_key == signature.public_key
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalSigner;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+
+    fn keyring(active: &SigningKey, retired: &SigningKey) -> KeyRing {
+        let meta = |kid: &str, key: &SigningKey, status| KeyMetadata {
+            kid: kid.to_string(),
+            public_key: B64.encode(key.verifying_key().to_bytes()),
+            created_at: "2026-03-01T00:00:00+00:00".to_string(),
+            status,
+            revoked_at: None,
+            valid_from: None,
+            valid_until: None,
+            compromised_at: None,
+        };
+        KeyRing {
+            version: 3,
+            active_kid: Some("k2".to_string()),
+            keys: vec![
+                meta("k1", retired, KeyStatus::Retired),
+                meta("k2", active, KeyStatus::Active),
+            ],
+        }
+    }
+
+    #[test]
+    fn published_key_set_round_trips_through_jwks_json() {
+        let active = SigningKey::generate(&mut OsRng);
+        let ring = keyring(&active, &SigningKey::generate(&mut OsRng));
+        let doc = sign_key_set(
+            &ring,
+            &LocalSigner::new("k2", active.clone()),
+            &[],
+            Utc::now(),
+        )
+        .expect("sign");
+
+        let json = serde_json::to_value(&doc).expect("json");
+        assert_eq!(json["keys"][1]["kty"], "OKP");
+        assert_eq!(json["keys"][1]["use"], "sig");
+        assert_eq!(json["keys"][1]["alg"], "EdDSA");
+        assert_eq!(json["version"], 3);
+
+        let parsed: SignedKeySet = serde_json::from_value(json).expect("parse");
+        let verified = verify_key_set(&parsed, &ring).expect("verify");
+        assert_eq!(verified.keys[0].public_key, ring.keys[0].public_key);
+        assert_eq!(verified.active_kid.as_deref(), Some("k2"));
+
+        assert!(
+            sign_key_set(
+                &ring,
+                &LocalSigner::new("k1", SigningKey::generate(&mut OsRng)),
+                &[],
+                Utc::now()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn tampered_or_rolled_back_key_sets_are_refused() {
+        let active = SigningKey::generate(&mut OsRng);
+        let ring = keyring(&active, &SigningKey::generate(&mut OsRng));
+        let doc = sign_key_set(
+            &ring,
+            &LocalSigner::new("k2", active.clone()),
+            &[],
+            Utc::now(),
+        )
+        .expect("sign");
+
+        let newer = KeyRing {
+            version: 4,
+            ..ring.clone()
+        };
+        let err = verify_key_set(&doc, &newer).expect_err("rollback");
+        assert!(err.to_string().contains("pinned version"));
+
+        let mut tampered = doc.clone();
+        tampered.set.keys[0].status = KeyStatus::Active;
+        assert!(verify_key_set(&tampered, &ring).is_err());
+        tampered.cid = tampered.set.cid().expect("cid");
+        let err = verify_key_set(&tampered, &ring).expect_err("same version");
+        assert!(err.to_string().contains("same version"), "{err:#}");
+        tampered.set.version = 4;
+        tampered.cid = tampered.set.cid().expect("cid");
+        let err = verify_key_set(&tampered, &ring).expect_err("bad signature");
+        assert!(err.to_string().contains("signature verification failed"));
+
+        // Re-issuing the same keys at the same version is fine.
+        let reissued = sign_key_set(
+            &ring,
+            &LocalSigner::new("k2", active.clone()),
+            &[],
+            Utc::now() + chrono::Duration::minutes(1),
+        )
+        .expect("sign");
+        assert!(verify_key_set(&reissued, &ring).is_ok());
+    }
+
+    #[test]
+    fn receipt_signatures_do_not_pass_for_key_set_signatures() {
+        let active = SigningKey::generate(&mut OsRng);
+        let ring = keyring(&active, &SigningKey::generate(&mut OsRng));
+        let signer = LocalSigner::new("k2", active);
+        let mut doc = sign_key_set(&ring, &signer, &[], Utc::now()).expect("sign");
+        assert!(verify_key_set(&doc, &ring).is_ok());
+
+        doc.signature = cosign_receipt(&doc.cid, &signer).expect("cosign");
+        let err = verify_key_set(&doc, &ring).expect_err("receipt-style signature");
+        assert!(
+            err.to_string().contains("signature verification failed"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn self_signed_forged_key_sets_are_refused() {
+        let active = SigningKey::generate(&mut OsRng);
+        let ring = keyring(&active, &SigningKey::generate(&mut OsRng));
+
+        // A set minted by someone holding none of the anchored keys: it is
+        // internally consistent and signed by its own active key.
+        let forger = SigningKey::generate(&mut OsRng);
+        let mut forged = keyring(&forger, &SigningKey::generate(&mut OsRng));
+        forged.version = 9;
+        forged.active_kid = Some("k9".to_string());
+        forged.keys[1].kid = "k9".to_string();
+        forged.keys.remove(0);
+        let doc = sign_key_set(
+            &forged,
+            &LocalSigner::new("k9", forger.clone()),
+            &[],
+            Utc::now(),
+        )
+        .expect("sign");
+        let err = verify_key_set(&doc, &ring).expect_err("forged set");
+        assert!(err.to_string().contains("does not trust"), "{err:#}");
+
+        // Reusing the anchored kid with the forger's key does not help.
+        let mut impostor = keyring(&forger, &SigningKey::generate(&mut OsRng));
+        impostor.version = 4;
+        let doc = sign_key_set(&impostor, &LocalSigner::new("k2", forger), &[], Utc::now())
+            .expect("sign");
+        let err = verify_key_set(&doc, &ring).expect_err("kid reuse");
+        assert!(
+            err.to_string().contains("changes the public key"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn rotations_endorsed_by_a_trusted_key_are_followed() {
+        let old = SigningKey::generate(&mut OsRng);
+        let ring = keyring(&old, &SigningKey::generate(&mut OsRng));
+
+        let new = SigningKey::generate(&mut OsRng);
+        let mut rotated = ring.clone();
+        rotated.version = 4;
+        rotated.keys[1].status = KeyStatus::Retired;
+        rotated.keys.push(KeyMetadata {
+            kid: "k3".to_string(),
+            public_key: B64.encode(new.verifying_key().to_bytes()),
+            status: KeyStatus::Active,
+            ..rotated.keys[1].clone()
+        });
+        rotated.active_kid = Some("k3".to_string());
+        let signer = LocalSigner::new("k3", new);
+
+        let bare = sign_key_set(&rotated, &signer, &[], Utc::now()).expect("sign");
+        assert!(verify_key_set(&bare, &ring).is_err());
+
+        let endorser = LocalSigner::new("k2", old);
+        let doc = sign_key_set(&rotated, &signer, &[&endorser], Utc::now()).expect("sign");
+        let accepted = verify_key_set(&doc, &ring).expect("chained rotation");
+        assert_eq!(accepted.active_kid.as_deref(), Some("k3"));
+        // And the accepted set anchors the next fetch on its own.
+        assert!(verify_key_set(&bare, &accepted).is_ok());
+
+        let mut compromised = ring.clone();
+        compromised.keys[1].compromised_at = Some(Utc::now());
+        assert!(verify_key_set(&doc, &compromised).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use aurea_core::Receipt;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{DayAnchor, VerifyResult, check_anchor, verify_receipt};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    Active,
+    Retired,
+    Revoked,
+    /// Someone else's key, trusted to countersign receipts; never signs here.
+    Cosigner,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyMetadata {
+    pub kid: String,
+    pub public_key: String,
+    pub created_at: String,
+    pub status: KeyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<String>,
+    /// Receipts created before this are not trusted; unbounded when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<DateTime<Utc>>,
+    /// Receipts created at or after this are not trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// From this instant on the key may be in someone else's hands. A revoked
+    /// key without it is distrusted for every receipt it signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compromised_at: Option<DateTime<Utc>>,
+}
+
+/// The `keyring.json` next to the signing keys: every key this deployment has
+/// signed with or accepts cosignatures from, with its validity windows.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct KeyRing {
+    /// Bumped every time the keyring changes on disk; published key sets
+    /// carry it so clients can detect rollback.
+    #[serde(default)]
+    pub version: u64,
+    pub active_kid: Option<String>,
+    pub keys: Vec<KeyMetadata>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyJudgement {
+    Valid,
+    Unknown,
+    KeyMismatch,
+    NotYetValid,
+    Expired,
+    Compromised,
+}
+
+/// How the keyring judges one signature, and why.
+///
+/// The judgement is only as good as the instant it is made at, and for a
+/// receipt that is `created_at`, which the signer asserts. Whoever still holds
+/// a key that was rotated out, or revoked without a `compromised_at`, can
+/// backdate a receipt into the key's window and get a valid verdict. Only the
+/// anchor of that day ([`verify_receipt_with_anchor`]) catches a receipt dated
+/// before the anchor was generated but signed after it.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct KeyVerdict {
+    pub kid: String,
+    pub judgement: KeyJudgement,
+    pub reason: String,
+}
+
+impl KeyVerdict {
+    pub fn ok(&self) -> bool {
+        self.judgement == KeyJudgement::Valid
+    }
+}
+
+/// A receipt checked against a keyring, for verifiers that only have the
+/// receipt and a copy of `keyring.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptVerdict {
+    pub ok: bool,
+    pub cid: String,
+    pub created_at: DateTime<Utc>,
+    /// CID, signatures and threshold, without the keyring.
+    pub signatures: VerifyResult,
+    pub keys: Vec<KeyVerdict>,
+    /// The cross-check of `created_at` against the anchor of its day, when
+    /// one was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<VerifyResult>,
+    /// Why the receipt passed or the first reason it failed.
+    pub reason: String,
+}
+
+impl KeyRing {
+    pub fn get(&self, kid: &str) -> Option<&KeyMetadata> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+
+    /// Judges a signature by `kid` over `public_key` on something created at
+    /// `at`, using the key's state at that instant rather than today's. `at`
+    /// is taken on trust; see [`KeyVerdict`] for what that allows.
+    pub fn judge(&self, kid: &str, public_key: &str, at: DateTime<Utc>) -> KeyVerdict {
+        let verdict = |judgement, reason: String| KeyVerdict {
+            kid: kid.to_string(),
+            judgement,
+            reason,
+        };
+        let Some(meta) = self.get(kid) else {
+            return verdict(
+                KeyJudgement::Unknown,
+                format!("key_id {kid} not found in keyring"),
+            );
+        };
+        if meta.public_key != public_key {
+            return verdict(
+                KeyJudgement::KeyMismatch,
+                format!("public key mismatch for key_id {kid}"),
+            );
+        }
+        match meta.compromised_at {
+            Some(compromised_at) if at >= compromised_at => {
+                return verdict(
+                    KeyJudgement::Compromised,
+                    format!(
+                        "key_id {kid} was compromised at {}, signed at {}",
+                        compromised_at.to_rfc3339(),
+                        at.to_rfc3339()
+                    ),
+                );
+            }
+            None if meta.status == KeyStatus::Revoked => {
+                return verdict(
+                    KeyJudgement::Compromised,
+                    format!("key_id {kid} is revoked with no compromise time"),
+                );
+            }
+            _ => {}
+        }
+        if let Some(valid_from) = meta.valid_from.filter(|from| at < *from) {
+            return verdict(
+                KeyJudgement::NotYetValid,
+                format!(
+                    "key_id {kid} is valid from {}, signed at {}",
+                    valid_from.to_rfc3339(),
+                    at.to_rfc3339()
+                ),
+            );
+        }
+        if let Some(valid_until) = meta.valid_until.filter(|until| at >= *until) {
+            return verdict(
+                KeyJudgement::Expired,
+                format!(
+                    "key_id {kid} expired at {}, signed at {}",
+                    valid_until.to_rfc3339(),
+                    at.to_rfc3339()
+                ),
+            );
+        }
+        let mut reason = format!("key_id {kid} was valid at {}", at.to_rfc3339());
+        if let Some(compromised_at) = meta.compromised_at {
+            reason.push_str(&format!(
+                ", before its compromise at {}",
+                compromised_at.to_rfc3339()
+            ));
+        }
+        verdict(KeyJudgement::Valid, reason)
+    }
+
+    /// One verdict per signature, each judged at the receipt's `created_at`.
+    pub fn judge_receipt(&self, receipt: &Receipt) -> Vec<KeyVerdict> {
+        receipt
+            .signatures
+            .iter()
+            .map(|signature| self.judge(&signature.kid, &signature.public_key, receipt.created_at))
+            .collect()
+    }
+}
+
+pub fn verify_receipt_with_keyring(receipt: &Receipt, keyring: &KeyRing) -> ReceiptVerdict {
+    verify_receipt_with_anchor(receipt, keyring, None)
+}
+
+/// [`verify_receipt_with_keyring`], also cross-checking the receipt's
+/// `created_at` against the anchor of its day when there is one.
+pub fn verify_receipt_with_anchor(
+    receipt: &Receipt,
+    keyring: &KeyRing,
+    anchor: Option<&DayAnchor>,
+) -> ReceiptVerdict {
+    let signatures = verify_receipt(receipt);
+    let keys = keyring.judge_receipt(receipt);
+    let anchor = anchor.map(|anchor| check_anchor(receipt, anchor));
+    let failed_key = keys.iter().find(|verdict| !verdict.ok());
+    let failed_anchor = anchor.as_ref().filter(|anchor| !anchor.ok);
+    let reason = if !signatures.ok {
+        signatures
+            .reason
+            .clone()
+            .unwrap_or_else(|| "signature check failed".to_string())
+    } else if let Some(verdict) = failed_key {
+        verdict.reason.clone()
+    } else if let Some(anchor) = failed_anchor {
+        anchor.reason.clone().unwrap_or_default()
+    } else {
+        keys.iter()
+            .map(|verdict| verdict.reason.as_str())
+            .chain(anchor.as_ref().and_then(|anchor| anchor.reason.as_deref()))
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+    ReceiptVerdict {
+        ok: signatures.ok && failed_key.is_none() && failed_anchor.is_none(),
+        cid: receipt.cid.clone(),
+        created_at: receipt.created_at,
+        signatures,
+        keys,
+        anchor,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LocalSigner, sign_receipt};
+    use aurea_core::{UnsignedReceipt, WorkStatus};
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as B64;
+    use chrono::TimeZone;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, 0, 0, 0).unwrap()
+    }
+
+    fn receipt_on(day: u32, signer: &SigningKey) -> Receipt {
+        receipt_for(day, &format!("ik-{day}"), signer)
+    }
+
+    fn receipt_for(day: u32, idem_key: &str, signer: &SigningKey) -> Receipt {
+        let unsigned = UnsignedReceipt {
+            work_id: Uuid::nil(),
+            tenant: "t1".to_string(),
+            topic: "echo:test".to_string(),
+            status: WorkStatus::Done,
+            idem_key: idem_key.to_string(),
+            plan_hash: "ph".to_string(),
+            policy_trace: vec![],
+            stage_time_ms: BTreeMap::new(),
+            artifacts: vec![],
+            attempts: vec![],
+            created_at: at(day),
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
+        };
+        sign_receipt(&unsigned, &LocalSigner::new("k1", signer.clone())).unwrap()
+    }
+
+    fn ring(signer: &SigningKey, status: KeyStatus) -> KeyRing {
+        KeyRing {
+            version: 1,
+            active_kid: None,
+            keys: vec![KeyMetadata {
+                kid: "k1".to_string(),
+                public_key: B64.encode(signer.verifying_key().to_bytes()),
+                created_at: at(1).to_rfc3339(),
+                status,
+                revoked_at: None,
+                valid_from: Some(at(1)),
+                valid_until: Some(at(20)),
+                compromised_at: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn receipts_are_judged_by_the_key_state_when_they_were_created() {
+        let signer = SigningKey::generate(&mut OsRng);
+        let mut keyring = ring(&signer, KeyStatus::Revoked);
+        keyring.keys[0].compromised_at = Some(at(10));
+
+        let before = verify_receipt_with_keyring(&receipt_on(5, &signer), &keyring);
+        assert!(before.ok, "{}", before.reason);
+        assert!(before.reason.contains("before its compromise"));
+
+        let after = verify_receipt_with_keyring(&receipt_on(12, &signer), &keyring);
+        assert!(!after.ok);
+        assert!(after.signatures.ok);
+        assert_eq!(after.keys[0].judgement, KeyJudgement::Compromised);
+
+        keyring.keys[0].compromised_at = None;
+        let legacy = verify_receipt_with_keyring(&receipt_on(5, &signer), &keyring);
+        assert_eq!(legacy.keys[0].judgement, KeyJudgement::Compromised);
+    }
+
+    #[test]
+    fn backdated_receipts_are_caught_by_the_anchor_of_their_day() {
+        let signer = SigningKey::generate(&mut OsRng);
+        let keyring = ring(&signer, KeyStatus::Retired);
+        let anchored = receipt_on(5, &signer);
+        let mut anchor = crate::anchor_day(
+            "2026-03-05",
+            std::slice::from_ref(&anchored.cid),
+            &aurea_core::MockClock::new(at(6)),
+        );
+
+        let verdict = verify_receipt_with_anchor(&anchored, &keyring, Some(&anchor));
+        assert!(verdict.ok, "{}", verdict.reason);
+
+        // Valid for the keyring alone: the key's window covers the claimed day.
+        let backdated = receipt_for(5, "ik-late", &signer);
+        assert!(verify_receipt_with_keyring(&backdated, &keyring).ok);
+        let verdict = verify_receipt_with_anchor(&backdated, &keyring, Some(&anchor));
+        assert!(!verdict.ok);
+        assert!(verdict.reason.contains("missing from the anchor"));
+
+        anchor.receipt_cids.clear();
+        let verdict = verify_receipt_with_anchor(&anchored, &keyring, Some(&anchor));
+        assert!(!verdict.ok);
+
+        // An anchor generated before the receipt's time does not cover it.
+        let early = crate::anchor_day("2026-03-05", &[], &aurea_core::MockClock::new(at(5)));
+        assert!(verify_receipt_with_anchor(&backdated, &keyring, Some(&early)).ok);
+        let elsewhere = crate::anchor_day("2026-03-06", &[], &aurea_core::MockClock::new(at(7)));
+        assert!(!verify_receipt_with_anchor(&backdated, &keyring, Some(&elsewhere)).ok);
+    }
+
+    #[test]
+    fn validity_window_bounds_the_receipts_a_key_may_sign() {
+        let signer = SigningKey::generate(&mut OsRng);
+        let keyring = ring(&signer, KeyStatus::Retired);
+        let public_key = B64.encode(signer.verifying_key().to_bytes());
+
+        assert!(keyring.judge("k1", &public_key, at(1)).ok());
+        assert_eq!(
+            keyring.judge("k1", &public_key, at(20)).judgement,
+            KeyJudgement::Expired
+        );
+        let early = Utc.with_ymd_and_hms(2026, 2, 28, 0, 0, 0).unwrap();
+        assert_eq!(
+            keyring.judge("k1", &public_key, early).judgement,
+            KeyJudgement::NotYetValid
+        );
+        assert_eq!(
+            keyring.judge("k1", "other", at(5)).judgement,
+            KeyJudgement::KeyMismatch
+        );
+        assert_eq!(
+            keyring.judge("k2", &public_key, at(5)).judgement,
+            KeyJudgement::Unknown
+        );
+    }
+}
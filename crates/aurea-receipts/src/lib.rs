@@ -1,13 +1,25 @@
 use std::path::Path;
 
 use anyhow::{Context, Result, anyhow};
-use aurea_core::{Receipt, ReceiptSignature, UnsignedReceipt};
+use aurea_core::{Clock, Receipt, ReceiptSignature, SystemClock, UnsignedReceipt};
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as B64;
-use chrono::Utc;
 use ed25519_dalek::{Signature as DalekSignature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
+mod jwks;
+mod keyring;
+mod signer;
+
+pub use jwks::{Jwk, KeySet, SignedKeySet, sign_key_set, verify_key_set};
+pub use keyring::{
+    KeyJudgement, KeyMetadata, KeyRing, KeyStatus, KeyVerdict, ReceiptVerdict,
+    verify_receipt_with_anchor, verify_receipt_with_keyring,
+};
+pub use signer::{
+    LocalSigner, ReceiptSigner, RemoteSigner, SignerAddress, serve_signer_connection,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Signature {
     pub alg: String,
@@ -28,28 +40,21 @@ pub struct DayAnchor {
     pub root: String,
     pub count: usize,
     pub generated_at: String,
+    /// The anchored receipt CIDs, sorted, so a receipt dated this day can be
+    /// checked against the anchor without the database; see [`check_anchor`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub receipt_cids: Vec<String>,
 }
 
 pub fn cid_of(bytes: &[u8]) -> String {
     aurea_core::cid_of(bytes)
 }
 
-pub fn sign_receipt(
-    unsigned: &UnsignedReceipt,
-    key_id: &str,
-    signer: &SigningKey,
-) -> Result<Receipt> {
+pub fn sign_receipt(unsigned: &UnsignedReceipt, signer: &dyn ReceiptSigner) -> Result<Receipt> {
     let unsigned_json = serde_json::to_value(unsigned).context("serialize unsigned receipt")?;
     let canon = aurea_core::to_nrf_bytes(unsigned_json, aurea_core::CanonProfile::default())?;
     let receipt_cid = cid_of(&canon);
-
-    let sig = signer.sign(receipt_cid.as_bytes());
-    let signature = ReceiptSignature {
-        alg: "ed25519".to_string(),
-        kid: key_id.to_string(),
-        public_key: B64.encode(signer.verifying_key().to_bytes()),
-        signature: B64.encode(sig.to_bytes()),
-    };
+    let signature = cosign_receipt(&receipt_cid, signer)?;
 
     Ok(Receipt {
         cid: receipt_cid,
@@ -59,105 +64,101 @@ pub fn sign_receipt(
         status: unsigned.status,
         idem_key: unsigned.idem_key.clone(),
         plan_hash: unsigned.plan_hash.clone(),
+        input_cid: unsigned.input_cid.clone(),
         policy_trace: unsigned.policy_trace.clone(),
         stage_time_ms: unsigned.stage_time_ms.clone(),
         artifacts: unsigned.artifacts.clone(),
+        result_cid: unsigned.result_cid.clone(),
+        attempts: unsigned.attempts.clone(),
         created_at: unsigned.created_at,
-        signature,
+        started_at: unsigned.started_at,
+        trace_id: unsigned.trace_id.clone(),
+        threshold: unsigned.threshold.clone(),
+        signatures: vec![signature],
+    })
+}
+
+/// A signature over the receipt CID, for the issuer or for an independent
+/// key countersigning an existing receipt.
+pub fn cosign_receipt(receipt_cid: &str, signer: &dyn ReceiptSigner) -> Result<ReceiptSignature> {
+    let sig = signer.sign(receipt_cid.as_bytes())?;
+    Ok(ReceiptSignature {
+        alg: "ed25519".to_string(),
+        kid: signer.key_id().to_string(),
+        public_key: B64.encode(signer.verifying_key().to_bytes()),
+        signature: B64.encode(sig.to_bytes()),
     })
 }
 
+/// Passes when the CID matches the content, every signature on the receipt
+/// is valid and the signers meet the receipt's threshold. `key_id` is the
+/// issuer's.
 pub fn verify_receipt(receipt: &Receipt) -> VerifyResult {
-    if receipt.signature.alg != "ed25519" {
-        return VerifyResult {
-            ok: false,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: Some("unsupported algorithm".to_string()),
-        };
-    }
+    let key_id = receipt.issuer().map(|signature| signature.kid.clone());
+    let fail = |reason: String| VerifyResult {
+        ok: false,
+        key_id: key_id.clone(),
+        reason: Some(reason),
+    };
 
     let computed = match receipt.computed_cid() {
         Ok(cid) => cid,
-        Err(_) => {
-            return VerifyResult {
-                ok: false,
-                key_id: Some(receipt.signature.kid.clone()),
-                reason: Some("failed to recompute receipt cid".to_string()),
-            };
-        }
+        Err(_) => return fail("failed to recompute receipt cid".to_string()),
     };
-
     if computed != receipt.cid {
-        return VerifyResult {
-            ok: false,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: Some("receipt cid mismatch".to_string()),
-        };
+        return fail("receipt cid mismatch".to_string());
     }
 
-    let pk_bytes = match B64.decode(receipt.signature.public_key.as_bytes()) {
-        Ok(v) => v,
-        Err(_) => {
-            return VerifyResult {
-                ok: false,
-                key_id: Some(receipt.signature.kid.clone()),
-                reason: Some("invalid public key encoding".to_string()),
-            };
+    let mut signed_by = Vec::with_capacity(receipt.signatures.len());
+    for signature in &receipt.signatures {
+        if let Err(reason) = check_signature(&receipt.cid, signature) {
+            return fail(format!("signature by {}: {reason}", signature.kid));
         }
-    };
-    let sig_bytes = match B64.decode(receipt.signature.signature.as_bytes()) {
-        Ok(v) => v,
-        Err(_) => {
-            return VerifyResult {
-                ok: false,
-                key_id: Some(receipt.signature.kid.clone()),
-                reason: Some("invalid signature encoding".to_string()),
-            };
-        }
-    };
+        signed_by.push(signature.kid.clone());
+    }
 
-    let Ok(pk_arr) = <[u8; 32]>::try_from(pk_bytes.as_slice()) else {
-        return VerifyResult {
-            ok: false,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: Some("invalid public key length".to_string()),
-        };
-    };
-    let Ok(sig_arr) = <[u8; 64]>::try_from(sig_bytes.as_slice()) else {
-        return VerifyResult {
-            ok: false,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: Some("invalid signature length".to_string()),
-        };
-    };
+    let threshold = receipt.signature_threshold();
+    if !threshold.is_met_by(&signed_by) {
+        let missing = threshold.missing_kids(&signed_by);
+        return fail(if missing.is_empty() {
+            format!(
+                "needs {} signatures, has {}",
+                threshold.min_signatures,
+                signed_by.len()
+            )
+        } else {
+            format!("missing signatures from {}", missing.join(", "))
+        });
+    }
 
-    let verifying_key = match VerifyingKey::from_bytes(&pk_arr) {
-        Ok(v) => v,
-        Err(_) => {
-            return VerifyResult {
-                ok: false,
-                key_id: Some(receipt.signature.kid.clone()),
-                reason: Some("invalid public key".to_string()),
-            };
-        }
-    };
-    let signature = DalekSignature::from_bytes(&sig_arr);
+    VerifyResult {
+        ok: true,
+        key_id,
+        reason: None,
+    }
+}
 
-    match verifying_key.verify(receipt.cid.as_bytes(), &signature) {
-        Ok(_) => VerifyResult {
-            ok: true,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: None,
-        },
-        Err(_) => VerifyResult {
-            ok: false,
-            key_id: Some(receipt.signature.kid.clone()),
-            reason: Some("signature verification failed".to_string()),
-        },
+fn check_signature(cid: &str, signature: &ReceiptSignature) -> Result<(), &'static str> {
+    if signature.alg != "ed25519" {
+        return Err("unsupported algorithm");
     }
+    let pk_bytes = B64
+        .decode(signature.public_key.as_bytes())
+        .map_err(|_| "invalid public key encoding")?;
+    let sig_bytes = B64
+        .decode(signature.signature.as_bytes())
+        .map_err(|_| "invalid signature encoding")?;
+    let pk_arr =
+        <[u8; 32]>::try_from(pk_bytes.as_slice()).map_err(|_| "invalid public key length")?;
+    let sig_arr =
+        <[u8; 64]>::try_from(sig_bytes.as_slice()).map_err(|_| "invalid signature length")?;
+    let verifying_key = VerifyingKey::from_bytes(&pk_arr).map_err(|_| "invalid public key")?;
+    verifying_key
+        .verify(cid.as_bytes(), &DalekSignature::from_bytes(&sig_arr))
+        .map_err(|_| "signature verification failed")
 }
 
-pub fn anchor_day(date: &str, receipt_cids: &[String]) -> DayAnchor {
+pub fn anchor_day(date: &str, receipt_cids: &[String], clock: &dyn Clock) -> DayAnchor {
     let mut leaves = receipt_cids.to_vec();
     leaves.sort();
     let root = merkle_root(&leaves);
@@ -166,12 +167,70 @@ pub fn anchor_day(date: &str, receipt_cids: &[String]) -> DayAnchor {
         date: date.to_string(),
         root,
         count: leaves.len(),
-        generated_at: Utc::now().to_rfc3339(),
+        generated_at: clock.now().to_rfc3339(),
+        receipt_cids: leaves,
     }
 }
 
+/// Cross-checks a receipt's `created_at`, which its signer asserts, against
+/// the anchor of that day: a receipt dated before the anchor was generated
+/// has to be one of the receipts it covers, or it was signed later and
+/// backdated. Receipts dated after the anchor are not covered by it yet.
+pub fn check_anchor(receipt: &Receipt, anchor: &DayAnchor) -> VerifyResult {
+    let result = |ok, reason: String| VerifyResult {
+        ok,
+        key_id: None,
+        reason: Some(reason),
+    };
+    let day = receipt.created_at.date_naive().to_string();
+    if anchor.date != day {
+        return result(
+            false,
+            format!(
+                "anchor is for {}, receipt was created on {day}",
+                anchor.date
+            ),
+        );
+    }
+    let Ok(generated_at) = chrono::DateTime::parse_from_rfc3339(&anchor.generated_at) else {
+        return result(
+            false,
+            format!("anchor of {day} has an invalid generated_at"),
+        );
+    };
+    if receipt.created_at >= generated_at {
+        return result(
+            true,
+            format!(
+                "receipt postdates the anchor of {day} generated at {}",
+                anchor.generated_at
+            ),
+        );
+    }
+    let mut leaves = anchor.receipt_cids.clone();
+    leaves.sort();
+    if leaves.len() != anchor.count || merkle_root(&leaves) != anchor.root {
+        return result(
+            false,
+            format!("anchor of {day} does not list the receipts its root covers"),
+        );
+    }
+    if leaves.binary_search(&receipt.cid).is_err() {
+        return result(
+            false,
+            format!(
+                "receipt {} is dated {} but missing from the anchor of {day} generated at {}",
+                receipt.cid,
+                receipt.created_at.to_rfc3339(),
+                anchor.generated_at
+            ),
+        );
+    }
+    result(true, format!("receipt is in the anchor of {day}"))
+}
+
 pub fn rebuild_anchor(date: &str, receipt_cids: &[String], expected_root: &str) -> VerifyResult {
-    let anchor = anchor_day(date, receipt_cids);
+    let anchor = anchor_day(date, receipt_cids, &SystemClock);
     if anchor.root == expected_root {
         VerifyResult {
             ok: true,
@@ -316,8 +375,8 @@ fn blake3_hex(bytes: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use aurea_core::{UnsignedReceipt, WorkStatus};
-    use chrono::Utc;
+    use aurea_core::{MockClock, SignatureThreshold, UnsignedReceipt, WorkStatus};
+    use chrono::{TimeZone, Utc};
     use ed25519_dalek::SigningKey;
     use rand::rngs::OsRng;
     use std::collections::BTreeMap;
@@ -334,15 +393,21 @@ mod tests {
             policy_trace: vec![],
             stage_time_ms: BTreeMap::new(),
             artifacts: vec![],
+            attempts: vec![],
             created_at: Utc::now(),
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
         }
     }
 
     #[test]
     fn anchor_is_stable_for_same_set() {
         let items = vec!["c1".to_string(), "c2".to_string(), "c3".to_string()];
-        let a1 = anchor_day("2026-02-19", &items);
-        let a2 = anchor_day("2026-02-19", &items);
+        let a1 = anchor_day("2026-02-19", &items, &SystemClock);
+        let a2 = anchor_day("2026-02-19", &items, &SystemClock);
         assert_eq!(a1.root, a2.root);
         assert_eq!(a1.count, 3);
     }
@@ -353,9 +418,9 @@ mod tests {
         let signing_key = SigningKey::generate(&mut csprng);
         let unsigned = make_unsigned();
 
-        let receipt = sign_receipt(&unsigned, "kid-1", &signing_key).unwrap();
-        assert_eq!(receipt.signature.alg, "ed25519");
-        assert_eq!(receipt.signature.kid, "kid-1");
+        let receipt = sign_receipt(&unsigned, &LocalSigner::new("kid-1", signing_key)).unwrap();
+        assert_eq!(receipt.signatures[0].alg, "ed25519");
+        assert_eq!(receipt.signatures[0].kid, "kid-1");
         assert!(!receipt.cid.is_empty());
 
         let result = verify_receipt(&receipt);
@@ -369,7 +434,7 @@ mod tests {
         let signing_key = SigningKey::generate(&mut csprng);
         let unsigned = make_unsigned();
 
-        let mut receipt = sign_receipt(&unsigned, "kid-1", &signing_key).unwrap();
+        let mut receipt = sign_receipt(&unsigned, &LocalSigner::new("kid-1", signing_key)).unwrap();
         // Tamper the CID to break signature verification
         receipt.cid = "tampered-cid-000000000000000000000000000000000000000000000000".to_string();
 
@@ -377,13 +442,53 @@ mod tests {
         assert!(!result.ok, "expected verify to fail after CID tampering");
     }
 
+    #[test]
+    fn cosigned_receipt_verifies_once_the_threshold_is_met() {
+        let issuer = SigningKey::generate(&mut OsRng);
+        let auditor = SigningKey::generate(&mut OsRng);
+        let mut unsigned = make_unsigned();
+        unsigned.threshold = Some(SignatureThreshold {
+            required_kids: vec!["auditor".to_string()],
+            min_signatures: 2,
+        });
+
+        let mut receipt = sign_receipt(&unsigned, &LocalSigner::new("issuer", issuer)).unwrap();
+        let pending = verify_receipt(&receipt);
+        assert!(!pending.ok);
+        assert_eq!(
+            pending.reason.as_deref(),
+            Some("missing signatures from auditor")
+        );
+
+        let cid = receipt.cid.clone();
+        receipt
+            .signatures
+            .push(cosign_receipt(&cid, &LocalSigner::new("auditor", auditor)).unwrap());
+        let result = verify_receipt(&receipt);
+        assert!(result.ok, "verify failed: {:?}", result.reason);
+        assert_eq!(result.key_id.as_deref(), Some("issuer"));
+        assert_eq!(receipt.cid, cid);
+
+        receipt.signatures[1].signature = receipt.signatures[0].signature.clone();
+        let forged = verify_receipt(&receipt);
+        assert!(!forged.ok);
+        assert!(
+            forged
+                .reason
+                .as_deref()
+                .is_some_and(|reason| reason.starts_with("signature by auditor")),
+            "{:?}",
+            forged.reason
+        );
+    }
+
     #[test]
     fn anchor_order_does_not_matter() {
         let ordered = vec!["aaa".to_string(), "bbb".to_string(), "ccc".to_string()];
         let reversed = vec!["ccc".to_string(), "bbb".to_string(), "aaa".to_string()];
         // Anchors are stable but order-dependent by design (Merkle tree is ordered)
-        let a1 = anchor_day("2026-02-19", &ordered);
-        let a2 = anchor_day("2026-02-19", &reversed);
+        let a1 = anchor_day("2026-02-19", &ordered, &SystemClock);
+        let a2 = anchor_day("2026-02-19", &reversed, &SystemClock);
         // Both produce valid anchors with correct count
         assert_eq!(a1.count, 3);
         assert_eq!(a2.count, 3);
@@ -393,11 +498,25 @@ mod tests {
     #[test]
     fn anchor_single_item_has_stable_root() {
         let items = vec!["only-one".to_string()];
-        let a = anchor_day("2026-01-01", &items);
+        let a = anchor_day("2026-01-01", &items, &SystemClock);
         assert_eq!(a.count, 1);
         assert!(!a.root.is_empty());
         // Re-computing gives same root
-        let a2 = anchor_day("2026-01-01", &items);
+        let a2 = anchor_day("2026-01-01", &items, &SystemClock);
         assert_eq!(a.root, a2.root);
     }
+
+    #[test]
+    fn anchor_is_stamped_by_the_given_clock() {
+        let at = Utc.with_ymd_and_hms(2026, 2, 20, 0, 5, 0).unwrap();
+        let clock = MockClock::new(at);
+        let items = vec!["c1".to_string(), "c2".to_string()];
+        let a1 = anchor_day("2026-02-19", &items, &clock);
+        let a2 = anchor_day("2026-02-19", &items, &clock);
+        assert_eq!(a1.generated_at, at.to_rfc3339());
+        assert_eq!(
+            serde_json::to_vec(&a1).unwrap(),
+            serde_json::to_vec(&a2).unwrap()
+        );
+    }
 }
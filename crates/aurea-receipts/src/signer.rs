@@ -0,0 +1,439 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as B64;
+use ed25519_dalek::{Signature as DalekSignature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Where receipt signatures come from. Implementations may keep the private
+/// key outside the process; callers only see the key id, the public key and
+/// signatures.
+pub trait ReceiptSigner: Send + Sync {
+    fn key_id(&self) -> &str;
+    fn verifying_key(&self) -> VerifyingKey;
+    fn sign(&self, message: &[u8]) -> Result<DalekSignature>;
+}
+
+/// A key held in this process, e.g. one read from `keys/current.json`.
+pub struct LocalSigner {
+    kid: String,
+    key: SigningKey,
+}
+
+impl LocalSigner {
+    pub fn new(kid: impl Into<String>, key: SigningKey) -> Self {
+        Self {
+            kid: kid.into(),
+            key,
+        }
+    }
+}
+
+impl ReceiptSigner for LocalSigner {
+    fn key_id(&self) -> &str {
+        &self.kid
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<DalekSignature> {
+        Ok(self.key.sign(message))
+    }
+}
+
+/// `http://host:port` or `unix:/path/to/socket`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerAddress {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl SignerAddress {
+    pub fn parse(raw: &str) -> Result<Self> {
+        if let Some(authority) = raw.strip_prefix("http://") {
+            let authority = authority.trim_end_matches('/');
+            if authority.is_empty() || authority.contains('/') {
+                return Err(anyhow!("remote signer `{raw}` must be http://HOST:PORT"));
+            }
+            return Ok(Self::Tcp(authority.to_string()));
+        }
+        #[cfg(unix)]
+        if let Some(path) = raw.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err(anyhow!("remote signer `{raw}` needs a socket path"));
+            }
+            return Ok(Self::Unix(path.into()));
+        }
+        Err(anyhow!(
+            "unsupported remote signer `{raw}`; expected http://HOST:PORT or unix:/PATH"
+        ))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignerKey {
+    pub kid: String,
+    pub alg: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub kid: String,
+    pub signature: String,
+}
+
+/// Signs through a separate signer process speaking HTTP/1.1 over TCP or a
+/// Unix socket:
+///
+/// - `GET /v1/key` → `{kid, alg, public_key}`
+/// - `POST /v1/sign` with `{message}` (base64) → `{kid, signature}`
+///
+/// The key is fetched once when connecting, and every signature is checked
+/// against it before use. There is no PKCS#11 backend in-tree; an HSM or
+/// token is reached by putting it behind this protocol.
+pub struct RemoteSigner {
+    address: SignerAddress,
+    timeout: Duration,
+    kid: String,
+    public_key: VerifyingKey,
+}
+
+impl RemoteSigner {
+    pub fn connect(address: SignerAddress, timeout: Duration) -> Result<Self> {
+        let (status, body) = request(&address, timeout, "GET", "/v1/key", None)?;
+        if status != 200 {
+            return Err(anyhow!("remote signer key request returned {status}"));
+        }
+        let key: SignerKey =
+            serde_json::from_slice(&body).context("parse remote signer key failed")?;
+        if key.alg != "ed25519" {
+            return Err(anyhow!("remote signer uses unsupported alg {}", key.alg));
+        }
+        Ok(Self {
+            address,
+            timeout,
+            public_key: decode_public_key(&key.public_key)?,
+            kid: key.kid,
+        })
+    }
+}
+
+impl ReceiptSigner for RemoteSigner {
+    fn key_id(&self) -> &str {
+        &self.kid
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.public_key
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<DalekSignature> {
+        let body = serde_json::to_vec(&SignRequest {
+            message: B64.encode(message),
+        })
+        .context("serialize sign request failed")?;
+        let (status, body) = request(&self.address, self.timeout, "POST", "/v1/sign", Some(&body))?;
+        if status != 200 {
+            return Err(anyhow!("remote signer returned {status}"));
+        }
+        let response: SignResponse =
+            serde_json::from_slice(&body).context("parse remote signature failed")?;
+        if response.kid != self.kid {
+            return Err(anyhow!(
+                "remote signer switched key from {} to {}",
+                self.kid,
+                response.kid
+            ));
+        }
+        let bytes = B64
+            .decode(&response.signature)
+            .context("invalid base64 remote signature")?;
+        let signature = DalekSignature::from_slice(&bytes).context("invalid remote signature")?;
+        self.public_key
+            .verify(message, &signature)
+            .context("remote signature does not match the signer's public key")?;
+        Ok(signature)
+    }
+}
+
+fn decode_public_key(raw: &str) -> Result<VerifyingKey> {
+    let bytes = B64.decode(raw).context("invalid base64 public key")?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("invalid public key length"))?;
+    VerifyingKey::from_bytes(&bytes).context("invalid public key")
+}
+
+fn request(
+    address: &SignerAddress,
+    timeout: Duration,
+    method: &str,
+    path: &str,
+    body: Option<&[u8]>,
+) -> Result<(u16, Vec<u8>)> {
+    match address {
+        SignerAddress::Tcp(authority) => {
+            let stream = connect_tcp(authority, timeout)
+                .with_context(|| format!("connect to remote signer {authority} failed"))?;
+            stream.set_read_timeout(Some(timeout))?;
+            stream.set_write_timeout(Some(timeout))?;
+            exchange(stream, authority, method, path, body)
+        }
+        #[cfg(unix)]
+        SignerAddress::Unix(socket) => {
+            let stream = std::os::unix::net::UnixStream::connect(socket)
+                .with_context(|| format!("connect to remote signer {socket:?} failed"))?;
+            stream.set_read_timeout(Some(timeout))?;
+            stream.set_write_timeout(Some(timeout))?;
+            exchange(stream, "localhost", method, path, body)
+        }
+    }
+}
+
+/// Tries each address `authority` resolves to, none for longer than `timeout`.
+fn connect_tcp(authority: &str, timeout: Duration) -> Result<TcpStream> {
+    let mut last = anyhow!("{authority} resolves to no address");
+    for addr in authority.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last = err.into(),
+        }
+    }
+    Err(last)
+}
+
+fn exchange(
+    mut stream: impl Read + Write,
+    host: &str,
+    method: &str,
+    path: &str,
+    body: Option<&[u8]>,
+) -> Result<(u16, Vec<u8>)> {
+    let body = body.unwrap_or_default();
+    let head = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body))
+        .and_then(|_| stream.flush())
+        .context("send to remote signer failed")?;
+
+    let mut reader = BufReader::new(stream);
+    let (status_line, length) = read_head(&mut reader)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("malformed remote signer status line `{status_line}`"))?;
+    Ok((status, read_body(&mut reader, length)?))
+}
+
+/// The first line and `Content-Length` of an HTTP message.
+fn read_head(reader: &mut impl BufRead) -> Result<(String, usize)> {
+    let mut first = String::new();
+    reader
+        .read_line(&mut first)
+        .context("read from remote signer failed")?;
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader
+            .read_line(&mut line)
+            .context("read from remote signer failed")?
+            == 0
+        {
+            return Err(anyhow!("remote signer closed the connection mid-header"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().context("invalid content-length")?;
+        }
+    }
+    if length > MAX_MESSAGE_BYTES {
+        return Err(anyhow!("signer message of {length} bytes is too large"));
+    }
+    Ok((first.trim_end().to_string(), length))
+}
+
+fn read_body(reader: &mut impl Read, length: usize) -> Result<Vec<u8>> {
+    let mut body = vec![0u8; length];
+    reader
+        .read_exact(&mut body)
+        .context("read from remote signer failed")?;
+    Ok(body)
+}
+
+/// Answers one request on `stream` with `signer`; the other side of
+/// [`RemoteSigner`], used by `aurea keys serve-signer`.
+pub fn serve_signer_connection(
+    mut stream: impl Read + Write,
+    signer: &dyn ReceiptSigner,
+) -> Result<()> {
+    let (status, body) = {
+        let mut reader = BufReader::new(&mut stream);
+        let (request_line, length) = read_head(&mut reader)?;
+        let body = read_body(&mut reader, length)?;
+        let mut parts = request_line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/v1/key")) => (
+                200,
+                json!(SignerKey {
+                    kid: signer.key_id().to_string(),
+                    alg: "ed25519".to_string(),
+                    public_key: B64.encode(signer.verifying_key().to_bytes()),
+                }),
+            ),
+            (Some("POST"), Some("/v1/sign")) => {
+                match serde_json::from_slice::<SignRequest>(&body)
+                    .ok()
+                    .and_then(|request| B64.decode(request.message).ok())
+                {
+                    Some(message) => (
+                        200,
+                        json!(SignResponse {
+                            kid: signer.key_id().to_string(),
+                            signature: B64.encode(signer.sign(&message)?.to_bytes()),
+                        }),
+                    ),
+                    None => (400, json!({"error": "expected {\"message\": base64}"})),
+                }
+            }
+            _ => (404, json!({"error": "not found"})),
+        }
+    };
+    let body = serde_json::to_vec(&body).context("serialize signer response failed")?;
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        _ => "Not Found",
+    };
+    let head = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(&body))
+        .and_then(|_| stream.flush())
+        .context("write signer response failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sign_receipt, verify_receipt};
+    use aurea_core::{UnsignedReceipt, WorkStatus};
+    use chrono::Utc;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeMap;
+    use std::net::TcpListener;
+    use uuid::Uuid;
+
+    fn unsigned() -> UnsignedReceipt {
+        UnsignedReceipt {
+            work_id: Uuid::new_v4(),
+            tenant: "t1".to_string(),
+            topic: "echo:test".to_string(),
+            status: WorkStatus::Done,
+            idem_key: "ik".to_string(),
+            plan_hash: "ph".to_string(),
+            policy_trace: vec![],
+            stage_time_ms: BTreeMap::new(),
+            artifacts: vec![],
+            attempts: vec![],
+            created_at: Utc::now(),
+            trace_id: None,
+            started_at: None,
+            input_cid: None,
+            result_cid: None,
+            threshold: None,
+        }
+    }
+
+    /// Serves `connections` requests on a local port from another thread.
+    fn stand_in(signer: LocalSigner, connections: usize) -> SignerAddress {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = SignerAddress::Tcp(listener.local_addr().expect("addr").to_string());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                serve_signer_connection(stream.expect("accept"), &signer).expect("serve");
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn remote_signer_signs_receipts_over_http() {
+        let key = SigningKey::generate(&mut OsRng);
+        let public_key = key.verifying_key();
+        let address = stand_in(LocalSigner::new("remote-kid", key), 3);
+        let signer = RemoteSigner::connect(address, Duration::from_secs(5)).expect("connect");
+        assert_eq!(signer.key_id(), "remote-kid");
+        assert_eq!(signer.verifying_key(), public_key);
+
+        let receipt = sign_receipt(&unsigned(), &signer).expect("sign");
+        assert!(verify_receipt(&receipt).ok);
+        assert_eq!(receipt.signatures[0].kid, "remote-kid");
+        assert!(signer.sign(b"second").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remote_signer_speaks_over_a_unix_socket_and_rejects_foreign_keys() {
+        use std::os::unix::net::UnixListener;
+
+        let socket = std::env::temp_dir().join(format!("aurea-signer-{}.sock", Uuid::new_v4()));
+        let listener = UnixListener::bind(&socket).expect("bind");
+        let key = SigningKey::generate(&mut OsRng);
+        let impostor = LocalSigner::new("remote-kid", SigningKey::generate(&mut OsRng));
+        let honest = LocalSigner::new("remote-kid", key);
+        std::thread::spawn(move || {
+            let mut incoming = listener.incoming();
+            let mut next = || incoming.next().expect("conn").expect("accept");
+            serve_signer_connection(next(), &honest).expect("key");
+            serve_signer_connection(next(), &honest).expect("sign");
+            serve_signer_connection(next(), &impostor).expect("forged sign");
+        });
+
+        let address = SignerAddress::parse(&format!("unix:{}", socket.display())).expect("parse");
+        let signer = RemoteSigner::connect(address, Duration::from_secs(5)).expect("connect");
+        assert!(signer.sign(b"cid").is_ok());
+        let err = signer.sign(b"cid").expect_err("signature by another key");
+        assert!(err.to_string().contains("public key"), "{err:#}");
+
+        let _ = std::fs::remove_file(socket);
+    }
+
+    #[test]
+    fn signer_addresses_need_a_known_scheme() {
+        assert_eq!(
+            SignerAddress::parse("http://127.0.0.1:7400").expect("tcp"),
+            SignerAddress::Tcp("127.0.0.1:7400".to_string())
+        );
+        assert!(SignerAddress::parse("https://signer").is_err());
+        assert!(SignerAddress::parse("http://host/path").is_err());
+    }
+}
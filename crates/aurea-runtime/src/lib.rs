@@ -1,45 +1,100 @@
 use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
+use aurea_artifacts_store::ArtifactStore;
 use aurea_core::{
-    ArtifactRef, PolicyEntry, Receipt, ReceiptSignature, UnsignedReceipt, WorkStatus, WorkUnit,
-    cid_for,
+    ArtifactRef, Clock, PolicyEntry, Receipt, ReceiptSignature, SignatureThreshold, TraceParent,
+    UnsignedReceipt, WorkStatus, WorkUnit,
+};
+use aurea_plugins::{ExecutionContext, ExecutionHooks, Plugin, PluginRegistry};
+use aurea_receipts::{LocalSigner, ReceiptSigner};
+use aurea_storage::{
+    BackupArchive, CancelOutcome, DeadLetter, EnqueueResult, FailureOutcome, LEASE_EXPIRED_ERROR,
+    LabelledMetrics, MetricLabels, QueuedJob, ReceiptPage, ReceiptQuery, Store, blob_cid,
+    topic_matches,
 };
-use aurea_plugins::PluginRegistry;
-use aurea_storage::{EnqueueResult, QueuedJob, RedbStore};
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD as B64;
 use chrono::{DateTime, Utc};
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use ed25519_dalek::{Signature, SigningKey, Verifier, VerifyingKey};
 use rand::rngs::OsRng;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::Value;
 use tokio::sync::broadcast;
-use tracing::{debug, error};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{Instrument, Span, debug, error, info, info_span, warn};
 use uuid::Uuid;
 
+mod pool;
+mod replay;
+mod trace;
+
+pub use aurea_core::StreamEvent;
+pub use replay::{ReplayDifference, ReplayOutcome, ReplayReport, SignedReplayReport};
+
+use pool::{TopicSlot, WorkerPool};
+
 struct ReceiptBuild {
     status: WorkStatus,
+    stages: BTreeMap<String, u64>,
     policy_trace: Vec<PolicyEntry>,
     artifacts: Vec<ArtifactRef>,
+    input_cid: String,
+    result_cid: Option<String>,
     ttft_ms: u64,
     ttr_ms: u64,
     created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StreamEvent {
-    pub at: DateTime<Utc>,
+/// How often [`Runtime::cancel_work`] looks the job up again after it moved
+/// while its receipt was being signed.
+const CANCEL_ATTEMPTS: usize = 3;
+
+/// How a job ended, as its final receipt records it.
+struct JobOutcome<'a> {
+    status: WorkStatus,
+    detail: Option<String>,
+    artifacts: Vec<ArtifactRef>,
+    result: Option<&'a Value>,
+    stages: BTreeMap<String, u64>,
+}
+
+impl JobOutcome<'_> {
+    fn failed(status: WorkStatus, detail: String, stages: BTreeMap<String, u64>) -> Self {
+        Self {
+            status,
+            detail: Some(detail),
+            artifacts: Vec::new(),
+            result: None,
+            stages,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkState {
+    pub work_id: Uuid,
     pub tenant: String,
     pub topic: String,
-    pub work_id: Uuid,
     pub status: WorkStatus,
+    /// Number of times a worker picked the job up.
+    pub attempts: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub receipt_cid: Option<String>,
+    pub lease: Option<WorkLease>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub detail: Option<String>,
+    pub receipt_cid: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkLease {
+    pub attempt: u32,
+    pub leased_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +104,22 @@ pub enum AcceptDisposition {
     DuplicateInFlight,
 }
 
+#[derive(Debug, Clone)]
+pub enum CancelDisposition {
+    /// The job never finished running; its `cancelled` receipt is issued.
+    Cancelled {
+        receipt_cid: String,
+    },
+    /// A worker is executing the job and has been asked to stop.
+    Cancelling,
+    /// Another process holds a live lease on the job; only it can stop the
+    /// plugin, so the cancel has to go through that process.
+    LeasedElsewhere {
+        lease_expires_at: Option<DateTime<Utc>>,
+    },
+    NotFound,
+}
+
 #[derive(Debug, Clone)]
 pub struct AcceptedWork {
     pub work_id: Uuid,
@@ -59,24 +130,101 @@ pub struct AcceptedWork {
 pub struct ReceiptVerification {
     pub ok: bool,
     pub cid_match: bool,
+    /// Every signature on the receipt checks out against its public key.
     pub signature_valid: bool,
+    pub threshold_met: bool,
+    /// Kids with a valid signature, issuer first.
+    pub signed_by: Vec<String>,
+    /// Kids the threshold requires that have not signed yet.
+    pub missing_kids: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CosignOutcome {
+    NotFound,
+    /// The kid has already signed this receipt.
+    AlreadySigned,
+    /// The signature does not verify against the receipt CID.
+    InvalidSignature,
+    Cosigned(Box<Receipt>),
 }
 
 #[derive(Clone)]
 pub struct Runtime {
-    store: RedbStore,
+    store: Arc<dyn Store>,
+    clock: Arc<dyn Clock>,
     plugins: PluginRegistry,
     events_tx: broadcast::Sender<StreamEvent>,
-    signer: Arc<SigningKey>,
-    kid: String,
+    event_order: Arc<Mutex<EventOrder>>,
+    signer: Arc<dyn ReceiptSigner>,
     lease_ttl_ms: u64,
     worker_tick_ms: u64,
+    workers: usize,
+    pool: Arc<WorkerPool>,
+    replay_dir: Arc<PathBuf>,
+    artifacts: Option<ArtifactStore>,
+    cosign: Arc<BTreeMap<String, SignatureThreshold>>,
+    cosign_lock: Arc<Mutex<()>>,
+}
+
+/// Broadcast bookkeeping for [`Runtime::emit_event`].
+#[derive(Default)]
+struct EventOrder {
+    in_flight: usize,
+    /// Sequence number of the next event to broadcast; read from the store by
+    /// the first emit.
+    next_seq: Option<u64>,
+    /// Highest sequence number persisted so far.
+    last_seq: u64,
+    /// Events waiting for the ones before them, keyed `(seq, 0)`. An event
+    /// that failed to persist has no seq and waits behind `last_seq` as it
+    /// was then, keyed `(last_seq, n)` with `n` counting such events.
+    held: BTreeMap<(u64, u64), StreamEvent>,
+    unpersisted: u64,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl EventOrder {
+    /// Takes the held events that can go out: the run continuing from
+    /// `next_seq`, or everything once no write is in flight.
+    fn release(&mut self) -> Vec<StreamEvent> {
+        let mut out = Vec::new();
+        while let Some(entry) = self.held.first_entry() {
+            let (seq, n) = *entry.key();
+            let due = match self.next_seq {
+                _ if self.in_flight == 0 => true,
+                Some(next) if n == 0 => seq <= next,
+                Some(next) => seq < next,
+                None => false,
+            };
+            if !due {
+                break;
+            }
+            if n == 0 {
+                self.next_seq = Some(self.next_seq.map_or(seq + 1, |next| next.max(seq + 1)));
+            }
+            out.push(entry.remove());
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     pub lease_ttl_ms: u64,
+    /// Idle poll interval; workers are woken immediately on enqueue.
     pub worker_tick_ms: u64,
+    pub workers: usize,
+    /// Maximum in-flight jobs per topic pattern (`vcx:*`, `echo:test`).
+    pub topic_concurrency: BTreeMap<String, usize>,
+    /// Each replay writes its output under a fresh directory in here, removed
+    /// afterwards unless the caller asks to keep it.
+    pub replay_dir: PathBuf,
+    /// Managed store plugins keep their artifacts in; without one they write
+    /// wherever their payload says.
+    pub artifacts: Option<ArtifactStore>,
+    /// Cosigning rules keyed by topic pattern; receipts of matching topics
+    /// only verify once the listed keys have countersigned them.
+    pub cosign: BTreeMap<String, SignatureThreshold>,
 }
 
 impl Default for RuntimeConfig {
@@ -84,19 +232,24 @@ impl Default for RuntimeConfig {
         Self {
             lease_ttl_ms: 15_000,
             worker_tick_ms: 150,
+            workers: 4,
+            topic_concurrency: BTreeMap::new(),
+            replay_dir: PathBuf::from("./replays"),
+            artifacts: None,
+            cosign: BTreeMap::new(),
         }
     }
 }
 
 impl Runtime {
-    pub fn new(store: RedbStore, plugins: PluginRegistry) -> Self {
+    pub fn new(store: impl Store + 'static, plugins: PluginRegistry) -> Self {
         let signer = SigningKey::generate(&mut OsRng);
-        let kid = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+        let kid = store.clock().now().format("%Y%m%d-%H%M%S").to_string();
         Self::new_with_signer_and_config(store, plugins, signer, kid, RuntimeConfig::default())
     }
 
     pub fn new_with_signer(
-        store: RedbStore,
+        store: impl Store + 'static,
         plugins: PluginRegistry,
         signer: SigningKey,
         kid: String,
@@ -105,37 +258,154 @@ impl Runtime {
     }
 
     pub fn new_with_signer_and_config(
-        store: RedbStore,
+        store: impl Store + 'static,
         plugins: PluginRegistry,
         signer: SigningKey,
         kid: String,
         config: RuntimeConfig,
+    ) -> Self {
+        Self::new_with_backend(
+            store,
+            plugins,
+            Arc::new(LocalSigner::new(kid, signer)),
+            config,
+        )
+    }
+
+    /// Signs receipts and replay reports through `signer`, which may keep its
+    /// key in another process.
+    pub fn new_with_backend(
+        store: impl Store + 'static,
+        plugins: PluginRegistry,
+        signer: Arc<dyn ReceiptSigner>,
+        config: RuntimeConfig,
     ) -> Self {
         let (events_tx, _) = broadcast::channel(2048);
         Self {
-            store,
+            clock: store.clock(),
+            store: Arc::new(store),
             plugins,
             events_tx,
-            signer: Arc::new(signer),
-            kid,
+            event_order: Arc::new(Mutex::new(EventOrder::default())),
+            signer,
             lease_ttl_ms: config.lease_ttl_ms,
             worker_tick_ms: config.worker_tick_ms,
+            workers: config.workers.max(1),
+            pool: Arc::new(WorkerPool::new(config.topic_concurrency)),
+            replay_dir: Arc::new(config.replay_dir),
+            artifacts: config.artifacts,
+            cosign: Arc::new(config.cosign),
+            cosign_lock: Arc::new(Mutex::new(())),
         }
     }
 
-    pub fn start_background_worker(&self) -> tokio::task::JoinHandle<()> {
+    /// Spawns the worker pool and the lease reaper. The handle resolves once
+    /// [`Runtime::shutdown`] was requested and every in-flight job finished.
+    pub fn start_background_worker(&self) -> JoinHandle<()> {
         let runtime = self.clone();
         tokio::spawn(async move {
-            loop {
-                if let Err(err) = runtime.tick_once().await {
-                    error!(error = %err, "runtime tick failed");
-                }
-                tokio::time::sleep(Duration::from_millis(runtime.worker_tick_ms)).await;
+            let mut tasks = JoinSet::new();
+            for worker in 0..runtime.workers {
+                let runtime = runtime.clone();
+                tasks.spawn(async move { runtime.run_worker(worker).await });
             }
+            let reaper = runtime.clone();
+            tasks.spawn(async move { reaper.run_reaper().await });
+            while tasks.join_next().await.is_some() {}
+            info!("runtime workers drained");
         })
     }
 
+    /// Stops leasing new work; jobs already running are allowed to finish.
+    pub fn shutdown(&self) {
+        self.pool.shutdown.cancel();
+    }
+
+    pub async fn shutdown_requested(&self) {
+        self.pool.shutdown.cancelled().await;
+    }
+
+    async fn run_worker(&self, worker: usize) {
+        while !self.pool.shutdown.is_cancelled() {
+            match self.run_next_job().await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(err) => error!(worker, error = %err, "runtime worker failed"),
+            }
+            tokio::select! {
+                _ = self.pool.shutdown.cancelled() => break,
+                _ = self.pool.wake.notified() => {}
+                _ = tokio::time::sleep(Duration::from_millis(self.worker_tick_ms)) => {}
+            }
+        }
+    }
+
+    async fn run_reaper(&self) {
+        while !self.pool.shutdown.is_cancelled() {
+            if let Err(err) = self.blocking(|runtime| runtime.reap_expired_leases()).await {
+                error!(error = %err, "lease reaper failed");
+            }
+            if let Err(err) = self.store.trim_event_log() {
+                error!(error = %err, "event log trim failed");
+            }
+            tokio::select! {
+                _ = self.pool.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(self.worker_tick_ms)) => {}
+            }
+        }
+    }
+
+    fn reap_expired_leases(&self) -> Result<()> {
+        let report = self.store.reassign_expired_leases()?;
+        if report.reassigned > 0 {
+            debug!(reassigned = report.reassigned, "reassigned expired leases");
+            self.pool.wake.notify_waiters();
+        }
+        for job in report.requeued {
+            let trace_id = job.work.trace_id();
+            self.emit_event(StreamEvent {
+                seq: 0,
+                at: self.clock.now(),
+                tenant: job.work.tenant,
+                topic: job.work.topic,
+                work_id: job.work.id,
+                status: WorkStatus::Retrying,
+                receipt_cid: None,
+                detail: Some(format!(
+                    "attempt {} lost its lease; retrying at {}",
+                    job.attempt,
+                    job.retry_at.map(|at| at.to_rfc3339()).unwrap_or_default()
+                )),
+                trace_id,
+            });
+        }
+        for job in report.exhausted {
+            self.dead_letter_job(&job, LEASE_EXPIRED_ERROR.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Enqueues `work` under an `accept` span. The span continues the trace
+    /// named by `work.traceparent`, and `work.traceparent` is replaced with
+    /// the span itself (or a fresh trace) so later stages nest under it.
     pub async fn accept_work(&self, mut work: WorkUnit) -> Result<AcceptedWork> {
+        let span = info_span!(
+            "accept",
+            work_id = %work.id,
+            tenant = %work.tenant,
+            topic = %work.topic,
+            request_id = work.request_id.as_deref().unwrap_or_default(),
+        );
+        let incoming = work.trace_parent();
+        if let Some(parent) = &incoming {
+            trace::set_parent(&span, parent);
+        }
+        let traceparent = trace::traceparent_of(&span)
+            .or(incoming)
+            .unwrap_or_else(TraceParent::generate);
+        work.traceparent = Some(traceparent.to_string());
+        let _accept = span.enter();
+
         let idem_key = work
             .effective_idem_key()
             .context("failed to compute idem_key")?;
@@ -148,16 +418,28 @@ impl Runtime {
 
         match outcome {
             EnqueueResult::Enqueued { seq: _, work_id } => {
-                self.store.increment_status_counter(WorkStatus::Accepted)?;
+                self.store.increment_status_counter(
+                    WorkStatus::Accepted,
+                    &MetricLabels::new(&work.tenant, &work.topic),
+                )?;
+                let now = self.clock.now();
+                let detail = work
+                    .not_before
+                    .filter(|_| !work.is_due(now))
+                    .map(|at| format!("scheduled for {}", at.to_rfc3339()));
+                let trace_id = work.trace_id();
                 self.emit_event(StreamEvent {
-                    at: Utc::now(),
+                    seq: 0,
+                    at: now,
                     tenant: work.tenant,
                     topic: work.topic,
                     work_id,
                     status: WorkStatus::Accepted,
                     receipt_cid: None,
-                    detail: None,
+                    detail,
+                    trace_id,
                 });
+                self.pool.wake.notify_one();
                 Ok(AcceptedWork {
                     work_id,
                     disposition: AcceptDisposition::Enqueued,
@@ -177,44 +459,328 @@ impl Runtime {
         }
     }
 
-    async fn tick_once(&self) -> Result<()> {
-        let reassigned = self.store.reassign_expired_leases()?;
-        if reassigned > 0 {
-            debug!(reassigned, "reassigned expired leases");
-        }
-
-        let Some(job) = self.store.lease_next(self.lease_ttl_ms)? else {
-            return Ok(());
+    /// Leases and runs one job; returns `false` when nothing was ready.
+    async fn run_next_job(&self) -> Result<bool> {
+        let leased = self
+            .pool
+            .lease(|filter| self.store.lease_next(self.lease_ttl_ms, filter))?;
+        let Some((job, slot)) = leased else {
+            return Ok(false);
         };
+        let span = job_span(&job);
+        self.run_job(job, slot).instrument(span).await?;
+        Ok(true)
+    }
+
+    async fn run_job(&self, job: QueuedJob, slot: TopicSlot) -> Result<()> {
+        let hooks = Arc::new(JobHooks::new(self.clone(), &job));
+        let cancel = slot.cancellation();
+        let started_at = job.leased_at.unwrap_or_else(|| self.clock.now());
+        let mut ctx = ExecutionContext::new(job.attempt, hooks.clone(), started_at)
+            .with_cancellation(cancel.clone());
+        if let Some(artifacts) = &self.artifacts {
+            ctx = ctx.with_artifact_store(artifacts.clone());
+        }
 
-        self.store.increment_status_counter(WorkStatus::Assigned)?;
+        let labels = MetricLabels::new(&job.work.tenant, &job.work.topic);
+        let lease = info_span!(
+            "lease",
+            queued_ms = job
+                .leased_at
+                .map_or(0, |at| (at - job.accepted_at).num_milliseconds().max(0)),
+            lease_expires_at = job
+                .lease_expires_at
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+        )
+        .entered();
+        self.store
+            .increment_status_counter(WorkStatus::Assigned, &labels)?;
         self.emit_event(StreamEvent {
-            at: Utc::now(),
+            seq: 0,
+            at: self.clock.now(),
             tenant: job.work.tenant.clone(),
             topic: job.work.topic.clone(),
             work_id: job.work.id,
             status: WorkStatus::Assigned,
             receipt_cid: None,
             detail: None,
+            trace_id: job.work.trace_id(),
         });
 
-        self.store.increment_status_counter(WorkStatus::Progress)?;
+        self.store
+            .increment_status_counter(WorkStatus::Progress, &labels)?;
         self.emit_event(StreamEvent {
-            at: Utc::now(),
+            seq: 0,
+            at: self.clock.now(),
             tenant: job.work.tenant.clone(),
             topic: job.work.topic.clone(),
             work_id: job.work.id,
             status: WorkStatus::Progress,
             receipt_cid: None,
             detail: Some("plugin execution started".to_string()),
+            trace_id: job.work.trace_id(),
+        });
+        drop(lease);
+
+        let (artifacts, result) = match self.execute_job(&job, &ctx).await {
+            Ok(outcome) => outcome,
+            Err(_) if hooks.lease_lost() => {
+                debug!(
+                    seq = job.seq,
+                    "lease lost while executing; leaving job to its new owner"
+                );
+                return Ok(());
+            }
+            Err(_) if cancel.is_cancelled() => {
+                let stages = hooks.take_stages();
+                return self
+                    .blocking(move |runtime| {
+                        runtime.finish_cancelled(&job, stages, |receipt, blobs| {
+                            runtime
+                                .store
+                                .complete_leased(job.seq, job.attempt, receipt, blobs)
+                        })?;
+                        Ok(())
+                    })
+                    .await;
+            }
+            Err(err) => {
+                let error = err.to_string();
+                return match self.store.fail_leased(job.seq, job.attempt, &error)? {
+                    FailureOutcome::Retrying { job, retry_at } => {
+                        self.store
+                            .increment_status_counter(WorkStatus::Retrying, &labels)?;
+                        let trace_id = job.work.trace_id();
+                        self.emit_event(StreamEvent {
+                            seq: 0,
+                            at: self.clock.now(),
+                            tenant: job.work.tenant,
+                            topic: job.work.topic,
+                            work_id: job.work.id,
+                            status: WorkStatus::Retrying,
+                            receipt_cid: None,
+                            detail: Some(format!(
+                                "attempt {} failed: {error}; retrying at {}",
+                                job.attempt,
+                                retry_at.to_rfc3339()
+                            )),
+                            trace_id,
+                        });
+                        Ok(())
+                    }
+                    FailureOutcome::Exhausted { job } => {
+                        self.blocking(move |runtime| runtime.dead_letter_job(&job, error))
+                            .await
+                    }
+                    FailureOutcome::LeaseLost => {
+                        debug!(
+                            seq = job.seq,
+                            "lease lost before the failure was recorded; leaving job to its new owner"
+                        );
+                        Ok(())
+                    }
+                };
+            }
+        };
+
+        let stages = hooks.take_stages();
+        self.blocking(move |runtime| runtime.finish_done(job, artifacts, result, stages))
+            .await
+    }
+
+    /// Issues the `Done` receipt for a job whose plugin succeeded.
+    fn finish_done(
+        &self,
+        job: QueuedJob,
+        artifacts: Vec<ArtifactRef>,
+        result: Value,
+        stages: BTreeMap<String, u64>,
+    ) -> Result<()> {
+        // Refresh the lease before signing so a long-expired attempt does not
+        // sign at all; `complete_leased` checks it again as it stores.
+        if !self
+            .store
+            .heartbeat_lease(job.seq, job.attempt, self.lease_ttl_ms)?
+        {
+            debug!(
+                seq = job.seq,
+                "lease lost before the result was recorded; dropping it"
+            );
+            return Ok(());
+        }
+        let outcome = JobOutcome {
+            status: WorkStatus::Done,
+            detail: None,
+            artifacts,
+            result: Some(&result),
+            stages,
+        };
+        let Some(receipt) = self.issue_receipt(&job, outcome, |receipt, blobs| {
+            self.store
+                .complete_leased(job.seq, job.attempt, receipt, blobs)
+        })?
+        else {
+            debug!(
+                seq = job.seq,
+                "lease lost while the receipt was signed; dropping the result"
+            );
+            return Ok(());
+        };
+
+        let trace_id = job.work.trace_id();
+        self.emit_event(StreamEvent {
+            seq: 0,
+            at: self.clock.now(),
+            tenant: job.work.tenant,
+            topic: job.work.topic,
+            work_id: job.work.id,
+            status: WorkStatus::Done,
+            receipt_cid: Some(receipt.cid),
+            detail: None,
+            trace_id,
         });
 
-        let execute_result = self.execute_job(&job).await;
-        let (status, detail, artifacts) = match execute_result {
-            Ok(artifacts) => (WorkStatus::Done, None, artifacts),
-            Err(err) => (WorkStatus::Fail, Some(err.to_string()), Vec::new()),
+        Ok(())
+    }
+
+    /// Runs `work` on the blocking pool, in the current span. Anything that
+    /// signs a receipt goes through here: a remote signer blocks on its
+    /// socket, and the store blocks on disk.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        work: impl FnOnce(&Runtime) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let runtime = self.clone();
+        let span = Span::current();
+        tokio::task::spawn_blocking(move || span.in_scope(|| work(&runtime)))
+            .await
+            .context("blocking runtime task failed")?
+    }
+
+    /// Issues the final `Fail` receipt for a job that ran out of attempts and
+    /// parks it in the dead-letter table.
+    fn dead_letter_job(&self, job: &QueuedJob, error: String) -> Result<()> {
+        let outcome = JobOutcome::failed(WorkStatus::Fail, error.clone(), BTreeMap::new());
+        let Some(receipt) = self.issue_receipt(job, outcome, |receipt, blobs| {
+            self.store
+                .dead_letter(job.seq, job.attempt, &error, receipt, blobs)
+        })?
+        else {
+            debug!(
+                seq = job.seq,
+                "lease lost before the job was dead-lettered; leaving it to its new owner"
+            );
+            return Ok(());
+        };
+
+        self.emit_event(StreamEvent {
+            seq: 0,
+            at: self.clock.now(),
+            tenant: job.work.tenant.clone(),
+            topic: job.work.topic.clone(),
+            work_id: job.work.id,
+            status: WorkStatus::Fail,
+            receipt_cid: Some(receipt.cid),
+            detail: Some(format!(
+                "dead-lettered after {} attempts: {error}",
+                job.attempt
+            )),
+            trace_id: job.work.trace_id(),
+        });
+        Ok(())
+    }
+
+    /// Cancels a job wherever it is. Queued and scheduled jobs get their
+    /// `cancelled` receipt right away; running ones are signalled and finish
+    /// once the plugin gives up.
+    pub fn cancel_work(&self, work_id: Uuid) -> Result<CancelDisposition> {
+        // The receipt is signed outside the pool lock and stored only if the
+        // job is still where it was found; when a worker leased it (or its
+        // expired lease was handed out) in between, look again.
+        for _ in 0..CANCEL_ATTEMPTS {
+            let (outcome, signalled) = self
+                .pool
+                .cancel(work_id, || self.store.cancel_work(work_id))?;
+            let settled = match outcome {
+                CancelOutcome::NotFound => return Ok(CancelDisposition::NotFound),
+                CancelOutcome::Leased(_) if signalled => return Ok(CancelDisposition::Cancelling),
+                CancelOutcome::Leased(job)
+                    if job
+                        .lease_expires_at
+                        .is_none_or(|expires_at| expires_at > self.clock.now()) =>
+                {
+                    return Ok(CancelDisposition::LeasedElsewhere {
+                        lease_expires_at: job.lease_expires_at,
+                    });
+                }
+                CancelOutcome::Leased(job) => {
+                    // The lease ran out with the worker gone; nobody else will finish it.
+                    self.finish_cancelled(&job, BTreeMap::new(), |receipt, blobs| {
+                        self.store
+                            .complete_leased(job.seq, job.attempt, receipt, blobs)
+                    })?
+                }
+                CancelOutcome::Queued(job) => {
+                    self.finish_cancelled(&job, BTreeMap::new(), |receipt, blobs| {
+                        self.store.cancel_queued(&job, receipt, blobs)
+                    })?
+                }
+            };
+            if let Some(receipt) = settled {
+                return Ok(CancelDisposition::Cancelled {
+                    receipt_cid: receipt.cid,
+                });
+            }
+        }
+        Err(anyhow!(
+            "work {work_id} kept changing hands while being cancelled"
+        ))
+    }
+
+    /// Issues the `cancelled` receipt through `settle` (see
+    /// [`Self::issue_receipt`]) and announces it once stored.
+    fn finish_cancelled(
+        &self,
+        job: &QueuedJob,
+        stages: BTreeMap<String, u64>,
+        settle: impl FnOnce(&Receipt, &[&Value]) -> Result<bool>,
+    ) -> Result<Option<Receipt>> {
+        let outcome = JobOutcome::failed(WorkStatus::Cancelled, "cancelled".to_string(), stages);
+        let Some(receipt) = self.issue_receipt(job, outcome, settle)? else {
+            return Ok(None);
         };
+        self.emit_event(StreamEvent {
+            seq: 0,
+            at: self.clock.now(),
+            tenant: job.work.tenant.clone(),
+            topic: job.work.topic.clone(),
+            work_id: job.work.id,
+            status: WorkStatus::Cancelled,
+            receipt_cid: Some(receipt.cid.clone()),
+            detail: None,
+            trace_id: job.work.trace_id(),
+        });
+        Ok(Some(receipt))
+    }
 
+    /// Signs the job's final receipt and hands it, with the payload and
+    /// result blobs, to `settle`, which stores it together with whatever
+    /// releases the job. `settle` returns `false` when the job has moved on
+    /// (its lease was lost); the receipt is then dropped and `None` returned.
+    fn issue_receipt(
+        &self,
+        job: &QueuedJob,
+        outcome: JobOutcome<'_>,
+        settle: impl FnOnce(&Receipt, &[&Value]) -> Result<bool>,
+    ) -> Result<Option<Receipt>> {
+        let JobOutcome {
+            status,
+            detail,
+            artifacts,
+            result,
+            stages,
+        } = outcome;
         let mut policy_trace = extract_policy_trace(&job.work.payload);
         if policy_trace.is_empty() {
             policy_trace.push(PolicyEntry {
@@ -223,7 +789,7 @@ impl Runtime {
                 detail: Some("work accepted into runtime".to_string()),
             });
         }
-        if let Some(d) = detail.clone() {
+        if let Some(d) = detail {
             policy_trace.push(PolicyEntry {
                 rule: "runtime_execute".to_string(),
                 ok: false,
@@ -231,48 +797,68 @@ impl Runtime {
             });
         }
 
-        let assigned_at = job.leased_at.unwrap_or_else(Utc::now);
-        let done_at = Utc::now();
+        let assigned_at = job.leased_at.unwrap_or_else(|| self.clock.now());
+        let done_at = self.clock.now();
         let ttft_ms = (assigned_at - job.accepted_at).num_milliseconds().max(0) as u64;
         let ttr_ms = (done_at - job.accepted_at).num_milliseconds().max(0) as u64;
 
-        let receipt = self.sign_receipt(
-            &job,
-            ReceiptBuild {
-                status,
-                policy_trace,
-                artifacts,
-                ttft_ms,
-                ttr_ms,
-                created_at: done_at,
-            },
-        )?;
-        self.store.put_receipt(&receipt)?;
-        self.store.complete_leased(job.seq)?;
-        self.store.observe_timings(ttft_ms, ttr_ms)?;
-        self.store.increment_status_counter(status)?;
-
-        self.emit_event(StreamEvent {
-            at: Utc::now(),
-            tenant: job.work.tenant,
-            topic: job.work.topic,
-            work_id: job.work.id,
-            status,
-            receipt_cid: Some(receipt.cid.clone()),
-            detail,
-        });
+        // Receipts issued outside a worker (cancellation, the lease reaper)
+        // still belong to the job's trace.
+        let outer = Span::current().is_none().then(|| job_span(job));
+        let _job = outer.as_ref().map(Span::enter);
+        let input_cid = blob_cid(&job.work.payload)?;
+        let result_cid = result.map(blob_cid).transpose()?;
+        let receipt = info_span!("sign", status = ?status).in_scope(|| {
+            self.sign_receipt(
+                job,
+                ReceiptBuild {
+                    status,
+                    stages: stages.clone(),
+                    policy_trace,
+                    artifacts,
+                    input_cid,
+                    result_cid,
+                    ttft_ms,
+                    ttr_ms,
+                    created_at: done_at,
+                },
+            )
+        })?;
+        let stored = info_span!("put_receipt", cid = %receipt.cid).in_scope(|| {
+            let blobs = std::iter::once(&job.work.payload)
+                .chain(result)
+                .collect::<Vec<_>>();
+            settle(&receipt, &blobs)
+        })?;
+        if !stored {
+            return Ok(None);
+        }
+        let labels = MetricLabels::new(&job.work.tenant, &job.work.topic);
+        self.store
+            .observe_timings(&labels, ttft_ms, ttr_ms, &stages)?;
+        self.store.increment_status_counter(status, &labels)?;
+        Ok(Some(receipt))
+    }
 
-        Ok(())
+    async fn execute_job(
+        &self,
+        job: &QueuedJob,
+        ctx: &ExecutionContext,
+    ) -> Result<(Vec<ArtifactRef>, Value)> {
+        let plugin = self.plugin_for(&job.work.topic)?;
+        let result = plugin
+            .execute(ctx, job.work.payload.clone())
+            .instrument(info_span!("execute", plugin = plugin.name()))
+            .await?;
+        Ok((extract_artifacts(&result)?, result))
     }
 
-    async fn execute_job(&self, job: &QueuedJob) -> Result<Vec<ArtifactRef>> {
-        let plugin_name = job.work.topic.split(':').next().unwrap_or("echo");
-        let plugin = self
-            .plugins
-            .get(plugin_name)
-            .ok_or_else(|| anyhow!("plugin not found: {plugin_name}"))?;
-        let result = plugin.execute(job.work.payload.clone()).await?;
-        extract_artifacts(&result)
+    /// The plugin named by the topic's family (`vcx:commit` → `vcx`).
+    fn plugin_for(&self, topic: &str) -> Result<Arc<dyn Plugin>> {
+        let name = topic.split(':').next().unwrap_or("echo");
+        self.plugins
+            .get(name)
+            .ok_or_else(|| anyhow!("plugin not found: {name}"))
     }
 
     fn sign_receipt(&self, job: &QueuedJob, build: ReceiptBuild) -> Result<Receipt> {
@@ -283,7 +869,7 @@ impl Runtime {
             .ok_or_else(|| anyhow!("queued job is missing idem_key"))?;
         let plan_hash = extract_plan_hash(&job.work.payload).unwrap_or(job.work.plan_hash()?);
 
-        let mut stage_time_ms = BTreeMap::new();
+        let mut stage_time_ms = build.stages;
         stage_time_ms.insert("ttft_ms".to_string(), build.ttft_ms);
         stage_time_ms.insert("ttr_ms".to_string(), build.ttr_ms);
 
@@ -294,41 +880,188 @@ impl Runtime {
             status: build.status,
             idem_key,
             plan_hash,
+            input_cid: Some(build.input_cid),
             policy_trace: build.policy_trace,
             stage_time_ms,
             artifacts: build.artifacts,
+            result_cid: build.result_cid,
+            attempts: job.failures.clone(),
             created_at: build.created_at,
+            started_at: job.leased_at,
+            trace_id: job.work.trace_id(),
+            threshold: self.threshold_for(&job.work.topic),
         };
 
-        let cid = cid_for(&unsigned)?;
-        let sig = self.signer.sign(cid.as_bytes());
-        let vk = self.signer.verifying_key();
+        aurea_receipts::sign_receipt(&unsigned, self.signer.as_ref())
+    }
 
-        let signature = ReceiptSignature {
-            alg: "ed25519".to_string(),
-            kid: self.kid.clone(),
-            public_key: B64.encode(vk.to_bytes()),
-            signature: B64.encode(sig.to_bytes()),
-        };
+    /// The most specific cosigning rule whose pattern matches `topic`.
+    fn threshold_for(&self, topic: &str) -> Option<SignatureThreshold> {
+        self.cosign
+            .iter()
+            .filter(|(pattern, _)| topic_matches(pattern, topic))
+            .max_by_key(|(pattern, _)| (!pattern.ends_with('*'), pattern.len()))
+            .map(|(_, threshold)| threshold.clone())
+    }
 
-        Ok(Receipt {
-            cid,
-            work_id: unsigned.work_id,
-            tenant: unsigned.tenant,
-            topic: unsigned.topic,
-            status: unsigned.status,
-            idem_key: unsigned.idem_key,
-            plan_hash: unsigned.plan_hash,
-            policy_trace: unsigned.policy_trace,
-            stage_time_ms: unsigned.stage_time_ms,
-            artifacts: unsigned.artifacts,
-            created_at: unsigned.created_at,
-            signature,
+    fn signature_for(&self, cid: &str) -> Result<ReceiptSignature> {
+        aurea_receipts::cosign_receipt(cid, self.signer.as_ref())
+    }
+
+    /// Persists the event, which assigns its sequence number and time, then
+    /// broadcasts it. An event whose predecessors are still being written is
+    /// held back until they land, so subscribers see sequence numbers in order
+    /// without a lock held across the store write; held events go out as soon
+    /// as the run before them is complete, not when the runtime goes idle.
+    fn emit_event(&self, mut event: StreamEvent) {
+        {
+            let mut order = self.event_order();
+            if order.next_seq.is_none() && order.in_flight == 0 {
+                match self.store.next_event_seq() {
+                    Ok(next) => order.next_seq = Some(next),
+                    Err(err) => warn!("failed to read the next event seq: {err:#}"),
+                }
+            }
+            order.in_flight += 1;
+        }
+        let persisted = self.store.append_work_event(&mut event);
+        let mut order = self.event_order();
+        order.in_flight -= 1;
+        match persisted {
+            Ok(()) => {
+                order.last_seq = order.last_seq.max(event.seq);
+                order.held.insert((event.seq, 0), event);
+            }
+            Err(err) => {
+                error!(work_id = %event.work_id, "failed to persist work event: {err:#}");
+                event.at = self.clock.now();
+                order.unpersisted += 1;
+                let key = (order.last_seq, order.unpersisted);
+                order.held.insert(key, event);
+            }
+        }
+        for event in order.release() {
+            let _ = self.events_tx.send(event);
+        }
+    }
+
+    fn event_order(&self) -> MutexGuard<'_, EventOrder> {
+        self.event_order.lock().unwrap_or_else(|poisoned| {
+            warn!("event order lock poisoned; recovering");
+            poisoned.into_inner()
         })
     }
 
-    fn emit_event(&self, event: StreamEvent) {
-        let _ = self.events_tx.send(event);
+    /// Persisted events with a sequence number above `after`, oldest first.
+    pub fn events_after(&self, after: u64, limit: usize) -> Result<Vec<StreamEvent>> {
+        self.store.events_after(after, limit)
+    }
+
+    pub fn work_events(&self, work_id: Uuid) -> Result<Vec<StreamEvent>> {
+        self.store.work_events(work_id)
+    }
+
+    /// Current state of a work unit, folded from its event history. `None`
+    /// when this runtime never saw the id.
+    pub fn work_state(&self, work_id: Uuid) -> Result<Option<WorkState>> {
+        let events = self.store.work_events(work_id)?;
+        let Some(last) = events.last() else {
+            return self.untracked_work_state(work_id);
+        };
+        let lease = match last.status {
+            WorkStatus::Assigned | WorkStatus::Progress => {
+                self.store.get_leased_job(work_id)?.map(|job| WorkLease {
+                    attempt: job.attempt,
+                    leased_at: job.leased_at,
+                    expires_at: job.lease_expires_at,
+                })
+            }
+            _ => None,
+        };
+        Ok(Some(WorkState {
+            work_id,
+            tenant: last.tenant.clone(),
+            topic: last.topic.clone(),
+            status: last.status,
+            attempts: events
+                .iter()
+                .filter(|event| event.status == WorkStatus::Assigned)
+                .count() as u32,
+            lease,
+            receipt_cid: events
+                .iter()
+                .rev()
+                .find_map(|event| event.receipt_cid.clone()),
+            updated_at: last.at,
+        }))
+    }
+
+    /// State of work whose event history has aged out (see
+    /// [`aurea_storage::EventRetention`]), from its job while it waits or
+    /// runs and from its latest receipt once it finished.
+    fn untracked_work_state(&self, work_id: Uuid) -> Result<Option<WorkState>> {
+        if let Some(job) = self.store.get_leased_job(work_id)? {
+            return Ok(Some(WorkState {
+                work_id,
+                tenant: job.work.tenant,
+                topic: job.work.topic,
+                status: WorkStatus::Assigned,
+                attempts: job.attempt,
+                lease: Some(WorkLease {
+                    attempt: job.attempt,
+                    leased_at: job.leased_at,
+                    expires_at: job.lease_expires_at,
+                }),
+                receipt_cid: None,
+                updated_at: job.leased_at.unwrap_or(job.accepted_at),
+            }));
+        }
+        if let Some(job) = self.store.get_queued_job(work_id)? {
+            let failed_at = job.failures.last().map(|failure| failure.failed_at);
+            return Ok(Some(WorkState {
+                work_id,
+                tenant: job.work.tenant,
+                topic: job.work.topic,
+                status: if failed_at.is_some() {
+                    WorkStatus::Retrying
+                } else {
+                    WorkStatus::Accepted
+                },
+                attempts: job.attempt,
+                lease: None,
+                receipt_cid: None,
+                updated_at: failed_at.unwrap_or(job.accepted_at),
+            }));
+        }
+
+        let receipts = self.store.query_receipts(&ReceiptQuery {
+            work_id: Some(work_id),
+            ..Default::default()
+        })?;
+        let Some(receipt) = receipts
+            .receipts
+            .into_iter()
+            .max_by_key(|receipt| receipt.created_at)
+        else {
+            return Ok(None);
+        };
+        // Every failed attempt is on the receipt; the attempt that produced it
+        // is too, unless it was the one that failed for good.
+        let failed = receipt.attempts.last().map_or(0, |failure| failure.attempt);
+        let attempts = match receipt.status {
+            WorkStatus::Fail => failed,
+            _ => failed + u32::from(receipt.started_at.is_some()),
+        };
+        Ok(Some(WorkState {
+            work_id,
+            tenant: receipt.tenant,
+            topic: receipt.topic,
+            status: receipt.status,
+            attempts,
+            lease: None,
+            receipt_cid: Some(receipt.cid),
+            updated_at: receipt.created_at,
+        }))
     }
 
     pub fn subscribe_events(&self) -> broadcast::Receiver<StreamEvent> {
@@ -339,21 +1072,82 @@ impl Runtime {
         self.store.get_receipt(cid)
     }
 
-    pub fn list_receipts(&self) -> Result<Vec<Receipt>> {
-        self.store.list_receipts()
+    /// Canonical JSON of a stored payload or result.
+    pub fn get_blob(&self, cid: &str) -> Result<Option<Vec<u8>>> {
+        self.store.get_blob(cid)
+    }
+
+    /// Bytes of a managed artifact; always `None` without an artifact store.
+    pub fn get_artifact(&self, cid: &str) -> Result<Option<Vec<u8>>> {
+        match &self.artifacts {
+            Some(artifacts) => artifacts.read(cid),
+            None => Ok(None),
+        }
+    }
+
+    /// The store's clock, which stamps every event and receipt.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    pub fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage> {
+        self.store.query_receipts(query)
     }
 
+    pub fn backup(&self) -> Result<BackupArchive> {
+        BackupArchive::new(self.store.snapshot()?, self.clock.now())
+    }
+
+    /// Checks the CID, every signature and the receipt's threshold. Whether
+    /// the signing keys are trusted is up to the caller.
     pub fn verify_receipt(&self, receipt: &Receipt) -> Result<ReceiptVerification> {
         let cid_match = receipt.cid_matches()?;
-        let signature_valid = verify_signature(receipt)?;
+        let mut signature_valid = !receipt.signatures.is_empty();
+        let mut signed_by = Vec::with_capacity(receipt.signatures.len());
+        for signature in &receipt.signatures {
+            if verify_signature(&receipt.cid, signature)? {
+                signed_by.push(signature.kid.clone());
+            } else {
+                signature_valid = false;
+            }
+        }
+        let threshold = receipt.signature_threshold();
+        let threshold_met = threshold.is_met_by(&signed_by);
 
         Ok(ReceiptVerification {
-            ok: cid_match && signature_valid,
+            ok: cid_match && signature_valid && threshold_met,
             cid_match,
             signature_valid,
+            threshold_met,
+            missing_kids: threshold
+                .missing_kids(&signed_by)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            signed_by,
         })
     }
 
+    /// Adds an independent signature to a stored receipt. The signature has
+    /// to cover the receipt's CID, which stays the same.
+    pub fn cosign_receipt(&self, cid: &str, signature: ReceiptSignature) -> Result<CosignOutcome> {
+        let Ok(_guard) = self.cosign_lock.lock() else {
+            return Err(anyhow!("cosign lock poisoned"));
+        };
+        let Some(mut receipt) = self.store.get_receipt(cid)? else {
+            return Ok(CosignOutcome::NotFound);
+        };
+        if receipt.signatures.iter().any(|s| s.kid == signature.kid) {
+            return Ok(CosignOutcome::AlreadySigned);
+        }
+        if !verify_signature(&receipt.cid, &signature).unwrap_or(false) {
+            return Ok(CosignOutcome::InvalidSignature);
+        }
+        receipt.signatures.push(signature);
+        self.store.put_receipt(&receipt)?;
+        Ok(CosignOutcome::Cosigned(Box::new(receipt)))
+    }
+
     pub fn verify_receipt_by_cid(&self, cid: &str) -> Result<Option<ReceiptVerification>> {
         let Some(receipt) = self.store.get_receipt(cid)? else {
             return Ok(None);
@@ -361,11 +1155,46 @@ impl Runtime {
         Ok(Some(self.verify_receipt(&receipt)?))
     }
 
+    pub fn list_dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        self.store.list_dead_letters()
+    }
+
+    pub fn get_dead_letter(&self, seq: u64) -> Result<Option<DeadLetter>> {
+        self.store.get_dead_letter(seq)
+    }
+
+    pub fn requeue_dead_letter(&self, seq: u64) -> Result<Option<QueuedJob>> {
+        let Some(job) = self.store.requeue_dead_letter(seq)? else {
+            return Ok(None);
+        };
+        self.emit_event(StreamEvent {
+            seq: 0,
+            at: self.clock.now(),
+            tenant: job.work.tenant.clone(),
+            topic: job.work.topic.clone(),
+            work_id: job.work.id,
+            status: WorkStatus::Accepted,
+            receipt_cid: None,
+            detail: Some("requeued from dead letters".to_string()),
+            trace_id: job.work.trace_id(),
+        });
+        self.pool.wake.notify_one();
+        Ok(Some(job))
+    }
+
+    pub fn purge_dead_letters(&self, seq: Option<u64>) -> Result<usize> {
+        self.store.purge_dead_letters(seq)
+    }
+
     pub fn metrics_snapshot(&self) -> Result<RuntimeMetrics> {
         let metrics = self.store.queue_metrics()?;
         Ok(RuntimeMetrics {
             queue_depth: metrics.queue_depth,
+            scheduled_depth: metrics.scheduled_depth,
             leased_depth: metrics.leased_depth,
+            in_flight: self.pool.in_flight(),
+            workers: self.workers,
+            dead_letter_depth: metrics.dead_letter_depth,
             reassigns_total: metrics.reassigns_total,
             receipts_total: metrics.receipts_total,
             plugins_total: self.plugins.names().len(),
@@ -376,6 +1205,7 @@ impl Runtime {
             ttr_count: metrics.ttr_count,
             ttft_bucket_counts: metrics.ttft_bucket_counts,
             ttr_bucket_counts: metrics.ttr_bucket_counts,
+            labelled: metrics.labelled,
         })
     }
 
@@ -384,16 +1214,110 @@ impl Runtime {
     }
 }
 
-fn verify_signature(receipt: &Receipt) -> Result<bool> {
-    if receipt.signature.alg != "ed25519" {
+/// The span one attempt at a job runs under, nested in the trace the job
+/// was accepted with.
+fn job_span(job: &QueuedJob) -> Span {
+    let span = info_span!(
+        "job",
+        work_id = %job.work.id,
+        tenant = %job.work.tenant,
+        topic = %job.work.topic,
+        seq = job.seq,
+        attempt = job.attempt,
+        request_id = job.work.request_id.as_deref().unwrap_or_default(),
+    );
+    if let Some(parent) = job.work.trace_parent() {
+        trace::set_parent(&span, &parent);
+    }
+    span
+}
+
+/// Binds an [`ExecutionContext`] to one leased job.
+struct JobHooks {
+    runtime: Runtime,
+    seq: u64,
+    attempt: u32,
+    work_id: Uuid,
+    tenant: String,
+    topic: String,
+    trace_id: Option<String>,
+    stages: Mutex<BTreeMap<String, u64>>,
+    lease_lost: AtomicBool,
+}
+
+impl JobHooks {
+    fn new(runtime: Runtime, job: &QueuedJob) -> Self {
+        Self {
+            runtime,
+            seq: job.seq,
+            attempt: job.attempt,
+            work_id: job.work.id,
+            tenant: job.work.tenant.clone(),
+            topic: job.work.topic.clone(),
+            trace_id: job.work.trace_id(),
+            stages: Mutex::new(BTreeMap::new()),
+            lease_lost: AtomicBool::new(false),
+        }
+    }
+
+    fn lease_lost(&self) -> bool {
+        self.lease_lost.load(Ordering::SeqCst)
+    }
+
+    fn take_stages(&self) -> BTreeMap<String, u64> {
+        self.stages
+            .lock()
+            .map(|mut stages| std::mem::take(&mut *stages))
+            .unwrap_or_default()
+    }
+}
+
+impl ExecutionHooks for JobHooks {
+    fn heartbeat(&self) -> Result<()> {
+        let alive = self.runtime.store.heartbeat_lease(
+            self.seq,
+            self.attempt,
+            self.runtime.lease_ttl_ms,
+        )?;
+        if !alive {
+            self.lease_lost.store(true, Ordering::SeqCst);
+            return Err(anyhow!("lease lost for job {}", self.work_id));
+        }
+        Ok(())
+    }
+
+    fn progress(&self, detail: &str) {
+        self.runtime.emit_event(StreamEvent {
+            seq: 0,
+            at: self.runtime.clock.now(),
+            tenant: self.tenant.clone(),
+            topic: self.topic.clone(),
+            work_id: self.work_id,
+            status: WorkStatus::Progress,
+            receipt_cid: None,
+            detail: Some(detail.to_string()),
+            trace_id: self.trace_id.clone(),
+        });
+    }
+
+    fn stage(&self, name: &str, elapsed_ms: u64) {
+        if let Ok(mut stages) = self.stages.lock() {
+            let total = stages.entry(name.to_string()).or_insert(0);
+            *total = total.saturating_add(elapsed_ms);
+        }
+    }
+}
+
+fn verify_signature(cid: &str, signature: &ReceiptSignature) -> Result<bool> {
+    if signature.alg != "ed25519" {
         return Ok(false);
     }
 
     let vk_bytes = B64
-        .decode(receipt.signature.public_key.as_bytes())
+        .decode(signature.public_key.as_bytes())
         .context("invalid base64 public key")?;
     let sig_bytes = B64
-        .decode(receipt.signature.signature.as_bytes())
+        .decode(signature.signature.as_bytes())
         .context("invalid base64 signature")?;
 
     let vk_array: [u8; 32] = vk_bytes
@@ -408,9 +1332,7 @@ fn verify_signature(receipt: &Receipt) -> Result<bool> {
     let verifying_key = VerifyingKey::from_bytes(&vk_array).context("invalid verifying key")?;
     let signature = Signature::from_bytes(&sig_array);
 
-    Ok(verifying_key
-        .verify(receipt.cid.as_bytes(), &signature)
-        .is_ok())
+    Ok(verifying_key.verify(cid.as_bytes(), &signature).is_ok())
 }
 
 fn extract_policy_trace(payload: &serde_json::Value) -> Vec<PolicyEntry> {
@@ -473,7 +1395,11 @@ fn extract_artifacts(result: &serde_json::Value) -> Result<Vec<ArtifactRef>> {
 #[derive(Debug, Clone)]
 pub struct RuntimeMetrics {
     pub queue_depth: usize,
+    pub scheduled_depth: usize,
     pub leased_depth: usize,
+    pub in_flight: usize,
+    pub workers: usize,
+    pub dead_letter_depth: usize,
     pub reassigns_total: u64,
     pub receipts_total: usize,
     pub plugins_total: usize,
@@ -484,4 +1410,5 @@ pub struct RuntimeMetrics {
     pub ttr_count: u64,
     pub ttft_bucket_counts: Vec<(u64, u64)>,
     pub ttr_bucket_counts: Vec<(u64, u64)>,
+    pub labelled: LabelledMetrics,
 }
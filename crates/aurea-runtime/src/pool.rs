@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use aurea_storage::{CancelOutcome, QueuedJob, TopicFilter, topic_matches};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Shared state of the worker pool: wake-ups, shutdown and per-topic slots.
+pub(crate) struct WorkerPool {
+    pub(crate) wake: Notify,
+    pub(crate) shutdown: CancellationToken,
+    /// Keyed by topic pattern (`vcx:*`, `echo:test`).
+    limits: BTreeMap<String, usize>,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    by_topic: HashMap<String, usize>,
+    /// Cancellation tokens of the jobs this process is executing.
+    running: HashMap<Uuid, CancellationToken>,
+}
+
+impl WorkerPool {
+    pub(crate) fn new(limits: BTreeMap<String, usize>) -> Self {
+        Self {
+            wake: Notify::new(),
+            shutdown: CancellationToken::new(),
+            limits,
+            state: Mutex::new(PoolState::default()),
+        }
+    }
+
+    /// Leases through `lease` while skipping topics that reached their
+    /// concurrency limit. The returned slot frees the topic when dropped.
+    pub(crate) fn lease<F>(self: &Arc<Self>, lease: F) -> Result<Option<(QueuedJob, TopicSlot)>>
+    where
+        F: FnOnce(&TopicFilter) -> Result<Option<QueuedJob>>,
+    {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("in-flight lock poisoned"))?;
+
+        let saturated = self
+            .limits
+            .iter()
+            .filter(|(pattern, limit)| {
+                let running: usize = state
+                    .by_topic
+                    .iter()
+                    .filter(|(topic, _)| topic_matches(pattern, topic))
+                    .map(|(_, n)| *n)
+                    .sum();
+                running >= **limit
+            })
+            .map(|(pattern, _)| pattern.clone())
+            .collect::<Vec<_>>();
+        let filter = if saturated.is_empty() {
+            TopicFilter::All
+        } else {
+            TopicFilter::Except(saturated)
+        };
+
+        let Some(job) = lease(&filter)? else {
+            return Ok(None);
+        };
+        *state.by_topic.entry(job.work.topic.clone()).or_insert(0) += 1;
+        let cancel = CancellationToken::new();
+        state.running.insert(job.work.id, cancel.clone());
+        let slot = TopicSlot {
+            pool: Arc::clone(self),
+            topic: job.work.topic.clone(),
+            work_id: job.work.id,
+            cancel,
+        };
+        Ok(Some((job, slot)))
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.by_topic.values().sum())
+            .unwrap_or_default()
+    }
+
+    /// Runs the store-side `cancel` under the pool lock, so a job cannot be
+    /// leased between the two, and signals the worker when it runs here. The
+    /// flag is false when a leased job has no worker in this process.
+    pub(crate) fn cancel<F>(&self, work_id: Uuid, cancel: F) -> Result<(CancelOutcome, bool)>
+    where
+        F: FnOnce() -> Result<CancelOutcome>,
+    {
+        let state = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("in-flight lock poisoned"))?;
+        let outcome = cancel()?;
+        let signalled = match (&outcome, state.running.get(&work_id)) {
+            (CancelOutcome::Leased(_), Some(token)) => {
+                token.cancel();
+                true
+            }
+            _ => false,
+        };
+        Ok((outcome, signalled))
+    }
+
+    fn release(&self, topic: &str, work_id: Uuid) {
+        if let Ok(mut state) = self.state.lock() {
+            state.running.remove(&work_id);
+            if let Some(count) = state.by_topic.get_mut(topic) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    state.by_topic.remove(topic);
+                }
+            }
+        }
+        if self
+            .limits
+            .keys()
+            .any(|pattern| topic_matches(pattern, topic))
+        {
+            self.wake.notify_one();
+        }
+    }
+}
+
+pub(crate) struct TopicSlot {
+    pool: Arc<WorkerPool>,
+    topic: String,
+    work_id: Uuid,
+    cancel: CancellationToken,
+}
+
+impl TopicSlot {
+    pub(crate) fn cancellation(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+}
+
+impl Drop for TopicSlot {
+    fn drop(&mut self) {
+        self.pool.release(&self.topic, self.work_id);
+    }
+}
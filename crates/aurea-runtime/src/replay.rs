@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use aurea_artifacts_store::ArtifactStore;
+use aurea_core::{ArtifactRef, ReceiptSignature, WorkStatus, cid_for};
+use aurea_plugins::ExecutionContext;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{Instrument, info_span, warn};
+use uuid::Uuid;
+
+use crate::{Runtime, extract_artifacts, extract_plan_hash, verify_signature};
+
+/// What re-running a receipt's work produced, next to what the receipt says.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub receipt_cid: String,
+    pub work_id: Uuid,
+    pub topic: String,
+    pub plan_hash: String,
+    /// Execution time handed to the plugin: the original attempt's start.
+    pub started_at: DateTime<Utc>,
+    pub replayed_at: DateTime<Utc>,
+    /// Where the replay wrote its files; only set when they were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    pub status: WorkStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_cid: Option<String>,
+    pub artifacts: Vec<ArtifactRef>,
+    pub identical: bool,
+    pub differences: Vec<ReplayDifference>,
+}
+
+/// One field where the replay disagrees with the receipt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayDifference {
+    pub field: String,
+    pub original: Value,
+    pub replayed: Value,
+}
+
+/// A replay report signed with the runtime's key, addressed by its CID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedReplayReport {
+    pub cid: String,
+    #[serde(flatten)]
+    pub report: ReplayReport,
+    pub signature: ReceiptSignature,
+}
+
+impl SignedReplayReport {
+    /// The CID matches the report and the signature covers the CID.
+    pub fn verify(&self) -> Result<bool> {
+        Ok(cid_for(&self.report)? == self.cid && verify_signature(&self.cid, &self.signature)?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayOutcome {
+    /// No receipt with that CID.
+    NotFound,
+    /// No payload was given and the receipt has no stored input to fall
+    /// back on.
+    MissingPayload,
+    /// The payload does not hash to the receipt's `plan_hash`, so it is not
+    /// the work the receipt describes.
+    PlanMismatch {
+        expected: String,
+        actual: String,
+    },
+    Replayed(Box<SignedReplayReport>),
+}
+
+impl Runtime {
+    /// Runs `payload` through the plugin that produced receipt `cid`, writing
+    /// into a fresh directory under the replay dir, and reports every
+    /// artifact, result or status that came out differently. Without a
+    /// payload the receipt's stored input is used. Nothing is enqueued and no
+    /// receipt is issued. The directory is removed once the report is signed
+    /// unless `keep_output` is set.
+    pub async fn replay(
+        &self,
+        cid: &str,
+        payload: Option<Value>,
+        keep_output: bool,
+    ) -> Result<ReplayOutcome> {
+        let Some(receipt) = self.store.get_receipt(cid)? else {
+            return Ok(ReplayOutcome::NotFound);
+        };
+        let payload = match (payload, &receipt.input_cid) {
+            (Some(payload), _) => payload,
+            (None, Some(input_cid)) => match self.store.get_blob(input_cid)? {
+                Some(bytes) => {
+                    serde_json::from_slice(&bytes).context("deserialize stored payload failed")?
+                }
+                None => return Ok(ReplayOutcome::MissingPayload),
+            },
+            (None, None) => return Ok(ReplayOutcome::MissingPayload),
+        };
+        let plan_hash = match extract_plan_hash(&payload) {
+            Some(plan_hash) => plan_hash,
+            None => cid_for(&payload)?,
+        };
+        if plan_hash != receipt.plan_hash {
+            return Ok(ReplayOutcome::PlanMismatch {
+                expected: receipt.plan_hash,
+                actual: plan_hash,
+            });
+        }
+
+        let plugin = self.plugin_for(&receipt.topic)?;
+        let output_dir = self
+            .replay_dir
+            .join(&receipt.cid)
+            .join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&output_dir)
+            .with_context(|| format!("create replay directory {} failed", output_dir.display()))?;
+        let sandbox = Sandbox {
+            dir: output_dir.clone(),
+            keep: keep_output,
+        };
+        let started_at = receipt.started_at.unwrap_or(receipt.created_at);
+        let mut ctx = ExecutionContext::detached(started_at).with_output_dir(&output_dir);
+        if self.artifacts.is_some() {
+            ctx = ctx.with_artifact_store(ArtifactStore::open(output_dir.join("artifacts"))?);
+        }
+
+        let outcome = plugin
+            .execute(&ctx, payload)
+            .instrument(info_span!("replay", receipt_cid = %receipt.cid))
+            .await
+            .and_then(|result| {
+                let artifacts = extract_artifacts(&result)?;
+                Ok((cid_for(&result)?, artifacts))
+            });
+        let (status, error, result_cid, artifacts) = match outcome {
+            Ok((result_cid, artifacts)) => (WorkStatus::Done, None, Some(result_cid), artifacts),
+            Err(err) => (WorkStatus::Fail, Some(err.to_string()), None, Vec::new()),
+        };
+
+        let mut differences = Vec::new();
+        if status != receipt.status {
+            differences.push(ReplayDifference {
+                field: "status".to_string(),
+                original: json!(receipt.status),
+                replayed: json!(status),
+            });
+        }
+        if receipt.result_cid.is_some() && result_cid != receipt.result_cid {
+            differences.push(ReplayDifference {
+                field: "result_cid".to_string(),
+                original: json!(receipt.result_cid),
+                replayed: json!(result_cid),
+            });
+        }
+        differences.extend(artifact_differences(&receipt.artifacts, &artifacts));
+
+        let report = ReplayReport {
+            receipt_cid: receipt.cid,
+            work_id: receipt.work_id,
+            topic: receipt.topic,
+            plan_hash,
+            started_at,
+            replayed_at: self.clock.now(),
+            output_dir: keep_output.then(|| output_dir.display().to_string()),
+            status,
+            error,
+            result_cid,
+            artifacts,
+            identical: differences.is_empty(),
+            differences,
+        };
+        let cid = cid_for(&report)?;
+        let signed = SignedReplayReport {
+            signature: self.signature_for(&cid)?,
+            cid,
+            report,
+        };
+        drop(sandbox);
+        Ok(ReplayOutcome::Replayed(Box::new(signed)))
+    }
+}
+
+/// A replay's output directory; removed on drop, so also when the replay
+/// fails, unless it was asked to be kept.
+struct Sandbox {
+    dir: PathBuf,
+    keep: bool,
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        if let Err(err) = std::fs::remove_dir_all(&self.dir) {
+            warn!(dir = %self.dir.display(), error = %err, "remove replay directory failed");
+        }
+        // The per-receipt parent goes too once no other replay is using it.
+        if let Some(parent) = self.dir.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+}
+
+/// Artifacts are matched by the path the plugin reported for them.
+fn artifact_differences(
+    original: &[ArtifactRef],
+    replayed: &[ArtifactRef],
+) -> Vec<ReplayDifference> {
+    let mut by_path: BTreeMap<&str, (Value, Value)> = BTreeMap::new();
+    for artifact in original {
+        by_path.entry(&artifact.path).or_default().0 = json!(artifact.cid);
+    }
+    for artifact in replayed {
+        by_path.entry(&artifact.path).or_default().1 = json!(artifact.cid);
+    }
+    by_path
+        .into_iter()
+        .filter(|(_, (original, replayed))| original != replayed)
+        .map(|(path, (original, replayed))| ReplayDifference {
+            field: format!("artifacts[{path}]"),
+            original,
+            replayed,
+        })
+        .collect()
+}
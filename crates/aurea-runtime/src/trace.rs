@@ -0,0 +1,35 @@
+use aurea_core::TraceParent;
+use opentelemetry::Context;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Makes `span` a child of the remote span `parent` names. A no-op unless an
+/// OpenTelemetry layer is installed.
+pub(crate) fn set_parent(span: &Span, parent: &TraceParent) {
+    let cx = Context::new().with_remote_span_context(span_context(parent));
+    let _ = span.set_parent(cx);
+}
+
+/// The `traceparent` naming `span` itself, or `None` when spans are not
+/// being exported.
+pub(crate) fn traceparent_of(span: &Span) -> Option<TraceParent> {
+    let cx = span.context();
+    let otel = cx.span();
+    let context = otel.span_context();
+    context.is_valid().then(|| TraceParent {
+        trace_id: context.trace_id().to_bytes(),
+        parent_id: context.span_id().to_bytes(),
+        flags: context.trace_flags().to_u8(),
+    })
+}
+
+fn span_context(parent: &TraceParent) -> SpanContext {
+    SpanContext::new(
+        TraceId::from_bytes(parent.trace_id),
+        SpanId::from_bytes(parent.parent_id),
+        TraceFlags::new(parent.flags),
+        true,
+        TraceState::default(),
+    )
+}
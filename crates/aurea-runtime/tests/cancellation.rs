@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aurea_core::{WorkStatus, WorkUnit};
+use aurea_plugins::{ExecutionContext, Plugin, PluginRegistry};
+use aurea_runtime::{AcceptDisposition, CancelDisposition, Runtime, RuntimeConfig};
+use aurea_storage::{MemoryStore, Store, TopicFilter};
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::{Value, json};
+use tokio::time::{sleep, timeout};
+
+struct StuckPlugin;
+
+#[async_trait]
+impl Plugin for StuckPlugin {
+    fn name(&self) -> &'static str {
+        "stuck"
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, _payload: Value) -> anyhow::Result<Value> {
+        ctx.cancelled().await;
+        anyhow::bail!("stopped on cancellation")
+    }
+}
+
+fn runtime() -> Runtime {
+    let mut registry = PluginRegistry::new();
+    registry.register(StuckPlugin);
+    Runtime::new_with_signer_and_config(
+        MemoryStore::new(),
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 5_000,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    )
+}
+
+fn stuck_work(idem: &str) -> WorkUnit {
+    WorkUnit::new(
+        "demo".to_string(),
+        "stuck:test".to_string(),
+        Some(idem.to_string()),
+        json!({"x": 1}),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelling_queued_work_issues_receipt_and_frees_idem_key() {
+    let runtime = runtime();
+
+    let accepted = runtime
+        .accept_work(stuck_work("queued-1"))
+        .await
+        .expect("submit work");
+    let CancelDisposition::Cancelled { receipt_cid } =
+        runtime.cancel_work(accepted.work_id).expect("cancel")
+    else {
+        panic!("queued work should be cancelled right away");
+    };
+
+    let receipt = runtime
+        .get_receipt(&receipt_cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    assert_eq!(receipt.status, WorkStatus::Cancelled);
+    assert!(runtime.verify_receipt(&receipt).expect("verify").ok);
+    assert!(matches!(
+        runtime.cancel_work(accepted.work_id).expect("cancel again"),
+        CancelDisposition::NotFound
+    ));
+
+    let again = runtime
+        .accept_work(stuck_work("queued-1"))
+        .await
+        .expect("resubmit work");
+    assert!(matches!(again.disposition, AcceptDisposition::Enqueued));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelling_running_work_stops_the_plugin() {
+    let runtime = runtime();
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    let accepted = runtime
+        .accept_work(stuck_work("running-1"))
+        .await
+        .expect("submit work");
+
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.status == WorkStatus::Progress {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the job to start");
+
+    assert!(matches!(
+        runtime.cancel_work(accepted.work_id).expect("cancel"),
+        CancelDisposition::Cancelling
+    ));
+
+    let cancelled_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.status == WorkStatus::Cancelled {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for cancelled event");
+
+    let receipt = runtime
+        .get_receipt(&cancelled_cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    assert_eq!(receipt.status, WorkStatus::Cancelled);
+    timeout(Duration::from_secs(5), async {
+        while runtime.metrics_snapshot().expect("metrics").in_flight > 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("timed out waiting for the slot to free up");
+    let metrics = runtime.metrics_snapshot().expect("metrics");
+    assert_eq!(metrics.leased_depth, 0);
+    assert_eq!(metrics.dead_letter_depth, 0);
+
+    worker.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn work_leased_by_another_process_is_not_cancelled_behind_its_back() {
+    let store = MemoryStore::new();
+    let mut registry = PluginRegistry::new();
+    registry.register(StuckPlugin);
+    let runtime = Runtime::new_with_signer_and_config(
+        store.clone(),
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig::default(),
+    );
+
+    let accepted = runtime
+        .accept_work(stuck_work("elsewhere-1"))
+        .await
+        .expect("submit work");
+    let leased = store
+        .lease_next(60_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("leased job");
+
+    let CancelDisposition::LeasedElsewhere { lease_expires_at } =
+        runtime.cancel_work(accepted.work_id).expect("cancel")
+    else {
+        panic!("a live lease held elsewhere must not be cancelled here");
+    };
+    assert_eq!(lease_expires_at, leased.lease_expires_at);
+    assert_eq!(runtime.metrics_snapshot().expect("metrics").leased_depth, 1);
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use aurea_core::{SignatureThreshold, WorkStatus, WorkUnit};
+use aurea_plugins::{EchoPlugin, PluginRegistry};
+use aurea_receipts::LocalSigner;
+use aurea_runtime::{CosignOutcome, Runtime, RuntimeConfig};
+use aurea_storage::MemoryStore;
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::json;
+use tokio::time::timeout;
+
+fn runtime() -> Runtime {
+    let mut registry = PluginRegistry::new();
+    registry.register(EchoPlugin);
+    Runtime::new_with_signer_and_config(
+        MemoryStore::new(),
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "issuer".to_string(),
+        RuntimeConfig {
+            worker_tick_ms: 10,
+            cosign: BTreeMap::from([(
+                "echo:commit".to_string(),
+                SignatureThreshold {
+                    required_kids: vec!["auditor".to_string()],
+                    min_signatures: 2,
+                },
+            )]),
+            ..RuntimeConfig::default()
+        },
+    )
+}
+
+async fn run_to_receipt(runtime: &Runtime, topic: &str) -> String {
+    let mut events = runtime.subscribe_events();
+    let accepted = runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            topic.to_string(),
+            None,
+            json!({"x": 1}),
+        ))
+        .await
+        .expect("submit work");
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.work_id == accepted.work_id && evt.status == WorkStatus::Done {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn commit_receipts_verify_once_the_auditor_countersigns() {
+    let runtime = runtime();
+    let worker = runtime.start_background_worker();
+
+    let plain = run_to_receipt(&runtime, "echo:test").await;
+    let check = runtime
+        .verify_receipt_by_cid(&plain)
+        .expect("verify")
+        .expect("receipt");
+    assert!(check.ok && check.threshold_met);
+
+    let cid = run_to_receipt(&runtime, "echo:commit").await;
+    let check = runtime
+        .verify_receipt_by_cid(&cid)
+        .expect("verify")
+        .expect("receipt");
+    assert!(check.cid_match && check.signature_valid);
+    assert!(!check.ok && !check.threshold_met);
+    assert_eq!(check.signed_by, ["issuer"]);
+    assert_eq!(check.missing_kids, ["auditor"]);
+
+    let auditor = LocalSigner::new("auditor", SigningKey::generate(&mut OsRng));
+    let forged = aurea_receipts::cosign_receipt("some-other-cid", &auditor).expect("sign");
+    assert!(matches!(
+        runtime.cosign_receipt(&cid, forged).expect("cosign"),
+        CosignOutcome::InvalidSignature
+    ));
+
+    let signature = aurea_receipts::cosign_receipt(&cid, &auditor).expect("sign");
+    let CosignOutcome::Cosigned(receipt) = runtime
+        .cosign_receipt(&cid, signature.clone())
+        .expect("cosign")
+    else {
+        panic!("expected the receipt to be cosigned");
+    };
+    assert_eq!(receipt.cid, cid);
+    assert_eq!(receipt.signatures.len(), 2);
+    assert!(aurea_receipts::verify_receipt(&receipt).ok);
+    let check = runtime
+        .verify_receipt_by_cid(&cid)
+        .expect("verify")
+        .expect("receipt");
+    assert!(check.ok, "{check:?}");
+    assert_eq!(check.signed_by, ["issuer", "auditor"]);
+
+    assert!(matches!(
+        runtime.cosign_receipt(&cid, signature).expect("cosign"),
+        CosignOutcome::AlreadySigned
+    ));
+    assert!(matches!(
+        runtime
+            .cosign_receipt(
+                "missing",
+                aurea_receipts::cosign_receipt("missing", &auditor).expect("sign")
+            )
+            .expect("cosign"),
+        CosignOutcome::NotFound
+    ));
+
+    worker.abort();
+}
@@ -0,0 +1,226 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aurea_artifacts_store::ArtifactStore;
+use aurea_core::{WorkStatus, WorkUnit};
+use aurea_plugins::{ExecutionContext, Plugin, PluginRegistry, VcxWorkerPlugin};
+use aurea_runtime::{ReplayOutcome, Runtime, RuntimeConfig};
+use aurea_storage::MemoryStore;
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::{Value, json};
+use tokio::time::timeout;
+use uuid::Uuid;
+
+/// Emits a different artifact every time it runs.
+#[derive(Default)]
+struct DriftPlugin {
+    runs: AtomicU32,
+}
+
+#[async_trait]
+impl Plugin for DriftPlugin {
+    fn name(&self) -> &'static str {
+        "drift"
+    }
+
+    async fn execute(&self, _ctx: &ExecutionContext, _payload: Value) -> anyhow::Result<Value> {
+        let run = self.runs.fetch_add(1, Ordering::SeqCst);
+        Ok(json!({
+            "artifacts": [{"cid": format!("cid-{run}"), "path": "out.bin", "size_bytes": 1}]
+        }))
+    }
+}
+
+fn runtime(replay_dir: &Path) -> Runtime {
+    runtime_with_artifacts(replay_dir, None)
+}
+
+fn runtime_with_artifacts(replay_dir: &Path, artifacts: Option<ArtifactStore>) -> Runtime {
+    let mut registry = PluginRegistry::new();
+    registry.register(VcxWorkerPlugin);
+    registry.register(DriftPlugin::default());
+    Runtime::new_with_signer_and_config(
+        MemoryStore::new(),
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            worker_tick_ms: 10,
+            replay_dir: replay_dir.to_path_buf(),
+            artifacts,
+            ..RuntimeConfig::default()
+        },
+    )
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aurea-{name}-{}", Uuid::new_v4()))
+}
+
+async fn run_to_receipt(runtime: &Runtime, topic: &str, payload: Value) -> String {
+    let mut events = runtime.subscribe_events();
+    let accepted = runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            topic.to_string(),
+            None,
+            payload,
+        ))
+        .await
+        .expect("submit work");
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.work_id == accepted.work_id && evt.status == WorkStatus::Done {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replaying_a_vcx_receipt_reproduces_its_pack() {
+    let replay_dir = temp_dir("replays");
+    let pack_dir = temp_dir("packs");
+    let runtime = runtime(&replay_dir);
+    let worker = runtime.start_background_worker();
+    let payload = json!({
+        "pack_dir": pack_dir.display().to_string(),
+        "items": [{"path": "a.txt", "content": "hello"}]
+    });
+
+    let cid = run_to_receipt(&runtime, "vcx:pack", payload.clone()).await;
+    let receipt = runtime
+        .get_receipt(&cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+
+    let ReplayOutcome::Replayed(replay) = runtime.replay(&cid, None, false).await.expect("replay")
+    else {
+        panic!("expected a replay report");
+    };
+    assert!(replay.report.identical, "{:?}", replay.report.differences);
+    assert_eq!(
+        replay.report.artifacts[0].cid, receipt.artifacts[0].cid,
+        "pack CID must be reproduced"
+    );
+    assert_eq!(replay.report.started_at, receipt.started_at.expect("start"));
+    assert_eq!(replay.report.result_cid, receipt.result_cid);
+    let input = runtime
+        .get_blob(receipt.input_cid.as_deref().expect("input cid"))
+        .expect("read input blob")
+        .expect("input blob");
+    assert_eq!(
+        serde_json::from_slice::<Value>(&input).expect("input json"),
+        payload
+    );
+    assert!(replay.verify().expect("verify report"));
+    assert_eq!(replay.report.output_dir, None);
+    assert!(!replay_dir.join(&cid).exists(), "sandbox must be removed");
+
+    let mut forged = (*replay).clone();
+    forged.report.identical = false;
+    assert!(!forged.verify().expect("verify forged report"));
+
+    assert!(matches!(
+        runtime
+            .replay(&cid, Some(json!({"items": []})), false)
+            .await
+            .expect("replay"),
+        ReplayOutcome::PlanMismatch { .. }
+    ));
+    assert!(matches!(
+        runtime
+            .replay("missing", None, false)
+            .await
+            .expect("replay"),
+        ReplayOutcome::NotFound
+    ));
+
+    worker.abort();
+    let _ = std::fs::remove_dir_all(replay_dir);
+    let _ = std::fs::remove_dir_all(pack_dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replay_lists_artifacts_that_came_out_differently() {
+    let replay_dir = temp_dir("replays");
+    let runtime = runtime(&replay_dir);
+    let worker = runtime.start_background_worker();
+
+    let cid = run_to_receipt(&runtime, "drift:test", json!({"x": 1})).await;
+    let ReplayOutcome::Replayed(replay) = runtime
+        .replay(&cid, Some(json!({"x": 1})), false)
+        .await
+        .expect("replay")
+    else {
+        panic!("expected a replay report");
+    };
+    assert!(!replay.report.identical);
+    let fields = replay
+        .report
+        .differences
+        .iter()
+        .map(|d| d.field.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["result_cid", "artifacts[out.bin]"]);
+    let difference = &replay.report.differences[1];
+    assert_eq!(
+        (&difference.original, &difference.replayed),
+        (&json!("cid-0"), &json!("cid-1"))
+    );
+
+    worker.abort();
+    let _ = std::fs::remove_dir_all(replay_dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn managed_artifacts_are_stored_by_cid_and_replayed_apart() {
+    let replay_dir = temp_dir("replays");
+    let store_dir = temp_dir("artifacts");
+    let artifacts = ArtifactStore::open(&store_dir).expect("open artifact store");
+    let runtime = runtime_with_artifacts(&replay_dir, Some(artifacts.clone()));
+    let worker = runtime.start_background_worker();
+
+    let cid = run_to_receipt(
+        &runtime,
+        "vcx:pack",
+        json!({"items": [{"path": "a.txt", "content": "hello"}]}),
+    )
+    .await;
+    let receipt = runtime
+        .get_receipt(&cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    let artifact = &receipt.artifacts[0];
+    assert!(!artifact.path.contains('/'), "{}", artifact.path);
+    let bytes = runtime
+        .get_artifact(&artifact.cid)
+        .expect("read artifact")
+        .expect("artifact stored");
+    assert_eq!(aurea_core::cid_of(&bytes), artifact.cid);
+    assert!(runtime.get_artifact("missing").expect("read").is_none());
+
+    let ReplayOutcome::Replayed(replay) = runtime.replay(&cid, None, true).await.expect("replay")
+    else {
+        panic!("expected a replay report");
+    };
+    assert!(replay.report.identical, "{:?}", replay.report.differences);
+    assert_eq!(
+        artifacts.list().expect("list"),
+        std::slice::from_ref(&artifact.cid)
+    );
+    let output_dir = PathBuf::from(replay.report.output_dir.as_deref().expect("kept"));
+    assert!(output_dir.starts_with(&replay_dir));
+    let sandbox = ArtifactStore::open(output_dir.join("artifacts")).expect("open sandbox store");
+    assert!(sandbox.contains(&artifact.cid));
+
+    worker.abort();
+    let _ = std::fs::remove_dir_all(replay_dir);
+    let _ = std::fs::remove_dir_all(store_dir);
+}
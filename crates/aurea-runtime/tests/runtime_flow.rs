@@ -1,10 +1,15 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use aurea_core::{WorkStatus, WorkUnit};
-use aurea_plugins::{Plugin, PluginRegistry};
+use aurea_core::{HybridClock, MockClock, WorkStatus, WorkUnit};
+use aurea_plugins::{ExecutionContext, Plugin, PluginRegistry};
 use aurea_runtime::{AcceptDisposition, Runtime, RuntimeConfig};
-use aurea_storage::RedbStore;
+use aurea_storage::{
+    Backoff, MemoryStore, ReceiptQuery, RedbStore, RetryConfig, RetryOn, RetryPolicy, StoreConfig,
+};
 use ed25519_dalek::SigningKey;
 use rand::rngs::OsRng;
 use serde_json::{Value, json};
@@ -19,7 +24,7 @@ impl Plugin for SlowPlugin {
         "slow"
     }
 
-    async fn execute(&self, payload: Value) -> anyhow::Result<Value> {
+    async fn execute(&self, _ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
         sleep(Duration::from_millis(300)).await;
         Ok(payload)
     }
@@ -43,6 +48,7 @@ async fn duplicate_in_flight_then_duplicate_receipt() {
         RuntimeConfig {
             lease_ttl_ms: 5_000,
             worker_tick_ms: 20,
+            ..RuntimeConfig::default()
         },
     );
 
@@ -105,3 +111,454 @@ async fn duplicate_in_flight_then_duplicate_receipt() {
     worker.abort();
     let _ = std::fs::remove_file(&path);
 }
+
+struct FlakyPlugin {
+    failures_left: AtomicU32,
+}
+
+#[async_trait]
+impl Plugin for FlakyPlugin {
+    fn name(&self) -> &'static str {
+        "flaky"
+    }
+
+    async fn execute(&self, _ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        let left = self.failures_left.load(Ordering::SeqCst);
+        if left > 0 {
+            self.failures_left.store(left - 1, Ordering::SeqCst);
+            anyhow::bail!("transient failure");
+        }
+        Ok(payload)
+    }
+}
+
+struct BrokenPlugin;
+
+#[async_trait]
+impl Plugin for BrokenPlugin {
+    fn name(&self) -> &'static str {
+        "broken"
+    }
+
+    async fn execute(&self, _ctx: &ExecutionContext, _payload: Value) -> anyhow::Result<Value> {
+        anyhow::bail!("always broken")
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retries_until_success_then_dead_letters_when_exhausted() {
+    let path = std::env::temp_dir().join(format!("aurea-runtime-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            retry: RetryConfig {
+                default: RetryPolicy {
+                    max_attempts: 3,
+                    backoff: Backoff {
+                        initial_ms: 10,
+                        multiplier: 2,
+                        max_ms: 50,
+                    },
+                    retry_on: RetryOn::Any,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .expect("open redb");
+    let mut registry = PluginRegistry::new();
+    registry.register(FlakyPlugin {
+        failures_left: AtomicU32::new(2),
+    });
+    registry.register(BrokenPlugin);
+
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 5_000,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    );
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "flaky:test".to_string(),
+            Some("flaky-1".to_string()),
+            json!({"x": 1}),
+        ))
+        .await
+        .expect("submit work");
+
+    let mut retries = 0;
+    let done_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            match evt.status {
+                WorkStatus::Retrying => retries += 1,
+                WorkStatus::Done => return evt.receipt_cid.expect("receipt cid"),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event");
+    assert_eq!(retries, 2);
+
+    let receipt = runtime
+        .get_receipt(&done_cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    assert_eq!(receipt.attempts.len(), 2);
+    assert!(runtime.verify_receipt(&receipt).expect("verify").ok);
+
+    runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "broken:test".to_string(),
+            Some("broken-1".to_string()),
+            json!({"x": 2}),
+        ))
+        .await
+        .expect("submit broken work");
+
+    let fail_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.status == WorkStatus::Fail {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for fail event");
+
+    let receipt = runtime
+        .get_receipt(&fail_cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    assert_eq!(receipt.attempts.len(), 3);
+    let letters = runtime.list_dead_letters().expect("dead letters");
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].receipt_cid.as_deref(), Some(fail_cid.as_str()));
+
+    worker.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Overruns its lease on the first attempt; later attempts finish at once.
+struct OverrunPlugin;
+
+#[async_trait]
+impl Plugin for OverrunPlugin {
+    fn name(&self) -> &'static str {
+        "overrun"
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        if ctx.attempt() == 1 {
+            sleep(Duration::from_millis(400)).await;
+        }
+        Ok(json!({"attempt": ctx.attempt(), "payload": payload}))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn workers_that_lost_their_lease_drop_their_result() {
+    let store = MemoryStore::with_config(StoreConfig {
+        retry: RetryConfig {
+            default: RetryPolicy {
+                max_attempts: 3,
+                backoff: Backoff {
+                    initial_ms: 10,
+                    multiplier: 1,
+                    max_ms: 10,
+                },
+                retry_on: RetryOn::Any,
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let mut registry = PluginRegistry::new();
+    registry.register(OverrunPlugin);
+
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 50,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    );
+    let worker = runtime.start_background_worker();
+
+    let accepted = runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "overrun:test".to_string(),
+            Some("overrun-1".to_string()),
+            json!({"x": 1}),
+        ))
+        .await
+        .expect("submit work");
+    // Long enough for the first attempt to wake up well after the retry won.
+    sleep(Duration::from_millis(700)).await;
+
+    let page = runtime
+        .query_receipts(&ReceiptQuery {
+            work_id: Some(accepted.work_id),
+            ..Default::default()
+        })
+        .expect("query receipts");
+    assert_eq!(page.receipts.len(), 1);
+    let receipt = &page.receipts[0];
+    assert_eq!(receipt.status, WorkStatus::Done);
+    assert_eq!(receipt.attempts.len(), 1);
+    let result = runtime
+        .get_blob(receipt.result_cid.as_deref().expect("result cid"))
+        .expect("read result")
+        .expect("result stored");
+    let result: Value = serde_json::from_slice(&result).expect("result json");
+    assert_eq!(result["attempt"], 2);
+
+    worker.abort();
+}
+
+struct LongPlugin;
+
+#[async_trait]
+impl Plugin for LongPlugin {
+    fn name(&self) -> &'static str {
+        "long"
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        for step in 0..4 {
+            let stage = ctx.stage("crunch_ms");
+            sleep(Duration::from_millis(60)).await;
+            stage.finish();
+            ctx.heartbeat()?;
+            ctx.progress(format!("step {step} done"));
+        }
+        Ok(payload)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn heartbeats_keep_long_jobs_leased_and_stages_reach_receipt() {
+    let path = std::env::temp_dir().join(format!("aurea-runtime-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+    let mut registry = PluginRegistry::new();
+    registry.register(LongPlugin);
+
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 100,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    );
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "long:test".to_string(),
+            Some("long-1".to_string()),
+            json!({"x": 1}),
+        ))
+        .await
+        .expect("submit work");
+
+    let mut progress = Vec::new();
+    let done_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            match evt.status {
+                WorkStatus::Progress => progress.extend(evt.detail),
+                WorkStatus::Done => return evt.receipt_cid.expect("receipt cid"),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event");
+
+    assert!(progress.contains(&"step 3 done".to_string()));
+    let receipt = runtime
+        .get_receipt(&done_cid)
+        .expect("read receipt")
+        .expect("receipt exists");
+    assert!(receipt.stage_time_ms["crunch_ms"] >= 240);
+    assert!(receipt.stage_time_ms.contains_key("ttr_ms"));
+    assert!(receipt.attempts.is_empty());
+    assert_eq!(
+        runtime.metrics_snapshot().expect("metrics").reassigns_total,
+        0
+    );
+
+    worker.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn traceparent_reaches_events_and_receipt() {
+    let path = std::env::temp_dir().join(format!("aurea-runtime-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+    let mut registry = PluginRegistry::new();
+    registry.register(SlowPlugin);
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            worker_tick_ms: 20,
+            ..RuntimeConfig::default()
+        },
+    );
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    let mut traced = WorkUnit::new(
+        "demo".to_string(),
+        "slow:test".to_string(),
+        Some("traced".to_string()),
+        json!({"x": 1}),
+    );
+    traced.traceparent =
+        Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string());
+    runtime.accept_work(traced).await.expect("submit traced");
+    runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "slow:test".to_string(),
+            Some("untraced".to_string()),
+            json!({"x": 2}),
+        ))
+        .await
+        .expect("submit untraced");
+
+    let mut trace_ids = BTreeMap::new();
+    let mut receipts = Vec::new();
+    timeout(Duration::from_secs(5), async {
+        while receipts.len() < 2 {
+            let evt = events.recv().await.expect("event");
+            let trace_id = evt.trace_id.clone().expect("event trace id");
+            assert_eq!(
+                trace_ids
+                    .entry(evt.work_id)
+                    .or_insert_with(|| trace_id.clone()),
+                &trace_id
+            );
+            if evt.status == WorkStatus::Done {
+                receipts.push(evt.receipt_cid.expect("receipt cid"));
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done events");
+
+    let traced = trace_ids
+        .values()
+        .filter(|id| id.as_str() == "4bf92f3577b34da6a3ce929d0e0e4736")
+        .count();
+    assert_eq!((trace_ids.len(), traced), (2, 1));
+    for cid in receipts {
+        let receipt = runtime
+            .get_receipt(&cid)
+            .expect("read receipt")
+            .expect("receipt exists");
+        assert_eq!(receipt.trace_id.as_ref(), trace_ids.get(&receipt.work_id));
+        assert!(receipt.cid_matches().expect("cid"));
+    }
+
+    worker.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn receipts_stay_ordered_when_the_wall_clock_stands_still() {
+    let start = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+        .unwrap()
+        .to_utc();
+    let frozen = MockClock::new(start);
+    let store = MemoryStore::with_config(StoreConfig {
+        clock: Arc::new(HybridClock::new(Arc::new(frozen))),
+        ..Default::default()
+    });
+    let mut registry = PluginRegistry::new();
+    registry.register(SlowPlugin);
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            worker_tick_ms: 20,
+            ..RuntimeConfig::default()
+        },
+    );
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    for n in 0..3 {
+        runtime
+            .accept_work(WorkUnit::new(
+                "demo".to_string(),
+                "slow:test".to_string(),
+                Some(format!("frozen-{n}")),
+                json!({"n": n}),
+            ))
+            .await
+            .expect("submit work");
+    }
+    let mut stamps = Vec::new();
+    timeout(Duration::from_secs(5), async {
+        let mut done = 0;
+        while done < 3 {
+            let evt = events.recv().await.expect("event");
+            stamps.push((evt.seq, evt.at));
+            done += usize::from(evt.status == WorkStatus::Done);
+        }
+    })
+    .await
+    .expect("timed out waiting for done events");
+
+    stamps.sort();
+    assert!(stamps.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    let mut receipts = runtime
+        .query_receipts(&ReceiptQuery::default())
+        .expect("query receipts")
+        .receipts;
+    assert_eq!(receipts.len(), 3);
+    receipts.sort_by_key(|r| r.created_at);
+    assert!(
+        receipts
+            .windows(2)
+            .all(|pair| pair[0].created_at < pair[1].created_at)
+    );
+    assert!(
+        receipts
+            .iter()
+            .all(|r| r.created_at - start < chrono::Duration::milliseconds(1))
+    );
+
+    worker.abort();
+}
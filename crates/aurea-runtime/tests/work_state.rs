@@ -0,0 +1,207 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aurea_core::{HybridClock, MockClock, WorkStatus, WorkUnit};
+use aurea_plugins::{ExecutionContext, Plugin, PluginRegistry};
+use aurea_runtime::{Runtime, RuntimeConfig};
+use aurea_storage::{EventRetention, MemoryStore, RedbStore, Store, StoreConfig};
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::{Value, json};
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+struct SleepyPlugin;
+
+#[async_trait]
+impl Plugin for SleepyPlugin {
+    fn name(&self) -> &'static str {
+        "sleepy"
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        tokio::select! {
+            _ = sleep(Duration::from_millis(200)) => Ok(payload),
+            _ = ctx.cancelled() => anyhow::bail!("cancelled"),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn work_state_follows_the_persisted_event_history() {
+    let path = std::env::temp_dir().join(format!("aurea-runtime-state-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+    let mut registry = PluginRegistry::new();
+    registry.register(SleepyPlugin);
+    let runtime = Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 5_000,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    );
+    assert!(runtime.work_state(Uuid::new_v4()).expect("state").is_none());
+
+    let accepted = runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            "sleepy:test".to_string(),
+            Some("state-1".to_string()),
+            json!({"x": 1}),
+        ))
+        .await
+        .expect("submit work");
+    let queued = runtime
+        .work_state(accepted.work_id)
+        .expect("state")
+        .expect("known work");
+    assert_eq!(queued.status, WorkStatus::Accepted);
+    assert_eq!(queued.attempts, 0);
+
+    let mut events = runtime.subscribe_events();
+    let worker = runtime.start_background_worker();
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if events.recv().await.expect("event").status == WorkStatus::Progress {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the job to start");
+
+    let running = runtime
+        .work_state(accepted.work_id)
+        .expect("state")
+        .expect("known work");
+    assert_eq!(running.status, WorkStatus::Progress);
+    let lease = running.lease.expect("running work has a lease");
+    assert_eq!(lease.attempt, 1);
+    assert!(lease.expires_at.is_some());
+
+    let done_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.status == WorkStatus::Done {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event");
+
+    let done = runtime
+        .work_state(accepted.work_id)
+        .expect("state")
+        .expect("known work");
+    assert_eq!(done.status, WorkStatus::Done);
+    assert_eq!(done.attempts, 1);
+    assert!(done.lease.is_none());
+    assert_eq!(done.receipt_cid.as_deref(), Some(done_cid.as_str()));
+
+    let history = runtime
+        .work_events(accepted.work_id)
+        .expect("history")
+        .into_iter()
+        .map(|event| event.status)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        history,
+        vec![
+            WorkStatus::Accepted,
+            WorkStatus::Assigned,
+            WorkStatus::Progress,
+            WorkStatus::Done,
+        ]
+    );
+
+    worker.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn work_state_outlives_the_trimmed_event_history() {
+    let clock = Arc::new(MockClock::new(chrono::Utc::now()));
+    let store = MemoryStore::with_config(StoreConfig {
+        events: EventRetention {
+            max_events: None,
+            max_age: Some(chrono::Duration::minutes(5)),
+        },
+        clock: Arc::new(HybridClock::new(clock.clone())),
+        ..Default::default()
+    });
+    let mut registry = PluginRegistry::new();
+    registry.register(SleepyPlugin);
+    let runtime = Runtime::new_with_signer_and_config(
+        store.clone(),
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 5_000,
+            worker_tick_ms: 10,
+            ..RuntimeConfig::default()
+        },
+    );
+    let submit = |idem: &str| {
+        WorkUnit::new(
+            "demo".to_string(),
+            "sleepy:test".to_string(),
+            Some(idem.to_string()),
+            json!({"idem": idem}),
+        )
+    };
+
+    let mut events = runtime.subscribe_events();
+    let worker = runtime.start_background_worker();
+    let finished = runtime
+        .accept_work(submit("aged-done"))
+        .await
+        .expect("submit work");
+    let done_cid = timeout(Duration::from_secs(5), async {
+        loop {
+            let evt = events.recv().await.expect("event");
+            if evt.status == WorkStatus::Done {
+                return evt.receipt_cid.expect("receipt cid");
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done event");
+    runtime.shutdown();
+    worker.await.expect("workers drained");
+
+    let waiting = runtime
+        .accept_work(submit("aged-queued"))
+        .await
+        .expect("submit work");
+    clock.advance(chrono::Duration::minutes(10));
+    store.trim_event_log().expect("trim");
+    assert!(
+        runtime
+            .work_events(finished.work_id)
+            .expect("history")
+            .is_empty()
+    );
+
+    let done = runtime
+        .work_state(finished.work_id)
+        .expect("state")
+        .expect("finished work is still known");
+    assert_eq!(done.status, WorkStatus::Done);
+    assert_eq!(done.attempts, 1);
+    assert_eq!(done.receipt_cid.as_deref(), Some(done_cid.as_str()));
+
+    let queued = runtime
+        .work_state(waiting.work_id)
+        .expect("state")
+        .expect("queued work is still known");
+    assert_eq!(queued.status, WorkStatus::Accepted);
+    assert_eq!(queued.attempts, 0);
+    assert!(queued.receipt_cid.is_none());
+}
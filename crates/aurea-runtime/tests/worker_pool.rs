@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aurea_core::{WorkStatus, WorkUnit};
+use aurea_plugins::{ExecutionContext, Plugin, PluginRegistry};
+use aurea_runtime::{Runtime, RuntimeConfig};
+use aurea_storage::RedbStore;
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use serde_json::{Value, json};
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct Gauge {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+struct GaugedPlugin {
+    name: &'static str,
+    gauge: Gauge,
+}
+
+#[async_trait]
+impl Plugin for GaugedPlugin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn execute(&self, _ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        let now = self.gauge.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.gauge.peak.fetch_max(now, Ordering::SeqCst);
+        sleep(Duration::from_millis(150)).await;
+        self.gauge.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(payload)
+    }
+}
+
+fn runtime_with(
+    registry: PluginRegistry,
+    workers: usize,
+    topic_concurrency: BTreeMap<String, usize>,
+) -> Runtime {
+    let path = std::env::temp_dir().join(format!("aurea-runtime-pool-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+    Runtime::new_with_signer_and_config(
+        store,
+        registry,
+        SigningKey::generate(&mut OsRng),
+        "test-kid".to_string(),
+        RuntimeConfig {
+            lease_ttl_ms: 5_000,
+            // Long enough that only enqueue notifications can explain prompt pickup.
+            worker_tick_ms: 10_000,
+            workers,
+            topic_concurrency,
+            ..RuntimeConfig::default()
+        },
+    )
+}
+
+async fn submit(runtime: &Runtime, topic: &str, n: usize) {
+    runtime
+        .accept_work(WorkUnit::new(
+            "demo".to_string(),
+            topic.to_string(),
+            Some(format!("{topic}-{n}")),
+            json!({"n": n}),
+        ))
+        .await
+        .expect("submit work");
+}
+
+async fn wait_for_done(runtime: &Runtime, expected: usize) {
+    let mut events = runtime.subscribe_events();
+    timeout(Duration::from_secs(5), async {
+        let mut done = 0;
+        while done < expected {
+            if events.recv().await.expect("event").status == WorkStatus::Done {
+                done += 1;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for done events");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pool_runs_jobs_concurrently_but_honours_topic_limits() {
+    let fast = Gauge::default();
+    let limited = Gauge::default();
+    let mut registry = PluginRegistry::new();
+    registry.register(GaugedPlugin {
+        name: "fast",
+        gauge: fast.clone(),
+    });
+    registry.register(GaugedPlugin {
+        name: "limited",
+        gauge: limited.clone(),
+    });
+
+    let mut limits = BTreeMap::new();
+    limits.insert("limited:*".to_string(), 1);
+    let runtime = runtime_with(registry, 4, limits);
+    let worker = runtime.start_background_worker();
+    sleep(Duration::from_millis(50)).await;
+
+    let done = tokio::spawn({
+        let runtime = runtime.clone();
+        async move { wait_for_done(&runtime, 6).await }
+    });
+    for n in 0..3 {
+        submit(&runtime, "fast:test", n).await;
+        submit(&runtime, "limited:test", n).await;
+    }
+    done.await.expect("join done waiter");
+
+    assert!(fast.peak.load(Ordering::SeqCst) >= 2);
+    assert_eq!(limited.peak.load(Ordering::SeqCst), 1);
+    worker.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_drains_in_flight_jobs() {
+    let gauge = Gauge::default();
+    let mut registry = PluginRegistry::new();
+    registry.register(GaugedPlugin {
+        name: "fast",
+        gauge: gauge.clone(),
+    });
+    let runtime = runtime_with(registry, 2, BTreeMap::new());
+    let worker = runtime.start_background_worker();
+    sleep(Duration::from_millis(50)).await;
+
+    submit(&runtime, "fast:test", 0).await;
+    timeout(Duration::from_secs(2), async {
+        while gauge.running.load(Ordering::SeqCst) == 0 {
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("job started");
+
+    runtime.shutdown();
+    timeout(Duration::from_secs(2), worker)
+        .await
+        .expect("pool drained")
+        .expect("pool task joined");
+
+    let metrics = runtime.metrics_snapshot().expect("metrics");
+    assert_eq!(metrics.leased_depth, 0);
+    assert_eq!(metrics.in_flight, 0);
+    assert_eq!(metrics.receipts_total, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_workers_broadcast_events_in_sequence_order() {
+    let mut registry = PluginRegistry::new();
+    registry.register(GaugedPlugin {
+        name: "fast",
+        gauge: Gauge::default(),
+    });
+    let runtime = runtime_with(registry, 4, BTreeMap::new());
+    let worker = runtime.start_background_worker();
+    let mut events = runtime.subscribe_events();
+
+    for n in 0..8 {
+        submit(&runtime, "fast:test", n).await;
+    }
+    let seqs = timeout(Duration::from_secs(5), async {
+        let (mut seqs, mut done) = (Vec::new(), 0);
+        while done < 8 {
+            let event = events.recv().await.expect("event");
+            done += usize::from(event.status == WorkStatus::Done);
+            seqs.push(event.seq);
+        }
+        seqs
+    })
+    .await
+    .expect("timed out waiting for done events");
+
+    assert!(seqs[0] > 0);
+    assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]), "{seqs:?}");
+    worker.abort();
+}
+
+/// Reports progress until cancelled, so some event write is nearly always in
+/// flight.
+struct ChattyPlugin;
+
+#[async_trait]
+impl Plugin for ChattyPlugin {
+    fn name(&self) -> &'static str {
+        "chatty"
+    }
+
+    async fn execute(&self, ctx: &ExecutionContext, payload: Value) -> anyhow::Result<Value> {
+        let mut step = 0;
+        while !ctx.is_cancelled() {
+            step += 1;
+            ctx.progress(format!("step {step}"));
+            tokio::task::yield_now().await;
+        }
+        Ok(payload)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_stream_in_order_while_writes_never_pause() {
+    let mut registry = PluginRegistry::new();
+    registry.register(ChattyPlugin);
+    let runtime = runtime_with(registry, 4, BTreeMap::new());
+    let mut events = runtime.subscribe_events();
+    let worker = runtime.start_background_worker();
+
+    for n in 0..4 {
+        submit(&runtime, "chatty:test", n).await;
+    }
+    let seqs = timeout(Duration::from_secs(5), async {
+        let mut seqs = Vec::new();
+        while seqs.len() < 500 {
+            seqs.push(events.recv().await.expect("event").seq);
+        }
+        seqs
+    })
+    .await
+    .expect("held events were never released");
+
+    assert!(
+        seqs.windows(2).all(|pair| pair[1] == pair[0] + 1),
+        "{seqs:?}"
+    );
+    worker.abort();
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use aurea_core::Receipt;
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+
+use crate::RECEIPTS;
+
+/// One `(artifact cid, receipt cid)` entry per artifact a stored receipt
+/// lists. The artifact bytes live in the artifact store; this table is what
+/// its garbage collection keeps alive.
+pub(crate) const ARTIFACT_REFS: TableDefinition<(&str, &str), ()> =
+    TableDefinition::new("artifact_refs");
+
+pub(crate) fn referenced(receipt: &Receipt) -> BTreeSet<&str> {
+    receipt
+        .artifacts
+        .iter()
+        .map(|artifact| artifact.cid.as_str())
+        .collect()
+}
+
+pub(crate) fn add_refs(write: &WriteTransaction, receipt: &Receipt) -> Result<()> {
+    let mut refs = write
+        .open_table(ARTIFACT_REFS)
+        .context("open artifact_refs failed")?;
+    for artifact in referenced(receipt) {
+        refs.insert((artifact, receipt.cid.as_str()), ())
+            .context("insert artifact ref failed")?;
+    }
+    Ok(())
+}
+
+pub(crate) fn release(write: &WriteTransaction, receipt: &Receipt) -> Result<()> {
+    let mut refs = write
+        .open_table(ARTIFACT_REFS)
+        .context("open artifact_refs failed")?;
+    for artifact in referenced(receipt) {
+        refs.remove((artifact, receipt.cid.as_str()))
+            .context("remove artifact ref failed")?;
+    }
+    Ok(())
+}
+
+/// Rewrites the reference table from the receipts table.
+pub(crate) fn rebuild_refs(write: &WriteTransaction) -> Result<()> {
+    let mut expected = BTreeSet::new();
+    {
+        let receipts = write.open_table(RECEIPTS).context("open receipts failed")?;
+        for row in receipts.iter().context("iterate receipts failed")? {
+            let (_, value) = row.context("read receipt row failed")?;
+            let receipt: Receipt =
+                serde_json::from_slice(value.value()).context("deserialize receipt failed")?;
+            for artifact in referenced(&receipt) {
+                expected.insert((artifact.to_string(), receipt.cid.clone()));
+            }
+        }
+    }
+    let mut refs = write
+        .open_table(ARTIFACT_REFS)
+        .context("open artifact_refs failed")?;
+    refs.retain(|_, _| false)
+        .context("clear artifact_refs failed")?;
+    for (artifact, receipt) in &expected {
+        refs.insert((artifact.as_str(), receipt.as_str()), ())
+            .context("insert artifact ref failed")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use aurea_core::{Receipt, cid_of};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{DeadLetter, IdemRecord, QueuedJob, SCHEMA_VERSION};
+
+const FORMAT: &str = "aurea-backup";
+const FORMAT_VERSION: u32 = 2;
+
+/// Every durable row of a store as of one point in time. The event log is
+/// not included.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub ready_jobs: Vec<QueuedJob>,
+    pub scheduled_jobs: Vec<QueuedJob>,
+    pub leased_jobs: Vec<QueuedJob>,
+    pub dead_letters: Vec<DeadLetter>,
+    pub receipts: Vec<Receipt>,
+    pub idem_keys: Vec<IdemRecord>,
+    /// Payload and result blobs by CID; absent from format v1 archives.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub blobs: BTreeMap<String, Value>,
+    pub counters: BTreeMap<String, u64>,
+}
+
+impl Snapshot {
+    pub fn is_empty(&self) -> bool {
+        self.ready_jobs.is_empty()
+            && self.scheduled_jobs.is_empty()
+            && self.leased_jobs.is_empty()
+            && self.dead_letters.is_empty()
+            && self.receipts.is_empty()
+            && self.idem_keys.is_empty()
+            && self.blobs.is_empty()
+    }
+
+    fn digests(&self, format_version: u32) -> Result<Vec<SectionDigest>> {
+        let mut sections = vec![
+            SectionDigest::of("ready_jobs", self.ready_jobs.len(), &self.ready_jobs)?,
+            SectionDigest::of(
+                "scheduled_jobs",
+                self.scheduled_jobs.len(),
+                &self.scheduled_jobs,
+            )?,
+            SectionDigest::of("leased_jobs", self.leased_jobs.len(), &self.leased_jobs)?,
+            SectionDigest::of("dead_letters", self.dead_letters.len(), &self.dead_letters)?,
+            SectionDigest::of("receipts", self.receipts.len(), &self.receipts)?,
+            SectionDigest::of("idem_keys", self.idem_keys.len(), &self.idem_keys)?,
+            SectionDigest::of("counters", self.counters.len(), &self.counters)?,
+        ];
+        if format_version >= 2 {
+            sections.push(SectionDigest::of("blobs", self.blobs.len(), &self.blobs)?);
+        }
+        Ok(sections)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionDigest {
+    pub name: String,
+    pub count: usize,
+    pub cid: String,
+}
+
+impl SectionDigest {
+    fn of<T: Serialize>(name: &str, count: usize, rows: &T) -> Result<Self> {
+        let bytes = serde_json::to_vec(rows).context("serialize backup section failed")?;
+        Ok(Self {
+            name: name.to_string(),
+            count,
+            cid: cid_of(&bytes),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub schema_version: u64,
+    pub taken_at: DateTime<Utc>,
+    pub sections: Vec<SectionDigest>,
+}
+
+/// A snapshot together with a manifest of per-section CIDs. The archive is
+/// addressed by the CID of its manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub manifest: BackupManifest,
+    pub snapshot: Snapshot,
+}
+
+impl BackupArchive {
+    pub fn new(snapshot: Snapshot, taken_at: DateTime<Utc>) -> Result<Self> {
+        Ok(Self {
+            manifest: BackupManifest {
+                format: FORMAT.to_string(),
+                format_version: FORMAT_VERSION,
+                schema_version: SCHEMA_VERSION,
+                taken_at,
+                sections: snapshot.digests(FORMAT_VERSION)?,
+            },
+            snapshot,
+        })
+    }
+
+    pub fn cid(&self) -> Result<String> {
+        let bytes = serde_json::to_vec(&self.manifest).context("serialize manifest failed")?;
+        Ok(cid_of(&bytes))
+    }
+
+    /// Checks the format and that every section still hashes to the CID the
+    /// manifest recorded for it.
+    pub fn verify(&self) -> Result<()> {
+        if self.manifest.format != FORMAT {
+            bail!("not an aurea backup (format `{}`)", self.manifest.format);
+        }
+        if self.manifest.format_version > FORMAT_VERSION {
+            bail!(
+                "backup format v{} is newer than this build supports (v{FORMAT_VERSION})",
+                self.manifest.format_version
+            );
+        }
+        if self.manifest.schema_version > SCHEMA_VERSION {
+            bail!(
+                "backup was taken at schema v{}, newer than this build supports (v{SCHEMA_VERSION})",
+                self.manifest.schema_version
+            );
+        }
+        let actual = self.snapshot.digests(self.manifest.format_version)?;
+        if actual.len() != self.manifest.sections.len() {
+            bail!(
+                "backup manifest lists {} sections, expected {}",
+                self.manifest.sections.len(),
+                actual.len()
+            );
+        }
+        for (expected, actual) in self.manifest.sections.iter().zip(&actual) {
+            if expected != actual {
+                bail!(
+                    "backup section {} does not match its manifest entry (expected {}, got {})",
+                    expected.name,
+                    expected.cid,
+                    actual.cid
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let bytes = serde_json::to_vec(self).context("serialize backup failed")?;
+        std::fs::write(path.as_ref(), bytes)
+            .with_context(|| format!("write backup {} failed", path.as_ref().display()))
+    }
+
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref())
+            .with_context(|| format!("read backup {} failed", path.as_ref().display()))?;
+        serde_json::from_slice(&bytes).context("deserialize backup failed")
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use aurea_core::{CanonProfile, Receipt, cid_of, to_nrf_bytes};
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+use serde_json::Value;
+
+use crate::RECEIPTS;
+
+/// Canonical JSON of work payloads and plugin results, keyed by CID.
+pub(crate) const BLOBS: TableDefinition<&str, &[u8]> = TableDefinition::new("blobs");
+/// One `(blob cid, receipt cid)` entry per reference; a blob is deleted with
+/// its last reference.
+pub(crate) const BLOB_REFS: TableDefinition<(&str, &str), ()> = TableDefinition::new("blob_refs");
+
+/// The canonical bytes of `value` and the CID they hash to, which is the same
+/// CID `cid_for` gives the value.
+pub(crate) fn encode(value: &Value) -> Result<(String, Vec<u8>)> {
+    let bytes =
+        to_nrf_bytes(value.clone(), CanonProfile::default()).context("canonicalize blob failed")?;
+    Ok((cid_of(&bytes), bytes))
+}
+
+/// Blobs a receipt points at.
+pub(crate) fn referenced(receipt: &Receipt) -> impl Iterator<Item = &str> {
+    [&receipt.input_cid, &receipt.result_cid]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+}
+
+pub(crate) fn put(write: &WriteTransaction, value: &Value) -> Result<String> {
+    let (cid, bytes) = encode(value)?;
+    let mut blobs = write.open_table(BLOBS).context("open blobs failed")?;
+    if blobs
+        .get(cid.as_str())
+        .context("read blob failed")?
+        .is_none()
+    {
+        blobs
+            .insert(cid.as_str(), bytes.as_slice())
+            .context("insert blob failed")?;
+    }
+    Ok(cid)
+}
+
+pub(crate) fn add_refs(write: &WriteTransaction, receipt: &Receipt) -> Result<()> {
+    let mut refs = write
+        .open_table(BLOB_REFS)
+        .context("open blob_refs failed")?;
+    for blob in referenced(receipt) {
+        refs.insert((blob, receipt.cid.as_str()), ())
+            .context("insert blob ref failed")?;
+    }
+    Ok(())
+}
+
+/// Drops the receipt's references and every blob left without one. Returns
+/// the number of blobs deleted.
+pub(crate) fn release(write: &WriteTransaction, receipt: &Receipt) -> Result<usize> {
+    let mut refs = write
+        .open_table(BLOB_REFS)
+        .context("open blob_refs failed")?;
+    let mut blobs = write.open_table(BLOBS).context("open blobs failed")?;
+    let mut deleted = 0;
+    for blob in referenced(receipt) {
+        refs.remove((blob, receipt.cid.as_str()))
+            .context("remove blob ref failed")?;
+        let still_used = refs
+            .range((blob, "")..)
+            .context("scan blob_refs failed")?
+            .next()
+            .transpose()
+            .context("read blob ref failed")?
+            .is_some_and(|(key, _)| key.value().0 == blob);
+        if !still_used && blobs.remove(blob).context("remove blob failed")?.is_some() {
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Rewrites the reference table from the receipts table.
+pub(crate) fn rebuild_refs(write: &WriteTransaction) -> Result<()> {
+    let mut expected = BTreeSet::new();
+    {
+        let receipts = write.open_table(RECEIPTS).context("open receipts failed")?;
+        for row in receipts.iter().context("iterate receipts failed")? {
+            let (_, value) = row.context("read receipt row failed")?;
+            let receipt: Receipt =
+                serde_json::from_slice(value.value()).context("deserialize receipt failed")?;
+            for blob in referenced(&receipt) {
+                expected.insert((blob.to_string(), receipt.cid.clone()));
+            }
+        }
+    }
+    let mut refs = write
+        .open_table(BLOB_REFS)
+        .context("open blob_refs failed")?;
+    refs.retain(|_, _| false)
+        .context("clear blob_refs failed")?;
+    for (blob, receipt) in &expected {
+        refs.insert((blob.as_str(), receipt.as_str()), ())
+            .context("insert blob ref failed")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use anyhow::{Context, Result};
+use aurea_core::Receipt;
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+
+use crate::{
+    DEAD_LETTERS, DeadLetter, LEASED_JOBS, QueuedJob, READY_JOBS, RECEIPTS, SCHEDULED_JOBS,
+};
+
+/// `(gauge, tenant, topic)` → rows currently held. Entries that reach zero
+/// are removed, so the table only grows with the tenants and topics in use.
+pub(crate) const COUNTS: TableDefinition<(&str, &str, &str), u64> = TableDefinition::new("counts");
+
+/// `(gauge, tenant, topic)` → count, as stored or as tallied from the rows.
+pub(crate) type Tally = BTreeMap<(&'static str, String, String), u64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Gauge {
+    Ready,
+    Scheduled,
+    Leased,
+    DeadLetters,
+    Receipts,
+}
+
+impl Gauge {
+    const ALL: [Gauge; 5] = [
+        Gauge::Ready,
+        Gauge::Scheduled,
+        Gauge::Leased,
+        Gauge::DeadLetters,
+        Gauge::Receipts,
+    ];
+
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Gauge::Ready => "ready",
+            Gauge::Scheduled => "scheduled",
+            Gauge::Leased => "leased",
+            Gauge::DeadLetters => "dead_letters",
+            Gauge::Receipts => "receipts",
+        }
+    }
+
+    fn parse(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|gauge| gauge.key() == key)
+    }
+}
+
+/// Jobs, dead letters and receipts held for one tenant and topic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueCounts {
+    pub ready: u64,
+    pub scheduled: u64,
+    pub leased: u64,
+    pub dead_letters: u64,
+    pub receipts: u64,
+}
+
+impl QueueCounts {
+    pub(crate) fn add(&mut self, gauge: Gauge, n: u64) {
+        let slot = match gauge {
+            Gauge::Ready => &mut self.ready,
+            Gauge::Scheduled => &mut self.scheduled,
+            Gauge::Leased => &mut self.leased,
+            Gauge::DeadLetters => &mut self.dead_letters,
+            Gauge::Receipts => &mut self.receipts,
+        };
+        *slot += n;
+    }
+}
+
+impl AddAssign for QueueCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.ready += other.ready;
+        self.scheduled += other.scheduled;
+        self.leased += other.leased;
+        self.dead_letters += other.dead_letters;
+        self.receipts += other.receipts;
+    }
+}
+
+pub(crate) fn adjust(
+    write: &WriteTransaction,
+    gauge: Gauge,
+    tenant: &str,
+    topic: &str,
+    delta: i64,
+) -> Result<()> {
+    let mut counts = write.open_table(COUNTS).context("open counts failed")?;
+    let key = (gauge.key(), tenant, topic);
+    let current = counts
+        .get(key)
+        .context("read count failed")?
+        .map_or(0, |v| v.value());
+    let next = current.saturating_add_signed(delta);
+    if next == 0 {
+        counts.remove(key).context("remove count failed")?;
+    } else {
+        counts.insert(key, next).context("write count failed")?;
+    }
+    Ok(())
+}
+
+pub(crate) fn stored(
+    table: &impl ReadableTable<(&'static str, &'static str, &'static str), u64>,
+) -> Result<Tally> {
+    let mut out = Tally::new();
+    for entry in table.iter().context("iterate counts failed")? {
+        let (key, value) = entry.context("read count failed")?;
+        let (gauge, tenant, topic) = key.value();
+        let Some(gauge) = Gauge::parse(gauge) else {
+            continue;
+        };
+        out.insert(
+            (gauge.key(), tenant.to_string(), topic.to_string()),
+            value.value(),
+        );
+    }
+    Ok(out)
+}
+
+/// Folds a tally into per-tenant, per-topic counts.
+pub(crate) fn breakdown(tally: &Tally) -> BTreeMap<(String, String), QueueCounts> {
+    let mut out = BTreeMap::<(String, String), QueueCounts>::new();
+    for ((gauge, tenant, topic), n) in tally {
+        if let Some(gauge) = Gauge::parse(gauge) {
+            out.entry((tenant.clone(), topic.clone()))
+                .or_default()
+                .add(gauge, *n);
+        }
+    }
+    out
+}
+
+pub(crate) fn tally_job(tally: &mut Tally, gauge: Gauge, job: &QueuedJob) {
+    *tally
+        .entry((gauge.key(), job.work.tenant.clone(), job.work.topic.clone()))
+        .or_default() += 1;
+}
+
+pub(crate) fn tally_receipt(tally: &mut Tally, receipt: &Receipt) {
+    *tally
+        .entry((
+            Gauge::Receipts.key(),
+            receipt.tenant.clone(),
+            receipt.topic.clone(),
+        ))
+        .or_default() += 1;
+}
+
+/// Rebuilds the counts table by scanning every job, dead letter and receipt.
+pub(crate) fn recount(write: &WriteTransaction) -> Result<()> {
+    let mut tally = Tally::new();
+    for (gauge, definition) in [
+        (Gauge::Ready, READY_JOBS),
+        (Gauge::Scheduled, SCHEDULED_JOBS),
+        (Gauge::Leased, LEASED_JOBS),
+    ] {
+        let table = write
+            .open_table(definition)
+            .with_context(|| format!("open {definition} failed"))?;
+        for entry in table.iter().context("iterate jobs failed")? {
+            let (_, value) = entry.context("read job failed")?;
+            let job: QueuedJob =
+                serde_json::from_slice(value.value()).context("deserialize queued job failed")?;
+            tally_job(&mut tally, gauge, &job);
+        }
+    }
+    {
+        let table = write
+            .open_table(DEAD_LETTERS)
+            .context("open dead_letters failed")?;
+        for entry in table.iter().context("iterate dead letters failed")? {
+            let (_, value) = entry.context("read dead letter failed")?;
+            let letter: DeadLetter =
+                serde_json::from_slice(value.value()).context("deserialize dead letter failed")?;
+            tally_job(&mut tally, Gauge::DeadLetters, &letter.job);
+        }
+    }
+    {
+        let table = write.open_table(RECEIPTS).context("open receipts failed")?;
+        for entry in table.iter().context("iterate receipts failed")? {
+            let (_, value) = entry.context("read receipt failed")?;
+            let receipt: Receipt =
+                serde_json::from_slice(value.value()).context("deserialize receipt failed")?;
+            tally_receipt(&mut tally, &receipt);
+        }
+    }
+
+    let mut counts = write.open_table(COUNTS).context("open counts failed")?;
+    counts.retain(|_, _| false).context("clear counts failed")?;
+    for ((gauge, tenant, topic), n) in &tally {
+        counts
+            .insert((*gauge, tenant.as_str(), topic.as_str()), *n)
+            .context("write count failed")?;
+    }
+    Ok(())
+}
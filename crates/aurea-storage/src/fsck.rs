@@ -0,0 +1,746 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{Context, Result};
+use aurea_core::{Receipt, StreamEvent, WorkStatus, cid_of};
+use redb::{
+    ReadTransaction, ReadableTable, ReadableTableMetadata, TableDefinition, TableHandle,
+    WriteTransaction,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::artifacts::{self, ARTIFACT_REFS};
+use crate::blobs::{self, BLOB_REFS, BLOBS};
+use crate::counts::{self, COUNTS, Gauge, Tally};
+use crate::{
+    DEAD_LETTERS, DeadLetter, EVENT_LOG, IDEM_KEYS, IdemRecord, LEASED_JOBS, META,
+    META_NEXT_EVENT_SEQ, META_NEXT_SEQ, QueuedJob, READY_INDEX, READY_JOBS, READY_QUEUES,
+    RECEIPT_INDEX, RECEIPTS, RedbStore, SCHEDULED_INDEX, SCHEDULED_JOBS, WORK_EVENTS,
+    idem_lookup_key, migrations, rebuild_ready_index, receipt_index, scheduled_due_ms,
+    status_meta_key,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckIssue {
+    UnreadableRow,
+    KeyMismatch,
+    DuplicateJob,
+    ReadyIndexDrift,
+    ScheduledIndexDrift,
+    ReceiptIndexDrift,
+    ReceiptCidMismatch,
+    ReceiptSignatureInvalid,
+    OrphanedIdemKey,
+    BlobCidMismatch,
+    MissingBlob,
+    OrphanedBlob,
+    BlobRefDrift,
+    ArtifactRefDrift,
+    CounterDrift,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckProblem {
+    pub issue: FsckIssue,
+    pub table: String,
+    pub key: String,
+    pub detail: String,
+    pub repairable: bool,
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    pub schema_version: u64,
+    pub rows: BTreeMap<String, u64>,
+    pub problems: Vec<FsckProblem>,
+}
+
+impl FsckReport {
+    /// True when every problem found, if any, has been repaired.
+    pub fn is_clean(&self) -> bool {
+        self.problems.iter().all(|p| p.repaired)
+    }
+}
+
+/// The safe fixes: derived tables and counters are rebuilt from the rows they
+/// describe, and idem keys and blobs that nothing points at are dropped. Jobs
+/// and receipts themselves are never rewritten.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Repair {
+    RebuildReadyIndex,
+    RebuildScheduledIndex,
+    RebuildReceiptIndex,
+    RemoveIdemKey(String),
+    RebuildBlobRefs,
+    RemoveBlob(String),
+    RebuildArtifactRefs,
+    SetCounter(&'static str, u64),
+    Recount,
+}
+
+#[derive(Default)]
+struct Findings {
+    report: FsckReport,
+    repairs: BTreeSet<Repair>,
+}
+
+impl Findings {
+    fn problem(&mut self, issue: FsckIssue, table: &str, key: String, detail: String) {
+        self.report.problems.push(FsckProblem {
+            issue,
+            table: table.to_string(),
+            key,
+            detail,
+            repairable: false,
+            repaired: false,
+        });
+    }
+
+    fn repairable(
+        &mut self,
+        issue: FsckIssue,
+        table: &str,
+        key: String,
+        detail: String,
+        repair: Repair,
+    ) {
+        self.report.problems.push(FsckProblem {
+            issue,
+            table: table.to_string(),
+            key,
+            detail,
+            repairable: true,
+            repaired: false,
+        });
+        self.repairs.insert(repair);
+    }
+}
+
+impl RedbStore {
+    /// Walks every table and cross-checks jobs, indexes, receipts, idem keys
+    /// and counters. With `repair`, the safe cases are fixed in a single
+    /// transaction and marked as repaired in the report.
+    pub fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        let mut findings = Findings::default();
+        findings.report.schema_version = self.schema_version()?;
+        {
+            let read = self.db.begin_read().context("begin fsck tx failed")?;
+            self.check(&read, &mut findings)?;
+        }
+
+        if repair && !findings.repairs.is_empty() {
+            let write = self.db.begin_write().context("begin repair tx failed")?;
+            for fix in &findings.repairs {
+                self.apply(&write, fix)?;
+            }
+            write.commit().context("commit repair tx failed")?;
+            for problem in &mut findings.report.problems {
+                problem.repaired = problem.repairable;
+            }
+        }
+        Ok(findings.report)
+    }
+
+    fn check(&self, read: &ReadTransaction, findings: &mut Findings) -> Result<()> {
+        let ready = load_by_seq::<QueuedJob>(read, READY_JOBS, findings)?;
+        let scheduled = load_by_seq::<QueuedJob>(read, SCHEDULED_JOBS, findings)?;
+        let leased = load_by_seq::<QueuedJob>(read, LEASED_JOBS, findings)?;
+        let dead = load_by_seq::<DeadLetter>(read, DEAD_LETTERS, findings)?;
+        let receipts = load_by_key::<Receipt>(read, RECEIPTS, findings)?;
+        let idem = load_by_key::<IdemRecord>(read, IDEM_KEYS, findings)?;
+
+        let jobs = [
+            ("ready_jobs", ready.iter().collect::<Vec<_>>()),
+            ("scheduled_jobs", scheduled.iter().collect()),
+            ("leased_jobs", leased.iter().collect()),
+            (
+                "dead_letters",
+                dead.iter().map(|(k, l)| (k, &l.job)).collect(),
+            ),
+        ];
+        let mut by_work = BTreeMap::<Uuid, Vec<String>>::new();
+        let mut max_seq = 0;
+        for (table, rows) in &jobs {
+            for (key, job) in rows {
+                if job.seq != **key {
+                    findings.problem(
+                        FsckIssue::KeyMismatch,
+                        table,
+                        key.to_string(),
+                        format!("row holds job seq {}", job.seq),
+                    );
+                }
+                max_seq = max_seq.max(**key).max(job.seq);
+                by_work
+                    .entry(job.work.id)
+                    .or_default()
+                    .push(format!("{table}#{key}"));
+            }
+        }
+        for (work_id, places) in &by_work {
+            if places.len() > 1 {
+                findings.problem(
+                    FsckIssue::DuplicateJob,
+                    "jobs",
+                    work_id.to_string(),
+                    format!("work is stored as {}", places.join(", ")),
+                );
+            }
+        }
+
+        self.check_ready_index(read, &ready, findings)?;
+        check_scheduled_index(read, &scheduled, findings)?;
+        check_receipts(read, &receipts, findings)?;
+        check_blobs(read, &receipts, findings)?;
+        check_artifact_refs(read, &receipts, findings)?;
+
+        for (key, record) in &idem {
+            let expected = idem_lookup_key(&record.tenant, &record.topic, &record.idem_key);
+            if *key != expected {
+                findings.problem(
+                    FsckIssue::KeyMismatch,
+                    "idem_keys",
+                    key.clone(),
+                    format!("record belongs under {expected:?}"),
+                );
+            }
+            let detail = match &record.receipt_cid {
+                Some(cid) if !receipts.contains_key(cid) => {
+                    format!("points at missing receipt {cid}")
+                }
+                None if !by_work.contains_key(&record.work_id) => {
+                    format!("work {} has no job and no receipt", record.work_id)
+                }
+                _ => continue,
+            };
+            findings.repairable(
+                FsckIssue::OrphanedIdemKey,
+                "idem_keys",
+                key.clone(),
+                detail,
+                Repair::RemoveIdemKey(key.clone()),
+            );
+        }
+
+        let meta = read.open_table(META).context("open meta failed")?;
+        let counter = |key: &str| -> Result<u64> {
+            Ok(meta
+                .get(key)
+                .context("read meta key failed")?
+                .map_or(0, |v| v.value()))
+        };
+
+        let next_seq = counter(META_NEXT_SEQ)?.max(1);
+        if max_seq >= next_seq {
+            findings.repairable(
+                FsckIssue::CounterDrift,
+                "meta",
+                META_NEXT_SEQ.to_string(),
+                format!("next seq {next_seq} is not past the highest job seq {max_seq}"),
+                Repair::SetCounter(META_NEXT_SEQ, max_seq + 1),
+            );
+        }
+
+        let max_event_seq = max_event_seq(read, findings)?;
+        let next_event_seq = counter(META_NEXT_EVENT_SEQ)?.max(1);
+        if max_event_seq >= next_event_seq {
+            findings.repairable(
+                FsckIssue::CounterDrift,
+                "meta",
+                META_NEXT_EVENT_SEQ.to_string(),
+                format!(
+                    "next event seq {next_event_seq} is not past the highest event seq {max_event_seq}"
+                ),
+                Repair::SetCounter(META_NEXT_EVENT_SEQ, max_event_seq + 1),
+            );
+        }
+
+        let mut tally = Tally::new();
+        for (gauge, rows) in [
+            (Gauge::Ready, &ready),
+            (Gauge::Scheduled, &scheduled),
+            (Gauge::Leased, &leased),
+        ] {
+            for job in rows.values() {
+                counts::tally_job(&mut tally, gauge, job);
+            }
+        }
+        for letter in dead.values() {
+            counts::tally_job(&mut tally, Gauge::DeadLetters, &letter.job);
+        }
+        for receipt in receipts.values() {
+            counts::tally_receipt(&mut tally, receipt);
+        }
+        let stored = counts::stored(&read.open_table(COUNTS).context("open counts failed")?)?;
+        let keys = tally.keys().chain(stored.keys()).collect::<BTreeSet<_>>();
+        for key in keys {
+            let expected = tally.get(key).copied().unwrap_or(0);
+            let actual = stored.get(key).copied().unwrap_or(0);
+            if expected != actual {
+                let (gauge, tenant, topic) = key;
+                findings.repairable(
+                    FsckIssue::CounterDrift,
+                    "counts",
+                    format!("{gauge}/{tenant}/{topic}"),
+                    format!("stored {actual}, tables hold {expected}"),
+                    Repair::Recount,
+                );
+            }
+        }
+
+        // Status totals only ever grow while retention removes receipts, so a
+        // total below the receipts still stored is the only detectable drift.
+        let mut by_status = BTreeMap::<&'static str, u64>::new();
+        for receipt in receipts.values() {
+            *by_status
+                .entry(status_meta_key(receipt.status))
+                .or_default() += 1;
+        }
+        for status in [WorkStatus::Done, WorkStatus::Fail, WorkStatus::Cancelled] {
+            let key = status_meta_key(status);
+            let stored = by_status.get(key).copied().unwrap_or(0);
+            let total = counter(key)?;
+            if total < stored {
+                findings.repairable(
+                    FsckIssue::CounterDrift,
+                    "meta",
+                    key.to_string(),
+                    format!("total {total} is below the {stored} receipts stored"),
+                    Repair::SetCounter(key, stored),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn check_ready_index(
+        &self,
+        read: &ReadTransaction,
+        ready: &BTreeMap<u64, QueuedJob>,
+        findings: &mut Findings,
+    ) -> Result<()> {
+        let partition = self.config.scheduler.partition;
+        let mut expected = BTreeSet::new();
+        let mut depths = BTreeMap::<String, u64>::new();
+        for job in ready.values() {
+            let queue = partition.queue_key(&job.work);
+            expected.insert((queue.clone(), job.work.priority.rank(), job.seq));
+            *depths.entry(queue).or_default() += 1;
+        }
+
+        let index = read
+            .open_table(READY_INDEX)
+            .context("open ready_index failed")?;
+        let mut actual = BTreeSet::new();
+        for entry in index.iter().context("iterate ready_index failed")? {
+            let (key, _) = entry.context("read ready_index entry failed")?;
+            let (queue, rank, seq) = key.value();
+            actual.insert((queue.to_string(), rank, seq));
+        }
+        let queues = read
+            .open_table(READY_QUEUES)
+            .context("open ready_queues failed")?;
+        let mut actual_depths = BTreeMap::new();
+        for entry in queues.iter().context("iterate ready_queues failed")? {
+            let (key, depth) = entry.context("read ready_queues entry failed")?;
+            actual_depths.insert(key.value().to_string(), depth.value());
+        }
+
+        let missing = expected.difference(&actual).count();
+        let dangling = actual.difference(&expected).count();
+        if missing > 0 || dangling > 0 || depths != actual_depths {
+            findings.repairable(
+                FsckIssue::ReadyIndexDrift,
+                "ready_index",
+                String::new(),
+                format!(
+                    "{missing} ready jobs unindexed, {dangling} dangling entries, queue depths {}",
+                    if depths == actual_depths {
+                        "match"
+                    } else {
+                        "differ"
+                    }
+                ),
+                Repair::RebuildReadyIndex,
+            );
+        }
+        Ok(())
+    }
+
+    fn apply(&self, write: &WriteTransaction, repair: &Repair) -> Result<()> {
+        match repair {
+            Repair::RebuildReadyIndex => {
+                rebuild_ready_index(write, self.config.scheduler.partition)
+            }
+            Repair::RebuildScheduledIndex => rebuild_scheduled_index(write),
+            Repair::RebuildReceiptIndex => migrations::index_receipts(write),
+            Repair::RebuildBlobRefs => blobs::rebuild_refs(write),
+            Repair::RemoveBlob(cid) => {
+                let mut blobs = write.open_table(BLOBS).context("open blobs failed")?;
+                blobs.remove(cid.as_str()).context("remove blob failed")?;
+                Ok(())
+            }
+            Repair::RebuildArtifactRefs => artifacts::rebuild_refs(write),
+            Repair::Recount => counts::recount(write),
+            Repair::RemoveIdemKey(key) => {
+                let mut idem = write
+                    .open_table(IDEM_KEYS)
+                    .context("open idem_keys failed")?;
+                idem.remove(key.as_str())
+                    .context("remove idem key failed")?;
+                Ok(())
+            }
+            Repair::SetCounter(key, value) => {
+                let mut meta = write.open_table(META).context("open meta failed")?;
+                meta.insert(*key, *value).context("write meta key failed")?;
+                Ok(())
+            }
+        }
+    }
+}
+
+fn check_scheduled_index(
+    read: &ReadTransaction,
+    scheduled: &BTreeMap<u64, QueuedJob>,
+    findings: &mut Findings,
+) -> Result<()> {
+    let expected = scheduled
+        .values()
+        .map(|job| (scheduled_due_ms(job), job.seq))
+        .collect::<BTreeSet<_>>();
+    let index = read
+        .open_table(SCHEDULED_INDEX)
+        .context("open scheduled_index failed")?;
+    let mut actual = BTreeSet::new();
+    for entry in index.iter().context("iterate scheduled_index failed")? {
+        let (key, _) = entry.context("read scheduled_index entry failed")?;
+        actual.insert(key.value());
+    }
+
+    let missing = expected.difference(&actual).count();
+    let dangling = actual.difference(&expected).count();
+    if missing > 0 || dangling > 0 {
+        findings.repairable(
+            FsckIssue::ScheduledIndexDrift,
+            "scheduled_index",
+            String::new(),
+            format!("{missing} scheduled jobs unindexed, {dangling} dangling entries"),
+            Repair::RebuildScheduledIndex,
+        );
+    }
+    Ok(())
+}
+
+fn check_receipts(
+    read: &ReadTransaction,
+    receipts: &BTreeMap<String, Receipt>,
+    findings: &mut Findings,
+) -> Result<()> {
+    let mut expected = BTreeSet::new();
+    for (key, receipt) in receipts {
+        if *key != receipt.cid {
+            findings.problem(
+                FsckIssue::KeyMismatch,
+                "receipts",
+                key.clone(),
+                format!("row holds receipt {}", receipt.cid),
+            );
+        }
+        match receipt.computed_cid() {
+            Ok(computed) if computed == receipt.cid => {
+                let verified = aurea_receipts::verify_receipt(receipt);
+                if !verified.ok {
+                    findings.problem(
+                        FsckIssue::ReceiptSignatureInvalid,
+                        "receipts",
+                        key.clone(),
+                        verified.reason.unwrap_or_default(),
+                    );
+                }
+            }
+            Ok(computed) => findings.problem(
+                FsckIssue::ReceiptCidMismatch,
+                "receipts",
+                key.clone(),
+                format!("content hashes to {computed}"),
+            ),
+            Err(err) => findings.problem(
+                FsckIssue::ReceiptCidMismatch,
+                "receipts",
+                key.clone(),
+                format!("cid cannot be recomputed: {err}"),
+            ),
+        }
+        let created_at_ms = receipt.created_at.timestamp_millis();
+        for dimension in receipt_index::dimensions(receipt) {
+            expected.insert((dimension, created_at_ms, receipt.cid.clone()));
+        }
+    }
+
+    let index = read
+        .open_table(RECEIPT_INDEX)
+        .context("open receipt_index failed")?;
+    let mut actual = BTreeSet::new();
+    for entry in index.iter().context("iterate receipt_index failed")? {
+        let (key, _) = entry.context("read receipt_index entry failed")?;
+        let (dimension, created_at_ms, cid) = key.value();
+        actual.insert((dimension.to_string(), created_at_ms, cid.to_string()));
+    }
+
+    let missing = expected.difference(&actual).count();
+    let dangling = actual.difference(&expected).count();
+    if missing > 0 || dangling > 0 {
+        findings.repairable(
+            FsckIssue::ReceiptIndexDrift,
+            "receipt_index",
+            String::new(),
+            format!("{missing} entries missing, {dangling} dangling entries"),
+            Repair::RebuildReceiptIndex,
+        );
+    }
+    Ok(())
+}
+
+fn check_blobs(
+    read: &ReadTransaction,
+    receipts: &BTreeMap<String, Receipt>,
+    findings: &mut Findings,
+) -> Result<()> {
+    let mut expected = BTreeSet::new();
+    for receipt in receipts.values() {
+        for blob in blobs::referenced(receipt) {
+            expected.insert((blob.to_string(), receipt.cid.clone()));
+        }
+    }
+    let referenced: HashSet<&str> = expected.iter().map(|(blob, _)| blob.as_str()).collect();
+
+    let table = read.open_table(BLOBS).context("open blobs failed")?;
+    findings.report.rows.insert(
+        "blobs".to_string(),
+        table.len().context("count blobs failed")?,
+    );
+    let mut stored = BTreeSet::new();
+    for entry in table.iter().context("iterate blobs failed")? {
+        let (key, value) = entry.context("read blob failed")?;
+        let cid = key.value().to_string();
+        let computed = cid_of(value.value());
+        if computed != cid {
+            findings.problem(
+                FsckIssue::BlobCidMismatch,
+                "blobs",
+                cid.clone(),
+                format!("content hashes to {computed}"),
+            );
+        }
+        if !referenced.contains(cid.as_str()) {
+            findings.repairable(
+                FsckIssue::OrphanedBlob,
+                "blobs",
+                cid.clone(),
+                "no receipt references it".to_string(),
+                Repair::RemoveBlob(cid.clone()),
+            );
+        }
+        stored.insert(cid);
+    }
+    for (blob, receipt) in &expected {
+        if !stored.contains(blob) {
+            findings.problem(
+                FsckIssue::MissingBlob,
+                "receipts",
+                receipt.clone(),
+                format!("references missing blob {blob}"),
+            );
+        }
+    }
+
+    let refs = read
+        .open_table(BLOB_REFS)
+        .context("open blob_refs failed")?;
+    let mut actual = BTreeSet::new();
+    for entry in refs.iter().context("iterate blob_refs failed")? {
+        let (key, _) = entry.context("read blob_refs entry failed")?;
+        let (blob, receipt) = key.value();
+        actual.insert((blob.to_string(), receipt.to_string()));
+    }
+    let missing = expected.difference(&actual).count();
+    let dangling = actual.difference(&expected).count();
+    if missing > 0 || dangling > 0 {
+        findings.repairable(
+            FsckIssue::BlobRefDrift,
+            "blob_refs",
+            String::new(),
+            format!("{missing} references missing, {dangling} dangling references"),
+            Repair::RebuildBlobRefs,
+        );
+    }
+    Ok(())
+}
+
+/// The artifact files themselves live outside the database, so only the
+/// reference table is checked against the receipts.
+fn check_artifact_refs(
+    read: &ReadTransaction,
+    receipts: &BTreeMap<String, Receipt>,
+    findings: &mut Findings,
+) -> Result<()> {
+    let mut expected = BTreeSet::new();
+    for receipt in receipts.values() {
+        for artifact in artifacts::referenced(receipt) {
+            expected.insert((artifact.to_string(), receipt.cid.clone()));
+        }
+    }
+    let refs = read
+        .open_table(ARTIFACT_REFS)
+        .context("open artifact_refs failed")?;
+    findings.report.rows.insert(
+        "artifact_refs".to_string(),
+        refs.len().context("count artifact_refs failed")?,
+    );
+    let mut actual = BTreeSet::new();
+    for entry in refs.iter().context("iterate artifact_refs failed")? {
+        let (key, _) = entry.context("read artifact_refs entry failed")?;
+        let (artifact, receipt) = key.value();
+        actual.insert((artifact.to_string(), receipt.to_string()));
+    }
+    let missing = expected.difference(&actual).count();
+    let dangling = actual.difference(&expected).count();
+    if missing > 0 || dangling > 0 {
+        findings.repairable(
+            FsckIssue::ArtifactRefDrift,
+            "artifact_refs",
+            String::new(),
+            format!("{missing} references missing, {dangling} dangling references"),
+            Repair::RebuildArtifactRefs,
+        );
+    }
+    Ok(())
+}
+
+fn max_event_seq(read: &ReadTransaction, findings: &mut Findings) -> Result<u64> {
+    let log = read
+        .open_table(EVENT_LOG)
+        .context("open event_log failed")?;
+    findings.report.rows.insert(
+        "event_log".to_string(),
+        log.len().context("count event_log failed")?,
+    );
+    let mut max = log
+        .last()
+        .context("read last event failed")?
+        .map_or(0, |(key, _)| key.value());
+
+    let history = read
+        .open_table(WORK_EVENTS)
+        .context("open work_events failed")?;
+    findings.report.rows.insert(
+        "work_events".to_string(),
+        history.len().context("count work_events failed")?,
+    );
+    for entry in history.iter().context("iterate work_events failed")? {
+        let (key, value) = entry.context("read work_events entry failed")?;
+        match serde_json::from_slice::<StreamEvent>(value.value()) {
+            Ok(event) => max = max.max(event.seq),
+            Err(err) => {
+                let (work_id, n) = key.value();
+                findings.problem(
+                    FsckIssue::UnreadableRow,
+                    "work_events",
+                    format!("{}/{n}", Uuid::from_u128(work_id)),
+                    err.to_string(),
+                );
+            }
+        }
+    }
+    Ok(max)
+}
+
+fn rebuild_scheduled_index(write: &WriteTransaction) -> Result<()> {
+    let mut keys = Vec::new();
+    {
+        let scheduled = write
+            .open_table(SCHEDULED_JOBS)
+            .context("open scheduled_jobs failed")?;
+        for entry in scheduled.iter().context("iterate scheduled jobs failed")? {
+            let (_, value) = entry.context("read scheduled job failed")?;
+            let job: QueuedJob = serde_json::from_slice(value.value())
+                .context("deserialize scheduled job failed")?;
+            keys.push((scheduled_due_ms(&job), job.seq));
+        }
+    }
+    let mut index = write
+        .open_table(SCHEDULED_INDEX)
+        .context("open scheduled_index failed")?;
+    index
+        .retain(|_, _| false)
+        .context("clear scheduled_index failed")?;
+    for key in keys {
+        index
+            .insert(key, ())
+            .context("insert scheduled index entry failed")?;
+    }
+    Ok(())
+}
+
+fn load_by_seq<T: DeserializeOwned>(
+    read: &ReadTransaction,
+    definition: TableDefinition<u64, &[u8]>,
+    findings: &mut Findings,
+) -> Result<BTreeMap<u64, T>> {
+    let table = read
+        .open_table(definition)
+        .with_context(|| format!("open {definition} failed"))?;
+    let mut rows = BTreeMap::new();
+    for entry in table.iter().context("iterate rows failed")? {
+        let (key, value) = entry.context("read row failed")?;
+        match serde_json::from_slice(value.value()) {
+            Ok(row) => {
+                rows.insert(key.value(), row);
+            }
+            Err(err) => findings.problem(
+                FsckIssue::UnreadableRow,
+                definition.name(),
+                key.value().to_string(),
+                err.to_string(),
+            ),
+        }
+    }
+    findings.report.rows.insert(
+        definition.name().to_string(),
+        table.len().context("count rows failed")?,
+    );
+    Ok(rows)
+}
+
+fn load_by_key<T: DeserializeOwned>(
+    read: &ReadTransaction,
+    definition: TableDefinition<&str, &[u8]>,
+    findings: &mut Findings,
+) -> Result<BTreeMap<String, T>> {
+    let table = read
+        .open_table(definition)
+        .with_context(|| format!("open {definition} failed"))?;
+    let mut rows = BTreeMap::new();
+    for entry in table.iter().context("iterate rows failed")? {
+        let (key, value) = entry.context("read row failed")?;
+        match serde_json::from_slice(value.value()) {
+            Ok(row) => {
+                rows.insert(key.value().to_string(), row);
+            }
+            Err(err) => findings.problem(
+                FsckIssue::UnreadableRow,
+                definition.name(),
+                key.value().to_string(),
+                err.to_string(),
+            ),
+        }
+    }
+    findings.report.rows.insert(
+        definition.name().to_string(),
+        table.len().context("count rows failed")?,
+    );
+    Ok(rows)
+}
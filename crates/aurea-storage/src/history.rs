@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use aurea_core::StreamEvent;
+use chrono::{DateTime, Utc};
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+use uuid::Uuid;
+
+use crate::WORK_EVENTS;
+
+/// Event seq → work id for every entry in [`WORK_EVENTS`], so age retention
+/// can walk the per-work history oldest first.
+pub(crate) const WORK_EVENT_SEQS: TableDefinition<u64, u128> =
+    TableDefinition::new("work_event_seqs");
+
+pub(crate) fn append(write: &WriteTransaction, event: &StreamEvent, bytes: &[u8]) -> Result<()> {
+    let id = event.work_id.as_u128();
+    write
+        .open_table(WORK_EVENTS)
+        .context("open work_events failed")?
+        .insert((id, event.seq), bytes)
+        .context("insert work event failed")?;
+    write
+        .open_table(WORK_EVENT_SEQS)
+        .context("open work_event_seqs failed")?
+        .insert(event.seq, id)
+        .context("insert work event seq failed")?;
+    Ok(())
+}
+
+/// Drops every history entry older than `cutoff`.
+pub(crate) fn trim_before(write: &WriteTransaction, cutoff: DateTime<Utc>) -> Result<u64> {
+    let mut events = write
+        .open_table(WORK_EVENTS)
+        .context("open work_events failed")?;
+    let mut seqs = write
+        .open_table(WORK_EVENT_SEQS)
+        .context("open work_event_seqs failed")?;
+    let mut trimmed = 0;
+    loop {
+        let Some((seq, id)) = seqs
+            .first()
+            .context("read first work event seq failed")?
+            .map(|(seq, id)| (seq.value(), id.value()))
+        else {
+            break;
+        };
+        let expired = match events.get((id, seq)).context("read work event failed")? {
+            Some(bytes) => {
+                let event: StreamEvent = serde_json::from_slice(bytes.value())
+                    .context("deserialize work event failed")?;
+                event.at < cutoff
+            }
+            None => true,
+        };
+        if !expired {
+            break;
+        }
+        if events
+            .remove((id, seq))
+            .context("remove work event failed")?
+            .is_some()
+        {
+            trimmed += 1;
+        }
+        seqs.remove(seq).context("remove work event seq failed")?;
+    }
+    Ok(trimmed)
+}
+
+/// Drops the whole history of `work_id`.
+pub(crate) fn release(write: &WriteTransaction, work_id: Uuid) -> Result<usize> {
+    let id = work_id.as_u128();
+    let mut events = write
+        .open_table(WORK_EVENTS)
+        .context("open work_events failed")?;
+    let doomed = events
+        .range((id, 0)..=(id, u64::MAX))
+        .context("range work events failed")?
+        .map(|row| row.map(|(key, _)| key.value().1))
+        .collect::<Result<Vec<_>, _>>()
+        .context("read work event failed")?;
+    let mut seqs = write
+        .open_table(WORK_EVENT_SEQS)
+        .context("open work_event_seqs failed")?;
+    for seq in &doomed {
+        events
+            .remove((id, *seq))
+            .context("remove work event failed")?;
+        seqs.remove(*seq).context("remove work event seq failed")?;
+    }
+    Ok(doomed.len())
+}
+
+/// Re-keys the history by event seq (it used to be a per-work ordinal) and
+/// rebuilds [`WORK_EVENT_SEQS`].
+pub(crate) fn rekey(write: &WriteTransaction) -> Result<()> {
+    let mut stored = Vec::new();
+    {
+        let mut events = write
+            .open_table(WORK_EVENTS)
+            .context("open work_events failed")?;
+        for row in events.iter().context("iterate work_events failed")? {
+            let (_, value) = row.context("read work event failed")?;
+            stored.push(value.value().to_vec());
+        }
+        events
+            .retain(|_, _| false)
+            .context("clear work_events failed")?;
+    }
+    write
+        .open_table(WORK_EVENT_SEQS)
+        .context("open work_event_seqs failed")?
+        .retain(|_, _| false)
+        .context("clear work_event_seqs failed")?;
+    for bytes in stored {
+        let event: StreamEvent =
+            serde_json::from_slice(&bytes).context("deserialize work event failed")?;
+        append(write, &event, &bytes)?;
+    }
+    Ok(())
+}
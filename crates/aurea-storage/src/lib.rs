@@ -1,22 +1,81 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result, anyhow};
-use aurea_core::{Receipt, WorkStatus, WorkUnit};
+use aurea_core::{AttemptRecord, Clock, HybridClock, Receipt, StreamEvent, WorkStatus, WorkUnit};
 use chrono::{DateTime, Duration, Utc};
-use redb::{Database, ReadOnlyTable, ReadableTable, Table, TableDefinition};
+use redb::{
+    Database, ReadOnlyTable, ReadTransaction, ReadableTable, ReadableTableMetadata, Table,
+    TableDefinition, WriteTransaction,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
+mod artifacts;
+mod backup;
+mod blobs;
+mod counts;
+mod fsck;
+mod history;
+mod memory;
+mod migrations;
+mod receipt_index;
+mod retry;
+mod scheduler;
+mod series;
+mod store;
+
+pub use backup::{BackupArchive, BackupManifest, SectionDigest, Snapshot};
+pub use counts::QueueCounts;
+pub use fsck::{FsckIssue, FsckProblem, FsckReport};
+pub use memory::MemoryStore;
+pub use migrations::{MigrationPlan, MigrationStep, SCHEMA_VERSION};
+pub use receipt_index::{ReceiptCursor, ReceiptPage, ReceiptQuery};
+pub use retry::{Backoff, LEASE_EXPIRED_ERROR, RetryConfig, RetryOn, RetryPolicy};
+pub use scheduler::{QueuePartition, SchedulerConfig, TopicFilter, topic_family, topic_matches};
+pub use series::{
+    Histogram, LabelledMetrics, MetricLabels, MetricsConfig, OTHER_LABEL, label_value,
+};
+pub use store::Store;
+
+/// The CID a value is stored under as a blob; the same as `cid_for`.
+pub fn blob_cid(value: &Value) -> Result<String> {
+    Ok(blobs::encode(value)?.0)
+}
+
+use counts::{COUNTS, Gauge};
+use scheduler::{FairScheduler, queue_topic};
+use series::RedbSeries;
+
 const READY_JOBS: TableDefinition<u64, &[u8]> = TableDefinition::new("ready_jobs");
+const READY_INDEX: TableDefinition<(&str, u8, u64), ()> = TableDefinition::new("ready_index");
+const READY_QUEUES: TableDefinition<&str, u64> = TableDefinition::new("ready_queues");
+const SCHEDULED_JOBS: TableDefinition<u64, &[u8]> = TableDefinition::new("scheduled_jobs");
+const SCHEDULED_INDEX: TableDefinition<(i64, u64), ()> = TableDefinition::new("scheduled_index");
 const LEASED_JOBS: TableDefinition<u64, &[u8]> = TableDefinition::new("leased_jobs");
+/// Work id → seq of its row in [`LEASED_JOBS`].
+const LEASED_INDEX: TableDefinition<u128, u64> = TableDefinition::new("leased_index");
+const DEAD_LETTERS: TableDefinition<u64, &[u8]> = TableDefinition::new("dead_letters");
 const RECEIPTS: TableDefinition<&str, &[u8]> = TableDefinition::new("receipts");
 const IDEM_KEYS: TableDefinition<&str, &[u8]> = TableDefinition::new("idem_keys");
+/// Receipts by (dimension, created_at ms, cid); see `receipt_index`.
+const RECEIPT_INDEX: TableDefinition<(&str, i64, &str), ()> = TableDefinition::new("receipt_index");
 const META: TableDefinition<&str, u64> = TableDefinition::new("meta");
+/// Append-only log of every lifecycle event, keyed by event seq; trimmed by
+/// [`EventRetention`].
+const EVENT_LOG: TableDefinition<u64, &[u8]> = TableDefinition::new("event_log");
+/// Lifecycle events per work unit, keyed by (work id, event seq); trimmed
+/// with the receipt and by the age limit of [`EventRetention`].
+const WORK_EVENTS: TableDefinition<(u128, u64), &[u8]> = TableDefinition::new("work_events");
 
 const META_NEXT_SEQ: &str = "next_job_seq";
+const META_NEXT_EVENT_SEQ: &str = "next_event_seq";
+const META_READY_PARTITION: &str = "ready_partition";
 const META_REASSIGNS_TOTAL: &str = "reassigns_total";
+/// Latest clock reading any write recorded, in microseconds since the epoch.
+const META_CLOCK_HIGH_WATER: &str = "clock_high_water_us";
 const META_TTFT_SUM_MS: &str = "ttft_sum_ms";
 const META_TTFT_COUNT: &str = "ttft_count";
 const META_TTR_SUM_MS: &str = "ttr_sum_ms";
@@ -33,17 +92,80 @@ pub struct QueuedJob {
     pub accepted_at: DateTime<Utc>,
     pub leased_at: Option<DateTime<Utc>>,
     pub lease_expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<AttemptRecord>,
+}
+
+impl QueuedJob {
+    fn record_failure(&mut self, error: &str, now: DateTime<Utc>) {
+        if self
+            .failures
+            .last()
+            .is_some_and(|f| f.attempt == self.attempt)
+        {
+            return;
+        }
+        self.failures.push(AttemptRecord {
+            attempt: self.attempt,
+            started_at: self.leased_at,
+            failed_at: now,
+            error: error.to_string(),
+        });
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct IdemRecord {
-    tenant: String,
-    topic: String,
-    idem_key: String,
-    work_id: Uuid,
-    status: String,
-    receipt_cid: Option<String>,
-    updated_at: DateTime<Utc>,
+pub struct DeadLetter {
+    pub job: QueuedJob,
+    pub reason: String,
+    pub dead_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_cid: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FailureOutcome {
+    Retrying {
+        job: QueuedJob,
+        retry_at: DateTime<Utc>,
+    },
+    /// The job stays leased until the caller issues its final receipt and moves
+    /// it with [`Store::dead_letter`].
+    Exhausted { job: QueuedJob },
+    /// The attempt no longer holds the lease; its failure is not recorded.
+    LeaseLost,
+}
+
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    /// The job is still queued or scheduled; nothing has been removed yet, that
+    /// is [`Store::cancel_queued`]'s job once the receipt is signed.
+    Queued(QueuedJob),
+    /// The job is leased; whoever runs it has to stop and finish it.
+    Leased(QueuedJob),
+    NotFound,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReassignReport {
+    pub reassigned: u64,
+    /// Jobs scheduled for another attempt after their retry backoff, as they
+    /// were requeued; `retry_at` says when.
+    pub requeued: Vec<QueuedJob>,
+    pub exhausted: Vec<QueuedJob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdemRecord {
+    pub tenant: String,
+    pub topic: String,
+    pub idem_key: String,
+    pub work_id: Uuid,
+    pub status: String,
+    pub receipt_cid: Option<String>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -56,7 +178,9 @@ pub enum EnqueueResult {
 #[derive(Debug, Clone)]
 pub struct QueueMetrics {
     pub queue_depth: usize,
+    pub scheduled_depth: usize,
     pub leased_depth: usize,
+    pub dead_letter_depth: usize,
     pub reassigns_total: u64,
     pub receipts_total: usize,
     pub status_totals: BTreeMap<String, u64>,
@@ -66,33 +190,215 @@ pub struct QueueMetrics {
     pub ttr_count: u64,
     pub ttft_bucket_counts: Vec<(u64, u64)>,
     pub ttr_bucket_counts: Vec<(u64, u64)>,
+    /// Depths and receipt totals keyed by `(tenant, topic)`.
+    pub by_tenant_topic: BTreeMap<(String, String), QueueCounts>,
+    pub labelled: LabelledMetrics,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct RetentionPurgeReport {
     pub deleted_receipts: usize,
     pub deleted_idem_keys: usize,
+    /// Payload and result blobs no remaining receipt references.
+    pub deleted_blobs: usize,
+    /// Lifecycle history of the purged receipts' work.
+    pub deleted_work_events: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    pub scheduler: SchedulerConfig,
+    pub retry: RetryConfig,
+    pub events: EventRetention,
+    pub metrics: MetricsConfig,
+    /// Stamps accepts, leases, retries and receipts. Shared with the runtime
+    /// through [`Store::clock`] so one node has one clock.
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            scheduler: SchedulerConfig::default(),
+            retry: RetryConfig::default(),
+            events: EventRetention::default(),
+            metrics: MetricsConfig::default(),
+            clock: Arc::new(HybridClock::system()),
+        }
+    }
+}
+
+/// How much of the event log is kept for resuming streams. The age limit
+/// also trims the per-work history; work whose history is gone is still
+/// described from its job and receipt.
+#[derive(Debug, Clone, Copy)]
+pub struct EventRetention {
+    pub max_events: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+impl Default for EventRetention {
+    fn default() -> Self {
+        Self {
+            max_events: Some(100_000),
+            max_age: Some(Duration::days(7)),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct RedbStore {
     db: Arc<Database>,
+    config: Arc<StoreConfig>,
+    scheduler: Arc<Mutex<FairScheduler>>,
 }
 
 impl RedbStore {
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_config(path, StoreConfig::default())
+    }
+
+    /// Opens the database at `path`, creating it when missing and applying
+    /// any pending schema migrations.
+    pub fn open_with_config(path: impl AsRef<Path>, config: StoreConfig) -> Result<Self> {
         let path = path.as_ref();
         let db = if path.exists() {
-            Database::open(path).context("failed to open redb")?
+            let db = Database::open(path).context("failed to open redb")?;
+            migrations::migrate(&db)?;
+            db
         } else {
             Database::create(path).context("failed to create redb")?
         };
 
-        let this = Self { db: Arc::new(db) };
+        let this = Self {
+            db: Arc::new(db),
+            config: Arc::new(config),
+            scheduler: Arc::new(Mutex::new(FairScheduler::default())),
+        };
         this.init_tables()?;
+        this.ensure_ready_index()?;
+        this.restore_clock()?;
         Ok(this)
     }
 
+    /// The migrations opening `path` would apply, without changing it.
+    pub fn migration_plan(path: impl AsRef<Path>) -> Result<MigrationPlan> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(anyhow!("database {} does not exist", path.display()));
+        }
+        let db = Database::open(path).context("failed to open redb")?;
+        migrations::plan(&db)
+    }
+
+    pub fn schema_version(&self) -> Result<u64> {
+        migrations::schema_version(&self.db)
+    }
+
+    /// Rebuilds the per-tenant, per-topic counts behind [`Store::queue_metrics`]
+    /// from a full scan of the job, dead letter and receipt tables.
+    pub fn recount(&self) -> Result<()> {
+        let write = self.db.begin_write().context("begin recount tx failed")?;
+        counts::recount(&write)?;
+        write.commit().context("commit recount tx failed")?;
+        Ok(())
+    }
+
+    /// Reads the clock for a write and records the reading, so that after a
+    /// restart [`Self::restore_clock`] can keep new readings past it.
+    fn stamp(&self, write: &WriteTransaction) -> Result<DateTime<Utc>> {
+        let now = self.config.clock.now();
+        let micros = now.timestamp_micros().max(0) as u64;
+        let mut meta = write.open_table(META).context("open meta failed")?;
+        let high_water = meta
+            .get(META_CLOCK_HIGH_WATER)
+            .context("read clock high water failed")?
+            .map_or(0, |g| g.value());
+        if micros > high_water {
+            meta.insert(META_CLOCK_HIGH_WATER, micros)
+                .context("write clock high water failed")?;
+        }
+        Ok(now)
+    }
+
+    /// Stores a receipt, its blobs and index entries and settles the
+    /// idempotency record of its work, as part of `write`.
+    fn write_receipt(
+        &self,
+        write: &WriteTransaction,
+        receipt: &Receipt,
+        values: &[&Value],
+    ) -> Result<()> {
+        let now = self.stamp(write)?;
+        for value in values {
+            blobs::put(write, value)?;
+        }
+
+        {
+            let bytes = serde_json::to_vec(receipt).context("serialize receipt failed")?;
+            let mut table = write.open_table(RECEIPTS).context("open receipts failed")?;
+            let replaced = table
+                .insert(receipt.cid.as_str(), bytes.as_slice())
+                .context("insert receipt failed")?
+                .is_some();
+            let mut index = write
+                .open_table(RECEIPT_INDEX)
+                .context("open receipt_index failed")?;
+            index_receipt(&mut index, receipt)?;
+            blobs::add_refs(write, receipt)?;
+            artifacts::add_refs(write, receipt)?;
+            if !replaced {
+                counts::adjust(write, Gauge::Receipts, &receipt.tenant, &receipt.topic, 1)?;
+            }
+        }
+
+        {
+            let key = idem_lookup_key(&receipt.tenant, &receipt.topic, &receipt.idem_key);
+            let mut idem = write
+                .open_table(IDEM_KEYS)
+                .context("open idem_keys failed")?;
+            let existing = idem
+                .get(key.as_str())
+                .context("read idem record failed")?
+                .map(|v| serde_json::from_slice::<IdemRecord>(v.value()))
+                .transpose()
+                .context("deserialize idem record failed")?;
+
+            let mut record = existing.unwrap_or(IdemRecord {
+                tenant: receipt.tenant.clone(),
+                topic: receipt.topic.clone(),
+                idem_key: receipt.idem_key.clone(),
+                work_id: receipt.work_id,
+                status: "queued".to_string(),
+                receipt_cid: None,
+                updated_at: now,
+            });
+
+            record.status = status_label(receipt.status).to_string();
+            record.receipt_cid = Some(receipt.cid.clone());
+            record.updated_at = now;
+
+            let bytes = serde_json::to_vec(&record).context("serialize idem record failed")?;
+            idem.insert(key.as_str(), bytes.as_slice())
+                .context("upsert idem record failed")?;
+        }
+
+        Ok(())
+    }
+
+    fn restore_clock(&self) -> Result<()> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let meta = read.open_table(META).context("open meta failed")?;
+        let high_water = meta
+            .get(META_CLOCK_HIGH_WATER)
+            .context("read clock high water failed")?
+            .and_then(|g| DateTime::from_timestamp_micros(g.value() as i64));
+        if let Some(high_water) = high_water {
+            self.config.clock.observe(high_water);
+        }
+        Ok(())
+    }
+
     fn init_tables(&self) -> Result<()> {
         let write = self
             .db
@@ -101,23 +407,118 @@ impl RedbStore {
         write
             .open_table(READY_JOBS)
             .context("failed to open ready_jobs table")?;
+        write
+            .open_table(READY_INDEX)
+            .context("failed to open ready_index table")?;
+        write
+            .open_table(READY_QUEUES)
+            .context("failed to open ready_queues table")?;
+        write
+            .open_table(SCHEDULED_JOBS)
+            .context("failed to open scheduled_jobs table")?;
+        write
+            .open_table(SCHEDULED_INDEX)
+            .context("failed to open scheduled_index table")?;
         write
             .open_table(LEASED_JOBS)
             .context("failed to open leased_jobs table")?;
+        write
+            .open_table(LEASED_INDEX)
+            .context("failed to open leased_index table")?;
+        write
+            .open_table(DEAD_LETTERS)
+            .context("failed to open dead_letters table")?;
         write
             .open_table(RECEIPTS)
             .context("failed to open receipts table")?;
+        write
+            .open_table(RECEIPT_INDEX)
+            .context("failed to open receipt_index table")?;
         write
             .open_table(IDEM_KEYS)
             .context("failed to open idem_keys table")?;
         write
             .open_table(META)
             .context("failed to open meta table")?;
+        write
+            .open_table(COUNTS)
+            .context("failed to open counts table")?;
+        write
+            .open_table(series::SERIES)
+            .context("failed to open metric_series table")?;
+        write
+            .open_table(series::LABELS)
+            .context("failed to open metric_labels table")?;
+        write
+            .open_table(blobs::BLOBS)
+            .context("failed to open blobs table")?;
+        write
+            .open_table(blobs::BLOB_REFS)
+            .context("failed to open blob_refs table")?;
+        write
+            .open_table(artifacts::ARTIFACT_REFS)
+            .context("failed to open artifact_refs table")?;
+        write
+            .open_table(EVENT_LOG)
+            .context("failed to open event_log table")?;
+        write
+            .open_table(WORK_EVENTS)
+            .context("failed to open work_events table")?;
+        write
+            .open_table(history::WORK_EVENT_SEQS)
+            .context("failed to open work_event_seqs table")?;
+        migrations::stamp(&write, SCHEMA_VERSION)?;
         write.commit().context("failed to commit init tx")?;
         Ok(())
     }
 
-    pub fn enqueue_work_idempotent(&self, work: WorkUnit) -> Result<EnqueueResult> {
+    /// Rebuilds the per-queue index when it is missing (databases written before
+    /// topic partitioning) or when the configured partitioning changed.
+    fn ensure_ready_index(&self) -> Result<()> {
+        let partition = self.config.scheduler.partition;
+        let write = self
+            .db
+            .begin_write()
+            .context("begin ready index tx failed")?;
+
+        let up_to_date = {
+            let ready = write
+                .open_table(READY_JOBS)
+                .context("open ready_jobs failed")?;
+            let index = write
+                .open_table(READY_INDEX)
+                .context("open ready_index failed")?;
+            let meta = write.open_table(META).context("open meta failed")?;
+            let stored = meta
+                .get(META_READY_PARTITION)
+                .context("read ready partition failed")?
+                .map(|v| v.value());
+            let ready_len = ready.len().context("count ready jobs failed")?;
+            let index_len = index.len().context("count ready index failed")?;
+            ready_len == index_len
+                && (stored == Some(partition.meta_value()) || (stored.is_none() && ready_len == 0))
+        };
+
+        if !up_to_date {
+            rebuild_ready_index(&write, partition)?;
+        }
+
+        {
+            let mut meta = write.open_table(META).context("open meta failed")?;
+            meta.insert(META_READY_PARTITION, partition.meta_value())
+                .context("write ready partition failed")?;
+        }
+        write.commit().context("commit ready index tx failed")?;
+        Ok(())
+    }
+}
+
+impl Store for RedbStore {
+    fn clock(&self) -> Arc<dyn Clock> {
+        self.config.clock.clone()
+    }
+
+    fn enqueue_work_idempotent(&self, work: WorkUnit) -> Result<EnqueueResult> {
         let idem_key = work
             .idem_key
             .clone()
@@ -146,7 +547,8 @@ impl RedbStore {
             }
         };
 
-        if let Some(record) = existing {
+        // A cancelled job keeps its idem record (and receipt) but may be resubmitted.
+        if let Some(record) = existing.filter(|record| record.status != "cancelled") {
             write.commit().context("commit idem hit tx failed")?;
             if let Some(receipt_cid) = record.receipt_cid {
                 return Ok(EnqueueResult::DuplicateReceipt {
@@ -171,23 +573,24 @@ impl RedbStore {
             current
         };
 
+        let now = self.stamp(&write)?;
+        let mut work = work;
+        work.submitted_at = now;
         let job = QueuedJob {
             seq,
             work: work.clone(),
             attempt: 0,
-            accepted_at: Utc::now(),
+            accepted_at: now,
             leased_at: None,
             lease_expires_at: None,
+            retry_at: None,
+            failures: Vec::new(),
         };
-        let job_bytes = serde_json::to_vec(&job).context("serialize queued job failed")?;
 
-        {
-            let mut ready = write
-                .open_table(READY_JOBS)
-                .context("open ready_jobs failed")?;
-            ready
-                .insert(seq, job_bytes.as_slice())
-                .context("insert ready job failed")?;
+        if work.is_due(now) {
+            push_ready_job(&write, self.config.scheduler.partition, &job)?;
+        } else {
+            push_scheduled_job(&write, &job)?;
         }
 
         {
@@ -198,7 +601,7 @@ impl RedbStore {
                 work_id: work.id,
                 status: "queued".to_string(),
                 receipt_cid: None,
-                updated_at: Utc::now(),
+                updated_at: now,
             };
             let record_bytes =
                 serde_json::to_vec(&record).context("serialize idem record failed")?;
@@ -216,67 +619,87 @@ impl RedbStore {
         })
     }
 
-    pub fn lease_next(&self, lease_ttl_ms: u64) -> Result<Option<QueuedJob>> {
+    fn lease_next(&self, lease_ttl_ms: u64, filter: &TopicFilter) -> Result<Option<QueuedJob>> {
         let write = self.db.begin_write().context("begin lease tx failed")?;
-        let first = {
-            let ready = write
-                .open_table(READY_JOBS)
-                .context("open ready_jobs failed")?;
-            let mut iter = ready.iter().context("iterate ready jobs failed")?;
-            iter.next()
-                .transpose()
-                .context("read ready iterator entry failed")?
-                .map(|(key, value)| (key.value(), value.value().to_vec()))
+        let now = self.stamp(&write)?;
+        promote_due_jobs(&write, self.config.scheduler.partition, now)?;
+
+        let candidates = {
+            let queues = write
+                .open_table(READY_QUEUES)
+                .context("open ready_queues failed")?;
+            let mut out = Vec::new();
+            for entry in queues.iter().context("iterate ready queues failed")? {
+                let (key, depth) = entry.context("read ready queue entry failed")?;
+                let queue = key.value();
+                let topic = queue_topic(queue);
+                if depth.value() > 0 && filter.matches(topic) {
+                    out.push((queue.to_string(), self.config.scheduler.weight_for(topic)));
+                }
+            }
+            out
         };
-
-        let Some((seq, job_bytes)) = first else {
+        let candidates = highest_priority_queues(&write, candidates)?;
+
+        let picked = self
+            .scheduler
+            .lock()
+            .map_err(|_| anyhow!("scheduler lock poisoned"))?
+            .pick(&candidates);
+        let Some(queue) = picked else {
             write.commit().context("commit empty lease tx failed")?;
             return Ok(None);
         };
 
-        {
-            let mut ready = write
-                .open_table(READY_JOBS)
-                .context("open ready_jobs failed")?;
-            ready.remove(seq).context("remove ready job failed")?;
-        }
+        let Some(mut job) = pop_ready_job(&write, &queue)? else {
+            write.commit().context("commit empty lease tx failed")?;
+            return Ok(None);
+        };
 
-        let mut job: QueuedJob =
-            serde_json::from_slice(&job_bytes).context("deserialize ready queued job failed")?;
-        let now = Utc::now();
         job.attempt += 1;
         job.leased_at = Some(now);
         job.lease_expires_at = Some(now + Duration::milliseconds(lease_ttl_ms as i64));
-
-        let bytes = serde_json::to_vec(&job).context("serialize leased job failed")?;
-        {
-            let mut leased = write
-                .open_table(LEASED_JOBS)
-                .context("open leased_jobs failed")?;
-            leased
-                .insert(seq, bytes.as_slice())
-                .context("insert leased job failed")?;
-        }
+        put_leased_job(&write, &job)?;
 
         write.commit().context("commit lease tx failed")?;
         Ok(Some(job))
     }
 
-    pub fn complete_leased(&self, seq: u64) -> Result<()> {
+    fn heartbeat_lease(&self, seq: u64, attempt: u32, lease_ttl_ms: u64) -> Result<bool> {
+        let write = self.db.begin_write().context("begin heartbeat tx failed")?;
+        let Some(mut job) = held_lease(&write, seq, attempt)? else {
+            write.commit().context("commit heartbeat tx failed")?;
+            return Ok(false);
+        };
+
+        job.lease_expires_at =
+            Some(self.stamp(&write)? + Duration::milliseconds(lease_ttl_ms as i64));
+        put_leased_job(&write, &job)?;
+        write.commit().context("commit heartbeat tx failed")?;
+        Ok(true)
+    }
+
+    fn complete_leased(
+        &self,
+        seq: u64,
+        attempt: u32,
+        receipt: &Receipt,
+        values: &[&Value],
+    ) -> Result<bool> {
         let write = self.db.begin_write().context("begin complete tx failed")?;
-        {
-            let mut leased = write
-                .open_table(LEASED_JOBS)
-                .context("open leased_jobs failed")?;
-            leased.remove(seq).context("remove leased job failed")?;
+        if held_lease(&write, seq, attempt)?.is_none() {
+            write.commit().context("commit complete tx failed")?;
+            return Ok(false);
         }
+        take_leased_job(&write, seq)?;
+        self.write_receipt(&write, receipt, values)?;
         write.commit().context("commit complete tx failed")?;
-        Ok(())
+        Ok(true)
     }
 
-    pub fn reassign_expired_leases(&self) -> Result<u64> {
+    fn reassign_expired_leases(&self) -> Result<ReassignReport> {
         let write = self.db.begin_write().context("begin reassign tx failed")?;
-        let now = Utc::now();
+        let now = self.stamp(&write)?;
         let mut to_move = Vec::new();
 
         {
@@ -296,51 +719,165 @@ impl RedbStore {
 
         if to_move.is_empty() {
             write.commit().context("commit no-op reassign tx failed")?;
-            return Ok(0);
+            return Ok(ReassignReport::default());
         }
 
-        let reassigned = to_move.len() as u64;
-        {
-            let mut leased = write
-                .open_table(LEASED_JOBS)
-                .context("open leased_jobs failed")?;
-            let mut ready = write
-                .open_table(READY_JOBS)
-                .context("open ready_jobs failed")?;
-
-            for (seq, mut job) in to_move {
-                leased.remove(seq).context("remove expired lease failed")?;
-                job.leased_at = None;
-                job.lease_expires_at = None;
-                let bytes = serde_json::to_vec(&job).context("serialize reassigned job failed")?;
-                ready
-                    .insert(seq, bytes.as_slice())
-                    .context("reinsert ready job failed")?;
+        let mut report = ReassignReport::default();
+        for (seq, mut job) in to_move {
+            job.record_failure(LEASE_EXPIRED_ERROR, now);
+            let policy = self.config.retry.policy_for(&job.work.topic);
+            if !policy.should_retry(job.attempt, LEASE_EXPIRED_ERROR) {
+                let grace = job
+                    .leased_at
+                    .zip(job.lease_expires_at)
+                    .map(|(leased, expires)| expires - leased)
+                    .unwrap_or_else(|| Duration::seconds(15));
+                job.lease_expires_at = Some(now + grace);
+                put_leased_job(&write, &job)?;
+                report.exhausted.push(job);
+                continue;
             }
+
+            take_leased_job(&write, seq)?;
+            job.leased_at = None;
+            job.lease_expires_at = None;
+            job.retry_at =
+                Some(now + Duration::milliseconds(policy.backoff.delay_ms(job.attempt) as i64));
+            push_scheduled_job(&write, &job)?;
+            report.reassigned += 1;
+            report.requeued.push(job);
         }
 
-        {
+        if report.reassigned > 0 {
             let mut meta = write.open_table(META).context("open meta failed")?;
-            inc_counter(&mut meta, META_REASSIGNS_TOTAL, reassigned)?;
+            inc_counter(&mut meta, META_REASSIGNS_TOTAL, report.reassigned)?;
         }
 
         write.commit().context("commit reassign tx failed")?;
-        Ok(reassigned)
+        Ok(report)
     }
 
-    pub fn put_receipt(&self, receipt: &Receipt) -> Result<()> {
-        let write = self.db.begin_write().context("begin receipt tx failed")?;
+    fn fail_leased(&self, seq: u64, attempt: u32, error: &str) -> Result<FailureOutcome> {
+        let write = self.db.begin_write().context("begin fail tx failed")?;
+        let Some(mut job) = held_lease(&write, seq, attempt)? else {
+            write.commit().context("commit fail tx failed")?;
+            return Ok(FailureOutcome::LeaseLost);
+        };
+        let now = self.stamp(&write)?;
+        job.record_failure(error, now);
+
+        let policy = self.config.retry.policy_for(&job.work.topic);
+        if !policy.should_retry(job.attempt, error) {
+            put_leased_job(&write, &job)?;
+            write.commit().context("commit fail tx failed")?;
+            return Ok(FailureOutcome::Exhausted { job });
+        }
 
+        let retry_at = now + Duration::milliseconds(policy.backoff.delay_ms(job.attempt) as i64);
+        take_leased_job(&write, seq)?;
+        job.leased_at = None;
+        job.lease_expires_at = None;
+        job.retry_at = Some(retry_at);
+        push_scheduled_job(&write, &job)?;
+
+        write.commit().context("commit fail tx failed")?;
+        Ok(FailureOutcome::Retrying { job, retry_at })
+    }
+
+    fn dead_letter(
+        &self,
+        seq: u64,
+        attempt: u32,
+        reason: &str,
+        receipt: &Receipt,
+        values: &[&Value],
+    ) -> Result<bool> {
+        let write = self
+            .db
+            .begin_write()
+            .context("begin dead letter tx failed")?;
+        let Some(job) = held_lease(&write, seq, attempt)? else {
+            write.commit().context("commit dead letter tx failed")?;
+            return Ok(false);
+        };
+        take_leased_job(&write, seq)?;
+        count_job(&write, Gauge::DeadLetters, &job, 1)?;
+        self.write_receipt(&write, receipt, values)?;
+
+        let letter = DeadLetter {
+            job,
+            reason: reason.to_string(),
+            dead_at: self.stamp(&write)?,
+            receipt_cid: Some(receipt.cid.clone()),
+        };
+        let bytes = serde_json::to_vec(&letter).context("serialize dead letter failed")?;
         {
-            let bytes = serde_json::to_vec(receipt).context("serialize receipt failed")?;
-            let mut table = write.open_table(RECEIPTS).context("open receipts failed")?;
-            table
-                .insert(receipt.cid.as_str(), bytes.as_slice())
-                .context("insert receipt failed")?;
+            let mut dead = write
+                .open_table(DEAD_LETTERS)
+                .context("open dead_letters failed")?;
+            dead.insert(seq, bytes.as_slice())
+                .context("insert dead letter failed")?;
         }
 
-        {
-            let key = idem_lookup_key(&receipt.tenant, &receipt.topic, &receipt.idem_key);
+        write.commit().context("commit dead letter tx failed")?;
+        Ok(true)
+    }
+
+    fn list_dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read
+            .open_table(DEAD_LETTERS)
+            .context("open dead_letters failed")?;
+        let mut out = Vec::new();
+        for row in table.iter().context("iterate dead letters failed")? {
+            let (_, value) = row.context("read dead letter row failed")?;
+            let letter: DeadLetter =
+                serde_json::from_slice(value.value()).context("deserialize dead letter failed")?;
+            out.push(letter);
+        }
+        Ok(out)
+    }
+
+    fn get_dead_letter(&self, seq: u64) -> Result<Option<DeadLetter>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read
+            .open_table(DEAD_LETTERS)
+            .context("open dead_letters failed")?;
+        let Some(bytes) = table.get(seq).context("read dead letter failed")? else {
+            return Ok(None);
+        };
+        let letter: DeadLetter =
+            serde_json::from_slice(bytes.value()).context("deserialize dead letter failed")?;
+        Ok(Some(letter))
+    }
+
+    fn requeue_dead_letter(&self, seq: u64) -> Result<Option<QueuedJob>> {
+        let write = self.db.begin_write().context("begin requeue tx failed")?;
+        let letter = {
+            let mut dead = write
+                .open_table(DEAD_LETTERS)
+                .context("open dead_letters failed")?;
+            dead.remove(seq)
+                .context("remove dead letter failed")?
+                .map(|v| serde_json::from_slice::<DeadLetter>(v.value()))
+                .transpose()
+                .context("deserialize dead letter failed")?
+        };
+        let Some(letter) = letter else {
+            write.commit().context("commit requeue tx failed")?;
+            return Ok(None);
+        };
+
+        let mut job = letter.job;
+        count_job(&write, Gauge::DeadLetters, &job, -1)?;
+        job.attempt = 0;
+        job.leased_at = None;
+        job.lease_expires_at = None;
+        job.retry_at = None;
+        push_ready_job(&write, self.config.scheduler.partition, &job)?;
+
+        if let Some(idem_key) = job.work.idem_key.as_deref() {
+            let key = idem_lookup_key(&job.work.tenant, &job.work.topic, idem_key);
             let mut idem = write
                 .open_table(IDEM_KEYS)
                 .context("open idem_keys failed")?;
@@ -350,37 +887,261 @@ impl RedbStore {
                 .map(|v| serde_json::from_slice::<IdemRecord>(v.value()))
                 .transpose()
                 .context("deserialize idem record failed")?;
+            if let Some(mut record) = existing {
+                record.status = "queued".to_string();
+                record.receipt_cid = None;
+                record.updated_at = self.stamp(&write)?;
+                let bytes = serde_json::to_vec(&record).context("serialize idem record failed")?;
+                idem.insert(key.as_str(), bytes.as_slice())
+                    .context("upsert idem record failed")?;
+            }
+        }
 
-            let mut record = existing.unwrap_or(IdemRecord {
-                tenant: receipt.tenant.clone(),
-                topic: receipt.topic.clone(),
-                idem_key: receipt.idem_key.clone(),
-                work_id: receipt.work_id,
-                status: "queued".to_string(),
-                receipt_cid: None,
-                updated_at: Utc::now(),
-            });
+        write.commit().context("commit requeue tx failed")?;
+        Ok(Some(job))
+    }
 
-            record.status = match receipt.status {
-                WorkStatus::Done => "done".to_string(),
-                WorkStatus::Fail => "fail".to_string(),
-                WorkStatus::Accepted => "accepted".to_string(),
-                WorkStatus::Assigned => "assigned".to_string(),
-                WorkStatus::Progress => "progress".to_string(),
-            };
-            record.receipt_cid = Some(receipt.cid.clone());
-            record.updated_at = Utc::now();
+    fn purge_dead_letters(&self, seq: Option<u64>) -> Result<usize> {
+        let write = self.db.begin_write().context("begin purge tx failed")?;
+        let purged = match seq {
+            Some(seq) => {
+                let letter = {
+                    let mut dead = write
+                        .open_table(DEAD_LETTERS)
+                        .context("open dead_letters failed")?;
+                    dead.remove(seq)
+                        .context("remove dead letter failed")?
+                        .map(|v| serde_json::from_slice::<DeadLetter>(v.value()))
+                        .transpose()
+                        .context("deserialize dead letter failed")?
+                };
+                if let Some(letter) = &letter {
+                    count_job(&write, Gauge::DeadLetters, &letter.job, -1)?;
+                }
+                usize::from(letter.is_some())
+            }
+            None => {
+                let total = {
+                    let mut dead = write
+                        .open_table(DEAD_LETTERS)
+                        .context("open dead_letters failed")?;
+                    let total = dead.len().context("count dead letters failed")? as usize;
+                    dead.retain(|_, _| false)
+                        .context("clear dead letters failed")?;
+                    total
+                };
+                let mut counts = write.open_table(COUNTS).context("open counts failed")?;
+                counts
+                    .retain(|(gauge, _, _), _| gauge != Gauge::DeadLetters.key())
+                    .context("clear dead letter counts failed")?;
+                total
+            }
+        };
+        write.commit().context("commit purge tx failed")?;
+        Ok(purged)
+    }
 
-            let bytes = serde_json::to_vec(&record).context("serialize idem record failed")?;
-            idem.insert(key.as_str(), bytes.as_slice())
-                .context("upsert idem record failed")?;
+    fn cancel_work(&self, work_id: Uuid) -> Result<CancelOutcome> {
+        let write = self.db.begin_write().context("begin cancel tx failed")?;
+
+        let queued = match find_job(&write, READY_JOBS, work_id)? {
+            Some(job) => Some(job),
+            None => find_job(&write, SCHEDULED_JOBS, work_id)?,
+        };
+        if let Some(job) = queued {
+            write.commit().context("commit cancel tx failed")?;
+            return Ok(CancelOutcome::Queued(job));
         }
+        let leased = {
+            let index = write
+                .open_table(LEASED_INDEX)
+                .context("open leased_index failed")?;
+            let leased = write
+                .open_table(LEASED_JOBS)
+                .context("open leased_jobs failed")?;
+            find_leased_job(&index, &leased, work_id)?
+        };
+        write.commit().context("commit cancel tx failed")?;
+        Ok(leased.map_or(CancelOutcome::NotFound, CancelOutcome::Leased))
+    }
 
+    fn cancel_queued(&self, job: &QueuedJob, receipt: &Receipt, values: &[&Value]) -> Result<bool> {
+        let write = self.db.begin_write().context("begin cancel tx failed")?;
+        let same = |found: &QueuedJob| found.seq == job.seq && found.attempt == job.attempt;
+        if let Some(found) = find_job(&write, READY_JOBS, job.work.id)?.filter(same) {
+            remove_ready_job(&write, self.config.scheduler.partition, &found)?;
+        } else if let Some(found) = find_job(&write, SCHEDULED_JOBS, job.work.id)?.filter(same) {
+            remove_scheduled_job(&write, &found)?;
+        } else {
+            write.commit().context("commit cancel tx failed")?;
+            return Ok(false);
+        }
+        self.write_receipt(&write, receipt, values)?;
+        write.commit().context("commit cancel tx failed")?;
+        Ok(true)
+    }
+
+    fn put_receipt_with_blobs(&self, receipt: &Receipt, values: &[&Value]) -> Result<()> {
+        let write = self.db.begin_write().context("begin receipt tx failed")?;
+        self.write_receipt(&write, receipt, values)?;
         write.commit().context("commit receipt tx failed")?;
         Ok(())
     }
 
-    pub fn get_receipt(&self, cid: &str) -> Result<Option<Receipt>> {
+    fn append_work_event(&self, event: &mut StreamEvent) -> Result<()> {
+        let write = self.db.begin_write().context("begin event tx failed")?;
+        let at = self.stamp(&write)?;
+        {
+            let mut meta = write.open_table(META).context("open meta failed")?;
+            let seq = meta
+                .get(META_NEXT_EVENT_SEQ)
+                .context("read next event seq failed")?
+                .map(|g| g.value())
+                .unwrap_or(1);
+            meta.insert(META_NEXT_EVENT_SEQ, seq + 1)
+                .context("write next event seq failed")?;
+            event.seq = seq;
+            event.at = at;
+        }
+        let bytes = serde_json::to_vec(event).context("serialize work event failed")?;
+        {
+            let mut log = write
+                .open_table(EVENT_LOG)
+                .context("open event_log failed")?;
+            log.insert(event.seq, bytes.as_slice())
+                .context("insert event log entry failed")?;
+        }
+        history::append(&write, event, &bytes)?;
+        write.commit().context("commit event tx failed")?;
+        Ok(())
+    }
+
+    fn next_event_seq(&self) -> Result<u64> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let meta = read.open_table(META).context("open meta failed")?;
+        Ok(meta_get_read(&meta, META_NEXT_EVENT_SEQ)?.max(1))
+    }
+
+    fn events_after(&self, after: u64, limit: usize) -> Result<Vec<StreamEvent>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read
+            .open_table(EVENT_LOG)
+            .context("open event_log failed")?;
+        let mut out = Vec::new();
+        for row in table
+            .range(after.saturating_add(1)..)
+            .context("range event log failed")?
+            .take(limit)
+        {
+            let (_, value) = row.context("read event log row failed")?;
+            let event: StreamEvent =
+                serde_json::from_slice(value.value()).context("deserialize event failed")?;
+            out.push(event);
+        }
+        Ok(out)
+    }
+
+    fn trim_event_log(&self) -> Result<u64> {
+        let retention = self.config.events;
+        if retention.max_events.is_none() && retention.max_age.is_none() {
+            return Ok(0);
+        }
+        let cutoff = retention.max_age.map(|age| self.config.clock.now() - age);
+
+        let doomed = {
+            let read = self.db.begin_read().context("begin read tx failed")?;
+            let table = read
+                .open_table(EVENT_LOG)
+                .context("open event_log failed")?;
+            let len = table.len().context("count event log failed")?;
+            let mut over = retention
+                .max_events
+                .map_or(0, |max| len.saturating_sub(max));
+            let mut doomed = Vec::new();
+            for row in table.iter().context("iterate event log failed")? {
+                let (key, value) = row.context("read event log row failed")?;
+                if over > 0 {
+                    over -= 1;
+                    doomed.push(key.value());
+                    continue;
+                }
+                let Some(cutoff) = cutoff else {
+                    break;
+                };
+                let event: StreamEvent =
+                    serde_json::from_slice(value.value()).context("deserialize event failed")?;
+                if event.at >= cutoff {
+                    break;
+                }
+                doomed.push(key.value());
+            }
+            doomed
+        };
+
+        let write = self
+            .db
+            .begin_write()
+            .context("begin event trim tx failed")?;
+        {
+            let mut table = write
+                .open_table(EVENT_LOG)
+                .context("open event_log failed")?;
+            for seq in &doomed {
+                table.remove(seq).context("remove event log entry failed")?;
+            }
+        }
+        if let Some(cutoff) = cutoff {
+            history::trim_before(&write, cutoff)?;
+        }
+        write.commit().context("commit event trim tx failed")?;
+        Ok(doomed.len() as u64)
+    }
+
+    fn work_events(&self, work_id: Uuid) -> Result<Vec<StreamEvent>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read
+            .open_table(WORK_EVENTS)
+            .context("open work_events failed")?;
+        let id = work_id.as_u128();
+        let mut out = Vec::new();
+        for row in table
+            .range((id, 0)..=(id, u64::MAX))
+            .context("range work events failed")?
+        {
+            let (_, value) = row.context("read work event row failed")?;
+            let event: StreamEvent =
+                serde_json::from_slice(value.value()).context("deserialize work event failed")?;
+            out.push(event);
+        }
+        Ok(out)
+    }
+
+    fn get_leased_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let index = read
+            .open_table(LEASED_INDEX)
+            .context("open leased_index failed")?;
+        let leased = read
+            .open_table(LEASED_JOBS)
+            .context("open leased_jobs failed")?;
+        find_leased_job(&index, &leased, work_id)
+    }
+
+    fn get_queued_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let ready = read
+            .open_table(READY_JOBS)
+            .context("open ready_jobs failed")?;
+        if let Some(job) = scan_jobs(&ready, work_id)? {
+            return Ok(Some(job));
+        }
+        let scheduled = read
+            .open_table(SCHEDULED_JOBS)
+            .context("open scheduled_jobs failed")?;
+        scan_jobs(&scheduled, work_id)
+    }
+
+    fn get_receipt(&self, cid: &str) -> Result<Option<Receipt>> {
         let read = self.db.begin_read().context("begin read tx failed")?;
         let table = read.open_table(RECEIPTS).context("open receipts failed")?;
         let Some(bytes) = table.get(cid).context("read receipt by cid failed")? else {
@@ -391,7 +1152,97 @@ impl RedbStore {
         Ok(Some(receipt))
     }
 
-    pub fn list_receipts(&self) -> Result<Vec<Receipt>> {
+    fn put_blob(&self, value: &Value) -> Result<String> {
+        let write = self.db.begin_write().context("begin blob tx failed")?;
+        let cid = blobs::put(&write, value)?;
+        write.commit().context("commit blob tx failed")?;
+        Ok(cid)
+    }
+
+    fn get_blob(&self, cid: &str) -> Result<Option<Vec<u8>>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read.open_table(blobs::BLOBS).context("open blobs failed")?;
+        Ok(table
+            .get(cid)
+            .context("read blob failed")?
+            .map(|bytes| bytes.value().to_vec()))
+    }
+
+    fn artifact_refs(&self, cid: &str) -> Result<u64> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let refs = read
+            .open_table(artifacts::ARTIFACT_REFS)
+            .context("open artifact_refs failed")?;
+        let mut count = 0;
+        for row in refs
+            .range((cid, "")..)
+            .context("scan artifact_refs failed")?
+        {
+            let (key, _) = row.context("read artifact ref failed")?;
+            if key.value().0 != cid {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn live_artifacts(&self) -> Result<BTreeSet<String>> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let refs = read
+            .open_table(artifacts::ARTIFACT_REFS)
+            .context("open artifact_refs failed")?;
+        let mut live = BTreeSet::new();
+        for row in refs.iter().context("iterate artifact_refs failed")? {
+            let (key, _) = row.context("read artifact ref failed")?;
+            live.insert(key.value().0.to_string());
+        }
+        Ok(live)
+    }
+
+    fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let receipts = read.open_table(RECEIPTS).context("open receipts failed")?;
+        let index = read
+            .open_table(RECEIPT_INDEX)
+            .context("open receipt_index failed")?;
+
+        let dimension = query.dimension();
+        let dimension = dimension.as_str();
+        let (start, end) = query.range();
+        let start = start.map(|(ms, cid)| (dimension, ms, cid));
+        let end = end.map(|(ms, cid)| (dimension, ms, cid));
+
+        let rows = index
+            .range::<(&str, i64, &str)>((start, end))
+            .context("range receipt index failed")?
+            .filter_map(|row| {
+                let loaded = row
+                    .context("read receipt index row failed")
+                    .and_then(|(key, _)| {
+                        let (_, _, cid) = key.value();
+                        receipts.get(cid).context("read indexed receipt failed")
+                    })
+                    .and_then(|bytes| {
+                        bytes
+                            .map(|bytes| {
+                                serde_json::from_slice::<Receipt>(bytes.value())
+                                    .context("deserialize receipt failed")
+                            })
+                            .transpose()
+                    });
+                loaded.transpose()
+            });
+        query.collect_page(rows)
+    }
+
+    fn count_receipts(&self) -> Result<u64> {
+        let read = self.db.begin_read().context("begin read tx failed")?;
+        let table = read.open_table(RECEIPTS).context("open receipts failed")?;
+        table.len().context("count receipts failed")
+    }
+
+    fn list_receipts(&self) -> Result<Vec<Receipt>> {
         let read = self.db.begin_read().context("begin read tx failed")?;
         let table = read.open_table(RECEIPTS).context("open receipts failed")?;
         let mut out = Vec::new();
@@ -404,7 +1255,7 @@ impl RedbStore {
         Ok(out)
     }
 
-    pub fn purge_receipts(&self, receipts: &[Receipt]) -> Result<RetentionPurgeReport> {
+    fn purge_receipts(&self, receipts: &[Receipt]) -> Result<RetentionPurgeReport> {
         if receipts.is_empty() {
             return Ok(RetentionPurgeReport::default());
         }
@@ -414,6 +1265,9 @@ impl RedbStore {
 
         {
             let mut table = write.open_table(RECEIPTS).context("open receipts failed")?;
+            let mut index = write
+                .open_table(RECEIPT_INDEX)
+                .context("open receipt_index failed")?;
             for receipt in receipts {
                 if table
                     .remove(receipt.cid.as_str())
@@ -421,6 +1275,13 @@ impl RedbStore {
                     .is_some()
                 {
                     report.deleted_receipts += 1;
+                    counts::adjust(&write, Gauge::Receipts, &receipt.tenant, &receipt.topic, -1)?;
+                }
+                let created_at_ms = receipt.created_at.timestamp_millis();
+                for dimension in receipt_index::dimensions(receipt) {
+                    index
+                        .remove((dimension.as_str(), created_at_ms, receipt.cid.as_str()))
+                        .context("remove receipt index entry failed")?;
                 }
             }
         }
@@ -441,11 +1302,17 @@ impl RedbStore {
             }
         }
 
+        for receipt in receipts {
+            report.deleted_blobs += blobs::release(&write, receipt)?;
+            artifacts::release(&write, receipt)?;
+            report.deleted_work_events += history::release(&write, receipt.work_id)?;
+        }
+
         write.commit().context("commit retention tx failed")?;
         Ok(report)
     }
 
-    pub fn increment_status_counter(&self, status: WorkStatus) -> Result<()> {
+    fn increment_status_counter(&self, status: WorkStatus, labels: &MetricLabels) -> Result<()> {
         let write = self
             .db
             .begin_write()
@@ -453,12 +1320,20 @@ impl RedbStore {
         {
             let mut meta = write.open_table(META).context("open meta failed")?;
             inc_counter(&mut meta, status_meta_key(status), 1)?;
+            let mut sink = redb_series(&write)?;
+            series::record_status(&mut sink, &self.config.metrics, labels, status)?;
         }
         write.commit().context("commit status counter tx failed")?;
         Ok(())
     }
 
-    pub fn observe_timings(&self, ttft_ms: u64, ttr_ms: u64) -> Result<()> {
+    fn observe_timings(
+        &self,
+        labels: &MetricLabels,
+        ttft_ms: u64,
+        ttr_ms: u64,
+        stages: &BTreeMap<String, u64>,
+    ) -> Result<()> {
         let write = self.db.begin_write().context("begin timings tx failed")?;
         {
             let mut meta = write.open_table(META).context("open meta failed")?;
@@ -468,76 +1343,708 @@ impl RedbStore {
             inc_counter(&mut meta, META_TTR_COUNT, 1)?;
             observe_histogram(&mut meta, "ttft", &TTFT_BUCKETS_MS, ttft_ms)?;
             observe_histogram(&mut meta, "ttr", &TTR_BUCKETS_MS, ttr_ms)?;
+            let mut sink = redb_series(&write)?;
+            series::record_timings(
+                &mut sink,
+                &self.config.metrics,
+                labels,
+                ttft_ms,
+                ttr_ms,
+                stages,
+            )?;
         }
         write.commit().context("commit timings tx failed")?;
         Ok(())
     }
 
-    pub fn queue_metrics(&self) -> Result<QueueMetrics> {
+    fn queue_metrics(&self) -> Result<QueueMetrics> {
         let read = self
             .db
             .begin_read()
             .context("begin metrics read tx failed")?;
+        let counts = read.open_table(COUNTS).context("open counts failed")?;
+        let meta = read.open_table(META).context("open meta failed")?;
+        let series_table = read
+            .open_table(series::SERIES)
+            .context("open metric_series failed")?;
+        let labels_table = read
+            .open_table(series::LABELS)
+            .context("open metric_labels failed")?;
+
+        let by_tenant_topic = counts::breakdown(&counts::stored(&counts)?);
+        let mut total = QueueCounts::default();
+        for counts in by_tenant_topic.values() {
+            total += *counts;
+        }
 
-        let ready = read
+        let mut rows = Vec::new();
+        for entry in series_table
+            .iter()
+            .context("iterate metric series failed")?
+        {
+            let (key, value) = entry.context("read metric series failed")?;
+            let (name, a, b, field) = key.value();
+            rows.push((
+                (
+                    name.to_string(),
+                    a.to_string(),
+                    b.to_string(),
+                    field.to_string(),
+                ),
+                value.value(),
+            ));
+        }
+        let mut admitted = BTreeSet::new();
+        for entry in labels_table
+            .iter()
+            .context("iterate metric labels failed")?
+        {
+            let (key, _) = entry.context("read metric label failed")?;
+            let (kind, value) = key.value();
+            admitted.insert((kind.to_string(), value.to_string()));
+        }
+        let labelled = series::collect(&self.config.metrics, rows, &admitted, &by_tenant_topic);
+
+        Ok(QueueMetrics {
+            queue_depth: total.ready as usize,
+            scheduled_depth: total.scheduled as usize,
+            leased_depth: total.leased as usize,
+            dead_letter_depth: total.dead_letters as usize,
+            receipts_total: total.receipts as usize,
+            by_tenant_topic,
+            labelled,
+            ..counter_metrics(|key| meta_get_read(&meta, key))?
+        })
+    }
+
+    fn snapshot(&self) -> Result<Snapshot> {
+        let read = self.db.begin_read().context("begin snapshot tx failed")?;
+        let mut counters = BTreeMap::new();
+        let meta = read.open_table(META).context("open meta failed")?;
+        for row in meta.iter().context("iterate meta failed")? {
+            let (key, value) = row.context("read meta row failed")?;
+            counters.insert(key.value().to_string(), value.value());
+        }
+        let mut blobs = BTreeMap::new();
+        let table = read.open_table(blobs::BLOBS).context("open blobs failed")?;
+        for row in table.iter().context("iterate blobs failed")? {
+            let (key, value) = row.context("read blob row failed")?;
+            let blob = serde_json::from_slice(value.value()).context("deserialize blob failed")?;
+            blobs.insert(key.value().to_string(), blob);
+        }
+        Ok(Snapshot {
+            ready_jobs: read_rows(&read, READY_JOBS)?,
+            scheduled_jobs: read_rows(&read, SCHEDULED_JOBS)?,
+            leased_jobs: read_rows(&read, LEASED_JOBS)?,
+            dead_letters: read_rows(&read, DEAD_LETTERS)?,
+            receipts: read_rows(&read, RECEIPTS)?,
+            idem_keys: read_rows(&read, IDEM_KEYS)?,
+            blobs,
+            counters,
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> Result<()> {
+        let partition = self.config.scheduler.partition;
+        let write = self.db.begin_write().context("begin restore tx failed")?;
+        for definition in [READY_JOBS, SCHEDULED_JOBS, LEASED_JOBS, DEAD_LETTERS] {
+            let table = write
+                .open_table(definition)
+                .with_context(|| format!("open {definition} failed"))?;
+            if !table.is_empty().context("count rows failed")? {
+                return Err(anyhow!(
+                    "restore needs an empty database; {definition} has rows"
+                ));
+            }
+        }
+        for definition in [RECEIPTS, IDEM_KEYS, blobs::BLOBS] {
+            let table = write
+                .open_table(definition)
+                .with_context(|| format!("open {definition} failed"))?;
+            if !table.is_empty().context("count rows failed")? {
+                return Err(anyhow!(
+                    "restore needs an empty database; {definition} has rows"
+                ));
+            }
+        }
+        for job in &snapshot.ready_jobs {
+            push_ready_job(&write, partition, job)?;
+        }
+        for job in &snapshot.scheduled_jobs {
+            push_scheduled_job(&write, job)?;
+        }
+        for job in &snapshot.leased_jobs {
+            put_leased_job(&write, job)?;
+        }
+        {
+            let mut dead = write
+                .open_table(DEAD_LETTERS)
+                .context("open dead_letters failed")?;
+            for letter in &snapshot.dead_letters {
+                let bytes = serde_json::to_vec(letter).context("serialize dead letter failed")?;
+                dead.insert(letter.job.seq, bytes.as_slice())
+                    .context("insert dead letter failed")?;
+            }
+        }
+        {
+            let mut receipts = write.open_table(RECEIPTS).context("open receipts failed")?;
+            let mut index = write
+                .open_table(RECEIPT_INDEX)
+                .context("open receipt_index failed")?;
+            for receipt in &snapshot.receipts {
+                let bytes = serde_json::to_vec(receipt).context("serialize receipt failed")?;
+                receipts
+                    .insert(receipt.cid.as_str(), bytes.as_slice())
+                    .context("insert receipt failed")?;
+                index_receipt(&mut index, receipt)?;
+            }
+        }
+        for (cid, blob) in &snapshot.blobs {
+            let stored = blobs::put(&write, blob)?;
+            if stored != *cid {
+                return Err(anyhow!("blob {cid} hashes to {stored}"));
+            }
+        }
+        blobs::rebuild_refs(&write)?;
+        artifacts::rebuild_refs(&write)?;
+        {
+            let mut idem = write
+                .open_table(IDEM_KEYS)
+                .context("open idem_keys failed")?;
+            for record in &snapshot.idem_keys {
+                let key = idem_lookup_key(&record.tenant, &record.topic, &record.idem_key);
+                let bytes = serde_json::to_vec(record).context("serialize idem record failed")?;
+                idem.insert(key.as_str(), bytes.as_slice())
+                    .context("insert idem record failed")?;
+            }
+        }
+        {
+            let mut meta = write.open_table(META).context("open meta failed")?;
+            for (key, value) in restorable_counters(&snapshot.counters) {
+                meta.insert(key, value).context("write meta key failed")?;
+            }
+        }
+        counts::recount(&write)?;
+        write.commit().context("commit restore tx failed")?;
+        Ok(())
+    }
+}
+
+fn read_rows<K: redb::Key + 'static, T: serde::de::DeserializeOwned>(
+    read: &ReadTransaction,
+    definition: TableDefinition<K, &[u8]>,
+) -> Result<Vec<T>> {
+    let table = read
+        .open_table(definition)
+        .with_context(|| format!("open {definition} failed"))?;
+    let mut out = Vec::new();
+    for row in table.iter().context("iterate rows failed")? {
+        let (_, value) = row.context("read row failed")?;
+        out.push(
+            serde_json::from_slice(value.value())
+                .with_context(|| format!("deserialize {definition} row failed"))?,
+        );
+    }
+    Ok(out)
+}
+
+/// Counters a restore carries over; layout bookkeeping belongs to the target.
+fn restorable_counters(counters: &BTreeMap<String, u64>) -> impl Iterator<Item = (&str, u64)> {
+    counters
+        .iter()
+        .filter(|(key, _)| {
+            key.as_str() != migrations::META_SCHEMA_VERSION && key.as_str() != META_READY_PARTITION
+        })
+        .map(|(key, value)| (key.as_str(), *value))
+}
+
+/// The counter-backed half of [`QueueMetrics`]; depths are left at zero.
+fn counter_metrics(counter: impl Fn(&str) -> Result<u64>) -> Result<QueueMetrics> {
+    let mut status_totals = BTreeMap::new();
+    for status in [
+        WorkStatus::Accepted,
+        WorkStatus::Assigned,
+        WorkStatus::Progress,
+        WorkStatus::Retrying,
+        WorkStatus::Done,
+        WorkStatus::Fail,
+        WorkStatus::Cancelled,
+    ] {
+        status_totals.insert(
+            status_label(status).to_string(),
+            counter(status_meta_key(status))?,
+        );
+    }
+
+    let mut ttft_bucket_counts = Vec::with_capacity(TTFT_BUCKETS_MS.len());
+    for le in TTFT_BUCKETS_MS {
+        ttft_bucket_counts.push((le, counter(&bucket_key("ttft", le))?));
+    }
+
+    let mut ttr_bucket_counts = Vec::with_capacity(TTR_BUCKETS_MS.len());
+    for le in TTR_BUCKETS_MS {
+        ttr_bucket_counts.push((le, counter(&bucket_key("ttr", le))?));
+    }
+
+    Ok(QueueMetrics {
+        queue_depth: 0,
+        scheduled_depth: 0,
+        leased_depth: 0,
+        dead_letter_depth: 0,
+        reassigns_total: counter(META_REASSIGNS_TOTAL)?,
+        receipts_total: 0,
+        status_totals,
+        ttft_sum_ms: counter(META_TTFT_SUM_MS)?,
+        ttft_count: counter(META_TTFT_COUNT)?,
+        ttr_sum_ms: counter(META_TTR_SUM_MS)?,
+        ttr_count: counter(META_TTR_COUNT)?,
+        ttft_bucket_counts,
+        ttr_bucket_counts,
+        by_tenant_topic: BTreeMap::new(),
+        labelled: LabelledMetrics::default(),
+    })
+}
+
+fn redb_series(write: &WriteTransaction) -> Result<RedbSeries<'_>> {
+    Ok(RedbSeries {
+        series: write
+            .open_table(series::SERIES)
+            .context("open metric_series failed")?,
+        labels: write
+            .open_table(series::LABELS)
+            .context("open metric_labels failed")?,
+    })
+}
+
+fn push_ready_job(
+    write: &WriteTransaction,
+    partition: QueuePartition,
+    job: &QueuedJob,
+) -> Result<()> {
+    let bytes = serde_json::to_vec(job).context("serialize queued job failed")?;
+    {
+        let mut ready = write
             .open_table(READY_JOBS)
             .context("open ready_jobs failed")?;
-        let leased = read
+        ready
+            .insert(job.seq, bytes.as_slice())
+            .context("insert ready job failed")?;
+    }
+    count_job(write, Gauge::Ready, job, 1)?;
+    index_ready_job(write, partition, job)
+}
+
+fn index_ready_job(
+    write: &WriteTransaction,
+    partition: QueuePartition,
+    job: &QueuedJob,
+) -> Result<()> {
+    let queue = partition.queue_key(&job.work);
+    let mut index = write
+        .open_table(READY_INDEX)
+        .context("open ready_index failed")?;
+    index
+        .insert((queue.as_str(), job.work.priority.rank(), job.seq), ())
+        .context("insert ready index entry failed")?;
+
+    let mut queues = write
+        .open_table(READY_QUEUES)
+        .context("open ready_queues failed")?;
+    let depth = queues
+        .get(queue.as_str())
+        .context("read ready queue depth failed")?
+        .map(|v| v.value())
+        .unwrap_or(0);
+    queues
+        .insert(queue.as_str(), depth + 1)
+        .context("write ready queue depth failed")?;
+    Ok(())
+}
+
+/// Clears the ready index and queue depths and rebuilds them from `ready_jobs`.
+fn rebuild_ready_index(write: &WriteTransaction, partition: QueuePartition) -> Result<()> {
+    let mut jobs = Vec::new();
+    {
+        let ready = write
+            .open_table(READY_JOBS)
+            .context("open ready_jobs failed")?;
+        for entry in ready.iter().context("iterate ready jobs failed")? {
+            let (_, value) = entry.context("read ready iterator entry failed")?;
+            let job: QueuedJob = serde_json::from_slice(value.value())
+                .context("deserialize ready queued job failed")?;
+            jobs.push(job);
+        }
+    }
+    {
+        let mut index = write
+            .open_table(READY_INDEX)
+            .context("open ready_index failed")?;
+        index
+            .retain(|_, _| false)
+            .context("clear ready_index failed")?;
+        let mut queues = write
+            .open_table(READY_QUEUES)
+            .context("open ready_queues failed")?;
+        queues
+            .retain(|_, _| false)
+            .context("clear ready_queues failed")?;
+    }
+    for job in &jobs {
+        index_ready_job(write, partition, job)?;
+    }
+    Ok(())
+}
+
+fn put_leased_job(write: &WriteTransaction, job: &QueuedJob) -> Result<()> {
+    let bytes = serde_json::to_vec(job).context("serialize leased job failed")?;
+    let replaced = {
+        let mut leased = write
             .open_table(LEASED_JOBS)
             .context("open leased_jobs failed")?;
-        let receipts = read.open_table(RECEIPTS).context("open receipts failed")?;
-        let meta = read.open_table(META).context("open meta failed")?;
+        leased
+            .insert(job.seq, bytes.as_slice())
+            .context("insert leased job failed")?
+            .is_some()
+    };
+    if !replaced {
+        write
+            .open_table(LEASED_INDEX)
+            .context("open leased_index failed")?
+            .insert(job.work.id.as_u128(), job.seq)
+            .context("insert leased index entry failed")?;
+        count_job(write, Gauge::Leased, job, 1)?;
+    }
+    Ok(())
+}
 
-        let queue_depth = ready.iter().context("iterate ready jobs failed")?.count();
-        let leased_depth = leased.iter().context("iterate leased jobs failed")?.count();
-        let receipts_total = receipts.iter().context("iterate receipts failed")?.count();
-        let reassigns_total = meta_get_read(&meta, META_REASSIGNS_TOTAL)?;
+fn take_leased_job(write: &WriteTransaction, seq: u64) -> Result<Option<QueuedJob>> {
+    let job = {
+        let mut leased = write
+            .open_table(LEASED_JOBS)
+            .context("open leased_jobs failed")?;
+        leased
+            .remove(seq)
+            .context("remove leased job failed")?
+            .map(|v| serde_json::from_slice::<QueuedJob>(v.value()))
+            .transpose()
+            .context("deserialize leased job failed")?
+    };
+    if let Some(job) = &job {
+        write
+            .open_table(LEASED_INDEX)
+            .context("open leased_index failed")?
+            .remove(job.work.id.as_u128())
+            .context("remove leased index entry failed")?;
+        count_job(write, Gauge::Leased, job, -1)?;
+    }
+    Ok(job)
+}
+
+/// The leased row for `seq`, if it is still held under `attempt`.
+fn held_lease(write: &WriteTransaction, seq: u64, attempt: u32) -> Result<Option<QueuedJob>> {
+    let leased = write
+        .open_table(LEASED_JOBS)
+        .context("open leased_jobs failed")?;
+    let job = leased
+        .get(seq)
+        .context("read leased job failed")?
+        .map(|v| serde_json::from_slice::<QueuedJob>(v.value()))
+        .transpose()
+        .context("deserialize leased job failed")?;
+    Ok(job.filter(|job| job.attempt == attempt))
+}
+
+fn find_leased_job(
+    index: &impl ReadableTable<u128, u64>,
+    leased: &impl ReadableTable<u64, &'static [u8]>,
+    work_id: Uuid,
+) -> Result<Option<QueuedJob>> {
+    let Some(seq) = index
+        .get(work_id.as_u128())
+        .context("read leased index failed")?
+    else {
+        return Ok(None);
+    };
+    leased
+        .get(seq.value())
+        .context("read leased job failed")?
+        .map(|v| serde_json::from_slice::<QueuedJob>(v.value()))
+        .transpose()
+        .context("deserialize leased job failed")
+}
 
-        let mut status_totals = BTreeMap::new();
-        for status in [
-            WorkStatus::Accepted,
-            WorkStatus::Assigned,
-            WorkStatus::Progress,
-            WorkStatus::Done,
-            WorkStatus::Fail,
-        ] {
-            status_totals.insert(
-                status_label(status).to_string(),
-                meta_get_read(&meta, status_meta_key(status))?,
-            );
+/// Rewrites [`LEASED_INDEX`] from the leased jobs table.
+fn rebuild_leased_index(write: &WriteTransaction) -> Result<()> {
+    let jobs: Vec<(u128, u64)> = {
+        let leased = write
+            .open_table(LEASED_JOBS)
+            .context("open leased_jobs failed")?;
+        let mut out = Vec::new();
+        for row in leased.iter().context("iterate leased jobs failed")? {
+            let (key, value) = row.context("read leased job row failed")?;
+            let job: QueuedJob =
+                serde_json::from_slice(value.value()).context("deserialize leased job failed")?;
+            out.push((job.work.id.as_u128(), key.value()));
         }
+        out
+    };
+    let mut index = write
+        .open_table(LEASED_INDEX)
+        .context("open leased_index failed")?;
+    index
+        .retain(|_, _| false)
+        .context("clear leased_index failed")?;
+    for (work_id, seq) in jobs {
+        index
+            .insert(work_id, seq)
+            .context("insert leased index entry failed")?;
+    }
+    Ok(())
+}
+
+fn count_job(write: &WriteTransaction, gauge: Gauge, job: &QueuedJob, delta: i64) -> Result<()> {
+    counts::adjust(write, gauge, &job.work.tenant, &job.work.topic, delta)
+}
+
+fn find_job(
+    write: &WriteTransaction,
+    table: TableDefinition<u64, &[u8]>,
+    work_id: Uuid,
+) -> Result<Option<QueuedJob>> {
+    let table = write.open_table(table).context("open job table failed")?;
+    scan_jobs(&table, work_id)
+}
+
+/// The job for `work_id` in a ready or scheduled job table, by full scan.
+fn scan_jobs(
+    table: &impl ReadableTable<u64, &'static [u8]>,
+    work_id: Uuid,
+) -> Result<Option<QueuedJob>> {
+    for entry in table.iter().context("iterate jobs failed")? {
+        let (_, value) = entry.context("read job entry failed")?;
+        let job: QueuedJob =
+            serde_json::from_slice(value.value()).context("deserialize queued job failed")?;
+        if job.work.id == work_id {
+            return Ok(Some(job));
+        }
+    }
+    Ok(None)
+}
+
+fn remove_ready_job(
+    write: &WriteTransaction,
+    partition: QueuePartition,
+    job: &QueuedJob,
+) -> Result<()> {
+    let queue = partition.queue_key(&job.work);
+    {
+        let mut index = write
+            .open_table(READY_INDEX)
+            .context("open ready_index failed")?;
+        index
+            .remove((queue.as_str(), job.work.priority.rank(), job.seq))
+            .context("remove ready index entry failed")?;
+    }
+    decrement_queue_depth(write, &queue)?;
+    {
+        let mut ready = write
+            .open_table(READY_JOBS)
+            .context("open ready_jobs failed")?;
+        ready.remove(job.seq).context("remove ready job failed")?;
+    }
+    count_job(write, Gauge::Ready, job, -1)
+}
 
-        let ttft_sum_ms = meta_get_read(&meta, META_TTFT_SUM_MS)?;
-        let ttft_count = meta_get_read(&meta, META_TTFT_COUNT)?;
-        let ttr_sum_ms = meta_get_read(&meta, META_TTR_SUM_MS)?;
-        let ttr_count = meta_get_read(&meta, META_TTR_COUNT)?;
+fn scheduled_due_ms(job: &QueuedJob) -> i64 {
+    job.retry_at
+        .or(job.work.not_before)
+        .map(|at| at.timestamp_millis())
+        .unwrap_or_default()
+}
+
+fn remove_scheduled_job(write: &WriteTransaction, job: &QueuedJob) -> Result<()> {
+    {
+        let mut index = write
+            .open_table(SCHEDULED_INDEX)
+            .context("open scheduled_index failed")?;
+        index
+            .remove((scheduled_due_ms(job), job.seq))
+            .context("remove scheduled index entry failed")?;
+    }
+    {
+        let mut scheduled = write
+            .open_table(SCHEDULED_JOBS)
+            .context("open scheduled_jobs failed")?;
+        scheduled
+            .remove(job.seq)
+            .context("remove scheduled job failed")?;
+    }
+    count_job(write, Gauge::Scheduled, job, -1)
+}
+
+fn push_scheduled_job(write: &WriteTransaction, job: &QueuedJob) -> Result<()> {
+    let due_ms = scheduled_due_ms(job);
+    let bytes = serde_json::to_vec(job).context("serialize scheduled job failed")?;
+    {
+        let mut scheduled = write
+            .open_table(SCHEDULED_JOBS)
+            .context("open scheduled_jobs failed")?;
+        scheduled
+            .insert(job.seq, bytes.as_slice())
+            .context("insert scheduled job failed")?;
+    }
+    count_job(write, Gauge::Scheduled, job, 1)?;
+    let mut index = write
+        .open_table(SCHEDULED_INDEX)
+        .context("open scheduled_index failed")?;
+    index
+        .insert((due_ms, job.seq), ())
+        .context("insert scheduled index entry failed")?;
+    Ok(())
+}
 
-        let mut ttft_bucket_counts = Vec::with_capacity(TTFT_BUCKETS_MS.len());
-        for le in TTFT_BUCKETS_MS {
-            let key = bucket_key("ttft", le);
-            ttft_bucket_counts.push((le, meta_get_read(&meta, &key)?));
+/// Moves every scheduled job whose `not_before` has passed into its ready queue.
+fn promote_due_jobs(
+    write: &WriteTransaction,
+    partition: QueuePartition,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let due = {
+        let mut index = write
+            .open_table(SCHEDULED_INDEX)
+            .context("open scheduled_index failed")?;
+        let keys = index
+            .range(..=(now.timestamp_millis(), u64::MAX))
+            .context("range scheduled_index failed")?
+            .map(|entry| entry.map(|(key, _)| key.value()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("read scheduled_index entry failed")?;
+        for key in &keys {
+            index
+                .remove(*key)
+                .context("remove scheduled index entry failed")?;
         }
+        keys
+    };
+
+    for (_, seq) in &due {
+        let bytes = {
+            let mut scheduled = write
+                .open_table(SCHEDULED_JOBS)
+                .context("open scheduled_jobs failed")?;
+            scheduled
+                .remove(*seq)
+                .context("remove scheduled job failed")?
+                .map(|v| v.value().to_vec())
+        };
+        let Some(bytes) = bytes else {
+            continue;
+        };
+        let mut job: QueuedJob =
+            serde_json::from_slice(&bytes).context("deserialize scheduled job failed")?;
+        count_job(write, Gauge::Scheduled, &job, -1)?;
+        job.retry_at = None;
+        push_ready_job(write, partition, &job)?;
+    }
+
+    Ok(due.len())
+}
 
-        let mut ttr_bucket_counts = Vec::with_capacity(TTR_BUCKETS_MS.len());
-        for le in TTR_BUCKETS_MS {
-            let key = bucket_key("ttr", le);
-            ttr_bucket_counts.push((le, meta_get_read(&meta, &key)?));
+/// Keeps only the queues whose head job has the most urgent priority; fairness
+/// applies among queues of equal priority.
+fn highest_priority_queues(
+    write: &WriteTransaction,
+    candidates: Vec<(String, u32)>,
+) -> Result<Vec<(String, u32)>> {
+    let index = write
+        .open_table(READY_INDEX)
+        .context("open ready_index failed")?;
+    let mut ranked = Vec::with_capacity(candidates.len());
+    for (queue, weight) in candidates {
+        let head = index
+            .range((queue.as_str(), 0u8, 0u64)..=(queue.as_str(), u8::MAX, u64::MAX))
+            .context("range ready_index failed")?
+            .next()
+            .transpose()
+            .context("read ready_index entry failed")?
+            .map(|(key, _)| key.value().1);
+        if let Some(rank) = head {
+            ranked.push((rank, queue, weight));
         }
+    }
 
-        Ok(QueueMetrics {
-            queue_depth,
-            leased_depth,
-            reassigns_total,
-            receipts_total,
-            status_totals,
-            ttft_sum_ms,
-            ttft_count,
-            ttr_sum_ms,
-            ttr_count,
-            ttft_bucket_counts,
-            ttr_bucket_counts,
-        })
+    let Some(best) = ranked.iter().map(|(rank, _, _)| *rank).min() else {
+        return Ok(Vec::new());
+    };
+    Ok(ranked
+        .into_iter()
+        .filter(|(rank, _, _)| *rank == best)
+        .map(|(_, queue, weight)| (queue, weight))
+        .collect())
+}
+
+/// Removes the most urgent, oldest job of `queue` from the ready tables and
+/// returns it.
+fn pop_ready_job(write: &WriteTransaction, queue: &str) -> Result<Option<QueuedJob>> {
+    let seq = {
+        let mut index = write
+            .open_table(READY_INDEX)
+            .context("open ready_index failed")?;
+        let first = index
+            .range((queue, 0u8, 0u64)..=(queue, u8::MAX, u64::MAX))
+            .context("range ready_index failed")?
+            .next()
+            .transpose()
+            .context("read ready_index entry failed")?
+            .map(|(key, _)| {
+                let (_, rank, seq) = key.value();
+                (rank, seq)
+            });
+        let Some((rank, seq)) = first else {
+            return Ok(None);
+        };
+        index
+            .remove((queue, rank, seq))
+            .context("remove ready index entry failed")?;
+        seq
+    };
+
+    decrement_queue_depth(write, queue)?;
+
+    let job = {
+        let mut ready = write
+            .open_table(READY_JOBS)
+            .context("open ready_jobs failed")?;
+        ready
+            .remove(seq)
+            .context("remove ready job failed")?
+            .map(|v| serde_json::from_slice::<QueuedJob>(v.value()))
+            .transpose()
+            .context("deserialize ready queued job failed")?
+    };
+    if let Some(job) = &job {
+        count_job(write, Gauge::Ready, job, -1)?;
     }
+    Ok(job)
+}
+
+fn decrement_queue_depth(write: &WriteTransaction, queue: &str) -> Result<()> {
+    let mut queues = write
+        .open_table(READY_QUEUES)
+        .context("open ready_queues failed")?;
+    let depth = queues
+        .get(queue)
+        .context("read ready queue depth failed")?
+        .map(|v| v.value())
+        .unwrap_or(0);
+    if depth <= 1 {
+        queues.remove(queue).context("remove ready queue failed")?;
+    } else {
+        queues
+            .insert(queue, depth - 1)
+            .context("write ready queue depth failed")?;
+    }
+    Ok(())
 }
 
 fn status_meta_key(status: WorkStatus) -> &'static str {
@@ -545,8 +2052,10 @@ fn status_meta_key(status: WorkStatus) -> &'static str {
         WorkStatus::Accepted => "jobs_total_accepted",
         WorkStatus::Assigned => "jobs_total_assigned",
         WorkStatus::Progress => "jobs_total_progress",
+        WorkStatus::Retrying => "jobs_total_retrying",
         WorkStatus::Done => "jobs_total_done",
         WorkStatus::Fail => "jobs_total_fail",
+        WorkStatus::Cancelled => "jobs_total_cancelled",
     }
 }
 
@@ -555,11 +2064,26 @@ fn status_label(status: WorkStatus) -> &'static str {
         WorkStatus::Accepted => "accepted",
         WorkStatus::Assigned => "assigned",
         WorkStatus::Progress => "progress",
+        WorkStatus::Retrying => "retrying",
         WorkStatus::Done => "done",
         WorkStatus::Fail => "fail",
+        WorkStatus::Cancelled => "cancelled",
     }
 }
 
+fn index_receipt(index: &mut Table<(&str, i64, &str), ()>, receipt: &Receipt) -> Result<()> {
+    let created_at_ms = receipt.created_at.timestamp_millis();
+    for dimension in receipt_index::dimensions(receipt) {
+        index
+            .insert(
+                (dimension.as_str(), created_at_ms, receipt.cid.as_str()),
+                (),
+            )
+            .context("insert receipt index entry failed")?;
+    }
+    Ok(())
+}
+
 fn idem_lookup_key(tenant: &str, topic: &str, idem_key: &str) -> String {
     format!("{tenant}\u{001F}{topic}\u{001F}{idem_key}")
 }
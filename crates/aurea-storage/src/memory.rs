@@ -0,0 +1,847 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Result, anyhow};
+use aurea_core::{Clock, Receipt, StreamEvent, WorkStatus, WorkUnit};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::artifacts;
+use crate::blobs;
+use crate::counts::{self, Gauge, Tally};
+use crate::receipt_index;
+use crate::scheduler::{FairScheduler, queue_topic};
+use crate::series::{self, MemorySeries};
+use crate::{
+    CancelOutcome, DeadLetter, EnqueueResult, FailureOutcome, IdemRecord, LEASE_EXPIRED_ERROR,
+    META_NEXT_EVENT_SEQ, META_NEXT_SEQ, META_REASSIGNS_TOTAL, META_TTFT_COUNT, META_TTFT_SUM_MS,
+    META_TTR_COUNT, META_TTR_SUM_MS, MetricLabels, QueueMetrics, QueuedJob, ReassignReport,
+    ReceiptPage, ReceiptQuery, RetentionPurgeReport, Snapshot, Store, StoreConfig, TTFT_BUCKETS_MS,
+    TTR_BUCKETS_MS, TopicFilter, bucket_key, counter_metrics, idem_lookup_key, restorable_counters,
+    scheduled_due_ms, status_label, status_meta_key,
+};
+
+/// Keeps everything in process; state is lost when the store is dropped.
+/// Meant for tests and throwaway runs (`--db memory:`).
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    config: Arc<StoreConfig>,
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    next_seq: u64,
+    next_event_seq: u64,
+    ready: BTreeMap<u64, QueuedJob>,
+    /// Ready queue key -> `(priority rank, seq)`, most urgent and oldest first.
+    ready_queues: BTreeMap<String, BTreeSet<(u8, u64)>>,
+    scheduled: BTreeMap<u64, QueuedJob>,
+    scheduled_index: BTreeSet<(i64, u64)>,
+    leased: BTreeMap<u64, QueuedJob>,
+    dead_letters: BTreeMap<u64, DeadLetter>,
+    receipts: BTreeMap<String, Receipt>,
+    receipt_index: BTreeSet<(String, i64, String)>,
+    blobs: BTreeMap<String, Vec<u8>>,
+    /// `(blob cid, receipt cid)`, as in the redb `blob_refs` table.
+    blob_refs: BTreeSet<(String, String)>,
+    /// `(artifact cid, receipt cid)`, as in the redb `artifact_refs` table.
+    artifact_refs: BTreeSet<(String, String)>,
+    idem: HashMap<String, IdemRecord>,
+    counters: HashMap<String, u64>,
+    series: MemorySeries,
+    event_log: BTreeMap<u64, StreamEvent>,
+    work_events: HashMap<Uuid, Vec<StreamEvent>>,
+    scheduler: FairScheduler,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: StoreConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            state: Arc::default(),
+        }
+    }
+
+    fn state(&self) -> Result<MutexGuard<'_, MemoryState>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("memory store lock poisoned"))
+    }
+}
+
+impl MemoryState {
+    fn push_ready(&mut self, config: &StoreConfig, job: QueuedJob) {
+        let queue = config.scheduler.partition.queue_key(&job.work);
+        self.ready_queues
+            .entry(queue)
+            .or_default()
+            .insert((job.work.priority.rank(), job.seq));
+        self.ready.insert(job.seq, job);
+    }
+
+    fn remove_ready(&mut self, config: &StoreConfig, job: &QueuedJob) {
+        let queue = config.scheduler.partition.queue_key(&job.work);
+        if let Some(entries) = self.ready_queues.get_mut(&queue) {
+            entries.remove(&(job.work.priority.rank(), job.seq));
+            if entries.is_empty() {
+                self.ready_queues.remove(&queue);
+            }
+        }
+        self.ready.remove(&job.seq);
+    }
+
+    fn push_scheduled(&mut self, job: QueuedJob) {
+        self.scheduled_index
+            .insert((scheduled_due_ms(&job), job.seq));
+        self.scheduled.insert(job.seq, job);
+    }
+
+    fn remove_scheduled(&mut self, job: &QueuedJob) {
+        self.scheduled_index
+            .remove(&(scheduled_due_ms(job), job.seq));
+        self.scheduled.remove(&job.seq);
+    }
+
+    fn promote_due(&mut self, config: &StoreConfig, now: DateTime<Utc>) {
+        let now_ms = now.timestamp_millis();
+        while let Some(&(due_ms, seq)) = self.scheduled_index.first() {
+            if due_ms > now_ms {
+                break;
+            }
+            self.scheduled_index.pop_first();
+            if let Some(mut job) = self.scheduled.remove(&seq) {
+                job.retry_at = None;
+                self.push_ready(config, job);
+            }
+        }
+    }
+
+    /// The leased job at `seq`, if it is still held under `attempt`.
+    fn held_lease(&self, seq: u64, attempt: u32) -> Option<&QueuedJob> {
+        self.leased.get(&seq).filter(|job| job.attempt == attempt)
+    }
+
+    /// Stores a receipt with its encoded blobs and settles the idempotency
+    /// record of its work.
+    fn store_receipt(
+        &mut self,
+        receipt: &Receipt,
+        encoded: Vec<(String, Vec<u8>)>,
+        now: DateTime<Utc>,
+    ) {
+        for (cid, bytes) in encoded {
+            self.blobs.entry(cid).or_insert(bytes);
+        }
+        let created_at_ms = receipt.created_at.timestamp_millis();
+        for dimension in receipt_index::dimensions(receipt) {
+            self.receipt_index
+                .insert((dimension, created_at_ms, receipt.cid.clone()));
+        }
+        for blob in blobs::referenced(receipt) {
+            self.blob_refs
+                .insert((blob.to_string(), receipt.cid.clone()));
+        }
+        for artifact in artifacts::referenced(receipt) {
+            self.artifact_refs
+                .insert((artifact.to_string(), receipt.cid.clone()));
+        }
+        self.receipts.insert(receipt.cid.clone(), receipt.clone());
+
+        let key = idem_lookup_key(&receipt.tenant, &receipt.topic, &receipt.idem_key);
+        let record = self.idem.entry(key).or_insert_with(|| IdemRecord {
+            tenant: receipt.tenant.clone(),
+            topic: receipt.topic.clone(),
+            idem_key: receipt.idem_key.clone(),
+            work_id: receipt.work_id,
+            status: "queued".to_string(),
+            receipt_cid: None,
+            updated_at: now,
+        });
+        record.status = status_label(receipt.status).to_string();
+        record.receipt_cid = Some(receipt.cid.clone());
+        record.updated_at = now;
+    }
+
+    fn find(jobs: &BTreeMap<u64, QueuedJob>, work_id: Uuid) -> Option<QueuedJob> {
+        jobs.values().find(|job| job.work.id == work_id).cloned()
+    }
+
+    fn counter(&self, key: &str) -> u64 {
+        self.counters.get(key).copied().unwrap_or(0)
+    }
+
+    fn inc_counter(&mut self, key: &str, delta: u64) {
+        let slot = self.counters.entry(key.to_string()).or_default();
+        *slot = slot.saturating_add(delta);
+    }
+
+    fn observe_histogram(&mut self, prefix: &str, buckets: &[u64], value: u64) {
+        for le in buckets {
+            if value <= *le {
+                self.inc_counter(&bucket_key(prefix, *le), 1);
+            }
+        }
+    }
+}
+
+impl Store for MemoryStore {
+    fn clock(&self) -> Arc<dyn Clock> {
+        self.config.clock.clone()
+    }
+
+    fn enqueue_work_idempotent(&self, work: WorkUnit) -> Result<EnqueueResult> {
+        let idem_key = work
+            .idem_key
+            .clone()
+            .ok_or_else(|| anyhow!("work.idem_key must be set before enqueue"))?;
+        let idem_lookup = idem_lookup_key(&work.tenant, &work.topic, &idem_key);
+        let mut state = self.state()?;
+
+        // A cancelled job keeps its idem record (and receipt) but may be resubmitted.
+        if let Some(record) = state
+            .idem
+            .get(&idem_lookup)
+            .filter(|record| record.status != "cancelled")
+        {
+            if let Some(receipt_cid) = record.receipt_cid.clone() {
+                return Ok(EnqueueResult::DuplicateReceipt {
+                    work_id: record.work_id,
+                    receipt_cid,
+                });
+            }
+            return Ok(EnqueueResult::DuplicateInFlight {
+                work_id: record.work_id,
+            });
+        }
+
+        state.next_seq = state.next_seq.max(1);
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        let now = self.config.clock.now();
+        let mut work = work;
+        work.submitted_at = now;
+        let job = QueuedJob {
+            seq,
+            work: work.clone(),
+            attempt: 0,
+            accepted_at: now,
+            leased_at: None,
+            lease_expires_at: None,
+            retry_at: None,
+            failures: Vec::new(),
+        };
+        if work.is_due(now) {
+            state.push_ready(&self.config, job);
+        } else {
+            state.push_scheduled(job);
+        }
+
+        state.idem.insert(
+            idem_lookup,
+            IdemRecord {
+                tenant: work.tenant.clone(),
+                topic: work.topic.clone(),
+                idem_key,
+                work_id: work.id,
+                status: "queued".to_string(),
+                receipt_cid: None,
+                updated_at: now,
+            },
+        );
+        Ok(EnqueueResult::Enqueued {
+            seq,
+            work_id: work.id,
+        })
+    }
+
+    fn lease_next(&self, lease_ttl_ms: u64, filter: &TopicFilter) -> Result<Option<QueuedJob>> {
+        let mut state = self.state()?;
+        let now = self.config.clock.now();
+        state.promote_due(&self.config, now);
+
+        let heads = state
+            .ready_queues
+            .iter()
+            .filter(|(queue, _)| filter.matches(queue_topic(queue)))
+            .filter_map(|(queue, entries)| entries.first().map(|(rank, _)| (queue, *rank)))
+            .collect::<Vec<_>>();
+        // Fairness only applies among queues whose head has the most urgent priority.
+        let Some(best) = heads.iter().map(|(_, rank)| *rank).min() else {
+            return Ok(None);
+        };
+        let candidates = heads
+            .into_iter()
+            .filter(|(_, rank)| *rank == best)
+            .map(|(queue, _)| {
+                let weight = self.config.scheduler.weight_for(queue_topic(queue));
+                (queue.clone(), weight)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(queue) = state.scheduler.pick(&candidates) else {
+            return Ok(None);
+        };
+        let Some(entries) = state.ready_queues.get_mut(&queue) else {
+            return Ok(None);
+        };
+        let Some((_, seq)) = entries.pop_first() else {
+            return Ok(None);
+        };
+        if entries.is_empty() {
+            state.ready_queues.remove(&queue);
+        }
+        let Some(mut job) = state.ready.remove(&seq) else {
+            return Ok(None);
+        };
+
+        job.attempt += 1;
+        job.leased_at = Some(now);
+        job.lease_expires_at = Some(now + Duration::milliseconds(lease_ttl_ms as i64));
+        state.leased.insert(job.seq, job.clone());
+        Ok(Some(job))
+    }
+
+    fn heartbeat_lease(&self, seq: u64, attempt: u32, lease_ttl_ms: u64) -> Result<bool> {
+        let mut state = self.state()?;
+        let Some(job) = state
+            .leased
+            .get_mut(&seq)
+            .filter(|job| job.attempt == attempt)
+        else {
+            return Ok(false);
+        };
+        job.lease_expires_at =
+            Some(self.config.clock.now() + Duration::milliseconds(lease_ttl_ms as i64));
+        Ok(true)
+    }
+
+    fn complete_leased(
+        &self,
+        seq: u64,
+        attempt: u32,
+        receipt: &Receipt,
+        values: &[&Value],
+    ) -> Result<bool> {
+        let encoded = encode_blobs(values)?;
+        let mut state = self.state()?;
+        if state.held_lease(seq, attempt).is_none() {
+            return Ok(false);
+        }
+        state.leased.remove(&seq);
+        state.store_receipt(receipt, encoded, self.config.clock.now());
+        Ok(true)
+    }
+
+    fn reassign_expired_leases(&self) -> Result<ReassignReport> {
+        let mut state = self.state()?;
+        let now = self.config.clock.now();
+        let expired = state
+            .leased
+            .values()
+            .filter(|job| job.lease_expires_at.is_some_and(|expires| expires <= now))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut report = ReassignReport::default();
+        for mut job in expired {
+            job.record_failure(LEASE_EXPIRED_ERROR, now);
+            let policy = self.config.retry.policy_for(&job.work.topic);
+            if !policy.should_retry(job.attempt, LEASE_EXPIRED_ERROR) {
+                let grace = job
+                    .leased_at
+                    .zip(job.lease_expires_at)
+                    .map(|(leased, expires)| expires - leased)
+                    .unwrap_or_else(|| Duration::seconds(15));
+                job.lease_expires_at = Some(now + grace);
+                state.leased.insert(job.seq, job.clone());
+                report.exhausted.push(job);
+                continue;
+            }
+
+            state.leased.remove(&job.seq);
+            job.leased_at = None;
+            job.lease_expires_at = None;
+            job.retry_at =
+                Some(now + Duration::milliseconds(policy.backoff.delay_ms(job.attempt) as i64));
+            state.push_scheduled(job.clone());
+            report.reassigned += 1;
+            report.requeued.push(job);
+        }
+
+        state.inc_counter(META_REASSIGNS_TOTAL, report.reassigned);
+        Ok(report)
+    }
+
+    fn fail_leased(&self, seq: u64, attempt: u32, error: &str) -> Result<FailureOutcome> {
+        let mut state = self.state()?;
+        let now = self.config.clock.now();
+        let Some(job) = state
+            .leased
+            .get_mut(&seq)
+            .filter(|job| job.attempt == attempt)
+        else {
+            return Ok(FailureOutcome::LeaseLost);
+        };
+        job.record_failure(error, now);
+
+        let policy = self.config.retry.policy_for(&job.work.topic);
+        if !policy.should_retry(job.attempt, error) {
+            return Ok(FailureOutcome::Exhausted { job: job.clone() });
+        }
+
+        let retry_at = now + Duration::milliseconds(policy.backoff.delay_ms(job.attempt) as i64);
+        let mut job = state
+            .leased
+            .remove(&seq)
+            .ok_or_else(|| anyhow!("leased job {seq} not found"))?;
+        job.leased_at = None;
+        job.lease_expires_at = None;
+        job.retry_at = Some(retry_at);
+        state.push_scheduled(job.clone());
+        Ok(FailureOutcome::Retrying { job, retry_at })
+    }
+
+    fn cancel_work(&self, work_id: Uuid) -> Result<CancelOutcome> {
+        let state = self.state()?;
+        if let Some(job) = MemoryState::find(&state.ready, work_id)
+            .or_else(|| MemoryState::find(&state.scheduled, work_id))
+        {
+            return Ok(CancelOutcome::Queued(job));
+        }
+        Ok(MemoryState::find(&state.leased, work_id)
+            .map_or(CancelOutcome::NotFound, CancelOutcome::Leased))
+    }
+
+    fn cancel_queued(&self, job: &QueuedJob, receipt: &Receipt, values: &[&Value]) -> Result<bool> {
+        let encoded = encode_blobs(values)?;
+        let mut state = self.state()?;
+        let same = |found: &&QueuedJob| found.attempt == job.attempt;
+        if let Some(found) = state.ready.get(&job.seq).filter(same).cloned() {
+            state.remove_ready(&self.config, &found);
+        } else if let Some(found) = state.scheduled.get(&job.seq).filter(same).cloned() {
+            state.remove_scheduled(&found);
+        } else {
+            return Ok(false);
+        }
+        state.store_receipt(receipt, encoded, self.config.clock.now());
+        Ok(true)
+    }
+
+    fn get_leased_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>> {
+        Ok(MemoryState::find(&self.state()?.leased, work_id))
+    }
+
+    fn get_queued_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>> {
+        let state = self.state()?;
+        Ok(MemoryState::find(&state.ready, work_id)
+            .or_else(|| MemoryState::find(&state.scheduled, work_id)))
+    }
+
+    fn dead_letter(
+        &self,
+        seq: u64,
+        attempt: u32,
+        reason: &str,
+        receipt: &Receipt,
+        values: &[&Value],
+    ) -> Result<bool> {
+        let encoded = encode_blobs(values)?;
+        let mut state = self.state()?;
+        let Some(job) = state.held_lease(seq, attempt).cloned() else {
+            return Ok(false);
+        };
+        state.leased.remove(&seq);
+        let now = self.config.clock.now();
+        state.store_receipt(receipt, encoded, now);
+        state.dead_letters.insert(
+            seq,
+            DeadLetter {
+                job,
+                reason: reason.to_string(),
+                dead_at: now,
+                receipt_cid: Some(receipt.cid.clone()),
+            },
+        );
+        Ok(true)
+    }
+
+    fn list_dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        Ok(self.state()?.dead_letters.values().cloned().collect())
+    }
+
+    fn get_dead_letter(&self, seq: u64) -> Result<Option<DeadLetter>> {
+        Ok(self.state()?.dead_letters.get(&seq).cloned())
+    }
+
+    fn requeue_dead_letter(&self, seq: u64) -> Result<Option<QueuedJob>> {
+        let mut state = self.state()?;
+        let Some(letter) = state.dead_letters.remove(&seq) else {
+            return Ok(None);
+        };
+
+        let mut job = letter.job;
+        job.attempt = 0;
+        job.leased_at = None;
+        job.lease_expires_at = None;
+        job.retry_at = None;
+        state.push_ready(&self.config, job.clone());
+
+        if let Some(idem_key) = job.work.idem_key.as_deref() {
+            let key = idem_lookup_key(&job.work.tenant, &job.work.topic, idem_key);
+            if let Some(record) = state.idem.get_mut(&key) {
+                record.status = "queued".to_string();
+                record.receipt_cid = None;
+                record.updated_at = self.config.clock.now();
+            }
+        }
+        Ok(Some(job))
+    }
+
+    fn purge_dead_letters(&self, seq: Option<u64>) -> Result<usize> {
+        let mut state = self.state()?;
+        Ok(match seq {
+            Some(seq) => usize::from(state.dead_letters.remove(&seq).is_some()),
+            None => std::mem::take(&mut state.dead_letters).len(),
+        })
+    }
+
+    fn put_receipt_with_blobs(&self, receipt: &Receipt, values: &[&Value]) -> Result<()> {
+        let encoded = encode_blobs(values)?;
+        let now = self.config.clock.now();
+        self.state()?.store_receipt(receipt, encoded, now);
+        Ok(())
+    }
+
+    fn get_receipt(&self, cid: &str) -> Result<Option<Receipt>> {
+        Ok(self.state()?.receipts.get(cid).cloned())
+    }
+
+    fn put_blob(&self, value: &Value) -> Result<String> {
+        let (cid, bytes) = blobs::encode(value)?;
+        self.state()?.blobs.entry(cid.clone()).or_insert(bytes);
+        Ok(cid)
+    }
+
+    fn get_blob(&self, cid: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.state()?.blobs.get(cid).cloned())
+    }
+
+    fn artifact_refs(&self, cid: &str) -> Result<u64> {
+        Ok(self
+            .state()?
+            .artifact_refs
+            .range((cid.to_string(), String::new())..)
+            .take_while(|(artifact, _)| artifact == cid)
+            .count() as u64)
+    }
+
+    fn live_artifacts(&self) -> Result<BTreeSet<String>> {
+        Ok(self
+            .state()?
+            .artifact_refs
+            .iter()
+            .map(|(artifact, _)| artifact.clone())
+            .collect())
+    }
+
+    fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage> {
+        let state = self.state()?;
+        let dimension = query.dimension();
+        let (start, end) = query.range();
+        let start = start.map(|(ms, cid)| (dimension.clone(), ms, cid.to_string()));
+        let end = end.map(|(ms, cid)| (dimension.clone(), ms, cid.to_string()));
+        let rows = state
+            .receipt_index
+            .range((start, end))
+            .filter_map(|(_, _, cid)| state.receipts.get(cid).cloned())
+            .map(Ok);
+        query.collect_page(rows)
+    }
+
+    fn count_receipts(&self) -> Result<u64> {
+        Ok(self.state()?.receipts.len() as u64)
+    }
+
+    fn list_receipts(&self) -> Result<Vec<Receipt>> {
+        Ok(self.state()?.receipts.values().cloned().collect())
+    }
+
+    fn purge_receipts(&self, receipts: &[Receipt]) -> Result<RetentionPurgeReport> {
+        let mut state = self.state()?;
+        let mut report = RetentionPurgeReport::default();
+        for receipt in receipts {
+            if state.receipts.remove(&receipt.cid).is_some() {
+                report.deleted_receipts += 1;
+            }
+            let created_at_ms = receipt.created_at.timestamp_millis();
+            for dimension in receipt_index::dimensions(receipt) {
+                state
+                    .receipt_index
+                    .remove(&(dimension, created_at_ms, receipt.cid.clone()));
+            }
+            let key = idem_lookup_key(&receipt.tenant, &receipt.topic, &receipt.idem_key);
+            if state.idem.remove(&key).is_some() {
+                report.deleted_idem_keys += 1;
+            }
+            for blob in blobs::referenced(receipt) {
+                state
+                    .blob_refs
+                    .remove(&(blob.to_string(), receipt.cid.clone()));
+                let still_used = state
+                    .blob_refs
+                    .range((blob.to_string(), String::new())..)
+                    .next()
+                    .is_some_and(|(referenced, _)| referenced == blob);
+                if !still_used && state.blobs.remove(blob).is_some() {
+                    report.deleted_blobs += 1;
+                }
+            }
+            for artifact in artifacts::referenced(receipt) {
+                state
+                    .artifact_refs
+                    .remove(&(artifact.to_string(), receipt.cid.clone()));
+            }
+            if let Some(history) = state.work_events.remove(&receipt.work_id) {
+                report.deleted_work_events += history.len();
+            }
+        }
+        Ok(report)
+    }
+
+    fn append_work_event(&self, event: &mut StreamEvent) -> Result<()> {
+        let mut state = self.state()?;
+        state.next_event_seq = state.next_event_seq.max(1);
+        event.seq = state.next_event_seq;
+        state.next_event_seq += 1;
+        event.at = self.config.clock.now();
+        state.event_log.insert(event.seq, event.clone());
+        state
+            .work_events
+            .entry(event.work_id)
+            .or_default()
+            .push(event.clone());
+        Ok(())
+    }
+
+    fn next_event_seq(&self) -> Result<u64> {
+        Ok(self.state()?.next_event_seq.max(1))
+    }
+
+    fn events_after(&self, after: u64, limit: usize) -> Result<Vec<StreamEvent>> {
+        Ok(self
+            .state()?
+            .event_log
+            .range(after.saturating_add(1)..)
+            .take(limit)
+            .map(|(_, event)| event.clone())
+            .collect())
+    }
+
+    fn trim_event_log(&self) -> Result<u64> {
+        let retention = self.config.events;
+        let cutoff = retention.max_age.map(|age| self.config.clock.now() - age);
+        let mut state = self.state()?;
+        let mut over = retention
+            .max_events
+            .map_or(0, |max| (state.event_log.len() as u64).saturating_sub(max));
+        let mut trimmed = 0;
+        while let Some(entry) = state.event_log.first_entry() {
+            if over > 0 {
+                over -= 1;
+            } else if cutoff.is_none_or(|cutoff| entry.get().at >= cutoff) {
+                break;
+            }
+            entry.remove();
+            trimmed += 1;
+        }
+        if let Some(cutoff) = cutoff {
+            state.work_events.retain(|_, history| {
+                history.retain(|event| event.at >= cutoff);
+                !history.is_empty()
+            });
+        }
+        Ok(trimmed)
+    }
+
+    fn work_events(&self, work_id: Uuid) -> Result<Vec<StreamEvent>> {
+        Ok(self
+            .state()?
+            .work_events
+            .get(&work_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn increment_status_counter(&self, status: WorkStatus, labels: &MetricLabels) -> Result<()> {
+        let mut state = self.state()?;
+        state.inc_counter(status_meta_key(status), 1);
+        series::record_status(&mut state.series, &self.config.metrics, labels, status)
+    }
+
+    fn observe_timings(
+        &self,
+        labels: &MetricLabels,
+        ttft_ms: u64,
+        ttr_ms: u64,
+        stages: &BTreeMap<String, u64>,
+    ) -> Result<()> {
+        let mut state = self.state()?;
+        state.inc_counter(META_TTFT_SUM_MS, ttft_ms);
+        state.inc_counter(META_TTFT_COUNT, 1);
+        state.inc_counter(META_TTR_SUM_MS, ttr_ms);
+        state.inc_counter(META_TTR_COUNT, 1);
+        state.observe_histogram("ttft", &TTFT_BUCKETS_MS, ttft_ms);
+        state.observe_histogram("ttr", &TTR_BUCKETS_MS, ttr_ms);
+        series::record_timings(
+            &mut state.series,
+            &self.config.metrics,
+            labels,
+            ttft_ms,
+            ttr_ms,
+            stages,
+        )
+    }
+
+    fn queue_metrics(&self) -> Result<QueueMetrics> {
+        let state = self.state()?;
+        let mut tally = Tally::new();
+        for (gauge, jobs) in [
+            (Gauge::Ready, &state.ready),
+            (Gauge::Scheduled, &state.scheduled),
+            (Gauge::Leased, &state.leased),
+        ] {
+            for job in jobs.values() {
+                counts::tally_job(&mut tally, gauge, job);
+            }
+        }
+        for letter in state.dead_letters.values() {
+            counts::tally_job(&mut tally, Gauge::DeadLetters, &letter.job);
+        }
+        for receipt in state.receipts.values() {
+            counts::tally_receipt(&mut tally, receipt);
+        }
+        let by_tenant_topic = counts::breakdown(&tally);
+        let labelled = series::collect(
+            &self.config.metrics,
+            state
+                .series
+                .series
+                .iter()
+                .map(|(key, value)| (key.clone(), *value)),
+            &state.series.labels,
+            &by_tenant_topic,
+        );
+        Ok(QueueMetrics {
+            queue_depth: state.ready.len(),
+            scheduled_depth: state.scheduled.len(),
+            leased_depth: state.leased.len(),
+            dead_letter_depth: state.dead_letters.len(),
+            receipts_total: state.receipts.len(),
+            by_tenant_topic,
+            labelled,
+            ..counter_metrics(|key| Ok(state.counter(key)))?
+        })
+    }
+
+    fn snapshot(&self) -> Result<Snapshot> {
+        let state = self.state()?;
+        let mut counters = state
+            .counters
+            .iter()
+            .map(|(key, value)| (key.clone(), *value))
+            .collect::<BTreeMap<_, _>>();
+        counters.insert(META_NEXT_SEQ.to_string(), state.next_seq.max(1));
+        counters.insert(META_NEXT_EVENT_SEQ.to_string(), state.next_event_seq.max(1));
+        Ok(Snapshot {
+            ready_jobs: state.ready.values().cloned().collect(),
+            scheduled_jobs: state.scheduled.values().cloned().collect(),
+            leased_jobs: state.leased.values().cloned().collect(),
+            dead_letters: state.dead_letters.values().cloned().collect(),
+            receipts: state.receipts.values().cloned().collect(),
+            idem_keys: state.idem.values().cloned().collect(),
+            blobs: state
+                .blobs
+                .iter()
+                .map(|(cid, bytes)| Ok((cid.clone(), serde_json::from_slice(bytes)?)))
+                .collect::<Result<_>>()?,
+            counters,
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> Result<()> {
+        let mut state = self.state()?;
+        if !(state.ready.is_empty()
+            && state.scheduled.is_empty()
+            && state.leased.is_empty()
+            && state.dead_letters.is_empty()
+            && state.receipts.is_empty()
+            && state.idem.is_empty()
+            && state.blobs.is_empty())
+        {
+            return Err(anyhow!("restore needs an empty store"));
+        }
+        for job in &snapshot.ready_jobs {
+            state.push_ready(&self.config, job.clone());
+        }
+        for job in &snapshot.scheduled_jobs {
+            state.push_scheduled(job.clone());
+        }
+        for job in &snapshot.leased_jobs {
+            state.leased.insert(job.seq, job.clone());
+        }
+        for letter in &snapshot.dead_letters {
+            state.dead_letters.insert(letter.job.seq, letter.clone());
+        }
+        for receipt in &snapshot.receipts {
+            let created_at_ms = receipt.created_at.timestamp_millis();
+            for dimension in receipt_index::dimensions(receipt) {
+                state
+                    .receipt_index
+                    .insert((dimension, created_at_ms, receipt.cid.clone()));
+            }
+            for blob in blobs::referenced(receipt) {
+                state
+                    .blob_refs
+                    .insert((blob.to_string(), receipt.cid.clone()));
+            }
+            for artifact in artifacts::referenced(receipt) {
+                state
+                    .artifact_refs
+                    .insert((artifact.to_string(), receipt.cid.clone()));
+            }
+            state.receipts.insert(receipt.cid.clone(), receipt.clone());
+        }
+        for (cid, blob) in &snapshot.blobs {
+            let (stored, bytes) = blobs::encode(blob)?;
+            if stored != *cid {
+                return Err(anyhow!("blob {cid} hashes to {stored}"));
+            }
+            state.blobs.insert(stored, bytes);
+        }
+        for record in &snapshot.idem_keys {
+            let key = idem_lookup_key(&record.tenant, &record.topic, &record.idem_key);
+            state.idem.insert(key, record.clone());
+        }
+        for (key, value) in restorable_counters(&snapshot.counters) {
+            match key {
+                META_NEXT_SEQ => state.next_seq = value,
+                META_NEXT_EVENT_SEQ => state.next_event_seq = value,
+                _ => {
+                    state.counters.insert(key.to_string(), value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn encode_blobs(values: &[&Value]) -> Result<Vec<(String, Vec<u8>)>> {
+    values.iter().map(|value| blobs::encode(value)).collect()
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result, bail};
+use aurea_core::Receipt;
+use redb::{Database, ReadableTable, TableDefinition, TableError, WriteTransaction};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    DEAD_LETTERS, DeadLetter, IDEM_KEYS, IdemRecord, LEASED_JOBS, META, META_READY_PARTITION,
+    QueuedJob, READY_INDEX, READY_JOBS, READY_QUEUES, RECEIPT_INDEX, RECEIPTS, SCHEDULED_JOBS,
+    artifacts, counts, history, index_receipt, rebuild_leased_index,
+};
+
+/// Layout version written by this build.
+pub const SCHEMA_VERSION: u64 = 7;
+/// Version assumed for databases written before the layout was versioned.
+const UNVERSIONED: u64 = 1;
+
+pub(crate) const META_SCHEMA_VERSION: &str = "schema_version";
+
+struct Migration {
+    version: u64,
+    description: &'static str,
+    apply: fn(&WriteTransaction) -> Result<()>,
+}
+
+/// Applied in order; each one runs in its own transaction together with the
+/// version bump, so an interrupted upgrade resumes where it stopped.
+const MIGRATIONS: [Migration; 6] = [
+    Migration {
+        version: 2,
+        description: "re-encode job, dead letter, idem and receipt rows with the current types",
+        apply: reencode_rows,
+    },
+    Migration {
+        version: 3,
+        description: "rebuild the ready index keyed by queue, priority and seq",
+        apply: reset_ready_index,
+    },
+    Migration {
+        version: 4,
+        description: "index receipts by time, tenant, topic, status and work id",
+        apply: index_receipts,
+    },
+    Migration {
+        version: 5,
+        description: "count jobs, dead letters and receipts per tenant and topic",
+        apply: counts::recount,
+    },
+    Migration {
+        version: 6,
+        description: "record which receipts reference each artifact",
+        apply: artifacts::rebuild_refs,
+    },
+    Migration {
+        version: 7,
+        description: "index leased jobs by work id and key work event history by event seq",
+        apply: index_leases_and_history,
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStep {
+    pub version: u64,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    pub from: u64,
+    pub to: u64,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl MigrationPlan {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+pub(crate) fn schema_version(db: &Database) -> Result<u64> {
+    let read = db.begin_read().context("begin read tx failed")?;
+    let meta = match read.open_table(META) {
+        Ok(meta) => meta,
+        Err(TableError::TableDoesNotExist(_)) => return Ok(UNVERSIONED),
+        Err(err) => return Err(err).context("open meta failed"),
+    };
+    Ok(meta
+        .get(META_SCHEMA_VERSION)
+        .context("read schema version failed")?
+        .map_or(UNVERSIONED, |v| v.value()))
+}
+
+pub(crate) fn plan(db: &Database) -> Result<MigrationPlan> {
+    let from = schema_version(db)?;
+    if from > SCHEMA_VERSION {
+        bail!(
+            "database schema v{from} is newer than this build supports (v{SCHEMA_VERSION}); \
+             upgrade aurea before opening it"
+        );
+    }
+    Ok(MigrationPlan {
+        from,
+        to: SCHEMA_VERSION,
+        steps: MIGRATIONS
+            .iter()
+            .filter(|m| m.version > from)
+            .map(|m| MigrationStep {
+                version: m.version,
+                description: m.description,
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn migrate(db: &Database) -> Result<MigrationPlan> {
+    let plan = plan(db)?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > plan.from) {
+        let write = db.begin_write().context("begin migration tx failed")?;
+        (migration.apply)(&write)
+            .with_context(|| format!("migration to schema v{} failed", migration.version))?;
+        stamp(&write, migration.version)?;
+        write.commit().context("commit migration tx failed")?;
+    }
+    Ok(plan)
+}
+
+pub(crate) fn stamp(write: &WriteTransaction, version: u64) -> Result<()> {
+    let mut meta = write.open_table(META).context("open meta failed")?;
+    meta.insert(META_SCHEMA_VERSION, version)
+        .context("write schema version failed")?;
+    Ok(())
+}
+
+fn index_leases_and_history(write: &WriteTransaction) -> Result<()> {
+    rebuild_leased_index(write)?;
+    history::rekey(write)
+}
+
+fn reencode_rows(write: &WriteTransaction) -> Result<()> {
+    reencode_by_seq::<QueuedJob>(write, READY_JOBS)?;
+    reencode_by_seq::<QueuedJob>(write, SCHEDULED_JOBS)?;
+    reencode_by_seq::<QueuedJob>(write, LEASED_JOBS)?;
+    reencode_by_seq::<DeadLetter>(write, DEAD_LETTERS)?;
+    reencode_by_key::<IdemRecord>(write, IDEM_KEYS)?;
+    reencode_by_key::<Receipt>(write, RECEIPTS)
+}
+
+fn reencode_by_seq<T: Serialize + DeserializeOwned>(
+    write: &WriteTransaction,
+    definition: TableDefinition<u64, &[u8]>,
+) -> Result<()> {
+    let mut table = write
+        .open_table(definition)
+        .with_context(|| format!("open {definition} failed"))?;
+    let mut rows = Vec::new();
+    for entry in table.iter().context("iterate rows failed")? {
+        let (key, value) = entry.context("read row failed")?;
+        let row: T = serde_json::from_slice(value.value())
+            .with_context(|| format!("row {} of {definition} is unreadable", key.value()))?;
+        rows.push((key.value(), row));
+    }
+    for (key, row) in rows {
+        let bytes = serde_json::to_vec(&row).context("serialize row failed")?;
+        table
+            .insert(key, bytes.as_slice())
+            .context("rewrite row failed")?;
+    }
+    Ok(())
+}
+
+fn reencode_by_key<T: Serialize + DeserializeOwned>(
+    write: &WriteTransaction,
+    definition: TableDefinition<&str, &[u8]>,
+) -> Result<()> {
+    let mut table = write
+        .open_table(definition)
+        .with_context(|| format!("open {definition} failed"))?;
+    let mut rows = Vec::new();
+    for entry in table.iter().context("iterate rows failed")? {
+        let (key, value) = entry.context("read row failed")?;
+        let row: T = serde_json::from_slice(value.value())
+            .with_context(|| format!("row {} of {definition} is unreadable", key.value()))?;
+        rows.push((key.value().to_string(), row));
+    }
+    for (key, row) in rows {
+        let bytes = serde_json::to_vec(&row).context("serialize row failed")?;
+        table
+            .insert(key.as_str(), bytes.as_slice())
+            .context("rewrite row failed")?;
+    }
+    Ok(())
+}
+
+/// Drops the ready index whatever its key layout; `ensure_ready_index`
+/// rebuilds it from `ready_jobs` on open.
+fn reset_ready_index(write: &WriteTransaction) -> Result<()> {
+    write
+        .delete_table(READY_INDEX)
+        .context("drop ready_index failed")?;
+    write
+        .delete_table(READY_QUEUES)
+        .context("drop ready_queues failed")?;
+    let mut meta = write.open_table(META).context("open meta failed")?;
+    meta.remove(META_READY_PARTITION)
+        .context("clear ready partition failed")?;
+    Ok(())
+}
+
+pub(crate) fn index_receipts(write: &WriteTransaction) -> Result<()> {
+    let receipts = write.open_table(RECEIPTS).context("open receipts failed")?;
+    let mut index = write
+        .open_table(RECEIPT_INDEX)
+        .context("open receipt_index failed")?;
+    index
+        .retain(|_, _| false)
+        .context("clear receipt_index failed")?;
+    for row in receipts.iter().context("iterate receipts failed")? {
+        let (_, value) = row.context("read receipt row failed")?;
+        let receipt: Receipt =
+            serde_json::from_slice(value.value()).context("deserialize receipt failed")?;
+        index_receipt(&mut index, &receipt)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::ops::Bound;
+use std::str::FromStr;
+
+use anyhow::{Context, anyhow};
+use aurea_core::{Receipt, WorkStatus};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::status_label;
+
+/// `(created_at ms, cid)` bound within one index dimension.
+pub(crate) type IndexBound<'a> = Bound<(i64, &'a str)>;
+
+/// Dimension under which every receipt is indexed, ordered by creation time.
+const ALL: &str = "all";
+/// Index entries per receipt.
+pub(crate) const DIMENSIONS: usize = 5;
+
+/// Filters for [`crate::RedbStore::query_receipts`]. Results are ordered by
+/// `created_at`, then CID; `to` is exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptQuery {
+    pub tenant: Option<String>,
+    pub topic: Option<String>,
+    pub status: Option<WorkStatus>,
+    pub work_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub after: Option<ReceiptCursor>,
+    /// `None` returns every match.
+    pub limit: Option<usize>,
+}
+
+impl ReceiptQuery {
+    /// The most selective index dimension for this query.
+    pub(crate) fn dimension(&self) -> String {
+        if let Some(work_id) = self.work_id {
+            work_dimension(work_id)
+        } else if let Some(tenant) = &self.tenant {
+            dimension("tenant", tenant)
+        } else if let Some(topic) = &self.topic {
+            dimension("topic", topic)
+        } else if let Some(status) = self.status {
+            dimension("status", status_label(status))
+        } else {
+            ALL.to_string()
+        }
+    }
+
+    /// Bounds of the scan within the query's dimension.
+    pub(crate) fn range(&self) -> (IndexBound<'_>, IndexBound<'_>) {
+        let from_ms = self.from.map_or(i64::MIN, |at| at.timestamp_millis());
+        let start = match &self.after {
+            Some(cursor) if cursor.created_at_ms >= from_ms => {
+                Bound::Excluded((cursor.created_at_ms, cursor.cid.as_str()))
+            }
+            _ => Bound::Included((from_ms, "")),
+        };
+        let to_ms = self.to.map_or(i64::MAX, |at| at.timestamp_millis());
+        (start, Bound::Excluded((to_ms, "")))
+    }
+
+    /// Filters indexed receipts, in index order, into one page.
+    pub(crate) fn collect_page(
+        &self,
+        receipts: impl Iterator<Item = anyhow::Result<Receipt>>,
+    ) -> anyhow::Result<ReceiptPage> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        let mut page = ReceiptPage::default();
+        for receipt in receipts {
+            let receipt = receipt?;
+            if !self.matches(&receipt) {
+                continue;
+            }
+            page.receipts.push(receipt);
+            if page.receipts.len() == limit {
+                page.next = page.receipts.last().map(ReceiptCursor::of);
+                break;
+            }
+        }
+        Ok(page)
+    }
+
+    fn matches(&self, receipt: &Receipt) -> bool {
+        self.tenant.as_ref().is_none_or(|t| &receipt.tenant == t)
+            && self.topic.as_ref().is_none_or(|t| &receipt.topic == t)
+            && self.status.is_none_or(|s| receipt.status == s)
+            && self.work_id.is_none_or(|id| receipt.work_id == id)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptPage {
+    pub receipts: Vec<Receipt>,
+    /// Set when more receipts may follow; pass it back as `after`.
+    pub next: Option<ReceiptCursor>,
+}
+
+/// Position after the last receipt of a page. Rendered as `<ms>.<cid>`;
+/// clients should treat it as opaque.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptCursor {
+    pub created_at_ms: i64,
+    pub cid: String,
+}
+
+impl ReceiptCursor {
+    pub fn of(receipt: &Receipt) -> Self {
+        Self {
+            created_at_ms: receipt.created_at.timestamp_millis(),
+            cid: receipt.cid.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ReceiptCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.created_at_ms, self.cid)
+    }
+}
+
+impl FromStr for ReceiptCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let (ms, cid) = raw
+            .split_once('.')
+            .filter(|(_, cid)| !cid.is_empty())
+            .ok_or_else(|| anyhow!("invalid receipt cursor `{raw}`"))?;
+        Ok(Self {
+            created_at_ms: ms
+                .parse()
+                .with_context(|| format!("invalid receipt cursor `{raw}`"))?,
+            cid: cid.to_string(),
+        })
+    }
+}
+
+/// Index dimensions a receipt is filed under.
+pub(crate) fn dimensions(receipt: &Receipt) -> [String; DIMENSIONS] {
+    [
+        ALL.to_string(),
+        dimension("tenant", &receipt.tenant),
+        dimension("topic", &receipt.topic),
+        dimension("status", status_label(receipt.status)),
+        work_dimension(receipt.work_id),
+    ]
+}
+
+fn dimension(kind: &str, value: &str) -> String {
+    format!("{kind}\u{001F}{value}")
+}
+
+fn work_dimension(work_id: Uuid) -> String {
+    dimension("work", &work_id.to_string())
+}
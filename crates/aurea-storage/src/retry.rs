@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use crate::scheduler::topic_family;
+
+pub const LEASE_EXPIRED_ERROR: &str = "lease expired";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial_ms: u64,
+    pub multiplier: u32,
+    pub max_ms: u64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial_ms: 500,
+            multiplier: 2,
+            max_ms: 60_000,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before retrying after the given (1-based) failed attempt.
+    pub fn delay_ms(&self, attempt: u32) -> u64 {
+        let factor = u64::from(self.multiplier.max(1)).saturating_pow(attempt.saturating_sub(1));
+        self.initial_ms.saturating_mul(factor).min(self.max_ms)
+    }
+}
+
+/// Decides which errors are worth another attempt. `Matching` compares error
+/// messages by substring, so `"timeout"` matches `"plugin timeout after 30s"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RetryOn {
+    #[default]
+    Any,
+    Never,
+    Matching(Vec<String>),
+}
+
+impl RetryOn {
+    pub fn allows(&self, error: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Never => false,
+            Self::Matching(patterns) => patterns.iter().any(|p| error.contains(p.as_str())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Backoff::default(),
+            retry_on: RetryOn::Any,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn should_retry(&self, attempt: u32, error: &str) -> bool {
+        attempt < self.max_attempts && self.retry_on.allows(error)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RetryConfig {
+    pub default: RetryPolicy,
+    /// Keyed by exact topic or by topic family, like scheduler weights.
+    pub topics: BTreeMap<String, RetryPolicy>,
+}
+
+impl RetryConfig {
+    pub fn policy_for(&self, topic: &str) -> &RetryPolicy {
+        self.topics
+            .get(topic)
+            .or_else(|| self.topics.get(topic_family(topic)))
+            .unwrap_or(&self.default)
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{BTreeMap, HashMap};
+
+use aurea_core::WorkUnit;
+
+const QUEUE_KEY_SEP: char = '\u{001F}';
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TopicFilter {
+    #[default]
+    All,
+    Only(Vec<String>),
+    Except(Vec<String>),
+}
+
+impl TopicFilter {
+    pub fn matches(&self, topic: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(patterns) => patterns.iter().any(|p| topic_matches(p, topic)),
+            Self::Except(patterns) => !patterns.iter().any(|p| topic_matches(p, topic)),
+        }
+    }
+}
+
+/// Matches `vcx:*` style family patterns as well as exact topics.
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+pub fn topic_family(topic: &str) -> &str {
+    topic.split(':').next().unwrap_or(topic)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueuePartition {
+    #[default]
+    Topic,
+    TenantTopic,
+}
+
+impl QueuePartition {
+    pub fn queue_key(self, work: &WorkUnit) -> String {
+        match self {
+            Self::Topic => work.topic.clone(),
+            Self::TenantTopic => format!("{}{QUEUE_KEY_SEP}{}", work.topic, work.tenant),
+        }
+    }
+
+    pub(crate) fn meta_value(self) -> u64 {
+        match self {
+            Self::Topic => 0,
+            Self::TenantTopic => 1,
+        }
+    }
+}
+
+pub(crate) fn queue_topic(queue_key: &str) -> &str {
+    queue_key
+        .split_once(QUEUE_KEY_SEP)
+        .map(|(topic, _)| topic)
+        .unwrap_or(queue_key)
+}
+
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    pub partition: QueuePartition,
+    pub default_weight: u32,
+    /// Keyed by exact topic or by topic family (`vcx`, `echo`, ...).
+    pub weights: BTreeMap<String, u32>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            partition: QueuePartition::Topic,
+            default_weight: 1,
+            weights: BTreeMap::new(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn weight_for(&self, topic: &str) -> u32 {
+        self.weights
+            .get(topic)
+            .or_else(|| self.weights.get(topic_family(topic)))
+            .copied()
+            .unwrap_or(self.default_weight)
+            .max(1)
+    }
+}
+
+/// Smooth weighted round-robin over the non-empty ready queues.
+#[derive(Debug, Default)]
+pub(crate) struct FairScheduler {
+    current: HashMap<String, i64>,
+}
+
+impl FairScheduler {
+    /// `candidates` must be sorted by queue key so ties resolve deterministically.
+    pub(crate) fn pick(&mut self, candidates: &[(String, u32)]) -> Option<String> {
+        if candidates.is_empty() {
+            return None;
+        }
+
+        self.current
+            .retain(|key, _| candidates.iter().any(|(k, _)| k == key));
+
+        let total: i64 = candidates.iter().map(|(_, w)| i64::from(*w)).sum();
+        let mut best: Option<(&str, i64)> = None;
+        for (key, weight) in candidates {
+            let slot = self.current.entry(key.clone()).or_insert(0);
+            *slot += i64::from(*weight);
+            if best.is_none_or(|(_, score)| *slot > score) {
+                best = Some((key.as_str(), *slot));
+            }
+        }
+
+        let (key, _) = best?;
+        if let Some(slot) = self.current.get_mut(key) {
+            *slot -= total;
+        }
+        Some(key.to_string())
+    }
+}
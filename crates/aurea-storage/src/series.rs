@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use aurea_core::WorkStatus;
+use redb::{ReadableTable, Table, TableDefinition};
+
+use crate::{QueueCounts, TTFT_BUCKETS_MS, TTR_BUCKETS_MS, status_label, topic_family};
+
+/// `(series, label, label, field)` → counter. See [`SeriesKey`].
+pub(crate) const SERIES: TableDefinition<(&str, &str, &str, &str), u64> =
+    TableDefinition::new("metric_series");
+/// `(label kind, value)` for every label value admitted under the caps.
+pub(crate) const LABELS: TableDefinition<(&str, &str), ()> = TableDefinition::new("metric_labels");
+
+/// Label value that absorbs everything past a cardinality cap. A real value
+/// that would read the same is stored with a leading `_`; see [`label_value`].
+pub const OTHER_LABEL: &str = "other";
+
+const STAGE_BUCKETS_MS: [u64; 8] = [10, 50, 100, 250, 500, 1000, 5000, 30000];
+
+const JOBS_TOTAL: &str = "jobs_total";
+const TTFT: &str = "ttft_ms";
+const TTR: &str = "ttr_ms";
+const STAGE: &str = "stage_ms";
+
+const TENANT: &str = "tenant";
+const FAMILY: &str = "topic_family";
+const STAGE_NAME: &str = "stage";
+
+/// Distinct label values kept per label; `None` means unlimited. Values are
+/// admitted first come, first served, so a series never changes labels.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsConfig {
+    pub max_tenants: Option<usize>,
+    pub max_topic_families: Option<usize>,
+    pub max_stages: Option<usize>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            max_tenants: Some(100),
+            max_topic_families: Some(50),
+            max_stages: Some(50),
+        }
+    }
+}
+
+/// Who a metric observation belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricLabels {
+    pub tenant: String,
+    pub topic_family: String,
+}
+
+impl MetricLabels {
+    pub fn new(tenant: &str, topic: &str) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            topic_family: topic_family(topic).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// Cumulative `(le, count)` pairs, without `+Inf`.
+    pub buckets: Vec<(u64, u64)>,
+    pub sum: u64,
+    pub count: u64,
+}
+
+/// Series labelled by tenant and topic family, after the caps were applied.
+#[derive(Debug, Clone, Default)]
+pub struct LabelledMetrics {
+    /// `(tenant, topic family, status)` → total.
+    pub jobs_total: BTreeMap<(String, String, String), u64>,
+    /// `(tenant, topic family)` → ready jobs.
+    pub queue_depth: BTreeMap<(String, String), u64>,
+    pub ttft: BTreeMap<(String, String), Histogram>,
+    pub ttr: BTreeMap<(String, String), Histogram>,
+    /// `(topic family, stage)` → plugin-reported stage durations.
+    pub stages: BTreeMap<(String, String), Histogram>,
+}
+
+pub(crate) type SeriesKey = (String, String, String, String);
+
+/// Where labelled counters live; implemented by both backends so label
+/// admission and key layout stay identical.
+pub(crate) trait SeriesSink {
+    fn is_admitted(&mut self, kind: &str, value: &str) -> Result<bool>;
+    fn admitted_count(&mut self, kind: &str) -> Result<usize>;
+    fn admit(&mut self, kind: &str, value: &str) -> Result<()>;
+    fn add(&mut self, key: (&str, &str, &str, &str), delta: u64) -> Result<()>;
+}
+
+/// Keeps real label values apart from [`OTHER_LABEL`]: `other`, `_other`,
+/// `__other`, ... each gain one more leading `_`, every other value is kept
+/// as is.
+pub fn label_value(value: &str) -> String {
+    if value.trim_start_matches('_') == OTHER_LABEL {
+        format!("_{value}")
+    } else {
+        value.to_string()
+    }
+}
+
+fn admit(
+    sink: &mut impl SeriesSink,
+    kind: &str,
+    value: &str,
+    cap: Option<usize>,
+) -> Result<String> {
+    let value = &label_value(value);
+    if sink.is_admitted(kind, value)? {
+        return Ok(value.to_string());
+    }
+    if let Some(cap) = cap
+        && sink.admitted_count(kind)? >= cap
+    {
+        return Ok(OTHER_LABEL.to_string());
+    }
+    sink.admit(kind, value)?;
+    Ok(value.to_string())
+}
+
+fn admit_labels(
+    sink: &mut impl SeriesSink,
+    config: &MetricsConfig,
+    labels: &MetricLabels,
+) -> Result<(String, String)> {
+    Ok((
+        admit(sink, TENANT, &labels.tenant, config.max_tenants)?,
+        admit(
+            sink,
+            FAMILY,
+            &labels.topic_family,
+            config.max_topic_families,
+        )?,
+    ))
+}
+
+pub(crate) fn record_status(
+    sink: &mut impl SeriesSink,
+    config: &MetricsConfig,
+    labels: &MetricLabels,
+    status: WorkStatus,
+) -> Result<()> {
+    let (tenant, family) = admit_labels(sink, config, labels)?;
+    sink.add((JOBS_TOTAL, &tenant, &family, status_label(status)), 1)
+}
+
+pub(crate) fn record_timings(
+    sink: &mut impl SeriesSink,
+    config: &MetricsConfig,
+    labels: &MetricLabels,
+    ttft_ms: u64,
+    ttr_ms: u64,
+    stages: &BTreeMap<String, u64>,
+) -> Result<()> {
+    let (tenant, family) = admit_labels(sink, config, labels)?;
+    observe(sink, (TTFT, &tenant, &family), &TTFT_BUCKETS_MS, ttft_ms)?;
+    observe(sink, (TTR, &tenant, &family), &TTR_BUCKETS_MS, ttr_ms)?;
+    for (stage, ms) in stages {
+        let stage = admit(sink, STAGE_NAME, stage, config.max_stages)?;
+        observe(sink, (STAGE, &family, &stage), &STAGE_BUCKETS_MS, *ms)?;
+    }
+    Ok(())
+}
+
+fn observe(
+    sink: &mut impl SeriesSink,
+    (series, a, b): (&str, &str, &str),
+    buckets: &[u64],
+    value: u64,
+) -> Result<()> {
+    sink.add((series, a, b, "sum"), value)?;
+    sink.add((series, a, b, "count"), 1)?;
+    for le in buckets.iter().filter(|le| value <= **le) {
+        sink.add((series, a, b, &format!("le_{le}")), 1)?;
+    }
+    Ok(())
+}
+
+/// Builds the labelled view from stored series and the current depths.
+/// Under a cap, depths of tenants or families that were never admitted land
+/// in `other`.
+pub(crate) fn collect(
+    config: &MetricsConfig,
+    rows: impl IntoIterator<Item = (SeriesKey, u64)>,
+    admitted: &BTreeSet<(String, String)>,
+    depths: &BTreeMap<(String, String), QueueCounts>,
+) -> LabelledMetrics {
+    let mut out = LabelledMetrics::default();
+    for ((series, a, b, field), value) in rows {
+        let (histograms, buckets) = match series.as_str() {
+            JOBS_TOTAL => {
+                out.jobs_total.insert((a, b, field), value);
+                continue;
+            }
+            TTFT => (&mut out.ttft, &TTFT_BUCKETS_MS[..]),
+            TTR => (&mut out.ttr, &TTR_BUCKETS_MS[..]),
+            STAGE => (&mut out.stages, &STAGE_BUCKETS_MS[..]),
+            _ => continue,
+        };
+        let histogram = histograms.entry((a, b)).or_insert_with(|| Histogram {
+            buckets: buckets.iter().map(|le| (*le, 0)).collect(),
+            ..Histogram::default()
+        });
+        match field.as_str() {
+            "sum" => histogram.sum = value,
+            "count" => histogram.count = value,
+            field => {
+                let le = field
+                    .strip_prefix("le_")
+                    .and_then(|le| le.parse::<u64>().ok());
+                if let Some(slot) = histogram
+                    .buckets
+                    .iter_mut()
+                    .find(|(bound, _)| Some(*bound) == le)
+                {
+                    slot.1 = value;
+                }
+            }
+        }
+    }
+
+    let label = |kind: &str, value: &str, cap: Option<usize>| {
+        let value = label_value(value);
+        if cap.is_none() || admitted.contains(&(kind.to_string(), value.clone())) {
+            value
+        } else {
+            OTHER_LABEL.to_string()
+        }
+    };
+    for ((tenant, topic), counts) in depths {
+        if counts.ready == 0 {
+            continue;
+        }
+        let key = (
+            label(TENANT, tenant, config.max_tenants),
+            label(FAMILY, topic_family(topic), config.max_topic_families),
+        );
+        *out.queue_depth.entry(key).or_default() += counts.ready;
+    }
+    out
+}
+
+pub(crate) struct RedbSeries<'txn> {
+    pub(crate) series: Table<'txn, (&'static str, &'static str, &'static str, &'static str), u64>,
+    pub(crate) labels: Table<'txn, (&'static str, &'static str), ()>,
+}
+
+impl SeriesSink for RedbSeries<'_> {
+    fn is_admitted(&mut self, kind: &str, value: &str) -> Result<bool> {
+        Ok(self
+            .labels
+            .get((kind, value))
+            .context("read metric label failed")?
+            .is_some())
+    }
+
+    fn admitted_count(&mut self, kind: &str) -> Result<usize> {
+        let mut count = 0;
+        for entry in self
+            .labels
+            .range((kind, "")..)
+            .context("range metric labels failed")?
+        {
+            let (key, _) = entry.context("read metric label failed")?;
+            if key.value().0 != kind {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn admit(&mut self, kind: &str, value: &str) -> Result<()> {
+        self.labels
+            .insert((kind, value), ())
+            .context("admit metric label failed")?;
+        Ok(())
+    }
+
+    fn add(&mut self, key: (&str, &str, &str, &str), delta: u64) -> Result<()> {
+        let current = self
+            .series
+            .get(key)
+            .context("read metric series failed")?
+            .map_or(0, |v| v.value());
+        self.series
+            .insert(key, current.saturating_add(delta))
+            .context("write metric series failed")?;
+        Ok(())
+    }
+}
+
+/// The in-memory store's series and admitted labels.
+#[derive(Debug, Default)]
+pub(crate) struct MemorySeries {
+    pub(crate) series: BTreeMap<SeriesKey, u64>,
+    pub(crate) labels: BTreeSet<(String, String)>,
+}
+
+impl SeriesSink for MemorySeries {
+    fn is_admitted(&mut self, kind: &str, value: &str) -> Result<bool> {
+        Ok(self.labels.contains(&(kind.to_string(), value.to_string())))
+    }
+
+    fn admitted_count(&mut self, kind: &str) -> Result<usize> {
+        Ok(self.labels.iter().filter(|(k, _)| k == kind).count())
+    }
+
+    fn admit(&mut self, kind: &str, value: &str) -> Result<()> {
+        self.labels.insert((kind.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    fn add(&mut self, (series, a, b, field): (&str, &str, &str, &str), delta: u64) -> Result<()> {
+        let slot = self
+            .series
+            .entry((
+                series.to_string(),
+                a.to_string(),
+                b.to_string(),
+                field.to_string(),
+            ))
+            .or_default();
+        *slot = slot.saturating_add(delta);
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use aurea_core::{Clock, Receipt, StreamEvent, WorkStatus, WorkUnit};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    CancelOutcome, DeadLetter, EnqueueResult, FailureOutcome, MetricLabels, QueueMetrics,
+    QueuedJob, ReassignReport, ReceiptPage, ReceiptQuery, RetentionPurgeReport, Snapshot,
+    TopicFilter,
+};
+
+/// Everything the runtime needs from a storage backend. [`crate::RedbStore`]
+/// is the durable default; [`crate::MemoryStore`] keeps state in process.
+pub trait Store: Send + Sync {
+    /// The clock every timestamp this store writes comes from.
+    fn clock(&self) -> Arc<dyn Clock>;
+
+    fn enqueue_work_idempotent(&self, work: WorkUnit) -> Result<EnqueueResult>;
+
+    fn lease_next(&self, lease_ttl_ms: u64, filter: &TopicFilter) -> Result<Option<QueuedJob>>;
+
+    /// Pushes the lease expiry of a running job forward. Returns `false` when the
+    /// job is no longer leased under `attempt` (expired and handed out again).
+    fn heartbeat_lease(&self, seq: u64, attempt: u32, lease_ttl_ms: u64) -> Result<bool>;
+
+    /// Releases a finished job and stores its final receipt (with `blobs`, as
+    /// in [`Store::put_receipt_with_blobs`]) in the same transaction. Returns
+    /// `false`, storing nothing, when the job is no longer leased under
+    /// `attempt`: its lease expired and another worker owns the job now.
+    fn complete_leased(
+        &self,
+        seq: u64,
+        attempt: u32,
+        receipt: &Receipt,
+        blobs: &[&Value],
+    ) -> Result<bool>;
+
+    /// Schedules expired leases that still have attempts left for a retry after
+    /// the topic's backoff, like [`Store::fail_leased`]. Exhausted jobs get
+    /// a fresh lease and are returned so the runtime can issue their final
+    /// receipt; if it never does, they come back on a later sweep.
+    fn reassign_expired_leases(&self) -> Result<ReassignReport>;
+
+    /// Records a failed attempt of a leased job and either schedules the retry
+    /// with backoff or reports the job as exhausted. An attempt that lost its
+    /// lease gets [`FailureOutcome::LeaseLost`] and changes nothing.
+    fn fail_leased(&self, seq: u64, attempt: u32, error: &str) -> Result<FailureOutcome>;
+
+    /// Finds the job for `work_id` and reports whether it is still queued or
+    /// scheduled, or leased. Nothing is removed: see [`Store::cancel_queued`].
+    fn cancel_work(&self, work_id: Uuid) -> Result<CancelOutcome>;
+
+    /// Removes a queued or scheduled job and stores its `cancelled` receipt in
+    /// the same transaction, so the job never disappears without one. Returns
+    /// `false`, storing nothing, when `job` has been leased (or has otherwise
+    /// moved) since [`Store::cancel_work`] reported it.
+    fn cancel_queued(&self, job: &QueuedJob, receipt: &Receipt, blobs: &[&Value]) -> Result<bool>;
+
+    fn get_leased_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>>;
+
+    /// The ready or scheduled job for `work_id`, if it is waiting to run.
+    fn get_queued_job(&self, work_id: Uuid) -> Result<Option<QueuedJob>>;
+
+    /// Moves a leased job to the dead letters and stores its final receipt in
+    /// the same transaction. Returns `false`, like [`Store::complete_leased`],
+    /// when the job is no longer leased under `attempt`.
+    fn dead_letter(
+        &self,
+        seq: u64,
+        attempt: u32,
+        reason: &str,
+        receipt: &Receipt,
+        blobs: &[&Value],
+    ) -> Result<bool>;
+
+    fn list_dead_letters(&self) -> Result<Vec<DeadLetter>>;
+
+    fn get_dead_letter(&self, seq: u64) -> Result<Option<DeadLetter>>;
+
+    /// Puts a dead letter back on its ready queue with a fresh attempt budget.
+    /// The failure history is kept so the next receipt still shows it.
+    fn requeue_dead_letter(&self, seq: u64) -> Result<Option<QueuedJob>>;
+
+    /// Deletes one dead letter, or all of them when `seq` is `None`.
+    fn purge_dead_letters(&self, seq: Option<u64>) -> Result<usize>;
+
+    /// Stores the receipt and settles the idempotency record of its work.
+    fn put_receipt(&self, receipt: &Receipt) -> Result<()> {
+        self.put_receipt_with_blobs(receipt, &[])
+    }
+
+    /// [`Store::put_receipt`], storing `blobs` (the receipt's payload and
+    /// result, whose CIDs come from [`crate::blob_cid`]) in the same
+    /// transaction, so a crash never leaves a blob without its reference.
+    fn put_receipt_with_blobs(&self, receipt: &Receipt, blobs: &[&Value]) -> Result<()>;
+
+    fn get_receipt(&self, cid: &str) -> Result<Option<Receipt>>;
+
+    /// Stores `value` as canonical JSON under its CID and returns the CID.
+    /// The blob lives as long as some receipt references it.
+    fn put_blob(&self, value: &Value) -> Result<String>;
+
+    /// The canonical JSON bytes stored under `cid`.
+    fn get_blob(&self, cid: &str) -> Result<Option<Vec<u8>>>;
+
+    /// How many stored receipts list the artifact `cid`.
+    fn artifact_refs(&self, cid: &str) -> Result<u64>;
+
+    /// Every artifact CID some stored receipt lists; artifact garbage
+    /// collection keeps these.
+    fn live_artifacts(&self) -> Result<BTreeSet<String>>;
+
+    /// Receipts matching `query`, ordered by `created_at` then CID.
+    fn query_receipts(&self, query: &ReceiptQuery) -> Result<ReceiptPage>;
+
+    fn count_receipts(&self) -> Result<u64>;
+
+    fn list_receipts(&self) -> Result<Vec<Receipt>>;
+
+    /// Deletes the receipts, their idem keys and any blob they were the last
+    /// to reference.
+    fn purge_receipts(&self, receipts: &[Receipt]) -> Result<RetentionPurgeReport>;
+
+    /// Appends to the event log and the work's history, assigning `event.seq`
+    /// and stamping `event.at` in the same step, so `at` never runs backwards
+    /// along `seq`.
+    fn append_work_event(&self, event: &mut StreamEvent) -> Result<()>;
+
+    /// The sequence number the next appended event gets.
+    fn next_event_seq(&self) -> Result<u64>;
+
+    /// Logged events with a seq above `after`, oldest first. A first seq
+    /// beyond `after + 1` means the events in between were trimmed.
+    fn events_after(&self, after: u64, limit: usize) -> Result<Vec<StreamEvent>>;
+
+    /// Drops the oldest log entries beyond the configured count or age, and
+    /// per-work history past the age limit. Returns the log entries dropped.
+    fn trim_event_log(&self) -> Result<u64>;
+
+    /// Every recorded event of one work unit, oldest first, until it ages out
+    /// or the work's receipt is purged.
+    fn work_events(&self, work_id: Uuid) -> Result<Vec<StreamEvent>>;
+
+    /// Bumps the global total for `status` and the one labelled by tenant and
+    /// topic family.
+    fn increment_status_counter(&self, status: WorkStatus, labels: &MetricLabels) -> Result<()>;
+
+    /// Records one finished job's TTFT and TTR, globally and by label, plus the
+    /// stage durations its plugin reported.
+    fn observe_timings(
+        &self,
+        labels: &MetricLabels,
+        ttft_ms: u64,
+        ttr_ms: u64,
+        stages: &BTreeMap<String, u64>,
+    ) -> Result<()>;
+
+    fn queue_metrics(&self) -> Result<QueueMetrics>;
+
+    /// Jobs, dead letters, receipts, idem keys and counters, read consistently.
+    fn snapshot(&self) -> Result<Snapshot>;
+
+    /// Loads a snapshot into an empty store.
+    fn restore(&self, snapshot: &Snapshot) -> Result<()>;
+}
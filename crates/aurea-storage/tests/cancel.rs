@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use aurea_core::{Receipt, WorkStatus, WorkUnit};
+use aurea_storage::{CancelOutcome, EnqueueResult, QueuedJob, RedbStore, Store, TopicFilter};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+fn work(n: u32) -> WorkUnit {
+    WorkUnit::new(
+        "tenant".to_string(),
+        "echo:test".to_string(),
+        Some(format!("cancel-{n}")),
+        json!({"n": n}),
+    )
+}
+
+fn cancelled(job: &QueuedJob) -> Receipt {
+    Receipt {
+        cid: format!("cid-cancel-{}", job.seq),
+        work_id: job.work.id,
+        tenant: job.work.tenant.clone(),
+        topic: job.work.topic.clone(),
+        status: WorkStatus::Cancelled,
+        idem_key: job.work.idem_key.clone().unwrap_or_default(),
+        plan_hash: "plan".to_string(),
+        policy_trace: vec![],
+        stage_time_ms: BTreeMap::new(),
+        artifacts: vec![],
+        attempts: vec![],
+        created_at: Utc::now(),
+        signatures: vec![],
+        trace_id: None,
+        started_at: None,
+        input_cid: None,
+        result_cid: None,
+        threshold: None,
+    }
+}
+
+fn queued(store: &RedbStore, work_id: Uuid) -> QueuedJob {
+    match store.cancel_work(work_id).expect("cancel") {
+        CancelOutcome::Queued(job) => job,
+        outcome => panic!("expected a queued job, got {outcome:?}"),
+    }
+}
+
+#[test]
+fn cancel_removes_queued_and_scheduled_jobs_with_their_receipt_but_not_leased_ones() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-cancel-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+
+    let ready = work(0);
+    let ready_id = ready.id;
+    store.enqueue_work_idempotent(ready).expect("enqueue ready");
+
+    let mut scheduled = work(1);
+    scheduled.not_before = Some(Utc::now() + Duration::hours(1));
+    let scheduled_id = scheduled.id;
+    store
+        .enqueue_work_idempotent(scheduled)
+        .expect("enqueue scheduled");
+
+    let running = work(2);
+    let running_id = running.id;
+    store
+        .enqueue_work_idempotent(running)
+        .expect("enqueue running");
+
+    // Finding a job does not remove it; only storing its receipt does.
+    let ready_job = queued(&store, ready_id);
+    let scheduled_job = queued(&store, scheduled_id);
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!((metrics.queue_depth, metrics.scheduled_depth), (2, 1));
+
+    for job in [&ready_job, &scheduled_job] {
+        assert!(
+            store
+                .cancel_queued(job, &cancelled(job), &[])
+                .expect("cancel queued")
+        );
+        assert!(
+            store
+                .get_receipt(&cancelled(job).cid)
+                .expect("get receipt")
+                .is_some()
+        );
+    }
+    assert!(
+        !store
+            .cancel_queued(&ready_job, &cancelled(&ready_job), &[])
+            .expect("cancel again")
+    );
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.queue_depth, 1);
+    assert_eq!(metrics.scheduled_depth, 0);
+    assert_eq!(metrics.receipts_total, 2);
+    assert!(matches!(
+        store.enqueue_work_idempotent(work(0)).expect("resubmit"),
+        EnqueueResult::Enqueued { .. }
+    ));
+
+    // A job leased after it was found keeps running and gets no receipt.
+    let found = queued(&store, running_id);
+    let leased = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("job");
+    assert_eq!(leased.work.id, running_id);
+    assert!(
+        !store
+            .cancel_queued(&found, &cancelled(&found), &[])
+            .expect("cancel leased")
+    );
+    assert!(
+        store
+            .get_receipt(&cancelled(&found).cid)
+            .expect("get receipt")
+            .is_none()
+    );
+    let outcome = store.cancel_work(running_id).expect("cancel leased");
+    assert!(matches!(outcome, CancelOutcome::Leased(job) if job.seq == leased.seq));
+    assert_eq!(store.queue_metrics().expect("metrics").leased_depth, 1);
+
+    let outcome = store.cancel_work(Uuid::new_v4()).expect("cancel unknown");
+    assert!(matches!(outcome, CancelOutcome::NotFound));
+
+    let _ = std::fs::remove_file(&path);
+}
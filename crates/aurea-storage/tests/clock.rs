@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use aurea_core::{Clock, HybridClock, MockClock, WorkUnit};
+use aurea_storage::{MemoryStore, RedbStore, Store, StoreConfig, TopicFilter};
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+fn mock_clock() -> MockClock {
+    MockClock::new(Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap())
+}
+
+fn config(clock: &MockClock) -> StoreConfig {
+    StoreConfig {
+        clock: Arc::new(clock.clone()),
+        ..Default::default()
+    }
+}
+
+fn with_stores(scenario: impl Fn(&dyn Store, &MockClock)) {
+    let clock = mock_clock();
+    scenario(&MemoryStore::with_config(config(&clock)), &clock);
+
+    let clock = mock_clock();
+    let path = std::env::temp_dir().join(format!("aurea-storage-clock-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open_with_config(&path, config(&clock)).expect("open redb");
+    scenario(&store, &clock);
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+fn work(idem: &str) -> WorkUnit {
+    WorkUnit::new(
+        "tenant".to_string(),
+        "echo:test".to_string(),
+        Some(idem.to_string()),
+        json!({"idem": idem}),
+    )
+}
+
+#[test]
+fn leases_expire_when_the_clock_moves_past_them() {
+    with_stores(|store, clock| {
+        store
+            .enqueue_work_idempotent(work("lease"))
+            .expect("enqueue");
+        let job = store
+            .lease_next(30_000, &TopicFilter::All)
+            .expect("lease")
+            .expect("job");
+        assert_eq!(job.leased_at, Some(clock.now()));
+
+        clock.advance(Duration::seconds(29));
+        assert_eq!(
+            store
+                .reassign_expired_leases()
+                .expect("reassign")
+                .reassigned,
+            0
+        );
+        assert!(
+            store
+                .heartbeat_lease(job.seq, job.attempt, 30_000)
+                .expect("heartbeat")
+        );
+
+        clock.advance(Duration::seconds(29));
+        assert_eq!(
+            store
+                .reassign_expired_leases()
+                .expect("reassign")
+                .reassigned,
+            0
+        );
+
+        clock.advance(Duration::seconds(2));
+        let report = store.reassign_expired_leases().expect("reassign");
+        assert_eq!(report.reassigned, 1);
+        assert_eq!(report.requeued[0].failures[0].failed_at, clock.now());
+
+        // The lost attempt backs off like a failed one before the next lease.
+        let retry_at = report.requeued[0].retry_at.expect("retry_at");
+        assert_eq!(retry_at, clock.now() + Duration::milliseconds(500));
+        assert_eq!(store.queue_metrics().expect("metrics").scheduled_depth, 1);
+        assert!(
+            store
+                .lease_next(30_000, &TopicFilter::All)
+                .expect("lease")
+                .is_none()
+        );
+        clock.advance(Duration::milliseconds(500));
+        let retried = store
+            .lease_next(30_000, &TopicFilter::All)
+            .expect("lease")
+            .expect("retried job");
+        assert_eq!(retried.attempt, 2);
+    });
+}
+
+#[test]
+fn scheduled_work_waits_for_the_clock() {
+    with_stores(|store, clock| {
+        let mut scheduled = work("later");
+        scheduled.not_before = Some(clock.now() + Duration::hours(1));
+        store.enqueue_work_idempotent(scheduled).expect("enqueue");
+
+        assert!(
+            store
+                .lease_next(5_000, &TopicFilter::All)
+                .expect("lease")
+                .is_none()
+        );
+        clock.advance(Duration::hours(1));
+        let job = store
+            .lease_next(5_000, &TopicFilter::All)
+            .expect("lease")
+            .expect("due job");
+        assert_eq!(job.accepted_at, clock.now() - Duration::hours(1));
+    });
+}
+
+#[test]
+fn reopened_store_keeps_its_clock_past_the_last_write() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-hlc-{}.redb", Uuid::new_v4()));
+    let wall = mock_clock();
+    let open = |wall: &MockClock| {
+        RedbStore::open_with_config(
+            &path,
+            StoreConfig {
+                clock: Arc::new(HybridClock::new(Arc::new(wall.clone()))),
+                ..Default::default()
+            },
+        )
+        .expect("open redb")
+    };
+
+    let store = open(&wall);
+    store
+        .enqueue_work_idempotent(work("before"))
+        .expect("enqueue");
+    let leased = store
+        .lease_next(30_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("job")
+        .leased_at
+        .expect("leased_at");
+    drop(store);
+
+    // The wall clock steps back across the restart.
+    wall.advance(Duration::hours(-1));
+    let store = open(&wall);
+    store
+        .enqueue_work_idempotent(work("after"))
+        .expect("enqueue");
+    let job = store
+        .lease_next(30_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("job");
+    assert!(job.accepted_at > leased);
+    assert_eq!(job.work.submitted_at, job.accepted_at);
+    assert!(job.leased_at.expect("leased_at") > job.accepted_at);
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
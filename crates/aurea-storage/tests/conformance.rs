@@ -0,0 +1,901 @@
+//! Behaviour every [`Store`] backend has to share. Each scenario runs against
+//! a fresh redb file and a fresh in-memory store.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use aurea_core::{
+    ArtifactRef, Priority, Receipt, ReceiptSignature, StreamEvent, WorkStatus, WorkUnit, cid_for,
+};
+use aurea_storage::{
+    Backoff, BackupArchive, CancelOutcome, EnqueueResult, EventRetention, FailureOutcome,
+    MemoryStore, MetricLabels, MetricsConfig, OTHER_LABEL, QueuedJob, ReceiptQuery, RedbStore,
+    RetryConfig, RetryOn, RetryPolicy, Store, StoreConfig, TopicFilter, blob_cid, label_value,
+};
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+fn config() -> StoreConfig {
+    StoreConfig {
+        retry: RetryConfig {
+            default: RetryPolicy {
+                max_attempts: 2,
+                backoff: Backoff {
+                    initial_ms: 0,
+                    multiplier: 1,
+                    max_ms: 0,
+                },
+                retry_on: RetryOn::Any,
+            },
+            ..Default::default()
+        },
+        events: EventRetention {
+            max_events: Some(3),
+            max_age: None,
+        },
+        metrics: MetricsConfig {
+            max_tenants: Some(2),
+            max_topic_families: None,
+            max_stages: Some(1),
+        },
+        ..Default::default()
+    }
+}
+
+fn work(topic: &str, idem: &str) -> WorkUnit {
+    WorkUnit::new(
+        "tenant".to_string(),
+        topic.to_string(),
+        Some(idem.to_string()),
+        json!({"idem": idem}),
+    )
+}
+
+fn enqueue(store: &dyn Store, work: WorkUnit) -> u64 {
+    match store.enqueue_work_idempotent(work).expect("enqueue") {
+        EnqueueResult::Enqueued { seq, .. } => seq,
+        other => panic!("expected a fresh enqueue, got {other:?}"),
+    }
+}
+
+fn lease(store: &dyn Store, ttl_ms: u64) -> Option<QueuedJob> {
+    store.lease_next(ttl_ms, &TopicFilter::All).expect("lease")
+}
+
+fn receipt(n: i64, work: &WorkUnit, status: WorkStatus) -> Receipt {
+    let base = Utc
+        .with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+        .single()
+        .expect("valid datetime");
+    Receipt {
+        cid: format!("cid-{n:03}"),
+        work_id: work.id,
+        tenant: work.tenant.clone(),
+        topic: work.topic.clone(),
+        status,
+        idem_key: work.idem_key.clone().unwrap_or_default(),
+        plan_hash: format!("plan-{n}"),
+        policy_trace: vec![],
+        stage_time_ms: BTreeMap::new(),
+        artifacts: vec![],
+        attempts: vec![],
+        created_at: base + Duration::hours(n),
+        signatures: vec![ReceiptSignature {
+            alg: "ed25519".to_string(),
+            kid: "kid-1".to_string(),
+            public_key: "pk".to_string(),
+            signature: "sig".to_string(),
+        }],
+        trace_id: None,
+        started_at: None,
+        input_cid: None,
+        result_cid: None,
+        threshold: None,
+    }
+}
+
+fn idempotency(store: &dyn Store) {
+    let first = work("echo:test", "idem-1");
+    let work_id = first.id;
+    enqueue(store, first.clone());
+
+    let again = store
+        .enqueue_work_idempotent(work("echo:test", "idem-1"))
+        .expect("enqueue duplicate");
+    assert!(matches!(again, EnqueueResult::DuplicateInFlight { work_id: id } if id == work_id));
+
+    let job = lease(store, 5_000).expect("job");
+    assert!(
+        store
+            .complete_leased(
+                job.seq,
+                job.attempt,
+                &receipt(1, &first, WorkStatus::Done),
+                &[]
+            )
+            .expect("complete")
+    );
+
+    let again = store
+        .enqueue_work_idempotent(work("echo:test", "idem-1"))
+        .expect("enqueue after receipt");
+    assert!(matches!(
+        again,
+        EnqueueResult::DuplicateReceipt { receipt_cid, .. } if receipt_cid == "cid-001"
+    ));
+    assert!(lease(store, 5_000).is_none());
+}
+
+fn priority_and_schedule(store: &dyn Store) {
+    let mut later = work("echo:test", "later");
+    later.not_before = Some(Utc::now() + Duration::hours(1));
+    enqueue(store, later);
+    let mut low = work("echo:test", "low");
+    low.priority = Priority::Low;
+    enqueue(store, low);
+    let mut critical = work("echo:test", "critical");
+    critical.priority = Priority::Critical;
+    enqueue(store, critical);
+    enqueue(store, work("echo:test", "normal"));
+
+    let order = std::iter::from_fn(|| lease(store, 5_000))
+        .map(|job| job.work.idem_key.expect("idem key"))
+        .collect::<Vec<_>>();
+    assert_eq!(order, ["critical", "normal", "low"]);
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.queue_depth, 0);
+    assert_eq!(metrics.scheduled_depth, 1);
+    assert_eq!(metrics.leased_depth, 3);
+}
+
+fn topic_filter(store: &dyn Store) {
+    enqueue(store, work("vcx:commit", "vcx"));
+    enqueue(store, work("echo:test", "echo"));
+
+    let only_echo = TopicFilter::Only(vec!["echo:*".to_string()]);
+    let job = store
+        .lease_next(5_000, &only_echo)
+        .expect("lease")
+        .expect("job");
+    assert_eq!(job.work.topic, "echo:test");
+    assert!(
+        store
+            .lease_next(5_000, &only_echo)
+            .expect("lease")
+            .is_none()
+    );
+}
+
+fn retry_then_dead_letter(store: &dyn Store) {
+    let unit = work("echo:test", "flaky");
+    let work_id = unit.id;
+    enqueue(store, unit.clone());
+
+    let first = lease(store, 5_000).expect("job");
+    assert_eq!(first.attempt, 1);
+    let outcome = store.fail_leased(first.seq, 1, "boom").expect("fail");
+    assert!(matches!(outcome, FailureOutcome::Retrying { .. }));
+
+    let second = lease(store, 5_000).expect("retried job");
+    assert_eq!(second.attempt, 2);
+    let outcome = store.fail_leased(second.seq, 2, "boom").expect("fail");
+    let FailureOutcome::Exhausted { job } = outcome else {
+        panic!("expected exhaustion, got {outcome:?}");
+    };
+    assert_eq!(job.failures.len(), 2);
+    assert_eq!(
+        store
+            .get_leased_job(work_id)
+            .expect("leased")
+            .map(|j| j.seq),
+        Some(job.seq)
+    );
+
+    let dead = receipt(1, &unit, WorkStatus::Fail);
+    assert!(
+        store
+            .dead_letter(job.seq, job.attempt, "boom", &dead, &[])
+            .expect("dead letter")
+    );
+    assert_eq!(store.list_dead_letters().expect("list").len(), 1);
+    let letter = store
+        .get_dead_letter(job.seq)
+        .expect("get")
+        .expect("letter");
+    assert_eq!(letter.receipt_cid.as_deref(), Some("cid-001"));
+    assert!(store.get_receipt("cid-001").expect("get receipt").is_some());
+
+    let requeued = store
+        .requeue_dead_letter(job.seq)
+        .expect("requeue")
+        .expect("requeued job");
+    assert_eq!(requeued.attempt, 0);
+    assert_eq!(requeued.failures.len(), 2);
+    let again = lease(store, 5_000).expect("requeued lease");
+    assert_eq!(again.work.id, work_id);
+    assert_eq!(store.purge_dead_letters(None).expect("purge"), 0);
+}
+
+fn lease_expiry(store: &dyn Store) {
+    enqueue(store, work("echo:test", "slow"));
+
+    let first = lease(store, 0).expect("job");
+    assert!(
+        !store
+            .heartbeat_lease(first.seq, 99, 5_000)
+            .expect("heartbeat")
+    );
+    let report = store.reassign_expired_leases().expect("reassign");
+    assert_eq!(report.reassigned, 1);
+    assert_eq!(report.requeued[0].failures.len(), 1);
+
+    let second = lease(store, 0).expect("reassigned job");
+    assert_eq!(second.attempt, 2);
+    let report = store.reassign_expired_leases().expect("reassign");
+    assert_eq!(report.reassigned, 0);
+    assert_eq!(report.exhausted.len(), 1);
+    assert!(
+        store
+            .heartbeat_lease(second.seq, 2, 5_000)
+            .expect("heartbeat")
+    );
+    assert_eq!(store.queue_metrics().expect("metrics").reassigns_total, 1);
+}
+
+fn lease_fencing(store: &dyn Store) {
+    let unit = work("echo:test", "fenced");
+    enqueue(store, unit.clone());
+
+    let stale = lease(store, 0).expect("job");
+    store.reassign_expired_leases().expect("reassign");
+    let current = lease(store, 5_000).expect("reassigned job");
+    assert_eq!(current.attempt, stale.attempt + 1);
+
+    let late = receipt(1, &unit, WorkStatus::Done);
+    assert!(
+        !store
+            .complete_leased(stale.seq, stale.attempt, &late, &[])
+            .expect("complete")
+    );
+    assert!(store.get_receipt("cid-001").expect("get receipt").is_none());
+    assert!(matches!(
+        store
+            .fail_leased(stale.seq, stale.attempt, "boom")
+            .expect("fail"),
+        FailureOutcome::LeaseLost
+    ));
+    assert!(
+        !store
+            .dead_letter(stale.seq, stale.attempt, "boom", &late, &[])
+            .expect("dead letter")
+    );
+    assert_eq!(
+        store
+            .get_leased_job(unit.id)
+            .expect("leased")
+            .map(|job| (job.attempt, job.failures.len())),
+        Some((current.attempt, 1))
+    );
+
+    let done = receipt(2, &unit, WorkStatus::Done);
+    assert!(
+        store
+            .complete_leased(current.seq, current.attempt, &done, &[])
+            .expect("complete")
+    );
+    assert!(store.get_leased_job(unit.id).expect("leased").is_none());
+    assert!(store.list_dead_letters().expect("list").is_empty());
+}
+
+fn cancellation(store: &dyn Store) {
+    let queued = work("echo:test", "queued");
+    let queued_id = queued.id;
+    enqueue(store, queued.clone());
+    let mut scheduled = work("echo:test", "scheduled");
+    scheduled.not_before = Some(Utc::now() + Duration::hours(1));
+    let scheduled_id = scheduled.id;
+    enqueue(store, scheduled);
+
+    let CancelOutcome::Queued(job) = store.cancel_work(queued_id).expect("cancel") else {
+        panic!("expected the queued job");
+    };
+    assert_eq!(job.work.id, queued_id);
+    assert!(
+        store
+            .cancel_queued(&job, &receipt(1, &queued, WorkStatus::Cancelled), &[])
+            .expect("cancel queued")
+    );
+    let CancelOutcome::Queued(job) = store.cancel_work(scheduled_id).expect("cancel") else {
+        panic!("expected the scheduled job");
+    };
+    assert!(
+        store
+            .cancel_queued(&job, &receipt(3, &job.work, WorkStatus::Cancelled), &[])
+            .expect("cancel scheduled")
+    );
+    assert!(
+        !store
+            .cancel_queued(&job, &receipt(4, &job.work, WorkStatus::Cancelled), &[])
+            .expect("cancel again")
+    );
+    assert!(matches!(
+        store.cancel_work(Uuid::new_v4()).expect("cancel"),
+        CancelOutcome::NotFound
+    ));
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!((metrics.queue_depth, metrics.scheduled_depth), (0, 0));
+
+    let resubmitted_unit = work("echo:test", "queued");
+    let resubmitted_id = resubmitted_unit.id;
+    enqueue(store, resubmitted_unit.clone());
+    let job = lease(store, 5_000).expect("job");
+    assert!(matches!(
+        store.cancel_work(resubmitted_id).expect("cancel"),
+        CancelOutcome::Leased(leased) if leased.seq == job.seq
+    ));
+    assert_eq!(
+        store
+            .get_leased_job(resubmitted_id)
+            .expect("leased")
+            .map(|leased| leased.seq),
+        Some(job.seq)
+    );
+    let cancelled = receipt(2, &resubmitted_unit, WorkStatus::Cancelled);
+    assert!(
+        store
+            .complete_leased(job.seq, job.attempt, &cancelled, &[])
+            .expect("complete")
+    );
+    assert!(
+        store
+            .get_leased_job(resubmitted_id)
+            .expect("leased")
+            .is_none()
+    );
+}
+
+fn receipts(store: &dyn Store) {
+    let units = (0..5)
+        .map(|n| work("echo:test", &format!("r-{n}")))
+        .collect::<Vec<_>>();
+    let receipts = units
+        .iter()
+        .enumerate()
+        .map(|(n, unit)| {
+            let status = if n % 2 == 0 {
+                WorkStatus::Done
+            } else {
+                WorkStatus::Fail
+            };
+            receipt(n as i64, unit, status)
+        })
+        .collect::<Vec<_>>();
+    for receipt in receipts.iter().rev() {
+        store.put_receipt(receipt).expect("put receipt");
+    }
+    assert_eq!(store.count_receipts().expect("count"), 5);
+    assert_eq!(
+        store
+            .get_receipt("cid-003")
+            .expect("get")
+            .map(|r| r.work_id),
+        Some(units[3].id)
+    );
+
+    let first = store
+        .query_receipts(&ReceiptQuery {
+            limit: Some(2),
+            ..Default::default()
+        })
+        .expect("query");
+    let cids = |receipts: &[Receipt]| receipts.iter().map(|r| r.cid.clone()).collect::<Vec<_>>();
+    assert_eq!(cids(&first.receipts), ["cid-000", "cid-001"]);
+    let rest = store
+        .query_receipts(&ReceiptQuery {
+            after: first.next,
+            ..Default::default()
+        })
+        .expect("query");
+    assert_eq!(cids(&rest.receipts), ["cid-002", "cid-003", "cid-004"]);
+    assert!(rest.next.is_none());
+
+    let done = store
+        .query_receipts(&ReceiptQuery {
+            status: Some(WorkStatus::Done),
+            to: Some(receipts[4].created_at),
+            ..Default::default()
+        })
+        .expect("query");
+    assert_eq!(cids(&done.receipts), ["cid-000", "cid-002"]);
+    let by_work = store
+        .query_receipts(&ReceiptQuery {
+            work_id: Some(units[1].id),
+            ..Default::default()
+        })
+        .expect("query");
+    assert_eq!(cids(&by_work.receipts), ["cid-001"]);
+
+    let report = store.purge_receipts(&receipts[..2]).expect("purge");
+    assert_eq!(report.deleted_receipts, 2);
+    assert_eq!(report.deleted_idem_keys, 2);
+    assert_eq!(store.list_receipts().expect("list").len(), 3);
+    let all = store
+        .query_receipts(&ReceiptQuery::default())
+        .expect("query");
+    assert_eq!(cids(&all.receipts), ["cid-002", "cid-003", "cid-004"]);
+}
+
+fn event_log(store: &dyn Store) {
+    let work_id = Uuid::new_v4();
+    let mut seqs = Vec::new();
+    for status in [
+        WorkStatus::Accepted,
+        WorkStatus::Assigned,
+        WorkStatus::Progress,
+        WorkStatus::Retrying,
+        WorkStatus::Done,
+    ] {
+        let mut event = StreamEvent {
+            seq: 0,
+            at: Utc::now(),
+            tenant: "tenant".to_string(),
+            topic: "echo:test".to_string(),
+            work_id,
+            status,
+            receipt_cid: None,
+            detail: None,
+            trace_id: None,
+        };
+        store.append_work_event(&mut event).expect("append");
+        seqs.push(event.seq);
+    }
+    assert_eq!(seqs, [1, 2, 3, 4, 5]);
+    assert_eq!(store.events_after(3, 10).expect("after").len(), 2);
+
+    assert_eq!(store.trim_event_log().expect("trim"), 2);
+    let kept = store.events_after(0, 10).expect("after");
+    assert_eq!(kept.iter().map(|e| e.seq).collect::<Vec<_>>(), [3, 4, 5]);
+    assert_eq!(store.work_events(work_id).expect("history").len(), 5);
+    assert!(
+        store
+            .work_events(Uuid::new_v4())
+            .expect("history")
+            .is_empty()
+    );
+
+    let mut unit = work("echo:test", "history");
+    unit.id = work_id;
+    let report = store
+        .purge_receipts(&[receipt(1, &unit, WorkStatus::Done)])
+        .expect("purge");
+    assert_eq!(report.deleted_work_events, 5);
+    assert!(store.work_events(work_id).expect("history").is_empty());
+}
+
+fn metrics(store: &dyn Store) {
+    let labels = MetricLabels::new("tenant", "echo:test");
+    store
+        .increment_status_counter(WorkStatus::Done, &labels)
+        .expect("count");
+    store
+        .increment_status_counter(WorkStatus::Done, &labels)
+        .expect("count");
+    store
+        .observe_timings(&labels, 300, 1_500, &BTreeMap::new())
+        .expect("observe");
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.status_totals.get("done"), Some(&2));
+    assert_eq!(metrics.status_totals.get("fail"), Some(&0));
+    assert_eq!((metrics.ttft_sum_ms, metrics.ttft_count), (300, 1));
+    assert_eq!((metrics.ttr_sum_ms, metrics.ttr_count), (1_500, 1));
+    assert_eq!(metrics.ttft_bucket_counts[1], (250, 0));
+    assert_eq!(metrics.ttft_bucket_counts[2], (500, 1));
+    assert_eq!(metrics.ttr_bucket_counts[2], (2000, 1));
+}
+
+fn labelled_metrics(store: &dyn Store) {
+    // A tenant really named `other` must not merge with the overflow bucket.
+    for tenant in ["acme", OTHER_LABEL, "initech"] {
+        let labels = MetricLabels::new(tenant, "echo:test");
+        store
+            .increment_status_counter(WorkStatus::Done, &labels)
+            .expect("count");
+        let stages = BTreeMap::from([("plan".to_string(), 40), ("render".to_string(), 700)]);
+        store
+            .observe_timings(&labels, 300, 1_500, &stages)
+            .expect("observe");
+    }
+    let mut queued = work("vcx:commit", "labelled-queued");
+    queued.tenant = "acme".to_string();
+    enqueue(store, queued);
+    let mut unseen = work("echo:test", "labelled-unseen");
+    unseen.tenant = "umbrella".to_string();
+    enqueue(store, unseen);
+
+    let metrics = store.queue_metrics().expect("metrics");
+    let labelled = metrics.labelled;
+    let key = |tenant: &str, family: &str| (tenant.to_string(), family.to_string());
+    let done = |tenant: &str| {
+        labelled
+            .jobs_total
+            .get(&(tenant.to_string(), "echo".to_string(), "done".to_string()))
+            .copied()
+    };
+    assert_eq!(done("acme"), Some(1));
+    assert_eq!(done(&label_value(OTHER_LABEL)), Some(1));
+    assert_eq!(done("_other"), Some(1));
+    assert_eq!(done("initech"), None);
+    assert_eq!(done(OTHER_LABEL), Some(1));
+    assert_eq!(metrics.status_totals.get("done"), Some(&3));
+
+    let ttft = &labelled.ttft[&key("acme", "echo")];
+    assert_eq!((ttft.sum, ttft.count), (300, 1));
+    assert_eq!(ttft.buckets[1], (250, 0));
+    assert_eq!(ttft.buckets[2], (500, 1));
+    assert_eq!(labelled.ttr[&key(OTHER_LABEL, "echo")].count, 1);
+
+    let plan = &labelled.stages[&key("echo", "plan")];
+    assert_eq!((plan.sum, plan.count), (120, 3));
+    assert_eq!(plan.buckets[0], (10, 0));
+    assert_eq!(plan.buckets[1], (50, 3));
+    assert_eq!(labelled.stages[&key("echo", OTHER_LABEL)].sum, 2_100);
+    assert!(!labelled.stages.contains_key(&key("echo", "render")));
+
+    assert_eq!(labelled.queue_depth.get(&key("acme", "vcx")), Some(&1));
+    assert_eq!(
+        labelled.queue_depth.get(&key(OTHER_LABEL, "echo")),
+        Some(&1)
+    );
+}
+
+fn tenant_topic_counts(store: &dyn Store) {
+    let counts = |store: &dyn Store, tenant: &str, topic: &str| {
+        store
+            .queue_metrics()
+            .expect("metrics")
+            .by_tenant_topic
+            .get(&(tenant.to_string(), topic.to_string()))
+            .copied()
+            .unwrap_or_default()
+    };
+
+    let mut other = work("vcx:commit", "other-tenant");
+    other.tenant = "other".to_string();
+    enqueue(store, other);
+    let mut later = work("echo:test", "counts-later");
+    later.not_before = Some(Utc::now() + Duration::hours(1));
+    enqueue(store, later);
+    let done = work("echo:test", "counts-done");
+    enqueue(store, done.clone());
+    let dead = work("echo:test", "counts-dead");
+    enqueue(store, dead.clone());
+
+    let echo = TopicFilter::Only(vec!["echo:*".to_string()]);
+    let first = store.lease_next(5_000, &echo).expect("lease").expect("job");
+    store
+        .complete_leased(
+            first.seq,
+            first.attempt,
+            &receipt(1, &done, WorkStatus::Done),
+            &[],
+        )
+        .expect("complete");
+    let second = store.lease_next(5_000, &echo).expect("lease").expect("job");
+    store
+        .dead_letter(
+            second.seq,
+            second.attempt,
+            "boom",
+            &receipt(2, &dead, WorkStatus::Fail),
+            &[],
+        )
+        .expect("dead letter");
+
+    let echo_counts = counts(store, "tenant", "echo:test");
+    assert_eq!(
+        (
+            echo_counts.ready,
+            echo_counts.scheduled,
+            echo_counts.leased,
+            echo_counts.dead_letters,
+            echo_counts.receipts
+        ),
+        (0, 1, 0, 1, 2)
+    );
+    assert_eq!(counts(store, "other", "vcx:commit").ready, 1);
+
+    store
+        .requeue_dead_letter(second.seq)
+        .expect("requeue")
+        .expect("job");
+    assert_eq!(counts(store, "tenant", "echo:test").ready, 1);
+    let again = store.lease_next(5_000, &echo).expect("lease").expect("job");
+    assert_eq!(counts(store, "tenant", "echo:test").leased, 1);
+    store
+        .dead_letter(
+            again.seq,
+            again.attempt,
+            "boom",
+            &receipt(3, &dead, WorkStatus::Fail),
+            &[],
+        )
+        .expect("dead letter");
+    assert_eq!(store.purge_dead_letters(None).expect("purge"), 1);
+    store
+        .purge_receipts(&[
+            receipt(1, &done, WorkStatus::Done),
+            receipt(2, &dead, WorkStatus::Fail),
+            receipt(3, &dead, WorkStatus::Fail),
+        ])
+        .expect("purge receipts");
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.queue_depth, 1);
+    assert_eq!(metrics.scheduled_depth, 1);
+    assert_eq!(metrics.leased_depth, 0);
+    assert_eq!(metrics.dead_letter_depth, 0);
+    assert_eq!(metrics.receipts_total, 0);
+    let echo_counts = counts(store, "tenant", "echo:test");
+    assert_eq!(echo_counts.scheduled, 1);
+    assert_eq!(echo_counts.dead_letters + echo_counts.receipts, 0);
+}
+
+fn backup_round_trip(store: &dyn Store) {
+    let done = work("echo:test", "backup-done");
+    enqueue(store, done.clone());
+    let job = lease(store, 5_000).expect("job");
+    store
+        .complete_leased(
+            job.seq,
+            job.attempt,
+            &receipt(1, &done, WorkStatus::Done),
+            &[],
+        )
+        .expect("complete");
+    store
+        .increment_status_counter(WorkStatus::Done, &MetricLabels::new("tenant", "echo:test"))
+        .expect("count");
+    let mut later = work("echo:test", "backup-later");
+    later.not_before = Some(Utc::now() + Duration::hours(1));
+    enqueue(store, later);
+    enqueue(store, work("echo:test", "backup-leased"));
+    lease(store, 60_000).expect("leased job");
+    enqueue(store, work("vcx:commit", "backup-ready"));
+
+    let archive = BackupArchive::new(store.snapshot().expect("snapshot"), store.clock().now())
+        .expect("archive");
+    archive.verify().expect("verify");
+    assert_eq!(archive.snapshot.receipts.len(), 1);
+    assert_eq!(archive.snapshot.ready_jobs.len(), 1);
+    assert_eq!(archive.snapshot.scheduled_jobs.len(), 1);
+    assert_eq!(archive.snapshot.leased_jobs.len(), 1);
+    assert_eq!(archive.snapshot.idem_keys.len(), 4);
+
+    let restored = MemoryStore::new();
+    restored.restore(&archive.snapshot).expect("restore");
+    let copy = restored.snapshot().expect("snapshot copy");
+    let seqs = |jobs: &[QueuedJob]| jobs.iter().map(|j| j.seq).collect::<Vec<_>>();
+    assert_eq!(seqs(&copy.ready_jobs), seqs(&archive.snapshot.ready_jobs));
+    assert_eq!(
+        seqs(&copy.scheduled_jobs),
+        seqs(&archive.snapshot.scheduled_jobs)
+    );
+    assert_eq!(seqs(&copy.leased_jobs), seqs(&archive.snapshot.leased_jobs));
+    assert_eq!(copy.receipts[0].cid, "cid-001");
+    assert_eq!(copy.idem_keys.len(), 4);
+    assert_eq!(
+        restored
+            .queue_metrics()
+            .expect("metrics")
+            .status_totals
+            .get("done"),
+        Some(&1)
+    );
+
+    let next = restored
+        .enqueue_work_idempotent(work("echo:test", "backup-next"))
+        .expect("enqueue after restore");
+    let EnqueueResult::Enqueued { seq, .. } = next else {
+        panic!("expected a fresh enqueue, got {next:?}");
+    };
+    assert!(archive.snapshot.ready_jobs.iter().all(|j| j.seq < seq));
+    assert!(matches!(
+        restored
+            .enqueue_work_idempotent(work("echo:test", "backup-done"))
+            .expect("enqueue duplicate"),
+        EnqueueResult::DuplicateReceipt { .. }
+    ));
+
+    assert!(restored.restore(&archive.snapshot).is_err());
+    assert!(store.restore(&archive.snapshot).is_err());
+
+    let mut tampered = archive.clone();
+    tampered.snapshot.receipts[0].topic = "echo:other".to_string();
+    assert!(tampered.verify().is_err());
+}
+
+fn blobs(store: &dyn Store) {
+    let payload = json!({"b": [1, 2], "a": "x"});
+    let input_cid = store.put_blob(&payload).expect("put payload");
+    assert_eq!(input_cid, cid_for(&payload).expect("cid"));
+    assert_eq!(store.put_blob(&payload).expect("put again"), input_cid);
+    assert_eq!(
+        store.get_blob(&input_cid).expect("get blob"),
+        Some(br#"{"a":"x","b":[1,2]}"#.to_vec())
+    );
+    let result = json!({"ok": true});
+    let result_cid = blob_cid(&result).expect("blob cid");
+    assert!(store.get_blob(&result_cid).expect("get").is_none());
+
+    let first = work("echo:test", "blob-1");
+    let mut done = receipt(1, &first, WorkStatus::Done);
+    done.input_cid = Some(input_cid.clone());
+    done.result_cid = Some(result_cid.clone());
+    store
+        .put_receipt_with_blobs(&done, &[&result])
+        .expect("put receipt");
+    assert_eq!(
+        store.get_blob(&result_cid).expect("get blob"),
+        Some(br#"{"ok":true}"#.to_vec())
+    );
+    let second = work("echo:test", "blob-2");
+    let mut failed = receipt(2, &second, WorkStatus::Fail);
+    failed.input_cid = Some(input_cid.clone());
+    store.put_receipt(&failed).expect("put receipt");
+
+    let archive = BackupArchive::new(store.snapshot().expect("snapshot"), store.clock().now())
+        .expect("archive");
+    archive.verify().expect("verify");
+    assert_eq!(archive.snapshot.blobs.len(), 2);
+    let restored = MemoryStore::new();
+    restored.restore(&archive.snapshot).expect("restore");
+    assert!(restored.get_blob(&result_cid).expect("get").is_some());
+    let mut tampered = archive.clone();
+    tampered
+        .snapshot
+        .blobs
+        .insert(result_cid.clone(), json!({"ok": false}));
+    assert!(tampered.verify().is_err());
+    assert!(MemoryStore::new().restore(&tampered.snapshot).is_err());
+
+    let report = store
+        .purge_receipts(std::slice::from_ref(&done))
+        .expect("purge");
+    assert_eq!(report.deleted_blobs, 1);
+    assert!(store.get_blob(&result_cid).expect("get").is_none());
+    assert!(store.get_blob(&input_cid).expect("get").is_some());
+
+    let report = store
+        .purge_receipts(std::slice::from_ref(&failed))
+        .expect("purge");
+    assert_eq!(report.deleted_blobs, 1);
+    assert!(store.get_blob(&input_cid).expect("get").is_none());
+}
+
+fn artifact_refs(store: &dyn Store) {
+    let artifact = |cid: &str| ArtifactRef {
+        cid: cid.to_string(),
+        path: format!("{cid}.bin"),
+        size_bytes: 1,
+    };
+    let first = work("vcx:pack", "artifact-1");
+    let mut one = receipt(1, &first, WorkStatus::Done);
+    one.artifacts = vec![artifact("shared"), artifact("only-one")];
+    store.put_receipt(&one).expect("put receipt");
+    let second = work("vcx:pack", "artifact-2");
+    let mut two = receipt(2, &second, WorkStatus::Done);
+    two.artifacts = vec![artifact("shared")];
+    store.put_receipt(&two).expect("put receipt");
+
+    assert_eq!(store.artifact_refs("shared").expect("refs"), 2);
+    assert_eq!(store.artifact_refs("only-one").expect("refs"), 1);
+    assert_eq!(store.artifact_refs("unknown").expect("refs"), 0);
+    assert_eq!(
+        store.live_artifacts().expect("live"),
+        BTreeSet::from(["only-one".to_string(), "shared".to_string()])
+    );
+
+    let restored = MemoryStore::new();
+    restored
+        .restore(&store.snapshot().expect("snapshot"))
+        .expect("restore");
+    assert_eq!(restored.artifact_refs("shared").expect("refs"), 2);
+
+    store
+        .purge_receipts(std::slice::from_ref(&one))
+        .expect("purge");
+    assert_eq!(store.artifact_refs("shared").expect("refs"), 1);
+    assert_eq!(
+        store.live_artifacts().expect("live"),
+        BTreeSet::from(["shared".to_string()])
+    );
+}
+
+macro_rules! conformance {
+    ($($scenario:ident),* $(,)?) => {
+        mod redb {
+            $(
+                #[test]
+                fn $scenario() {
+                    let path = std::env::temp_dir()
+                        .join(format!("aurea-storage-conformance-{}.redb", super::Uuid::new_v4()));
+                    let store = super::RedbStore::open_with_config(&path, super::config())
+                        .expect("open redb");
+                    super::$scenario(&store);
+                    drop(store);
+                    let _ = std::fs::remove_file(&path);
+                }
+            )*
+        }
+
+        mod memory {
+            $(
+                #[test]
+                fn $scenario() {
+                    super::$scenario(&super::MemoryStore::with_config(super::config()));
+                }
+            )*
+        }
+    };
+}
+
+conformance!(
+    idempotency,
+    priority_and_schedule,
+    topic_filter,
+    retry_then_dead_letter,
+    lease_expiry,
+    lease_fencing,
+    cancellation,
+    receipts,
+    event_log,
+    metrics,
+    labelled_metrics,
+    tenant_topic_counts,
+    backup_round_trip,
+    blobs,
+    artifact_refs,
+);
+
+#[test]
+fn memory_backups_restore_into_redb() {
+    let source = MemoryStore::with_config(config());
+    let done = work("echo:test", "cross-done");
+    enqueue(&source, done.clone());
+    let job = lease(&source, 5_000).expect("job");
+    source
+        .complete_leased(
+            job.seq,
+            job.attempt,
+            &receipt(1, &done, WorkStatus::Done),
+            &[],
+        )
+        .expect("complete");
+    enqueue(&source, work("echo:test", "cross-ready"));
+    let archive = BackupArchive::new(source.snapshot().expect("snapshot"), source.clock().now())
+        .expect("archive");
+
+    let path = std::env::temp_dir().join(format!("aurea-storage-restore-{}.redb", Uuid::new_v4()));
+    let target = RedbStore::open_with_config(&path, config()).expect("open redb");
+    target.restore(&archive.snapshot).expect("restore");
+    let receipts = target
+        .query_receipts(&ReceiptQuery {
+            tenant: Some("tenant".to_string()),
+            ..Default::default()
+        })
+        .expect("query receipts");
+    assert_eq!(receipts.receipts.len(), 1);
+    let ready = lease(&target, 5_000).expect("restored job");
+    assert_eq!(ready.work.idem_key.as_deref(), Some("cross-ready"));
+    assert!(matches!(
+        target
+            .enqueue_work_idempotent(work("echo:test", "cross-done"))
+            .expect("enqueue duplicate"),
+        EnqueueResult::DuplicateReceipt { .. }
+    ));
+
+    drop(target);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use aurea_core::{Receipt, WorkStatus, WorkUnit};
+use aurea_storage::{
+    Backoff, EnqueueResult, FailureOutcome, QueuedJob, RedbStore, RetryConfig, RetryOn,
+    RetryPolicy, Store, StoreConfig, TopicFilter,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+fn open_store(max_attempts: u32, retry_on: RetryOn) -> RedbStore {
+    let path = std::env::temp_dir().join(format!("aurea-storage-dlq-{}.redb", Uuid::new_v4()));
+    RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            retry: RetryConfig {
+                default: RetryPolicy {
+                    max_attempts,
+                    backoff: Backoff {
+                        initial_ms: 20,
+                        multiplier: 2,
+                        max_ms: 40,
+                    },
+                    retry_on,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .expect("open redb")
+}
+
+fn enqueue(store: &RedbStore, idem: &str) {
+    let outcome = store
+        .enqueue_work_idempotent(WorkUnit::new(
+            "tenant".to_string(),
+            "echo:test".to_string(),
+            Some(idem.to_string()),
+            json!({"idem": idem}),
+        ))
+        .expect("enqueue work");
+    assert!(matches!(outcome, EnqueueResult::Enqueued { .. }));
+}
+
+fn fail_receipt(job: &QueuedJob, cid: &str) -> Receipt {
+    Receipt {
+        cid: cid.to_string(),
+        work_id: job.work.id,
+        tenant: job.work.tenant.clone(),
+        topic: job.work.topic.clone(),
+        status: WorkStatus::Fail,
+        idem_key: job.work.idem_key.clone().unwrap_or_default(),
+        plan_hash: "plan".to_string(),
+        policy_trace: vec![],
+        stage_time_ms: BTreeMap::new(),
+        artifacts: vec![],
+        attempts: job.failures.clone(),
+        created_at: Utc::now(),
+        signatures: vec![],
+        trace_id: None,
+        started_at: job.leased_at,
+        input_cid: None,
+        result_cid: None,
+        threshold: None,
+    }
+}
+
+#[test]
+fn failed_attempts_back_off_then_dead_letter() {
+    let store = open_store(2, RetryOn::Any);
+    enqueue(&store, "idem-1");
+
+    let first = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("job");
+    let outcome = store
+        .fail_leased(first.seq, first.attempt, "boom")
+        .expect("fail first");
+    let FailureOutcome::Retrying { job, retry_at } = outcome else {
+        panic!("expected a retry, got {outcome:?}");
+    };
+    assert!(retry_at > job.failures[0].failed_at);
+    assert_eq!(store.queue_metrics().expect("metrics").scheduled_depth, 1);
+    assert!(
+        store
+            .lease_next(5_000, &TopicFilter::All)
+            .expect("lease during backoff")
+            .is_none()
+    );
+
+    std::thread::sleep(Duration::from_millis(40));
+    let second = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease retry")
+        .expect("retried job");
+    assert_eq!(second.seq, first.seq);
+    assert_eq!(second.attempt, 2);
+
+    let outcome = store
+        .fail_leased(second.seq, second.attempt, "boom again")
+        .expect("fail");
+    let FailureOutcome::Exhausted { job } = outcome else {
+        panic!("expected exhaustion, got {outcome:?}");
+    };
+    let errors = job
+        .failures
+        .iter()
+        .map(|f| (f.attempt, f.error.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec![(1, "boom"), (2, "boom again")]);
+
+    store
+        .dead_letter(
+            job.seq,
+            job.attempt,
+            "boom again",
+            &fail_receipt(&job, "receipt-cid"),
+            &[],
+        )
+        .expect("dead letter");
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.leased_depth, 0);
+    assert_eq!(metrics.dead_letter_depth, 1);
+
+    let letters = store.list_dead_letters().expect("list");
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].receipt_cid.as_deref(), Some("receipt-cid"));
+
+    let requeued = store
+        .requeue_dead_letter(job.seq)
+        .expect("requeue")
+        .expect("dead letter exists");
+    assert_eq!(requeued.attempt, 0);
+    assert_eq!(requeued.failures.len(), 2);
+    assert!(store.get_dead_letter(job.seq).expect("get").is_none());
+
+    let third = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease requeued")
+        .expect("requeued job");
+    assert_eq!(third.attempt, 1);
+}
+
+#[test]
+fn non_retryable_errors_skip_retries() {
+    let store = open_store(5, RetryOn::Matching(vec!["timeout".to_string()]));
+    enqueue(&store, "idem-1");
+
+    let job = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("job");
+    let outcome = store
+        .fail_leased(job.seq, job.attempt, "schema invalid")
+        .expect("fail");
+    assert!(matches!(outcome, FailureOutcome::Exhausted { .. }));
+}
+
+#[test]
+fn exhausted_expired_leases_are_reported() {
+    let store = open_store(1, RetryOn::Any);
+    enqueue(&store, "idem-1");
+    enqueue(&store, "idem-2");
+
+    store
+        .lease_next(1, &TopicFilter::All)
+        .expect("lease")
+        .expect("job");
+    std::thread::sleep(Duration::from_millis(5));
+
+    let report = store.reassign_expired_leases().expect("reassign");
+    assert_eq!(report.reassigned, 0);
+    assert_eq!(report.exhausted.len(), 1);
+    assert_eq!(store.queue_metrics().expect("metrics").leased_depth, 1);
+
+    assert_eq!(store.purge_dead_letters(None).expect("purge"), 0);
+    let job = &report.exhausted[0];
+    store
+        .dead_letter(
+            job.seq,
+            job.attempt,
+            "lease expired",
+            &fail_receipt(job, "receipt-cid"),
+            &[],
+        )
+        .expect("dead letter");
+    assert_eq!(store.purge_dead_letters(None).expect("purge"), 1);
+}
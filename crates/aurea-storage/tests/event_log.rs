@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use aurea_core::{MockClock, StreamEvent, WorkStatus};
+use aurea_storage::{EventRetention, RedbStore, Store, StoreConfig};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+fn event(work_id: Uuid, status: WorkStatus) -> StreamEvent {
+    StreamEvent {
+        seq: 0,
+        at: Utc::now(),
+        tenant: "tenant".to_string(),
+        topic: "echo:test".to_string(),
+        work_id,
+        status,
+        receipt_cid: None,
+        detail: None,
+        trace_id: None,
+    }
+}
+
+#[test]
+fn event_log_assigns_sequence_and_trims_oldest_entries() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-events-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            events: EventRetention {
+                max_events: Some(3),
+                max_age: None,
+            },
+            ..StoreConfig::default()
+        },
+    )
+    .expect("open redb");
+
+    let work_id = Uuid::new_v4();
+    let mut seqs = Vec::new();
+    for status in [
+        WorkStatus::Accepted,
+        WorkStatus::Assigned,
+        WorkStatus::Progress,
+        WorkStatus::Retrying,
+        WorkStatus::Done,
+    ] {
+        let mut evt = event(work_id, status);
+        store.append_work_event(&mut evt).expect("append event");
+        seqs.push(evt.seq);
+    }
+    assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+
+    let page = store.events_after(1, 2).expect("page");
+    assert_eq!(page.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![2, 3]);
+
+    assert_eq!(store.trim_event_log().expect("trim"), 2);
+    assert_eq!(store.trim_event_log().expect("trim again"), 0);
+    let retained = store.events_after(0, 100).expect("retained");
+    assert_eq!(
+        retained.iter().map(|e| e.seq).collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+
+    // The per-work history outlives the log.
+    assert_eq!(store.work_events(work_id).expect("history").len(), 5);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn work_history_ages_out_with_the_event_log() {
+    let clock = Arc::new(MockClock::new(Utc::now() - Duration::hours(3)));
+    let path = std::env::temp_dir().join(format!("aurea-storage-events-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            events: EventRetention {
+                max_events: None,
+                max_age: Some(Duration::hours(1)),
+            },
+            clock: clock.clone(),
+            ..StoreConfig::default()
+        },
+    )
+    .expect("open redb");
+
+    let old = Uuid::new_v4();
+    let recent = Uuid::new_v4();
+    for (work_id, hours) in [(old, 0), (old, 1), (recent, 2)] {
+        clock.advance(Duration::hours(hours));
+        let mut evt = event(work_id, WorkStatus::Accepted);
+        store.append_work_event(&mut evt).expect("append event");
+    }
+
+    assert_eq!(store.trim_event_log().expect("trim"), 2);
+    assert!(store.work_events(old).expect("history").is_empty());
+    assert_eq!(store.work_events(recent).expect("history").len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
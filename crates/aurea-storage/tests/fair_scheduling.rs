@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use aurea_core::WorkUnit;
+use aurea_storage::{
+    EnqueueResult, QueuePartition, RedbStore, SchedulerConfig, Store, StoreConfig, TopicFilter,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+fn enqueue(store: &RedbStore, tenant: &str, topic: &str, n: u32) {
+    let outcome = store
+        .enqueue_work_idempotent(WorkUnit::new(
+            tenant.to_string(),
+            topic.to_string(),
+            Some(format!("{tenant}-{topic}-{n}")),
+            json!({"n": n}),
+        ))
+        .expect("enqueue work");
+    assert!(matches!(outcome, EnqueueResult::Enqueued { .. }));
+}
+
+fn lease_topics(store: &RedbStore, filter: &TopicFilter, n: usize) -> Vec<String> {
+    (0..n)
+        .filter_map(|_| store.lease_next(5_000, filter).expect("lease"))
+        .map(|job| job.work.topic)
+        .collect()
+}
+
+#[test]
+fn noisy_topic_does_not_starve_others() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-fair-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+
+    for n in 0..10 {
+        enqueue(&store, "tenant", "vcx:commit", n);
+    }
+    enqueue(&store, "tenant", "echo:test", 0);
+    enqueue(&store, "tenant", "science:run", 0);
+
+    let first = lease_topics(&store, &TopicFilter::All, 3);
+    assert!(first.contains(&"echo:test".to_string()));
+    assert!(first.contains(&"science:run".to_string()));
+    assert!(first.contains(&"vcx:commit".to_string()));
+
+    let rest = lease_topics(&store, &TopicFilter::All, 20);
+    assert_eq!(rest.len(), 9);
+    assert!(rest.iter().all(|t| t == "vcx:commit"));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn weights_and_filters_shape_lease_order() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-fair-{}.redb", Uuid::new_v4()));
+    let mut weights = BTreeMap::new();
+    weights.insert("echo".to_string(), 3);
+    let store = RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            scheduler: SchedulerConfig {
+                weights,
+                ..SchedulerConfig::default()
+            },
+            ..StoreConfig::default()
+        },
+    )
+    .expect("open redb");
+
+    for n in 0..8 {
+        enqueue(&store, "tenant", "echo:test", n);
+        enqueue(&store, "tenant", "vcx:commit", n);
+    }
+
+    let vcx_only = lease_topics(&store, &TopicFilter::Only(vec!["vcx:*".to_string()]), 2);
+    assert_eq!(vcx_only, vec!["vcx:commit", "vcx:commit"]);
+
+    let mixed = lease_topics(&store, &TopicFilter::All, 8);
+    let echo = mixed.iter().filter(|t| t.as_str() == "echo:test").count();
+    assert_eq!(echo, 6);
+
+    let none = lease_topics(
+        &store,
+        &TopicFilter::Except(vec!["echo:*".to_string(), "vcx:*".to_string()]),
+        1,
+    );
+    assert!(none.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn tenant_partitioning_interleaves_tenants_and_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-fair-{}.redb", Uuid::new_v4()));
+    {
+        let store = RedbStore::open(&path).expect("open redb");
+        for n in 0..4 {
+            enqueue(&store, "noisy", "echo:test", n);
+        }
+        enqueue(&store, "quiet", "echo:test", 0);
+    }
+
+    let store = RedbStore::open_with_config(
+        &path,
+        StoreConfig {
+            scheduler: SchedulerConfig {
+                partition: QueuePartition::TenantTopic,
+                ..SchedulerConfig::default()
+            },
+            ..StoreConfig::default()
+        },
+    )
+    .expect("reopen with tenant partitioning");
+
+    let tenants: Vec<String> = (0..2)
+        .filter_map(|_| store.lease_next(5_000, &TopicFilter::All).expect("lease"))
+        .map(|job| job.work.tenant)
+        .collect();
+    assert!(tenants.contains(&"quiet".to_string()));
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.queue_depth, 3);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use aurea_core::{ArtifactRef, Receipt, UnsignedReceipt, WorkStatus, WorkUnit};
+use aurea_receipts::LocalSigner;
+use aurea_storage::{EnqueueResult, FsckIssue, MetricLabels, RedbStore, Store, TopicFilter};
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use redb::{ReadableTable, TableDefinition};
+use serde_json::json;
+use uuid::Uuid;
+
+const LEASED_JOBS: TableDefinition<u64, &[u8]> = TableDefinition::new("leased_jobs");
+const READY_JOBS: TableDefinition<u64, &[u8]> = TableDefinition::new("ready_jobs");
+const RECEIPTS: TableDefinition<&str, &[u8]> = TableDefinition::new("receipts");
+const META: TableDefinition<&str, u64> = TableDefinition::new("meta");
+const BLOBS: TableDefinition<&str, &[u8]> = TableDefinition::new("blobs");
+const BLOB_REFS: TableDefinition<(&str, &str), ()> = TableDefinition::new("blob_refs");
+const ARTIFACT_REFS: TableDefinition<(&str, &str), ()> = TableDefinition::new("artifact_refs");
+
+fn temp_db() -> PathBuf {
+    std::env::temp_dir().join(format!("aurea-storage-fsck-{}.redb", Uuid::new_v4()))
+}
+
+fn work(idem: &str) -> WorkUnit {
+    WorkUnit::new(
+        "acme".to_string(),
+        "echo:test".to_string(),
+        Some(idem.to_string()),
+        json!({"idem": idem}),
+    )
+}
+
+fn signed_receipt(work: &WorkUnit) -> Receipt {
+    signed_receipt_with_blobs(work, None, None)
+}
+
+fn signed_receipt_with_blobs(
+    work: &WorkUnit,
+    input_cid: Option<String>,
+    result_cid: Option<String>,
+) -> Receipt {
+    let unsigned = UnsignedReceipt {
+        work_id: work.id,
+        tenant: work.tenant.clone(),
+        topic: work.topic.clone(),
+        status: WorkStatus::Done,
+        idem_key: work.idem_key.clone().unwrap_or_default(),
+        plan_hash: "plan".to_string(),
+        policy_trace: vec![],
+        stage_time_ms: BTreeMap::new(),
+        artifacts: vec![],
+        attempts: vec![],
+        created_at: Utc::now(),
+        trace_id: None,
+        started_at: None,
+        input_cid,
+        result_cid,
+        threshold: None,
+    };
+    aurea_receipts::sign_receipt(
+        &unsigned,
+        &LocalSigner::new("kid-1", SigningKey::from_bytes(&[7; 32])),
+    )
+    .expect("sign receipt")
+}
+
+/// Two finished jobs with signed receipts and one job still queued.
+fn populate(store: &RedbStore) -> Vec<Receipt> {
+    let mut receipts = Vec::new();
+    for idem in ["first", "second"] {
+        let unit = work(idem);
+        let EnqueueResult::Enqueued { .. } = store.enqueue_work_idempotent(unit.clone()).unwrap()
+        else {
+            panic!("expected a fresh enqueue");
+        };
+        let job = store
+            .lease_next(5_000, &TopicFilter::All)
+            .expect("lease")
+            .expect("job");
+        let receipt = signed_receipt(&unit);
+        store
+            .complete_leased(job.seq, job.attempt, &receipt, &[])
+            .expect("complete");
+        store
+            .increment_status_counter(
+                WorkStatus::Done,
+                &MetricLabels::new(&unit.tenant, &unit.topic),
+            )
+            .expect("count");
+        receipts.push(receipt);
+    }
+    store
+        .enqueue_work_idempotent(work("queued"))
+        .expect("enqueue");
+    receipts
+}
+
+#[test]
+fn consistent_databases_pass() {
+    let path = temp_db();
+    let store = RedbStore::open(&path).expect("open");
+    populate(&store);
+
+    let report = store.fsck(false).expect("fsck");
+    assert!(report.problems.is_empty(), "{:#?}", report.problems);
+    assert!(report.is_clean());
+    assert_eq!(report.rows.get("receipts"), Some(&2));
+    assert_eq!(report.rows.get("ready_jobs"), Some(&1));
+    assert_eq!(report.rows.get("idem_keys"), Some(&3));
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn problems_are_reported_and_safe_ones_repaired() {
+    let path = temp_db();
+    let store = RedbStore::open(&path).expect("open");
+    let receipts = populate(&store);
+    drop(store);
+
+    {
+        let db = redb::Database::open(&path).expect("open raw");
+        let write = db.begin_write().expect("begin write");
+        {
+            let ready = write.open_table(READY_JOBS).expect("open ready");
+            let (seq, bytes) = ready
+                .first()
+                .expect("read ready")
+                .map(|(k, v)| (k.value(), v.value().to_vec()))
+                .expect("queued job");
+            let mut leased = write.open_table(LEASED_JOBS).expect("open leased");
+            leased.insert(seq, bytes.as_slice()).expect("duplicate job");
+        }
+        {
+            let mut table = write.open_table(RECEIPTS).expect("open receipts");
+            table
+                .remove(receipts[0].cid.as_str())
+                .expect("drop receipt");
+            let mut forged = receipts[1].clone();
+            forged.plan_hash = "forged".to_string();
+            let bytes = serde_json::to_vec(&forged).expect("serialize");
+            table
+                .insert(forged.cid.as_str(), bytes.as_slice())
+                .expect("forge receipt");
+        }
+        {
+            let mut meta = write.open_table(META).expect("open meta");
+            meta.insert("next_job_seq", 1).expect("rewind seq");
+        }
+        write.commit().expect("commit");
+    }
+
+    let store = RedbStore::open(&path).expect("reopen");
+    let report = store.fsck(false).expect("fsck");
+    let issues = report
+        .problems
+        .iter()
+        .map(|p| (p.issue, p.repairable))
+        .collect::<Vec<_>>();
+    for expected in [
+        (FsckIssue::DuplicateJob, false),
+        (FsckIssue::ReceiptCidMismatch, false),
+        (FsckIssue::ReceiptIndexDrift, true),
+        (FsckIssue::OrphanedIdemKey, true),
+        (FsckIssue::CounterDrift, true),
+    ] {
+        assert!(issues.contains(&expected), "{expected:?} in {issues:?}");
+    }
+    assert!(
+        report
+            .problems
+            .iter()
+            .any(|p| p.issue == FsckIssue::CounterDrift && p.table == "counts")
+    );
+    assert!(!report.is_clean());
+    assert!(report.problems.iter().all(|p| !p.repaired));
+
+    let repaired = store.fsck(true).expect("repair");
+    assert!(repaired.problems.iter().all(|p| p.repaired == p.repairable));
+
+    let after = store.fsck(false).expect("fsck after repair");
+    let mut left = after.problems.iter().map(|p| p.issue).collect::<Vec<_>>();
+    left.sort_by_key(|issue| format!("{issue:?}"));
+    assert_eq!(
+        left,
+        [FsckIssue::DuplicateJob, FsckIssue::ReceiptCidMismatch]
+    );
+    assert!(after.problems.iter().all(|p| !p.repairable));
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!((metrics.leased_depth, metrics.receipts_total), (1, 1));
+
+    let next = store
+        .enqueue_work_idempotent(work("after-repair"))
+        .expect("enqueue after repair");
+    let EnqueueResult::Enqueued { seq, .. } = next else {
+        panic!("expected a fresh enqueue, got {next:?}");
+    };
+    assert!(seq > 3);
+    assert!(matches!(
+        store
+            .enqueue_work_idempotent(work("first"))
+            .expect("resubmit orphaned idem key"),
+        EnqueueResult::Enqueued { .. }
+    ));
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn blob_problems_are_reported_and_orphans_removed() {
+    let path = temp_db();
+    let store = RedbStore::open(&path).expect("open");
+    let input_cid = store
+        .put_blob(&json!({"idem": "blobs"}))
+        .expect("put input");
+    let result_cid = store.put_blob(&json!({"ok": true})).expect("put result");
+    let orphan_cid = store
+        .put_blob(&json!({"orphan": true}))
+        .expect("put orphan");
+    let receipt = signed_receipt_with_blobs(
+        &work("blobs"),
+        Some(input_cid.clone()),
+        Some(result_cid.clone()),
+    );
+    store.put_receipt(&receipt).expect("put receipt");
+    let missing = signed_receipt_with_blobs(&work("missing"), Some("b3:missing".to_string()), None);
+    store.put_receipt(&missing).expect("put receipt");
+    drop(store);
+
+    {
+        let db = redb::Database::open(&path).expect("open raw");
+        let write = db.begin_write().expect("begin write");
+        {
+            let mut blobs = write.open_table(BLOBS).expect("open blobs");
+            blobs
+                .insert(result_cid.as_str(), br#"{"ok":false}"#.as_slice())
+                .expect("corrupt blob");
+            let mut refs = write.open_table(BLOB_REFS).expect("open blob_refs");
+            refs.remove((input_cid.as_str(), receipt.cid.as_str()))
+                .expect("drop ref");
+        }
+        write.commit().expect("commit");
+    }
+
+    let store = RedbStore::open(&path).expect("reopen");
+    let report = store.fsck(false).expect("fsck");
+    let issues = report
+        .problems
+        .iter()
+        .map(|p| (p.issue, p.repairable))
+        .collect::<Vec<_>>();
+    for expected in [
+        (FsckIssue::BlobCidMismatch, false),
+        (FsckIssue::MissingBlob, false),
+        (FsckIssue::OrphanedBlob, true),
+        (FsckIssue::BlobRefDrift, true),
+    ] {
+        assert!(issues.contains(&expected), "{expected:?} in {issues:?}");
+    }
+    assert_eq!(report.rows.get("blobs"), Some(&3));
+
+    store.fsck(true).expect("repair");
+    let after = store.fsck(false).expect("fsck after repair");
+    let mut left = after.problems.iter().map(|p| p.issue).collect::<Vec<_>>();
+    left.sort_by_key(|issue| format!("{issue:?}"));
+    assert_eq!(left, [FsckIssue::BlobCidMismatch, FsckIssue::MissingBlob]);
+    assert!(store.get_blob(&orphan_cid).expect("get").is_none());
+
+    let report = store
+        .purge_receipts(std::slice::from_ref(&receipt))
+        .expect("purge");
+    assert_eq!(report.deleted_blobs, 2);
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn artifact_ref_drift_is_rebuilt_from_receipts() {
+    let path = temp_db();
+    let store = RedbStore::open(&path).expect("open");
+    let unit = work("artifacts");
+    let unsigned = UnsignedReceipt {
+        artifacts: vec![ArtifactRef {
+            cid: "packcid".to_string(),
+            path: "plan.vcxpack".to_string(),
+            size_bytes: 10,
+        }],
+        ..signed_receipt(&unit).unsigned()
+    };
+    let receipt = aurea_receipts::sign_receipt(
+        &unsigned,
+        &LocalSigner::new("kid-1", SigningKey::from_bytes(&[7; 32])),
+    )
+    .expect("sign receipt");
+    store.put_receipt(&receipt).expect("put receipt");
+    drop(store);
+
+    {
+        let db = redb::Database::open(&path).expect("open raw");
+        let write = db.begin_write().expect("begin write");
+        {
+            let mut refs = write.open_table(ARTIFACT_REFS).expect("open artifact_refs");
+            refs.remove(("packcid", receipt.cid.as_str()))
+                .expect("drop ref");
+            refs.insert(("stray", "cid-gone"), ())
+                .expect("add stray ref");
+        }
+        write.commit().expect("commit");
+    }
+
+    let store = RedbStore::open(&path).expect("reopen");
+    assert!(store.live_artifacts().expect("live").contains("stray"));
+    let report = store.fsck(false).expect("fsck");
+    let issues = report
+        .problems
+        .iter()
+        .filter(|p| p.issue == FsckIssue::ArtifactRefDrift)
+        .map(|p| (p.repairable, p.detail.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        issues,
+        [(true, "1 references missing, 1 dangling references")]
+    );
+
+    store.fsck(true).expect("repair");
+    assert!(store.fsck(false).expect("fsck").problems.is_empty());
+    assert_eq!(store.artifact_refs("packcid").expect("refs"), 1);
+    assert_eq!(store.artifact_refs("stray").expect("refs"), 0);
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
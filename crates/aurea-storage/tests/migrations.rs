@@ -0,0 +1,126 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use aurea_core::WorkStatus;
+use aurea_storage::{ReceiptQuery, RedbStore, SCHEMA_VERSION, Store, TopicFilter};
+use flate2::read::GzDecoder;
+use uuid::Uuid;
+
+/// Databases written by earlier builds, each holding one done job with its
+/// receipt, one leased job and two queued jobs (`echo:test`, `vcx:commit`).
+const FIXTURES: [&str; 3] = [
+    "unversioned-initial",
+    "unversioned-topic-queues",
+    "unversioned-receipt-index",
+];
+
+fn unpack(fixture: &str) -> PathBuf {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{fixture}.redb.gz"));
+    let mut bytes = Vec::new();
+    GzDecoder::new(std::fs::File::open(&src).expect("open fixture"))
+        .read_to_end(&mut bytes)
+        .expect("unpack fixture");
+    let path =
+        std::env::temp_dir().join(format!("aurea-storage-{fixture}-{}.redb", Uuid::new_v4()));
+    std::fs::write(&path, bytes).expect("write fixture copy");
+    path
+}
+
+#[test]
+fn dry_run_lists_pending_migrations_without_applying_them() {
+    let path = unpack(FIXTURES[0]);
+
+    let plan = RedbStore::migration_plan(&path).expect("plan");
+    assert_eq!(plan.from, 1);
+    assert_eq!(plan.to, SCHEMA_VERSION);
+    let versions = plan.steps.iter().map(|s| s.version).collect::<Vec<_>>();
+    assert_eq!(versions, (2..=SCHEMA_VERSION).collect::<Vec<_>>());
+    assert_eq!(RedbStore::migration_plan(&path).expect("plan").from, 1);
+
+    let store = RedbStore::open(&path).expect("open");
+    assert_eq!(store.schema_version().expect("version"), SCHEMA_VERSION);
+    drop(store);
+    assert!(RedbStore::migration_plan(&path).expect("plan").is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn older_layouts_upgrade_on_open() {
+    for fixture in FIXTURES {
+        let path = unpack(fixture);
+        let store = RedbStore::open(&path).unwrap_or_else(|err| panic!("{fixture}: {err:#}"));
+        assert_eq!(store.schema_version().expect("version"), SCHEMA_VERSION);
+
+        let metrics = store.queue_metrics().expect("metrics");
+        assert_eq!(metrics.queue_depth, 2, "{fixture}");
+        assert_eq!(metrics.leased_depth, 1, "{fixture}");
+        assert_eq!(metrics.receipts_total, 1, "{fixture}");
+        assert_eq!(metrics.status_totals.get("done"), Some(&1), "{fixture}");
+        assert_eq!(metrics.ttft_count, 1, "{fixture}");
+
+        let page = store
+            .query_receipts(&ReceiptQuery {
+                tenant: Some("acme".to_string()),
+                status: Some(WorkStatus::Done),
+                ..Default::default()
+            })
+            .expect("query receipts");
+        assert_eq!(page.receipts.len(), 1, "{fixture}");
+        assert_eq!(page.receipts[0].idem_key, "fixture-done");
+
+        let mut topics = std::iter::from_fn(|| {
+            store
+                .lease_next(5_000, &TopicFilter::All)
+                .expect("lease")
+                .map(|job| job.work.topic)
+        })
+        .collect::<Vec<_>>();
+        topics.sort();
+        assert_eq!(topics, ["echo:test", "vcx:commit"], "{fixture}");
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+#[test]
+fn new_databases_start_at_the_current_version() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-schema-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open");
+    assert_eq!(store.schema_version().expect("version"), SCHEMA_VERSION);
+    drop(store);
+
+    let plan = RedbStore::migration_plan(&path).expect("plan");
+    assert_eq!(plan.from, SCHEMA_VERSION);
+    assert!(plan.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn newer_schemas_are_refused() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-schema-{}.redb", Uuid::new_v4()));
+    drop(RedbStore::open(&path).expect("open"));
+    {
+        let db = redb::Database::open(&path).expect("open raw");
+        let write = db.begin_write().expect("begin write");
+        {
+            let mut meta = write
+                .open_table(redb::TableDefinition::<&str, u64>::new("meta"))
+                .expect("open meta");
+            meta.insert("schema_version", SCHEMA_VERSION + 1)
+                .expect("bump version");
+        }
+        write.commit().expect("commit");
+    }
+
+    let err = RedbStore::open(&path)
+        .err()
+        .expect("newer schema must not open");
+    assert!(err.to_string().contains("newer than this build"), "{err:#}");
+
+    let _ = std::fs::remove_file(&path);
+}
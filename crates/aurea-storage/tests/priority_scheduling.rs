@@ -0,0 +1,90 @@
+use aurea_core::{Priority, WorkUnit};
+use aurea_storage::{RedbStore, Store, TopicFilter};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+fn work(topic: &str, n: u32, priority: Priority) -> WorkUnit {
+    let mut work = WorkUnit::new(
+        "tenant".to_string(),
+        topic.to_string(),
+        Some(format!("{topic}-{n}")),
+        json!({"n": n}),
+    );
+    work.priority = priority;
+    work
+}
+
+#[test]
+fn higher_priority_jobs_are_leased_first() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-prio-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+
+    store
+        .enqueue_work_idempotent(work("echo:test", 0, Priority::Low))
+        .expect("enqueue low");
+    store
+        .enqueue_work_idempotent(work("echo:test", 1, Priority::Normal))
+        .expect("enqueue normal");
+    store
+        .enqueue_work_idempotent(work("vcx:commit", 2, Priority::Critical))
+        .expect("enqueue critical");
+    store
+        .enqueue_work_idempotent(work("echo:test", 3, Priority::High))
+        .expect("enqueue high");
+
+    let order = (0..4)
+        .filter_map(|_| store.lease_next(5_000, &TopicFilter::All).expect("lease"))
+        .map(|job| job.work.priority)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        order,
+        vec![
+            Priority::Critical,
+            Priority::High,
+            Priority::Normal,
+            Priority::Low
+        ]
+    );
+}
+
+#[test]
+fn not_before_jobs_wait_until_due() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-delay-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+
+    let mut later = work("echo:test", 0, Priority::Critical);
+    later.not_before = Some(Utc::now() + Duration::hours(1));
+    store.enqueue_work_idempotent(later).expect("enqueue later");
+
+    let mut soon = work("echo:test", 1, Priority::Normal);
+    soon.not_before = Some(Utc::now() + Duration::milliseconds(50));
+    store.enqueue_work_idempotent(soon).expect("enqueue soon");
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.queue_depth, 0);
+    assert_eq!(metrics.scheduled_depth, 2);
+    assert!(
+        store
+            .lease_next(5_000, &TopicFilter::All)
+            .expect("lease")
+            .is_none()
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(80));
+    let leased = store
+        .lease_next(5_000, &TopicFilter::All)
+        .expect("lease")
+        .expect("due job");
+    assert_eq!(leased.work.payload, json!({"n": 1}));
+
+    let metrics = store.queue_metrics().expect("metrics");
+    assert_eq!(metrics.scheduled_depth, 1);
+    assert!(
+        store
+            .lease_next(5_000, &TopicFilter::All)
+            .expect("lease")
+            .is_none()
+    );
+}
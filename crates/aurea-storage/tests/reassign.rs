@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use aurea_core::WorkUnit;
-use aurea_storage::{EnqueueResult, RedbStore};
+use aurea_storage::{EnqueueResult, LEASE_EXPIRED_ERROR, RedbStore, Store, TopicFilter};
 use serde_json::json;
 use uuid::Uuid;
 
@@ -23,23 +23,33 @@ fn expired_lease_is_reassigned() {
     assert!(matches!(enqueued, EnqueueResult::Enqueued { .. }));
 
     let first = store
-        .lease_next(1)
+        .lease_next(1, &TopicFilter::All)
         .expect("lease first")
         .expect("job exists");
 
     std::thread::sleep(Duration::from_millis(5));
 
-    let moved = store
+    let report = store
         .reassign_expired_leases()
         .expect("reassign expired leases");
-    assert_eq!(moved, 1);
-
+    assert_eq!(report.reassigned, 1);
+    assert!(report.exhausted.is_empty());
+    assert!(
+        store
+            .lease_next(1000, &TopicFilter::All)
+            .expect("lease during backoff")
+            .is_none()
+    );
+
+    std::thread::sleep(Duration::from_millis(600));
     let second = store
-        .lease_next(1000)
+        .lease_next(1000, &TopicFilter::All)
         .expect("lease second")
         .expect("job exists again");
     assert_eq!(second.seq, first.seq);
     assert!(second.attempt >= 2);
+    assert_eq!(second.failures.len(), 1);
+    assert_eq!(second.failures[0].error, LEASE_EXPIRED_ERROR);
 
     let metrics = store.queue_metrics().expect("queue metrics");
     assert_eq!(metrics.reassigns_total, 1);
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use aurea_core::{Receipt, ReceiptSignature, WorkStatus};
+use aurea_storage::{ReceiptQuery, RedbStore, Store};
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+fn receipt(n: i64, tenant: &str, status: WorkStatus) -> Receipt {
+    let base = Utc
+        .with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+        .single()
+        .expect("valid datetime");
+    Receipt {
+        cid: format!("cid-{n:03}"),
+        work_id: Uuid::new_v4(),
+        tenant: tenant.to_string(),
+        topic: "echo:test".to_string(),
+        status,
+        idem_key: format!("idem-{n}"),
+        plan_hash: format!("plan-{n}"),
+        policy_trace: vec![],
+        stage_time_ms: BTreeMap::new(),
+        artifacts: vec![],
+        attempts: vec![],
+        created_at: base + Duration::hours(n),
+        signatures: vec![ReceiptSignature {
+            alg: "ed25519".to_string(),
+            kid: "kid-1".to_string(),
+            public_key: "pk".to_string(),
+            signature: "sig".to_string(),
+        }],
+        trace_id: None,
+        started_at: None,
+        input_cid: None,
+        result_cid: None,
+        threshold: None,
+    }
+}
+
+#[test]
+fn receipt_queries_use_indexes_and_paginate() {
+    let path = std::env::temp_dir().join(format!("aurea-storage-rindex-{}.redb", Uuid::new_v4()));
+    let store = RedbStore::open(&path).expect("open redb");
+
+    let mut all = Vec::new();
+    for n in 0..10 {
+        let tenant = if n % 2 == 0 { "even" } else { "odd" };
+        let status = if n == 3 {
+            WorkStatus::Fail
+        } else {
+            WorkStatus::Done
+        };
+        let r = receipt(n, tenant, status);
+        store.put_receipt(&r).expect("put receipt");
+        all.push(r);
+    }
+
+    let mut seen = Vec::new();
+    let mut query = ReceiptQuery {
+        tenant: Some("even".to_string()),
+        limit: Some(2),
+        ..ReceiptQuery::default()
+    };
+    loop {
+        let page = store.query_receipts(&query).expect("query page");
+        seen.extend(page.receipts.into_iter().map(|r| r.cid));
+        match page.next {
+            Some(cursor) => query.after = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(
+        seen,
+        vec!["cid-000", "cid-002", "cid-004", "cid-006", "cid-008"]
+    );
+
+    let failed = store
+        .query_receipts(&ReceiptQuery {
+            status: Some(WorkStatus::Fail),
+            ..ReceiptQuery::default()
+        })
+        .expect("query status");
+    assert_eq!(failed.receipts.len(), 1);
+    assert_eq!(failed.receipts[0].cid, "cid-003");
+
+    let window = store
+        .query_receipts(&ReceiptQuery {
+            tenant: Some("odd".to_string()),
+            from: Some(all[2].created_at),
+            to: Some(all[7].created_at),
+            ..ReceiptQuery::default()
+        })
+        .expect("query window");
+    let cids = window
+        .receipts
+        .iter()
+        .map(|r| r.cid.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(cids, vec!["cid-003", "cid-005"]);
+
+    let by_work = store
+        .query_receipts(&ReceiptQuery {
+            work_id: Some(all[4].work_id),
+            ..ReceiptQuery::default()
+        })
+        .expect("query work id");
+    assert_eq!(by_work.receipts.len(), 1);
+
+    store.purge_receipts(&all[..5]).expect("purge receipts");
+    let remaining = store
+        .query_receipts(&ReceiptQuery::default())
+        .expect("query all");
+    assert_eq!(remaining.receipts.len(), 5);
+    assert_eq!(remaining.receipts[0].cid, "cid-005");
+    assert_eq!(store.count_receipts().expect("count"), 5);
+
+    drop(store);
+    let reopened = RedbStore::open(&path).expect("reopen redb");
+    assert_eq!(
+        reopened
+            .query_receipts(&ReceiptQuery::default())
+            .expect("query after reopen")
+            .receipts
+            .len(),
+        5
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
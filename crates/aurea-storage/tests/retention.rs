@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use aurea_core::{Receipt, ReceiptSignature, WorkStatus, WorkUnit};
-use aurea_storage::{EnqueueResult, RedbStore};
+use aurea_storage::{EnqueueResult, RedbStore, Store};
 use chrono::Utc;
 use serde_json::json;
 use uuid::Uuid;
@@ -25,13 +25,19 @@ fn purge_receipts_removes_receipt_and_idem_record() {
         policy_trace: vec![],
         stage_time_ms: BTreeMap::new(),
         artifacts: vec![],
+        attempts: vec![],
         created_at: Utc::now(),
-        signature: ReceiptSignature {
+        signatures: vec![ReceiptSignature {
             alg: "ed25519".to_string(),
             kid: "kid-1".to_string(),
             public_key: "pk".to_string(),
             signature: "sig".to_string(),
-        },
+        }],
+        trace_id: None,
+        started_at: None,
+        input_cid: None,
+        result_cid: None,
+        threshold: None,
     };
     store.put_receipt(&receipt).expect("insert receipt");
 
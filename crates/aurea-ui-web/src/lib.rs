@@ -284,6 +284,7 @@ pub fn render_plan_card_html(plan: &PlanCardData, lang: Lang) -> Result<String,
     };
     let local_only = plan.local_only;
     let warnings = plan.warnings.clone();
+    let plan_hash_attr = plan_hash.clone();
 
     let html = Owner::new().with(|| {
         view! {
@@ -345,11 +346,12 @@ pub fn render_plan_card_html(plan: &PlanCardData, lang: Lang) -> Result<String,
                     }.into_any()
                 }}
 
-                <div class="aurea-actions" role="group" aria-label="plan actions">
-                    <button class="aurea-btn" data-strong="true" aria-label={l.buttons.confirm.clone()}>{l.buttons.confirm.clone()}</button>
-                    <button class="aurea-btn" aria-label={l.buttons.edit.clone()}>{l.buttons.edit.clone()}</button>
-                    <button class="aurea-btn" aria-label={l.buttons.schedule.clone()}>{l.buttons.schedule.clone()}</button>
-                    <button class="aurea-btn" aria-label={l.buttons.repeat.clone()}>{l.buttons.repeat.clone()}</button>
+                <div class="aurea-actions" role="group" aria-label="plan actions" data-commit="/v1/oc/commit" data-plan-hash={plan_hash_attr}>
+                    <button class="aurea-btn" data-strong="true" data-action="confirm" aria-label={l.buttons.confirm.clone()}>{l.buttons.confirm.clone()}</button>
+                    <button class="aurea-btn" data-action="edit" aria-label={l.buttons.edit.clone()}>{l.buttons.edit.clone()}</button>
+                    <input class="aurea-code" type="datetime-local" name="not_before" aria-label={l.buttons.schedule.clone()}/>
+                    <button class="aurea-btn" data-action="schedule" data-field="not_before" aria-label={l.buttons.schedule.clone()}>{l.buttons.schedule.clone()}</button>
+                    <button class="aurea-btn" data-action="repeat" aria-label={l.buttons.repeat.clone()}>{l.buttons.repeat.clone()}</button>
                 </div>
             </section>
         }
@@ -452,6 +454,8 @@ mod tests {
 
         assert!(html.contains("Executa local"));
         assert!(html.contains("Confirmar"));
+        assert!(html.contains("data-action=\"schedule\""));
+        assert!(html.contains("name=\"not_before\""));
     }
 
     #[test]